@@ -0,0 +1,180 @@
+//! Spawn animations for newly managed tiled windows.
+//!
+//! When `appearance.spawn_animation` is enabled, a window that is placed for
+//! the first time starts from a slightly smaller, offset rectangle and is
+//! stepped to its final geometry by the main loop. Each step is a plain
+//! `configure_window`, so no compositor is needed. With `"none"` nothing is
+//! ever queued here and the main loop does no extra work.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use x11rb::protocol::xproto::Window;
+
+use crate::config::SpawnAnimation;
+use crate::types::Rect;
+
+/// Minimum time between two animation steps (~60 steps per second)
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// A window moving from `from` to `to`
+#[derive(Debug, Clone)]
+struct Animation {
+    from: Rect,
+    to: Rect,
+    start: Instant,
+    duration: Duration,
+}
+
+impl Animation {
+    /// Geometry at `now`, and whether the animation has finished
+    fn rect_at(&self, now: Instant) -> (Rect, bool) {
+        let elapsed = now.saturating_duration_since(self.start);
+        if self.duration.is_zero() || elapsed >= self.duration {
+            return (self.to, true);
+        }
+        let t = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        (interpolate(self.from, self.to, ease_out(t)), false)
+    }
+}
+
+/// Cubic ease-out: fast start, gentle landing
+fn ease_out(t: f64) -> f64 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Linear interpolation between two rectangles (`t` in 0..=1)
+fn interpolate(from: Rect, to: Rect, t: f64) -> Rect {
+    let lerp = |a: f64, b: f64| a + (b - a) * t;
+    Rect::new(
+        lerp(from.x as f64, to.x as f64).round() as i32,
+        lerp(from.y as f64, to.y as f64).round() as i32,
+        lerp(from.width as f64, to.width as f64).round().max(1.0) as u32,
+        lerp(from.height as f64, to.height as f64).round().max(1.0) as u32,
+    )
+}
+
+/// Rectangle a window starts from when animating into `to`
+pub fn start_rect(kind: SpawnAnimation, to: Rect) -> Rect {
+    match kind {
+        SpawnAnimation::None => to,
+        SpawnAnimation::Slide => {
+            // 90% of the final size, centered horizontally and pushed down
+            let width = (to.width * 9 / 10).max(1);
+            let height = (to.height * 9 / 10).max(1);
+            let x = to.x + (to.width - width) as i32 / 2;
+            let y = to.y + (to.height / 10) as i32;
+            Rect::new(x, y, width, height)
+        }
+    }
+}
+
+/// Running spawn animations
+#[derive(Default)]
+pub struct Animator {
+    active: HashMap<Window, Animation>,
+    last_step: Option<Instant>,
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start animating `window` from `from` to `to`.
+    pub fn start(&mut self, window: Window, from: Rect, to: Rect, duration: Duration) {
+        self.active.insert(window, Animation { from, to, start: Instant::now(), duration });
+    }
+
+    /// Whether `window` is currently animating.
+    pub fn is_animating(&self, window: Window) -> bool {
+        self.active.contains_key(&window)
+    }
+
+    /// Change the destination of a running animation (e.g. the layout changed
+    /// while the window was still moving).
+    pub fn retarget(&mut self, window: Window, to: Rect) {
+        if let Some(animation) = self.active.get_mut(&window) {
+            animation.to = to;
+        }
+    }
+
+    /// Geometry of a running animation at `now`.
+    pub fn current_rect(&self, window: Window, now: Instant) -> Option<Rect> {
+        self.active.get(&window).map(|animation| animation.rect_at(now).0)
+    }
+
+    /// Stop animating `window` (it was hidden or unmanaged).
+    pub fn cancel(&mut self, window: Window) {
+        self.active.remove(&window);
+    }
+
+    /// Advance all animations to `now`, returning the geometry to apply to each
+    /// window. Finished animations are dropped after their final step.
+    /// Returns nothing if called again within `FRAME_INTERVAL`.
+    pub fn step(&mut self, now: Instant) -> Vec<(Window, Rect)> {
+        if self.active.is_empty() {
+            return Vec::new();
+        }
+        if self.last_step.is_some_and(|last| now.saturating_duration_since(last) < FRAME_INTERVAL) {
+            return Vec::new();
+        }
+        self.last_step = Some(now);
+
+        let mut steps = Vec::with_capacity(self.active.len());
+        self.active.retain(|&window, animation| {
+            let (rect, done) = animation.rect_at(now);
+            steps.push((window, rect));
+            !done
+        });
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slide_start_rect() {
+        let to = Rect::new(100, 50, 1000, 500);
+        assert_eq!(start_rect(SpawnAnimation::Slide, to), Rect::new(150, 100, 900, 450));
+        assert_eq!(start_rect(SpawnAnimation::None, to), to);
+    }
+
+    #[test]
+    fn test_animation_steps_to_target() {
+        let from = Rect::new(0, 100, 800, 400);
+        let to = Rect::new(0, 0, 1000, 500);
+        let animation = Animation { from, to, start: Instant::now(), duration: Duration::from_millis(100) };
+
+        let (first, done) = animation.rect_at(animation.start);
+        assert_eq!(first, from);
+        assert!(!done);
+
+        let (mid, done) = animation.rect_at(animation.start + Duration::from_millis(50));
+        assert!(!done);
+        assert!(mid.y < from.y && mid.y > to.y);
+        assert!(mid.width > from.width && mid.width < to.width);
+
+        let (last, done) = animation.rect_at(animation.start + Duration::from_millis(150));
+        assert_eq!(last, to);
+        assert!(done);
+    }
+
+    #[test]
+    fn test_animator_finishes_and_retargets() {
+        let mut animator = Animator::new();
+        assert!(animator.step(Instant::now()).is_empty());
+
+        let target = Rect::new(0, 0, 400, 300);
+        animator.start(1, Rect::new(0, 30, 360, 270), Rect::new(0, 0, 10, 10), Duration::from_millis(50));
+        animator.retarget(1, target);
+        assert!(animator.is_animating(1));
+        assert_eq!(animator.current_rect(1, Instant::now() + Duration::from_secs(1)), Some(target));
+
+        let steps = animator.step(Instant::now() + Duration::from_millis(100));
+        assert_eq!(steps, vec![(1, target)]);
+        assert!(!animator.is_animating(1));
+    }
+}
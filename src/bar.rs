@@ -0,0 +1,226 @@
+//! Optional built-in status bar.
+//!
+//! For setups without an external panel, `[bar] enabled = true` gives every
+//! monitor a thin override-redirect window along its top or bottom edge
+//! showing workspace indicators and the focused window's title. Its height is
+//! taken out of the monitor's usable area the same way a dock's strut is, so
+//! tiled windows never overlap it. With the bar disabled no windows are
+//! created and nothing is reserved.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::config::{BarPosition, LayoutConfig};
+use crate::monitor::MonitorId;
+use crate::render::FontRenderer;
+use crate::types::Rect;
+use crate::workspaces::NUM_WORKSPACES;
+
+/// Horizontal padding around each indicator and the title
+const PADDING: u32 = 6;
+
+/// What a bar shows for one monitor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BarContent {
+    /// Workspace shown on the monitor (0-based)
+    pub current: usize,
+    /// Workspaces that contain at least one window
+    pub occupied: [bool; NUM_WORKSPACES],
+    /// Title of the focused window (empty if none)
+    pub title: String,
+}
+
+/// Where the bar sits on a monitor
+pub fn bar_rect(monitor: Rect, position: BarPosition, height: u32) -> Rect {
+    let height = height.min(monitor.height);
+    let y = match position {
+        BarPosition::Top => monitor.y,
+        BarPosition::Bottom => monitor.y + (monitor.height - height) as i32,
+    };
+    Rect::new(monitor.x, y, monitor.width, height)
+}
+
+/// The part of a monitor left over once the bar's height is reserved
+pub fn reserve(monitor: Rect, position: BarPosition, height: u32) -> Rect {
+    let height = height.min(monitor.height);
+    let y = match position {
+        BarPosition::Top => monitor.y + height as i32,
+        BarPosition::Bottom => monitor.y,
+    };
+    Rect::new(monitor.x, y, monitor.width, monitor.height - height)
+}
+
+/// Bar windows, one per monitor
+pub struct StatusBar {
+    windows: HashMap<MonitorId, (Window, Rect)>,
+}
+
+impl StatusBar {
+    /// Create an empty bar manager (no windows yet).
+    pub fn new() -> Self {
+        Self { windows: HashMap::new() }
+    }
+
+    /// Create and map a bar window on `monitor` (geometry `rect`), replacing
+    /// any previous one. Returns the new window.
+    pub fn create(
+        &mut self,
+        conn: &impl Connection,
+        root: Window,
+        monitor: MonitorId,
+        rect: Rect,
+        config: &LayoutConfig,
+    ) -> Result<Window> {
+        if let Some((old, _)) = self.windows.remove(&monitor) {
+            conn.destroy_window(old)?;
+        }
+        let window = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            rect.x as i16,
+            rect.y as i16,
+            rect.width.max(1) as u16,
+            rect.height.max(1) as u16,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+                .background_pixel(config.tab_bar_bg)
+                .override_redirect(1) // Don't manage this window
+                .event_mask(EventMask::EXPOSURE),
+        )?;
+        conn.map_window(window)?;
+        self.windows.insert(monitor, (window, rect));
+        Ok(window)
+    }
+
+    /// Destroy every bar window (before recreating them for new monitors)
+    pub fn destroy_all(&mut self, conn: &impl Connection) -> Result<()> {
+        for (_, (window, _)) in self.windows.drain() {
+            conn.destroy_window(window)?;
+        }
+        Ok(())
+    }
+
+    /// All bar windows
+    pub fn windows(&self) -> impl Iterator<Item = Window> + '_ {
+        self.windows.values().map(|&(window, _)| window)
+    }
+
+    /// Check whether a window is one of the bar windows
+    pub fn owns_window(&self, window: Window) -> bool {
+        self.windows.values().any(|&(w, _)| w == window)
+    }
+
+    /// Redraw the bar on `monitor`.
+    pub fn draw(
+        &self,
+        conn: &impl Connection,
+        monitor: MonitorId,
+        content: &BarContent,
+        font: &FontRenderer,
+        gc: Gcontext,
+        config: &LayoutConfig,
+    ) -> Result<()> {
+        let Some(&(window, rect)) = self.windows.get(&monitor) else {
+            return Ok(());
+        };
+        let height = rect.height as u16;
+        let text_y = (rect.height.saturating_sub(font.char_height()) / 2) as i16;
+
+        // Background
+        conn.change_gc(gc, &ChangeGCAux::new().foreground(config.tab_bar_bg))?;
+        conn.poly_fill_rectangle(window, gc, &[Rectangle { x: 0, y: 0, width: rect.width as u16, height }])?;
+
+        // Workspace indicators: the current one highlighted, empty ones dimmed
+        let cell_width = (font.measure_text("9") + PADDING * 2).max(rect.height);
+        let mut x = 0u32;
+        for ws in 0..NUM_WORKSPACES {
+            let label = (ws + 1).to_string();
+            let (bg, fg) = if ws == content.current {
+                (config.tab_focused_bg, config.tab_text_color)
+            } else if content.occupied[ws] {
+                (config.tab_unfocused_bg, config.tab_text_color)
+            } else {
+                (config.tab_bar_bg, config.tab_text_unfocused)
+            };
+            conn.change_gc(gc, &ChangeGCAux::new().foreground(bg))?;
+            conn.poly_fill_rectangle(window, gc, &[Rectangle {
+                x: x as i16,
+                y: 0,
+                width: cell_width as u16,
+                height,
+            }])?;
+            let label_x = x + (cell_width.saturating_sub(font.measure_text(&label))) / 2;
+            put_text(conn, window, gc, font, &label, fg, bg, label_x as i16, text_y)?;
+            x += cell_width;
+        }
+
+        // Focused window title in the remaining space
+        let title_x = x + PADDING;
+        let title_width = rect.width.saturating_sub(title_x + PADDING);
+        if !content.title.is_empty() && title_width > 0 {
+            let title = font.truncate_text_to_width(&content.title, title_width);
+            put_text(conn, window, gc, font, &title, config.tab_text_color, config.tab_bar_bg, title_x as i16, text_y)?;
+        }
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+/// Render `text` and copy it into `window` at (x, y)
+#[allow(clippy::too_many_arguments)]
+fn put_text(
+    conn: &impl Connection,
+    window: Window,
+    gc: Gcontext,
+    font: &FontRenderer,
+    text: &str,
+    fg: u32,
+    bg: u32,
+    x: i16,
+    y: i16,
+) -> Result<()> {
+    let (pixels, w, h) = font.render_text(text, fg, bg);
+    if !pixels.is_empty() && w > 0 && h > 0 {
+        conn.put_image(
+            ImageFormat::Z_PIXMAP,
+            window,
+            gc,
+            w as u16,
+            h as u16,
+            x,
+            y,
+            0,
+            24, // depth (24-bit color, will be padded to 32)
+            &pixels,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar_rect_and_reserved_area() {
+        let monitor = Rect::new(1920, 0, 1920, 1080);
+        assert_eq!(bar_rect(monitor, BarPosition::Top, 20), Rect::new(1920, 0, 1920, 20));
+        assert_eq!(reserve(monitor, BarPosition::Top, 20), Rect::new(1920, 20, 1920, 1060));
+        assert_eq!(bar_rect(monitor, BarPosition::Bottom, 20), Rect::new(1920, 1060, 1920, 20));
+        assert_eq!(reserve(monitor, BarPosition::Bottom, 20), Rect::new(1920, 0, 1920, 1060));
+    }
+
+    #[test]
+    fn test_bar_height_is_capped_to_monitor() {
+        let monitor = Rect::new(0, 0, 800, 10);
+        assert_eq!(bar_rect(monitor, BarPosition::Bottom, 50), Rect::new(0, 0, 800, 10));
+        assert_eq!(reserve(monitor, BarPosition::Top, 50).height, 0);
+    }
+}
@@ -0,0 +1,190 @@
+//! Pointer barriers at the edges shared by monitors (`general.pointer_barriers`).
+//!
+//! An XFixes pointer barrier along each shared edge stops the pointer from
+//! drifting onto the neighbouring monitor. XInput 2.3 reports every push
+//! against a barrier; once the pushes add up to `pointer_barrier_push` pixels
+//! the pointer is released and crosses. Pressure starts over whenever the
+//! pointer leaves a barrier. Disabled if either extension is missing.
+
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xfixes::{self, Barrier, BarrierDirections, ConnectionExt as _};
+use x11rb::protocol::xinput::{self, BarrierHitEvent, ConnectionExt as _, Fp3232};
+use x11rb::protocol::xproto::Window;
+
+use crate::monitor::Edge;
+
+/// Barriers on the shared monitor edges and the push against the current one.
+pub struct PointerBarriers {
+    /// Barriers and the edge each one covers
+    barriers: Vec<(Barrier, Edge)>,
+    /// Pixels of push needed to cross
+    threshold: u32,
+    /// Barrier and event ID of the current push, and its total so far
+    push: Option<(Barrier, u32, f64)>,
+    available: bool,
+}
+
+impl PointerBarriers {
+    /// Create the manager, negotiating XFixes 5 and XInput 2.3 and selecting
+    /// barrier events on the root window.
+    pub fn new(conn: &impl Connection, root: Window, threshold: u32) -> Self {
+        let available = Self::init_extensions(conn, root).unwrap_or(false);
+        if !available {
+            log::warn!("XFixes 5/XInput 2.3 unavailable, pointer barriers disabled");
+        }
+        Self {
+            barriers: Vec::new(),
+            threshold,
+            push: None,
+            available,
+        }
+    }
+
+    fn init_extensions(conn: &impl Connection, root: Window) -> Result<bool> {
+        if conn.extension_information(xfixes::X11_EXTENSION_NAME)?.is_none()
+            || conn.extension_information(xinput::X11_EXTENSION_NAME)?.is_none()
+        {
+            return Ok(false);
+        }
+        let fixes = conn.xfixes_query_version(5, 0)?.reply()?;
+        let input = conn.xinput_xi_query_version(2, 3)?.reply()?;
+        if fixes.major_version < 5 || (input.major_version, input.minor_version) < (2, 3) {
+            return Ok(false);
+        }
+        conn.xinput_xi_select_events(
+            root,
+            &[xinput::EventMask {
+                deviceid: u16::from(bool::from(xinput::Device::ALL_MASTER)),
+                mask: vec![xinput::XIEventMask::BARRIER_HIT | xinput::XIEventMask::BARRIER_LEAVE],
+            }],
+        )?;
+        Ok(true)
+    }
+
+    /// Replace the barriers with one per edge, blocking both directions.
+    pub fn update(&mut self, conn: &impl Connection, root: Window, edges: &[Edge]) -> Result<()> {
+        self.destroy(conn)?;
+        if !self.available {
+            return Ok(());
+        }
+        for &edge in edges {
+            let barrier = conn.generate_id()?;
+            conn.xfixes_create_pointer_barrier(
+                barrier,
+                root,
+                edge.x1 as u16,
+                edge.y1 as u16,
+                edge.x2 as u16,
+                edge.y2 as u16,
+                BarrierDirections::from(0u32),
+                &[],
+            )?;
+            self.barriers.push((barrier, edge));
+        }
+        if !edges.is_empty() {
+            log::info!("Created {} pointer barrier(s)", edges.len());
+        }
+        Ok(())
+    }
+
+    /// Remove every barrier (on monitor changes and at exit).
+    pub fn destroy(&mut self, conn: &impl Connection) -> Result<()> {
+        for (barrier, _) in self.barriers.drain(..) {
+            conn.xfixes_delete_pointer_barrier(barrier)?;
+        }
+        self.push = None;
+        Ok(())
+    }
+
+    /// The pointer pushed against a barrier: release it once the push
+    /// reaches the threshold.
+    pub fn handle_hit(&mut self, conn: &impl Connection, event: &BarrierHitEvent) -> Result<()> {
+        let Some(&(_, edge)) = self.barriers.iter().find(|(b, _)| *b == event.barrier) else {
+            return Ok(());
+        };
+        // Only movement across the barrier counts as pushing
+        let delta = if edge.is_vertical() { event.dx } else { event.dy };
+        if self.push_through(event.barrier, event.eventid, fp3232_to_f64(delta).abs()) {
+            conn.xinput_xi_barrier_release_pointer(&[xinput::BarrierReleasePointerInfo {
+                deviceid: event.deviceid,
+                barrier: event.barrier,
+                eventid: event.eventid,
+            }])?;
+            log::debug!("Pointer released through barrier 0x{:x}", event.barrier);
+        }
+        Ok(())
+    }
+
+    /// The pointer moved away from a barrier.
+    pub fn handle_leave(&mut self, event: &BarrierHitEvent) {
+        if self.push.is_some_and(|(barrier, _, _)| barrier == event.barrier) {
+            self.push = None;
+        }
+    }
+
+    /// Add `distance` to the push against `barrier` during barrier event
+    /// `eventid` (a new event ID means a new push). Returns true once the
+    /// push reaches the threshold, starting over for the next one.
+    fn push_through(&mut self, barrier: Barrier, eventid: u32, distance: f64) -> bool {
+        let total = match self.push {
+            Some((b, id, total)) if b == barrier && id == eventid => total + distance,
+            _ => distance,
+        };
+        if total >= self.threshold as f64 {
+            self.push = None;
+            true
+        } else {
+            self.push = Some((barrier, eventid, total));
+            false
+        }
+    }
+}
+
+/// Convert XInput's 32.32 fixed point to a float.
+fn fp3232_to_f64(value: Fp3232) -> f64 {
+    value.integral as f64 + value.frac as f64 / (1u64 << 32) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(threshold: u32) -> PointerBarriers {
+        PointerBarriers {
+            barriers: Vec::new(),
+            threshold,
+            push: None,
+            available: false,
+        }
+    }
+
+    #[test]
+    fn test_push_through_threshold() {
+        let mut barriers = manager(100);
+        assert!(!barriers.push_through(1, 7, 40.0));
+        assert!(!barriers.push_through(1, 7, 40.0));
+        assert!(barriers.push_through(1, 7, 25.0));
+
+        // Pushes don't carry over to a new barrier event or another barrier
+        assert!(!barriers.push_through(1, 8, 60.0));
+        assert!(!barriers.push_through(2, 8, 60.0));
+        assert!(!barriers.push_through(2, 9, 60.0));
+
+        // Leaving another barrier keeps the push; leaving this one ends it
+        barriers.handle_leave(&BarrierHitEvent { barrier: 1, ..Default::default() });
+        assert_eq!(barriers.push.map(|(b, id, _)| (b, id)), Some((2, 9)));
+        barriers.handle_leave(&BarrierHitEvent { barrier: 2, ..Default::default() });
+        assert!(barriers.push.is_none());
+        assert!(!barriers.push_through(2, 9, 60.0));
+
+        // A zero threshold lets every push through
+        assert!(manager(0).push_through(1, 1, 0.0));
+    }
+
+    #[test]
+    fn test_fp3232_to_f64() {
+        assert_eq!(fp3232_to_f64(Fp3232 { integral: 3, frac: 1 << 31 }), 3.5);
+        assert_eq!(fp3232_to_f64(Fp3232 { integral: -2, frac: 0 }), -2.0);
+    }
+}
@@ -74,15 +74,44 @@ enum Commands {
     Focused,
 
     /// Validate WM state invariants
-    Validate,
+    Validate {
+        /// Also dump ttwm's internal maps and check them for orphaned entries
+        #[arg(long)]
+        verify: bool,
+    },
 
     /// Get recent event log
     EventLog {
         /// Number of events to retrieve
         #[arg(short, long)]
         count: Option<usize>,
+        /// Only events after this sequence number (for incremental polling)
+        #[arg(long)]
+        since: Option<u64>,
+        /// Only events of this kind (x11, transition, ipc) or exact type
+        /// (e.g. MapRequest, window_managed)
+        #[arg(long = "type")]
+        event_type: Option<String>,
     },
 
+    /// List processes spawned by ttwm with their PIDs and exit status
+    Children,
+
+    /// Show interned atoms and what is advertised in _NET_SUPPORTED
+    Atoms,
+
+    /// List the WM_PROTOCOLS a window supports (WM_DELETE_WINDOW, WM_TAKE_FOCUS, ...)
+    Protocols {
+        /// Window ID (decimal or hex with 0x prefix)
+        window: String,
+    },
+
+    /// Show tab bar and layout render timings (ttwm must run with --profile)
+    RenderStats,
+
+    /// Describe the JSON fields of every response (and the schema version)
+    Schema,
+
     /// Focus a specific window by ID
     Focus {
         /// Window ID (decimal or hex with 0x prefix)
@@ -95,6 +124,14 @@ enum Commands {
         index: usize,
     },
 
+    /// Focus a tab of a named frame, wherever the frame is
+    FocusTabIn {
+        /// Frame name
+        frame: String,
+        /// Tab index (1-based)
+        index: usize,
+    },
+
     /// Focus the next or previous frame
     FocusFrame {
         /// Direction: next or prev
@@ -107,6 +144,12 @@ enum Commands {
         direction: String,
     },
 
+    /// Add an empty frame beside the focused one without moving its windows
+    NewFrame {
+        /// Side of the focused frame: left, right, up or down
+        direction: String,
+    },
+
     /// Move the focused window to an adjacent frame
     MoveWindow {
         /// Direction: next or prev
@@ -119,9 +162,18 @@ enum Commands {
         direction: String,
     },
 
+    /// Size the first child of the focused split to an exact pixel count
+    SplitSize {
+        /// Width (horizontal split) or height (vertical split) in pixels
+        pixels: u32,
+    },
+
     /// Close the focused window
     Close,
 
+    /// Close every window in the focused frame
+    CloseFrame,
+
     /// Cycle tabs in the focused frame
     CycleTab {
         /// Direction: next or prev
@@ -129,6 +181,36 @@ enum Commands {
         direction: String,
     },
 
+    /// Move the focused tab to the first position in its frame
+    Promote,
+
+    /// Swap the focused frame with its sibling (flip the two panes of a split)
+    SwapWithSibling,
+
+    /// Remove empty frames, collapsing their splits (keeps focused, named and locked frames)
+    Normalize,
+
+    /// Move every window on the current workspace into a single tabbed frame
+    Flatten,
+
+    /// Give frames side by side in the same direction equal space
+    Balance,
+
+    /// Re-apply every window's border width and focused/unfocused color
+    RepairBorders,
+
+    /// Undo the last layout operation (split, move, close, reorder)
+    Undo,
+
+    /// Run a keybinding action by name, e.g. cycle_tab_forward or focus_tab_2
+    Action {
+        /// Action name (the key used in [keybindings])
+        name: String,
+        /// Number of times to run it (1-100)
+        #[arg(long, short = 'n', default_value = "1")]
+        repeat: u32,
+    },
+
     /// Tag a window (uses focused window if not specified)
     Tag {
         /// Window ID (decimal or hex with 0x prefix)
@@ -156,6 +238,26 @@ enum Commands {
     /// Get list of tagged window IDs
     Tagged,
 
+    /// Mark a window with a single character (uses focused window if not specified)
+    Mark {
+        /// Mark character
+        name: String,
+        /// Window ID (decimal or hex with 0x prefix)
+        window: Option<String>,
+    },
+
+    /// Focus the window with a mark (switches workspace/monitor as needed)
+    GotoMark {
+        /// Mark character
+        name: String,
+    },
+
+    /// Move a window on the current workspace into the focused frame as a new tab
+    Pull {
+        /// Window ID (decimal or hex with 0x prefix)
+        window: String,
+    },
+
     /// Toggle floating state for a window
     ToggleFloat {
         /// Window ID (uses focused if not specified)
@@ -165,6 +267,12 @@ enum Commands {
     /// Get list of floating window IDs
     Floating,
 
+    /// Toggle pinning a floating window to every workspace of its monitor
+    PinFloat {
+        /// Window ID (uses focused if not specified)
+        window: Option<String>,
+    },
+
     /// Toggle fullscreen state for a window
     ToggleFullscreen {
         /// Window ID (uses focused if not specified)
@@ -174,6 +282,12 @@ enum Commands {
     /// Get fullscreen window ID (if any)
     Fullscreen,
 
+    /// Toggle zoom across all monitors for a window
+    ToggleZoom {
+        /// Window ID (uses focused if not specified)
+        window: Option<String>,
+    },
+
     /// Switch to a workspace (1-9) or next/prev
     Workspace {
         /// Workspace number (1-9) or "next" or "prev"
@@ -204,6 +318,21 @@ enum Commands {
         target: String,
     },
 
+    /// Focus a monitor by its exact output name (case-sensitive)
+    FocusMonitorByName {
+        /// Output name as shown by `ttwmctl monitors` (e.g., "DP-1")
+        name: String,
+    },
+
+    /// Show the focused monitor with its geometry
+    FocusedMonitor,
+
+    /// Treat all outputs as one big screen (merged) or as separate monitors (split)
+    MonitorMode {
+        /// merged or split
+        mode: String,
+    },
+
     /// Set or clear the name of the focused frame
     NameFrame {
         /// Name to assign (omit or use --clear to remove name)
@@ -219,12 +348,95 @@ enum Commands {
         name: String,
     },
 
+    /// Lock or unlock the focused frame (locked frames don't receive new windows)
+    ToggleFrameLock,
+
+    /// Show or query vertical tabs on the focused frame
+    VerticalTabs {
+        /// "on" or "off" (omit to query)
+        state: Option<String>,
+    },
+
+    /// Switch the focused frame between tabbed and stacked titles
+    ToggleStacked,
+
+    /// Tint a named frame's empty placeholder and tab bar background
+    FrameColor {
+        /// Frame name
+        name: String,
+        /// Color as #rrggbb (omit to clear the tint)
+        color: Option<String>,
+    },
+
+    /// Set how the focused frame sizes its tabs
+    TabMode {
+        /// content, equal, fixed, or default (use appearance.tab_sizing)
+        mode: String,
+    },
+
+    /// Show a view-only copy of a window in a named frame
+    Mirror {
+        /// Window ID to mirror
+        window: String,
+        /// Name of the frame to show the mirror in
+        frame: String,
+    },
+
+    /// Print a workspace's layout as a [startup.workspace.N] config snippet
+    ExportConfig {
+        /// Workspace number (1-9, default: current)
+        workspace: Option<usize>,
+    },
+
+    /// Query or set focus-follows-mouse
+    FocusFollowsMouse {
+        /// "on" or "off" (omit to query)
+        state: Option<String>,
+    },
+
+    /// Toggle presentation mode (disables every keybinding except its own toggle)
+    Presentation,
+
+    /// Change gaps live (values clamp to 0-200; omit both to query)
+    Gaps {
+        /// Gap between windows in pixels
+        #[arg(long)]
+        gap: Option<u32>,
+        /// Margin from the screen edge in pixels
+        #[arg(long)]
+        outer: Option<u32>,
+    },
+
+    /// Switch to a theme file (colors, gaps and borders)
+    Theme {
+        /// Path to the theme TOML
+        path: PathBuf,
+    },
+
+    /// Set the root window wallpaper (PNG)
+    Wallpaper {
+        /// Path to the image
+        path: PathBuf,
+        /// How to fit the image: center, tile, stretch or fill
+        #[arg(long, default_value = "fill")]
+        mode: String,
+    },
+
+    /// Save which workspace each monitor is showing
+    SaveSession,
+
+    /// Restore per-monitor workspaces from the saved session
+    RestoreSession,
+
     /// Capture a screenshot
     Screenshot {
         /// Path to save the screenshot
         path: PathBuf,
     },
 
+    /// Re-apply layout, redraw tab bars and re-publish EWMH properties
+    Refresh,
+
     /// Quit the window manager
     Quit,
 }
@@ -240,10 +452,24 @@ fn main() {
         Commands::Layout => serde_json::json!({"command": "get_layout"}),
         Commands::Windows => serde_json::json!({"command": "get_windows"}),
         Commands::Focused => serde_json::json!({"command": "get_focused"}),
-        Commands::Validate => serde_json::json!({"command": "validate_state"}),
-        Commands::EventLog { count } => {
-            serde_json::json!({"command": "get_event_log", "count": count})
+        Commands::Validate { verify: false } => serde_json::json!({"command": "validate_state"}),
+        Commands::Validate { verify: true } => serde_json::json!({"command": "verify_state"}),
+        Commands::EventLog { count, since, event_type } => {
+            serde_json::json!({
+                "command": "get_event_log",
+                "count": count,
+                "since_seq": since,
+                "event_type": event_type,
+            })
+        }
+        Commands::Children => serde_json::json!({"command": "get_children"}),
+        Commands::Atoms => serde_json::json!({"command": "get_atoms"}),
+        Commands::Protocols { window } => {
+            let window_id = parse_window_id(window);
+            serde_json::json!({"command": "get_protocols", "window": window_id})
         }
+        Commands::RenderStats => serde_json::json!({"command": "get_render_stats"}),
+        Commands::Schema => serde_json::json!({"command": "get_schema"}),
         Commands::Focus { window } => {
             let window_id = parse_window_id(window);
             serde_json::json!({"command": "focus_window", "window": window_id})
@@ -251,6 +477,9 @@ fn main() {
         Commands::FocusTab { index } => {
             serde_json::json!({"command": "focus_tab", "index": index})
         }
+        Commands::FocusTabIn { frame, index } => {
+            serde_json::json!({"command": "focus_tab_in_frame", "frame": frame, "index": index})
+        }
         Commands::FocusFrame { direction } => {
             let forward = direction.to_lowercase() != "prev";
             serde_json::json!({"command": "focus_frame", "forward": forward})
@@ -258,6 +487,9 @@ fn main() {
         Commands::Split { direction } => {
             serde_json::json!({"command": "split", "direction": direction})
         }
+        Commands::NewFrame { direction } => {
+            serde_json::json!({"command": "new_frame", "direction": direction})
+        }
         Commands::MoveWindow { direction } => {
             let forward = direction.to_lowercase() != "prev";
             serde_json::json!({"command": "move_window", "forward": forward})
@@ -266,11 +498,23 @@ fn main() {
             let delta = if direction.to_lowercase() == "grow" { 0.05 } else { -0.05 };
             serde_json::json!({"command": "resize_split", "delta": delta})
         }
+        Commands::SplitSize { pixels } => serde_json::json!({"command": "set_split_pixels", "pixels": pixels}),
         Commands::Close => serde_json::json!({"command": "close_window"}),
+        Commands::CloseFrame => serde_json::json!({"command": "close_frame"}),
         Commands::CycleTab { direction } => {
             let forward = direction.to_lowercase() != "prev";
             serde_json::json!({"command": "cycle_tab", "forward": forward})
         }
+        Commands::Promote => serde_json::json!({"command": "promote"}),
+        Commands::SwapWithSibling => serde_json::json!({"command": "swap_with_sibling"}),
+        Commands::Normalize => serde_json::json!({"command": "normalize_tree"}),
+        Commands::Flatten => serde_json::json!({"command": "flatten_workspace"}),
+        Commands::Balance => serde_json::json!({"command": "balance_splits"}),
+        Commands::RepairBorders => serde_json::json!({"command": "repair_borders"}),
+        Commands::Undo => serde_json::json!({"command": "undo"}),
+        Commands::Action { name, repeat } => {
+            serde_json::json!({"command": "send_action", "action": name, "repeat": repeat})
+        }
         Commands::Tag { window } => {
             let window_id = window.as_ref().map(|w| parse_window_id(w));
             serde_json::json!({"command": "tag_window", "window": window_id})
@@ -286,16 +530,33 @@ fn main() {
         Commands::MoveTagged => serde_json::json!({"command": "move_tagged"}),
         Commands::UntagAll => serde_json::json!({"command": "untag_all"}),
         Commands::Tagged => serde_json::json!({"command": "get_tagged"}),
+        Commands::Mark { name, window } => {
+            let window_id = window.as_ref().map(|w| parse_window_id(w));
+            serde_json::json!({"command": "mark", "name": name, "window": window_id})
+        }
+        Commands::GotoMark { name } => serde_json::json!({"command": "goto_mark", "name": name}),
+        Commands::Pull { window } => {
+            let window_id = parse_window_id(window);
+            serde_json::json!({"command": "pull_window", "window": window_id})
+        }
         Commands::ToggleFloat { window } => {
             let window_id = window.as_ref().map(|w| parse_window_id(w));
             serde_json::json!({"command": "toggle_float", "window": window_id})
         }
         Commands::Floating => serde_json::json!({"command": "get_floating"}),
+        Commands::PinFloat { window } => {
+            let window_id = window.as_ref().map(|w| parse_window_id(w));
+            serde_json::json!({"command": "pin_float_global", "window": window_id})
+        }
         Commands::ToggleFullscreen { window } => {
             let window_id = window.as_ref().map(|w| parse_window_id(w));
             serde_json::json!({"command": "toggle_fullscreen", "window": window_id})
         }
         Commands::Fullscreen => serde_json::json!({"command": "get_fullscreen"}),
+        Commands::ToggleZoom { window } => {
+            let window_id = window.as_ref().map(|w| parse_window_id(w));
+            serde_json::json!({"command": "toggle_zoom", "window": window_id})
+        }
         Commands::Workspace { target } => {
             let lower = target.to_lowercase();
             if lower == "next" {
@@ -308,7 +569,7 @@ fn main() {
                     eprintln!("Invalid workspace: {}. Use 1-9 or next/prev", target);
                     std::process::exit(1);
                 });
-                if num < 1 || num > 9 {
+                if !(1..=9).contains(&num) {
                     eprintln!("Workspace must be 1-9, got {}", num);
                     std::process::exit(1);
                 }
@@ -333,6 +594,18 @@ fn main() {
         Commands::FocusMonitor { target } => {
             serde_json::json!({"command": "focus_monitor", "target": target})
         }
+        Commands::FocusMonitorByName { name } => {
+            serde_json::json!({"command": "focus_monitor_by_name", "name": name})
+        }
+        Commands::FocusedMonitor => serde_json::json!({"command": "get_focused_monitor"}),
+        Commands::MonitorMode { mode } => match mode.to_lowercase().as_str() {
+            "merged" => serde_json::json!({"command": "set_monitor_mode", "merged": true}),
+            "split" => serde_json::json!({"command": "set_monitor_mode", "merged": false}),
+            other => {
+                eprintln!("Invalid monitor mode: {}. Use merged or split", other);
+                std::process::exit(1);
+            }
+        },
         Commands::NameFrame { name, clear } => {
             // If --clear is specified, clear the name
             // If a name is provided, set it
@@ -353,9 +626,70 @@ fn main() {
         Commands::FindFrame { name } => {
             serde_json::json!({"command": "get_frame_by_name", "name": name})
         }
+        Commands::ToggleFrameLock => serde_json::json!({"command": "toggle_frame_lock"}),
+        Commands::VerticalTabs { state } => match state.as_deref().map(str::to_lowercase).as_deref() {
+            None => serde_json::json!({"command": "get_vertical_tabs"}),
+            Some("on") => serde_json::json!({"command": "set_vertical_tabs", "enabled": true}),
+            Some("off") => serde_json::json!({"command": "set_vertical_tabs", "enabled": false}),
+            Some(other) => {
+                eprintln!("Invalid state: {}. Use on or off", other);
+                std::process::exit(1);
+            }
+        },
+        Commands::ToggleStacked => serde_json::json!({"command": "toggle_stacked"}),
+        Commands::FrameColor { name, color } => {
+            serde_json::json!({"command": "set_frame_color", "name": name, "color": color})
+        }
+        Commands::TabMode { mode } => match mode.to_lowercase().as_str() {
+            "default" => serde_json::json!({"command": "set_frame_tab_mode"}),
+            "content" | "equal" | "fixed" => {
+                serde_json::json!({"command": "set_frame_tab_mode", "mode": mode.to_lowercase()})
+            }
+            other => {
+                eprintln!("Invalid tab mode: {}. Use content, equal, fixed or default", other);
+                std::process::exit(1);
+            }
+        },
+        Commands::Mirror { window, frame } => {
+            serde_json::json!({"command": "mirror_window", "window": parse_window_id(window), "frame": frame})
+        }
+        Commands::ExportConfig { workspace } => {
+            if let Some(n) = workspace.filter(|n| !(1..=9).contains(n)) {
+                eprintln!("Workspace must be 1-9, got {}", n);
+                std::process::exit(1);
+            }
+            let index = workspace.map(|n| n - 1);
+            serde_json::json!({"command": "export_startup_config", "workspace": index})
+        }
+        Commands::FocusFollowsMouse { state } => match state.as_deref().map(str::to_lowercase).as_deref() {
+            None => serde_json::json!({"command": "get_focus_follows_mouse"}),
+            Some("on") => serde_json::json!({"command": "set_focus_follows_mouse", "enabled": true}),
+            Some("off") => serde_json::json!({"command": "set_focus_follows_mouse", "enabled": false}),
+            Some(other) => {
+                eprintln!("Invalid state: {}. Use on or off", other);
+                std::process::exit(1);
+            }
+        },
+        Commands::Presentation => serde_json::json!({"command": "toggle_presentation_mode"}),
+        Commands::Gaps { gap, outer } => {
+            serde_json::json!({"command": "set_gaps", "gap": gap, "outer_gap": outer})
+        }
+        Commands::Theme { path } => {
+            // Relative paths would otherwise be taken from the config's directory
+            let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            serde_json::json!({"command": "set_theme", "path": path.to_string_lossy()})
+        }
+        Commands::Wallpaper { path, mode } => {
+            // The WM resolves paths relative to its own working directory
+            let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            serde_json::json!({"command": "set_wallpaper", "path": path.to_string_lossy(), "mode": mode})
+        }
+        Commands::SaveSession => serde_json::json!({"command": "save_session"}),
+        Commands::RestoreSession => serde_json::json!({"command": "restore_session"}),
         Commands::Screenshot { path } => {
             serde_json::json!({"command": "screenshot", "path": path.to_string_lossy()})
         }
+        Commands::Refresh => serde_json::json!({"command": "refresh"}),
         Commands::Quit => serde_json::json!({"command": "quit"}),
     };
 
@@ -420,8 +754,10 @@ fn send_command(socket_path: &PathBuf, command: &Value, raw: bool) -> std::io::R
         }
     }
 
-    // Output the response
-    if raw {
+    // Output the response (config snippets are printed as-is for pasting)
+    if let (false, Some(toml)) = (raw, value.get("toml").and_then(|v| v.as_str())) {
+        print!("{}", toml);
+    } else if raw {
         println!("{}", response.trim());
     } else {
         let pretty = serde_json::to_string_pretty(&value)?;
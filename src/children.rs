@@ -0,0 +1,140 @@
+//! Tracking of processes spawned by ttwm.
+//!
+//! Every program started from an exec binding, the launcher or the startup
+//! config is recorded here with its PID. Exited children are reaped from the
+//! main loop (so they don't linger as zombies) and kept for a while with
+//! their exit status, which helps answer "did my terminal actually launch?".
+
+use std::collections::VecDeque;
+use std::process::Child;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ipc::ChildInfo;
+
+/// Number of exited children kept for inspection
+pub const EXITED_HISTORY_LIMIT: usize = 50;
+
+struct TrackedChild {
+    /// Process handle; None once the child has been reaped
+    child: Option<Child>,
+    pid: u32,
+    command: String,
+    /// Seconds since the Unix epoch
+    spawned_at: u64,
+    exit_code: Option<i32>,
+}
+
+/// Table of spawned processes
+#[derive(Default)]
+pub struct ChildTracker {
+    /// Children in spawn order (running and recently exited)
+    children: VecDeque<TrackedChild>,
+}
+
+impl ChildTracker {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a newly spawned child.
+    pub fn track(&mut self, command: &str, child: Child) {
+        let spawned_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.children.push_back(TrackedChild {
+            pid: child.id(),
+            child: Some(child),
+            command: command.to_string(),
+            spawned_at,
+            exit_code: None,
+        });
+    }
+
+    /// Reap children that have exited and trim old exited entries.
+    pub fn reap(&mut self) {
+        for tracked in self.children.iter_mut() {
+            let Some(child) = tracked.child.as_mut() else {
+                continue;
+            };
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    log::debug!("Child {} ('{}') exited: {}", tracked.pid, tracked.command, status);
+                    tracked.exit_code = status.code();
+                    tracked.child = None;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("Failed to check child {}: {}", tracked.pid, e);
+                    tracked.child = None;
+                }
+            }
+        }
+
+        let mut exited = self.children.iter().filter(|c| c.child.is_none()).count();
+        while exited > EXITED_HISTORY_LIMIT {
+            if let Some(index) = self.children.iter().position(|c| c.child.is_none()) {
+                self.children.remove(index);
+            }
+            exited -= 1;
+        }
+    }
+
+    /// Snapshot of all tracked children, oldest first.
+    pub fn list(&self) -> Vec<ChildInfo> {
+        self.children.iter().map(|c| ChildInfo {
+            pid: c.pid,
+            command: c.command.clone(),
+            spawned_at: c.spawned_at,
+            alive: c.child.is_some(),
+            exit_code: c.exit_code,
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn wait_for_exit(tracker: &mut ChildTracker) {
+        for _ in 0..200 {
+            tracker.reap();
+            if tracker.list().iter().all(|c| !c.alive) {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("child did not exit");
+    }
+
+    #[test]
+    fn test_track_and_reap() {
+        let mut tracker = ChildTracker::new();
+        let child = Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap();
+        let pid = child.id();
+        tracker.track("sh -c 'exit 3'", child);
+
+        let listed = tracker.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].pid, pid);
+        assert!(listed[0].spawned_at > 0);
+
+        wait_for_exit(&mut tracker);
+        let listed = tracker.list();
+        assert!(!listed[0].alive);
+        assert_eq!(listed[0].exit_code, Some(3));
+    }
+
+    #[test]
+    fn test_exited_history_is_limited() {
+        let mut tracker = ChildTracker::new();
+        for _ in 0..EXITED_HISTORY_LIMIT + 5 {
+            let child = Command::new("true").spawn().unwrap();
+            tracker.track("true", child);
+        }
+        wait_for_exit(&mut tracker);
+        assert_eq!(tracker.list().len(), EXITED_HISTORY_LIMIT);
+    }
+}
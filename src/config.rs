@@ -1,14 +1,24 @@
 //! Configuration file support for ttwm.
 //!
 //! Loads settings from ~/.config/ttwm/config.toml if it exists,
-//! otherwise uses sensible defaults.
+//! otherwise uses sensible defaults. The path can be overridden with
+//! `--config <path>` or the `TTWM_CONFIG` environment variable.
 //!
 //! Also provides `LayoutConfig` - the runtime configuration struct with
 //! resolved color values and layout parameters.
 
-use serde::Deserialize;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::keysyms;
+use crate::layout::Direction;
+use crate::workspaces::NUM_WORKSPACES;
+
+/// Environment variable that overrides the config file path
+pub const CONFIG_ENV_VAR: &str = "TTWM_CONFIG";
 
 // =============================================================================
 // Runtime Configuration (resolved values)
@@ -31,6 +41,8 @@ pub struct LayoutConfig {
     pub tab_bar_height: u32,
     /// Vertical tab bar width (for vertical tabs)
     pub vertical_tab_width: u32,
+    /// Minimum width/height of a frame created by splitting (0 = no limit)
+    pub min_frame_size: u32,
     /// Tab bar background color
     pub tab_bar_bg: u32,
     /// Tab bar focused tab color
@@ -49,12 +61,48 @@ pub struct LayoutConfig {
     pub tab_text_unfocused: u32,
     /// Tab separator color
     pub tab_separator: u32,
+    /// Background of empty frame placeholders
+    pub empty_frame_bg: u32,
     /// Border color for focused window
     pub border_focused: u32,
+    /// Per-workspace overrides of `border_focused`, keyed by 0-based workspace index
+    pub workspace_border_focused: HashMap<usize, u32>,
+    /// Per-workspace overrides of `gap`, keyed by 0-based workspace index
+    pub workspace_gap: HashMap<usize, u32>,
     /// Border color for unfocused window
     pub border_unfocused: u32,
     /// Show application icons in tabs
     pub show_tab_icons: bool,
+    /// Horizontal tabs show only the icon and shrink to fit it
+    pub tab_compact: bool,
+    /// Tab icon width/height in pixels
+    pub tab_icon_size: u32,
+    /// Space between a tab's icon and its title
+    pub tab_icon_padding: u32,
+    /// Radius of a tab's rounded corners (0 = square tabs)
+    pub tab_corner_radius: u32,
+    /// What tabs show as their label
+    pub tab_label: TabLabel,
+    /// Per-WM_CLASS overrides of `tab_label`
+    pub tab_label_by_class: HashMap<String, TabLabel>,
+    /// How horizontal tabs are sized (frames may override it)
+    pub tab_sizing: TabSizing,
+    /// Tab width used by `TabSizing::Fixed`
+    pub fixed_tab_width: u32,
+    /// Modifier mask required for middle-click removal of empty frames
+    pub middle_click_close_mask: u16,
+    /// Actions run by modifier+left-click on a tab, keyed by modifier mask
+    pub tab_modifier_actions: Vec<(u16, TabDoubleClickAction)>,
+    /// Highlight the drop target while dragging a tab
+    pub drag_feedback: bool,
+    /// Pixels the pointer must move after pressing a tab before it drags
+    pub drag_threshold: u32,
+    /// How newly managed tiled windows appear
+    pub spawn_animation: SpawnAnimation,
+    /// Length of the spawn animation
+    pub spawn_animation_ms: u64,
+    /// Where client content stays anchored when a frame is resized
+    pub resize_gravity: ResizeGravity,
 }
 
 impl Default for LayoutConfig {
@@ -65,6 +113,7 @@ impl Default for LayoutConfig {
             border_width: 2,
             tab_bar_height: 28,
             vertical_tab_width: 28,
+            min_frame_size: 0,
             tab_bar_bg: 0x000000,       // Black (fallback)
             tab_focused_bg: 0x5294e2,   // Blue (matching border)
             tab_unfocused_bg: 0x3a3a3a, // Darker gray
@@ -74,13 +123,68 @@ impl Default for LayoutConfig {
             tab_text_color: 0xffffff,   // White
             tab_text_unfocused: 0x888888, // Dim gray
             tab_separator: 0x4a4a4a,    // Subtle separator
+            empty_frame_bg: 0x000000,   // Same as tab_bar_bg
             border_focused: 0x5294e2,   // Blue
+            workspace_border_focused: HashMap::new(),
+            workspace_gap: HashMap::new(),
             border_unfocused: 0x3a3a3a, // Gray
             show_tab_icons: true,
+            tab_compact: false,
+            tab_icon_size: 20,
+            tab_icon_padding: 4,
+            tab_corner_radius: 6,
+            tab_label: TabLabel::Title,
+            tab_label_by_class: HashMap::new(),
+            tab_sizing: TabSizing::Content,
+            fixed_tab_width: 160,
+            middle_click_close_mask: 4, // Control
+            tab_modifier_actions: vec![
+                (1, TabDoubleClickAction::Close), // Shift
+                (4, TabDoubleClickAction::Float), // Control
+            ],
+            drag_feedback: true,
+            drag_threshold: 5,
+            spawn_animation: SpawnAnimation::None,
+            spawn_animation_ms: 120,
+            resize_gravity: ResizeGravity::TopLeft,
         }
     }
 }
 
+impl LayoutConfig {
+    /// Take the settings a theme can change (see `THEME_APPEARANCE_KEYS`)
+    /// from `config`, keeping the default for colors that don't parse
+    pub fn apply_theme(&mut self, config: &Config) {
+        let colors = &config.colors;
+        self.gap = config.appearance.gap;
+        self.outer_gap = config.appearance.outer_gap;
+        self.border_width = config.appearance.border_width;
+        self.tab_corner_radius = config.appearance.tab_corner_radius;
+        self.tab_bar_bg = parse_color(&colors.tab_bar_bg).unwrap_or(0x2e2e2e);
+        self.tab_focused_bg = parse_color(&colors.tab_focused_bg).unwrap_or(0x5294e2);
+        self.tab_unfocused_bg = parse_color(&colors.tab_unfocused_bg).unwrap_or(0x3a3a3a);
+        self.tab_visible_unfocused_bg = parse_color(&colors.tab_visible_unfocused_bg).unwrap_or(0x4a6a9a);
+        self.tab_tagged_bg = parse_color(&colors.tab_tagged_bg).unwrap_or(0xe06c75);
+        self.tab_urgent_bg = parse_color(&colors.tab_urgent_bg).unwrap_or(0xd19a66);
+        self.tab_text_color = parse_color(&colors.tab_text).unwrap_or(0xffffff);
+        self.tab_text_unfocused = parse_color(&colors.tab_text_unfocused).unwrap_or(0x888888);
+        self.tab_separator = parse_color(&colors.tab_separator).unwrap_or(0x4a4a4a);
+        self.empty_frame_bg = parse_color(&colors.empty_frame_bg).unwrap_or(self.tab_bar_bg);
+        self.border_focused = parse_color(&colors.border_focused).unwrap_or(0x5294e2);
+        self.border_unfocused = parse_color(&colors.border_unfocused).unwrap_or(0x3a3a3a);
+    }
+
+    /// Focused border color for a workspace (0-based), falling back to `border_focused`
+    pub fn border_focused_for(&self, workspace: usize) -> u32 {
+        self.workspace_border_focused.get(&workspace).copied().unwrap_or(self.border_focused)
+    }
+
+    /// Gap between windows on a workspace (0-based), honoring `[workspace.N] gap`
+    pub fn gap_for(&self, workspace: usize) -> u32 {
+        self.workspace_gap.get(&workspace).copied().unwrap_or(self.gap)
+    }
+}
+
 // =============================================================================
 // File-based Configuration (TOML parsing)
 // =============================================================================
@@ -95,6 +199,60 @@ pub struct Config {
     pub keybindings: KeybindingConfig,
     pub exec: ExecConfig,
     pub startup: StartupConfig,
+    /// Per-workspace settings, keyed by workspace number as string ("1"-"9")
+    pub workspace: HashMap<String, WorkspaceConfig>,
+    /// Per-monitor settings, keyed by RandR output name (`[monitor."DP-1"]`)
+    pub monitor: HashMap<String, MonitorConfig>,
+    pub bar: BarConfig,
+}
+
+/// Built-in status bar (`[bar]`)
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BarConfig {
+    /// Show the bar (off by default; external panels are handled as docks)
+    pub enabled: bool,
+    /// Monitor edge the bar is placed on
+    pub position: BarPosition,
+    /// Bar height in pixels (reserved from each monitor's usable area)
+    pub height: u32,
+}
+
+impl Default for BarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            position: BarPosition::Top,
+            height: 20,
+        }
+    }
+}
+
+/// Monitor edge the built-in bar sits on
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BarPosition {
+    #[default]
+    Top,
+    Bottom,
+}
+
+/// Settings for a single workspace (`[workspace.N]`)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    /// Focused border color on this workspace (overrides colors.border_focused)
+    pub border_focused: Option<String>,
+    /// Gap between windows on this workspace (overrides appearance.gap)
+    pub gap: Option<u32>,
+}
+
+/// Settings for a single monitor (`[monitor."NAME"]`)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MonitorConfig {
+    /// Tab font size in points on this monitor (overrides appearance.tab_font_size)
+    pub tab_font_size: Option<u32>,
 }
 
 /// Exec keybindings (key combo -> command to run)
@@ -106,7 +264,7 @@ pub struct ExecConfig {
 }
 
 /// Startup layout configuration
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct StartupConfig {
     /// Per-workspace layout configurations, keyed by workspace number as string ("1"-"9")
@@ -115,14 +273,14 @@ pub struct StartupConfig {
 }
 
 /// Configuration for a single workspace's startup layout
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct WorkspaceStartup {
     /// The layout tree definition
     pub layout: LayoutNodeConfig,
 }
 
 /// Recursive enum representing either a frame or a split in the layout tree
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum LayoutNodeConfig {
     /// A leaf frame that can contain windows
@@ -132,26 +290,27 @@ pub enum LayoutNodeConfig {
 }
 
 /// Configuration for a frame (leaf node)
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct FrameConfig {
     /// Optional name for the frame (used for window placement rules)
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// Whether tabs should be displayed vertically
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub vertical_tabs: bool,
     /// Applications to spawn in this frame at startup
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub apps: Vec<String>,
 }
 
 /// Configuration for a split node
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SplitConfig {
     /// Split direction: "horizontal" or "vertical"
     pub direction: SplitDirectionConfig,
     /// Ratio of space given to first child (0.0 to 1.0, default 0.5)
-    #[serde(default = "default_ratio")]
+    #[serde(default = "default_ratio", serialize_with = "serialize_ratio")]
     pub ratio: f32,
     /// First child (left or top)
     pub first: Box<LayoutNodeConfig>,
@@ -160,7 +319,7 @@ pub struct SplitConfig {
 }
 
 /// Split direction for config parsing
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SplitDirectionConfig {
     Horizontal,
@@ -171,11 +330,279 @@ fn default_ratio() -> f32 {
     0.5
 }
 
+/// Serialize a ratio rounded to two decimals, avoiding f32 noise like 0.6000000238418579
+fn serialize_ratio<S: serde::Serializer>(ratio: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64((*ratio as f64 * 100.0).round() / 100.0)
+}
+
+/// Render a `[startup.workspace.N]` TOML snippet for the given layout.
+/// `workspace` is the 1-based workspace number used as the config key.
+pub fn startup_config_snippet(workspace: usize, layout: LayoutNodeConfig) -> Result<String, toml::ser::Error> {
+    #[derive(Serialize)]
+    struct Snippet {
+        startup: StartupConfig,
+    }
+
+    let mut startup = StartupConfig::default();
+    startup.workspace.insert(workspace.to_string(), WorkspaceStartup { layout });
+    toml::to_string(&Snippet { startup })
+}
+
 /// General settings
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize)]
 #[serde(default)]
 pub struct GeneralConfig {
-    // Reserved for future general settings
+    /// Which windows focus_next/focus_prev cycle through
+    pub cycle_scope: CycleScope,
+    /// Whether focus_next/focus_prev stay on the focused monitor or cycle
+    /// through the visible workspaces of every monitor
+    pub cycle_monitor_scope: CycleMonitorScope,
+    /// Order in which repeated focus_urgent calls visit urgent windows
+    pub urgent_order: UrgentOrder,
+    /// Wrap around from the last window to the first when cycling focus or
+    /// tabs, or moving a window between frames (false stops at the ends)
+    pub focus_wrap: bool,
+    /// Warp the pointer to the newly focused window when cycling/selecting tabs
+    pub tab_cycle_warps: bool,
+    /// Warp the pointer to the newly focused frame after directional focus
+    pub focus_direction_warps: bool,
+    /// Switch to the destination workspace (and focus the window) after the
+    /// user moves a window to another workspace; client requests never follow
+    pub move_follows: bool,
+    /// Where newly managed tiled windows are placed
+    pub open_mode: OpenMode,
+    /// Which tab gains focus when the focused tab closes
+    pub close_focus: CloseFocus,
+    /// Delay (ms) before unmanaging a window that unmapped itself; 0 = immediately
+    pub unmap_grace_ms: u64,
+    /// Focus the monitor under the pointer when the mouse moves onto it
+    pub monitor_focus_follows_mouse: bool,
+    /// Also ignore mouse clicks on tab bars, gaps and frames in presentation mode
+    pub presentation_blocks_mouse: bool,
+    /// Seconds without input before `idle_action` runs; 0 = disabled
+    pub idle_timeout_s: u64,
+    /// Action run once per idle period ("workspace:N" or "exec:command")
+    pub idle_action: String,
+    /// Mouse button (1-3) that starts a split resize when pressed on a gap
+    pub resize_button: u8,
+    /// Pressing resize_button on a tiled window's border also resizes the
+    /// split along that edge
+    pub resize_from_border: bool,
+    /// Put pointer barriers on the edges where monitors meet
+    pub pointer_barriers: bool,
+    /// Pixels the pointer has to push against a barrier to cross it
+    pub pointer_barrier_push: u32,
+    /// Raise every tab bar on the workspace whenever focus changes, not just
+    /// the focused frame's
+    pub raise_tabbar_always: bool,
+    /// Collapse empty frames left behind when windows are moved or closed
+    pub auto_normalize: bool,
+    /// Reset split ratios to equal shares whenever frames are added or removed
+    pub auto_balance: bool,
+    /// Terminals tried in order when a spawned terminal fails to start
+    pub terminal_fallbacks: Vec<String>,
+    /// workspace_next/workspace_prev wrap from the last workspace to the
+    /// first (false stops at the ends)
+    pub workspace_wrap: bool,
+    /// Workspaces (1-9) that workspace_next/workspace_prev cycle through
+    pub workspace_count: usize,
+    /// Show the new workspace's number for this long after a switch; 0 = off
+    pub workspace_indicator_ms: u64,
+    /// Start a newly floated window exactly where its tiled slot was
+    pub float_at_tiled_position: bool,
+    /// Dead space (pixels) left along edges shared by two monitors, split
+    /// evenly between them; 0 = off
+    pub bezel_gap: u32,
+    /// WM_CLASS instance or class names ttwm never manages
+    pub unmanaged_classes: Vec<String>,
+    /// Open new tiled windows as a tab next to a window of the same class
+    pub auto_group_by_class: bool,
+    /// Most windows a frame takes as tabs before new windows go to another
+    /// frame or a new split (0 = unlimited)
+    pub max_tabs_per_frame: usize,
+    /// Clean-up done to client windows on exit
+    pub exit_behavior: ExitBehavior,
+    /// What happens when a managed window turns override-redirect
+    pub override_redirect_flip: OverrideRedirectFlip,
+    /// Save each monitor's workspace on exit and show it again at startup
+    pub restore_session: bool,
+    /// Theme file overlaid on `[colors]` and the visual `[appearance]`
+    /// settings (relative to the config file's directory)
+    pub theme: Option<String>,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            cycle_scope: CycleScope::default(),
+            cycle_monitor_scope: CycleMonitorScope::default(),
+            urgent_order: UrgentOrder::default(),
+            focus_wrap: true,
+            tab_cycle_warps: false,
+            focus_direction_warps: false,
+            move_follows: false,
+            open_mode: OpenMode::default(),
+            close_focus: CloseFocus::default(),
+            unmap_grace_ms: 0,
+            monitor_focus_follows_mouse: false,
+            presentation_blocks_mouse: false,
+            idle_timeout_s: 0,
+            idle_action: String::new(),
+            resize_button: 1,
+            resize_from_border: false,
+            pointer_barriers: false,
+            pointer_barrier_push: 150,
+            raise_tabbar_always: false,
+            auto_normalize: false,
+            auto_balance: false,
+            terminal_fallbacks: vec!["alacritty".to_string(), "kitty".to_string(), "xterm".to_string()],
+            workspace_wrap: true,
+            workspace_count: NUM_WORKSPACES,
+            workspace_indicator_ms: 0,
+            float_at_tiled_position: false,
+            bezel_gap: 0,
+            unmanaged_classes: Vec::new(),
+            auto_group_by_class: false,
+            max_tabs_per_frame: 0,
+            exit_behavior: ExitBehavior::default(),
+            override_redirect_flip: OverrideRedirectFlip::default(),
+            restore_session: false,
+            theme: None,
+        }
+    }
+}
+
+impl GeneralConfig {
+    /// Commands to try, in order, when spawning `command`.
+    ///
+    /// `command` comes first, followed by each of `terminal_fallbacks` whose
+    /// program differs from it, with the arguments after the original
+    /// program name appended (so `kitty -e htop` falls back to
+    /// `alacritty -e htop`).
+    pub fn spawn_candidates(&self, command: &str) -> Vec<String> {
+        let program = |c: &str| c.split_whitespace().next().unwrap_or("").to_string();
+        let first = program(command);
+        let args = command.trim_start().strip_prefix(first.as_str()).unwrap_or("").trim();
+        let mut candidates = vec![command.to_string()];
+        candidates.extend(
+            self.terminal_fallbacks.iter()
+                .filter(|t| program(t) != first)
+                .map(|t| if args.is_empty() { t.clone() } else { format!("{} {}", t, args) }),
+        );
+        candidates
+    }
+
+    /// Whether a window with this WM_CLASS is left alone entirely
+    /// (matches either the instance or the class name).
+    pub fn is_unmanaged_class(&self, instance: &str, class: &str) -> bool {
+        self.unmanaged_classes.iter().any(|c| c == instance || c == class)
+    }
+}
+
+/// Action triggered after `general.idle_timeout_s` without input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdleAction {
+    /// Switch to a workspace (0-based index)
+    Workspace(usize),
+    /// Run a command
+    Exec(String),
+}
+
+impl IdleAction {
+    /// Parse "workspace:N" (1-based, like the keybindings) or "exec:command".
+    pub fn parse(s: &str) -> Option<Self> {
+        let (kind, arg) = s.split_once(':')?;
+        let arg = arg.trim();
+        match kind.trim() {
+            "workspace" => match arg.parse::<usize>() {
+                Ok(n) if (1..=9).contains(&n) => Some(Self::Workspace(n - 1)),
+                _ => None,
+            },
+            "exec" if !arg.is_empty() => Some(Self::Exec(arg.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// Tab focused after the focused tab in a frame closes
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseFocus {
+    /// The tab that followed it (or the new last tab)
+    #[default]
+    Next,
+    /// The tab that preceded it (or the new first tab)
+    Prev,
+    /// The most recently focused remaining tab
+    Mru,
+}
+
+/// What happens to client windows when ttwm exits
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExitBehavior {
+    /// Map every managed window (including background tabs and other
+    /// workspaces) without a border, ready for the next window manager
+    #[default]
+    Remap,
+    /// Leave windows exactly as they are
+    Leave,
+}
+
+/// What happens when a managed window sets override-redirect on itself
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OverrideRedirectFlip {
+    /// Stop managing it (out of the layout, left mapped) so ttwm doesn't
+    /// fight a client that positions itself
+    #[default]
+    Release,
+    /// Keep managing it as before
+    Ignore,
+}
+
+/// Placement of newly managed tiled windows
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenMode {
+    /// Add as a new tab in the focused frame
+    #[default]
+    Tab,
+    /// Split the focused frame and place the window in the new frame
+    Split,
+}
+
+/// Scope of window focus cycling
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CycleScope {
+    /// Every tiled and floating window, including background tabs
+    #[default]
+    All,
+    /// Only the focused tab of each frame plus floating windows
+    Visible,
+}
+
+/// Which urgent window focus_urgent goes to first
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UrgentOrder {
+    /// The one that has been waiting longest
+    #[default]
+    Fifo,
+    /// The one that became urgent most recently
+    Lifo,
+}
+
+/// Monitors that focus cycling moves across
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CycleMonitorScope {
+    /// The focused monitor's current workspace only
+    #[default]
+    Current,
+    /// The current workspace of every monitor, left to right
+    All,
 }
 
 /// Appearance settings (gaps, borders, etc.)
@@ -185,11 +612,238 @@ pub struct AppearanceConfig {
     pub gap: u32,
     pub outer_gap: u32,
     pub border_width: u32,
-    pub tab_bar_height: u32,
-    pub vertical_tab_width: u32,
+    pub tab_bar_height: TabBarSize,
+    pub vertical_tab_width: TabBarSize,
+    pub min_frame_size: u32,
     pub tab_font: String,
     pub tab_font_size: u32,
     pub show_tab_icons: bool,
+    /// Icon-only horizontal tabs, each just wide enough for the icon
+    pub tab_compact: bool,
+    pub tab_icon_size: u32,
+    pub tab_icon_padding: u32,
+    /// Most window icons kept cached before the least recently used are
+    /// dropped (0 = unlimited)
+    pub icon_cache_size: usize,
+    pub tab_corner_radius: u32,
+    pub tab_label: TabLabel,
+    pub tab_label_by_class: HashMap<String, TabLabel>,
+    pub tab_sizing: TabSizing,
+    pub fixed_tab_width: u32,
+    pub tab_doubleclick_action: TabDoubleClickAction,
+    pub tab_modifier_actions: HashMap<String, TabDoubleClickAction>,
+    pub middle_click_close_modifier: String,
+    /// Left-click on the tab bar background focuses the frame's current window
+    pub tab_bar_click_through: bool,
+    pub drag_feedback: bool,
+    pub drag_threshold: u32,
+    pub spawn_animation: SpawnAnimation,
+    pub spawn_animation_ms: u64,
+    pub resize_gravity: ResizeGravity,
+    pub scale: UiScale,
+}
+
+impl AppearanceConfig {
+    /// Resolve `tab_modifier_actions` to modifier masks sorted by mask,
+    /// dropping entries whose modifier doesn't parse or is empty (a plain
+    /// click selects/drags). When two spellings name the same modifiers
+    /// ("Control+Shift" and "Shift+Control"), the one sorting first wins.
+    pub fn tab_modifier_masks(&self) -> Vec<(u16, TabDoubleClickAction)> {
+        let mut entries: Vec<_> = self.tab_modifier_actions.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut masks: Vec<(u16, TabDoubleClickAction)> = Vec::new();
+        for (modifiers, &action) in entries {
+            match parse_modifiers(modifiers) {
+                Some(0) => {}
+                Some(mask) if masks.iter().any(|&(m, _)| m == mask) => {
+                    log::warn!("Ignoring duplicate modifiers '{}' in tab_modifier_actions", modifiers);
+                }
+                Some(mask) => masks.push((mask, action)),
+                None => log::warn!("Invalid modifier '{}' in tab_modifier_actions", modifiers),
+            }
+        }
+        masks.sort_by_key(|&(mask, _)| mask);
+        masks
+    }
+
+    /// Multiply every pixel size (gaps, border width, tab corner radius,
+    /// explicit tab bar sizes, tab icon size and padding, fixed tab width,
+    /// minimum frame size and drag threshold) and the font size by `scale`
+    /// (for HiDPI screens). `"auto"` tab bar sizes follow the scaled font
+    /// and icons.
+    pub fn apply_scale(&mut self, scale: f64) {
+        let px = |value: u32| (value as f64 * scale).round() as u32;
+        self.gap = px(self.gap);
+        self.outer_gap = px(self.outer_gap);
+        self.border_width = px(self.border_width);
+        self.tab_corner_radius = px(self.tab_corner_radius);
+        self.tab_font_size = px(self.tab_font_size).max(1);
+        self.tab_icon_size = px(self.tab_icon_size);
+        self.tab_icon_padding = px(self.tab_icon_padding);
+        self.fixed_tab_width = px(self.fixed_tab_width);
+        self.min_frame_size = px(self.min_frame_size);
+        self.drag_threshold = px(self.drag_threshold);
+        if let TabBarSize::Pixels(pixels) = self.tab_bar_height {
+            self.tab_bar_height = TabBarSize::Pixels(px(pixels));
+        }
+        if let TabBarSize::Pixels(pixels) = self.vertical_tab_width {
+            self.vertical_tab_width = TabBarSize::Pixels(px(pixels));
+        }
+    }
+}
+
+/// Multiplier for pixel sizes: a number, or `"auto"` to derive it from the
+/// X server's DPI (96 DPI = 1.0)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiScale {
+    Auto,
+    Factor(f64),
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        UiScale::Factor(1.0)
+    }
+}
+
+impl UiScale {
+    /// Smallest and largest scale applied
+    pub const RANGE: (f64, f64) = (0.5, 4.0);
+
+    /// Scale factor to use; `dpi` is only queried for `Auto` (1.0 if unknown)
+    pub fn resolve(self, dpi: impl FnOnce() -> Option<f64>) -> f64 {
+        let scale = match self {
+            UiScale::Factor(factor) => factor,
+            UiScale::Auto => dpi().map(|dpi| dpi / 96.0).unwrap_or(1.0),
+        };
+        if scale.is_finite() { scale.clamp(Self::RANGE.0, Self::RANGE.1) } else { 1.0 }
+    }
+}
+
+impl<'de> Deserialize<'de> for UiScale {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Factor(f64),
+            Keyword(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Factor(factor) => Ok(UiScale::Factor(factor)),
+            Raw::Keyword(s) if s.eq_ignore_ascii_case("auto") => Ok(UiScale::Auto),
+            Raw::Keyword(s) => Err(serde::de::Error::custom(format!(
+                "expected a scale factor or \"auto\", got \"{}\"",
+                s
+            ))),
+        }
+    }
+}
+
+/// A tab bar dimension: an explicit pixel count, or `"auto"` to derive it
+/// from the tab font and icon size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabBarSize {
+    Auto,
+    Pixels(u32),
+}
+
+impl TabBarSize {
+    /// Pixel value to use, with `auto` standing in for `Auto`
+    pub fn resolve(self, auto: u32) -> u32 {
+        match self {
+            TabBarSize::Auto => auto,
+            TabBarSize::Pixels(pixels) => pixels,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TabBarSize {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Pixels(u32),
+            Keyword(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Pixels(pixels) => Ok(TabBarSize::Pixels(pixels)),
+            Raw::Keyword(s) if s.eq_ignore_ascii_case("auto") => Ok(TabBarSize::Auto),
+            Raw::Keyword(s) => Err(serde::de::Error::custom(format!(
+                "expected a pixel count or \"auto\", got \"{}\"",
+                s
+            ))),
+        }
+    }
+}
+
+/// Source of the text shown in a tab
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TabLabel {
+    /// Window title (_NET_WM_NAME / WM_NAME)
+    #[default]
+    Title,
+    /// WM_CLASS class name (falls back to the title if unset)
+    Class,
+    /// Class name followed by the title
+    ClassTitle,
+}
+
+/// How horizontal tab widths are chosen
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TabSizing {
+    /// Fit each tab to its title, within fixed minimum/maximum widths
+    #[default]
+    Content,
+    /// Split the tab bar equally between all tabs, truncating titles
+    Equal,
+    /// Every tab is `fixed_tab_width` pixels wide
+    Fixed,
+}
+
+/// Animation used when a tiled window first appears
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpawnAnimation {
+    /// Place the window at its final geometry immediately
+    #[default]
+    None,
+    /// Grow from a slightly smaller rectangle while sliding up into place
+    Slide,
+}
+
+/// Anchor for a client's existing contents when its frame is resized
+/// (sets the window's bit gravity, so it only matters until the client
+/// repaints)
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeGravity {
+    /// Keep the contents pinned to the top-left corner
+    #[default]
+    TopLeft,
+    /// Keep the contents centered
+    Center,
+}
+
+/// Action performed when a tab is double-clicked or modifier-clicked
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TabDoubleClickAction {
+    /// Do nothing (double-click behaves like two single clicks)
+    None,
+    /// Toggle fullscreen on the clicked window
+    #[default]
+    Fullscreen,
+    /// Toggle maximize on the clicked window (fill the monitor below docks and the bar)
+    Maximize,
+    /// Toggle floating on the clicked window
+    Float,
+    /// Close the clicked window
+    Close,
 }
 
 /// Color settings (hex strings like "#5294e2")
@@ -205,6 +859,8 @@ pub struct ColorConfig {
     pub tab_text: String,
     pub tab_text_unfocused: String,
     pub tab_separator: String,
+    /// Empty frame placeholder background (empty = same as tab_bar_bg)
+    pub empty_frame_bg: String,
     pub border_focused: String,
     pub border_unfocused: String,
 }
@@ -245,10 +901,32 @@ pub struct KeybindingConfig {
     pub untag_all: Option<String>,
     pub toggle_float: Option<String>,
     pub toggle_fullscreen: Option<String>,
+    pub toggle_zoom_all_monitors: Option<String>,
     pub toggle_vertical_tabs: Option<String>,
     pub focus_urgent: Option<String>,
     pub focus_monitor_left: Option<String>,
     pub focus_monitor_right: Option<String>,
+    pub promote_to_first: Option<String>,
+    pub focus_left: Option<String>,
+    pub focus_right: Option<String>,
+    pub focus_up: Option<String>,
+    pub focus_down: Option<String>,
+    pub new_frame_left: Option<String>,
+    pub new_frame_right: Option<String>,
+    pub new_frame_up: Option<String>,
+    pub new_frame_down: Option<String>,
+    pub toggle_frame_lock: Option<String>,
+    pub swap_with_sibling: Option<String>,
+    pub undo_layout: Option<String>,
+    pub toggle_presentation_mode: Option<String>,
+    pub set_mark: Option<String>,
+    pub goto_mark: Option<String>,
+    pub pull_window: Option<String>,
+    pub launcher: Option<String>,
+    pub close_frame: Option<String>,
+    pub toggle_stacked: Option<String>,
+    pub flatten_workspace: Option<String>,
+    pub show_help: Option<String>,
 }
 
 /// Parsed keybinding (ready for X11 grab)
@@ -286,16 +964,140 @@ pub enum WmAction {
     UntagAll,
     ToggleFloat,
     ToggleFullscreen,
+    ToggleZoomAllMonitors,
     ToggleVerticalTabs,
     FocusUrgent,
     FocusMonitorLeft,
     FocusMonitorRight,
+    PromoteToFirst,
+    /// Focus the frame in a direction, continuing onto the adjacent monitor
+    FocusDirection(Direction),
+    /// Add an empty frame on a side of the focused frame, leaving its windows in place
+    NewFrame(Direction),
+    ToggleFrameLock,
+    SwapWithSibling,
+    /// Restore the layout from before the last split/move/close/reorder
+    UndoLayout,
+    /// Disable every other keybinding (and optionally tab mouse actions) until toggled off
+    TogglePresentationMode,
+    /// Mark the focused window with the next key typed
+    SetMark,
+    /// Jump to the window marked with the next key typed
+    GotoMark,
+    /// Pull the window marked with the next key typed into the focused frame
+    PullWindow,
+    /// Open the built-in application launcher
+    Launcher,
+    /// Ask every window in the focused frame to close
+    CloseFrame,
+    /// Switch the focused frame between tabbed and stacked titles
+    ToggleStacked,
+    /// Move every window on the workspace into a single frame
+    FlattenWorkspace,
+    /// Show the keybinding cheat sheet
+    ShowHelp,
+}
+
+/// Keybinding names of the actions that take no argument; both
+/// `WmAction::from_name` and `WmAction::name` read this table
+const ACTION_NAMES: &[(&str, WmAction)] = &[
+    ("cycle_tab_forward", WmAction::CycleTabForward),
+    ("cycle_tab_backward", WmAction::CycleTabBackward),
+    ("focus_next", WmAction::FocusNext),
+    ("focus_prev", WmAction::FocusPrev),
+    ("focus_frame_left", WmAction::FocusFrameLeft),
+    ("focus_frame_right", WmAction::FocusFrameRight),
+    ("focus_frame_up", WmAction::FocusFrameUp),
+    ("focus_frame_down", WmAction::FocusFrameDown),
+    ("move_window_left", WmAction::MoveWindowLeft),
+    ("move_window_right", WmAction::MoveWindowRight),
+    ("resize_shrink", WmAction::ResizeShrink),
+    ("resize_grow", WmAction::ResizeGrow),
+    ("split_horizontal", WmAction::SplitHorizontal),
+    ("split_vertical", WmAction::SplitVertical),
+    ("close_window", WmAction::CloseWindow),
+    ("quit", WmAction::Quit),
+    ("workspace_next", WmAction::WorkspaceNext),
+    ("workspace_prev", WmAction::WorkspacePrev),
+    ("tag_window", WmAction::TagWindow),
+    ("move_tagged_windows", WmAction::MoveTaggedToFrame),
+    ("untag_all", WmAction::UntagAll),
+    ("toggle_float", WmAction::ToggleFloat),
+    ("toggle_fullscreen", WmAction::ToggleFullscreen),
+    ("toggle_zoom_all_monitors", WmAction::ToggleZoomAllMonitors),
+    ("toggle_vertical_tabs", WmAction::ToggleVerticalTabs),
+    ("focus_urgent", WmAction::FocusUrgent),
+    ("focus_monitor_left", WmAction::FocusMonitorLeft),
+    ("focus_monitor_right", WmAction::FocusMonitorRight),
+    ("promote_to_first", WmAction::PromoteToFirst),
+    ("focus_left", WmAction::FocusDirection(Direction::Left)),
+    ("focus_right", WmAction::FocusDirection(Direction::Right)),
+    ("focus_up", WmAction::FocusDirection(Direction::Up)),
+    ("focus_down", WmAction::FocusDirection(Direction::Down)),
+    ("new_frame_left", WmAction::NewFrame(Direction::Left)),
+    ("new_frame_right", WmAction::NewFrame(Direction::Right)),
+    ("new_frame_up", WmAction::NewFrame(Direction::Up)),
+    ("new_frame_down", WmAction::NewFrame(Direction::Down)),
+    ("toggle_frame_lock", WmAction::ToggleFrameLock),
+    ("swap_with_sibling", WmAction::SwapWithSibling),
+    ("undo_layout", WmAction::UndoLayout),
+    ("toggle_presentation_mode", WmAction::TogglePresentationMode),
+    ("set_mark", WmAction::SetMark),
+    ("goto_mark", WmAction::GotoMark),
+    ("pull_window", WmAction::PullWindow),
+    ("launcher", WmAction::Launcher),
+    ("close_frame", WmAction::CloseFrame),
+    ("toggle_stacked", WmAction::ToggleStacked),
+    ("flatten_workspace", WmAction::FlattenWorkspace),
+    ("show_help", WmAction::ShowHelp),
+];
+
+impl WmAction {
+    /// Look up an action by its keybinding name (e.g. "cycle_tab_forward",
+    /// "focus_tab_3"). Spawn actions have no name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(n) = name.strip_prefix("focus_tab_") {
+            return match n.parse() {
+                Ok(n @ 1..=9) => Some(WmAction::FocusTab(n)),
+                _ => None,
+            };
+        }
+        ACTION_NAMES.iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|(_, action)| action.clone())
+    }
+
+    /// Keybinding name of the action (inverse of `from_name`); spawn actions
+    /// show their command.
+    pub fn name(&self) -> String {
+        match self {
+            WmAction::Spawn(command) => format!("exec: {}", command),
+            WmAction::FocusTab(n) => format!("focus_tab_{}", n),
+            action => ACTION_NAMES.iter()
+                .find(|(_, named)| named == action)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_else(|| format!("{:?}", action)),
+        }
+    }
 }
 
 impl Config {
-    /// Load config from default path (~/.config/ttwm/config.toml)
+    /// Load config from the resolved path (`$TTWM_CONFIG` or ~/.config/ttwm/config.toml)
+    #[allow(dead_code)]
     pub fn load() -> Self {
-        Self::load_from_path(Self::default_path())
+        Self::load_from_path(Self::resolve_path(None))
+    }
+
+    /// Resolve the config file path.
+    /// Precedence: explicit path (--config) > $TTWM_CONFIG > default path.
+    pub fn resolve_path(cli_path: Option<PathBuf>) -> PathBuf {
+        Self::resolve_path_with_env(cli_path, std::env::var_os(CONFIG_ENV_VAR))
+    }
+
+    fn resolve_path_with_env(cli_path: Option<PathBuf>, env_path: Option<OsString>) -> PathBuf {
+        cli_path
+            .or_else(|| env_path.filter(|p| !p.is_empty()).map(PathBuf::from))
+            .unwrap_or_else(Self::default_path)
     }
 
     /// Default config file path
@@ -309,7 +1111,7 @@ impl Config {
     /// Load config from a specific path
     pub fn load_from_path(path: PathBuf) -> Self {
         match std::fs::read_to_string(&path) {
-            Ok(contents) => match toml::from_str(&contents) {
+            Ok(contents) => match Self::parse(&contents, &path) {
                 Ok(config) => {
                     log::info!("Loaded config from {:?}", path);
                     config
@@ -326,6 +1128,100 @@ impl Config {
         }
     }
 
+    /// Parse the config file at `path`, overlaid with its `general.theme`.
+    /// A theme that can't be loaded is skipped with a warning.
+    fn parse(contents: &str, path: &Path) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = toml::from_str(contents)?;
+        if let Err(e) = overlay_theme_file(&mut table, path) {
+            log::warn!("Ignoring theme: {:#}", e);
+        }
+        table.try_into()
+    }
+
+    /// Load the config at `path` with `theme` in place of its `general.theme`,
+    /// for switching themes at runtime. Unlike at startup, a config or theme
+    /// that can't be loaded is an error.
+    pub fn load_with_theme(path: &Path, theme: &str) -> anyhow::Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+        };
+        let mut table: toml::Table = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse {:?}", path))?;
+        let general = table.entry("general").or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if let toml::Value::Table(general) = general {
+            general.insert("theme".to_string(), toml::Value::String(theme.to_string()));
+        }
+        overlay_theme_file(&mut table, path)?;
+        Ok(table.try_into()?)
+    }
+
+    /// Scale `[appearance]` (see `AppearanceConfig::apply_scale`), the
+    /// built-in bar height and per-workspace gaps by `scale`
+    pub fn apply_scale(&mut self, scale: f64) {
+        let px = |value: u32| (value as f64 * scale).round() as u32;
+        self.appearance.apply_scale(scale);
+        self.bar.height = px(self.bar.height);
+        for ws in self.workspace.values_mut() {
+            ws.gap = ws.gap.map(px);
+        }
+    }
+
+    /// Per-workspace gap overrides from `[workspace.N] gap`, keyed by
+    /// 0-based index. Out-of-range workspace numbers are skipped.
+    pub fn workspace_gaps(&self) -> HashMap<usize, u32> {
+        self.workspace.iter()
+            .filter_map(|(key, ws)| {
+                let gap = ws.gap?;
+                match key.parse::<usize>() {
+                    Ok(n @ 1..=9) => Some((n - 1, gap)),
+                    _ => {
+                        log::warn!("Ignoring [workspace.{}] gap: workspace must be 1-9", key);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Per-monitor tab font sizes from `[monitor."NAME"] tab_font_size`,
+    /// keyed by output name and multiplied by the UI scale like
+    /// `appearance.tab_font_size`
+    pub fn monitor_font_sizes(&self, scale: f64) -> HashMap<String, u32> {
+        self.monitor.iter()
+            .filter_map(|(name, monitor)| {
+                let size = monitor.tab_font_size?;
+                Some((name.clone(), ((size as f64 * scale).round() as u32).max(1)))
+            })
+            .collect()
+    }
+
+    /// Parse `[workspace.N] border_focused` overrides into 0-based index -> color.
+    /// Invalid workspace numbers or colors are skipped with a warning.
+    pub fn workspace_border_colors(&self) -> HashMap<usize, u32> {
+        let mut colors = HashMap::new();
+        for (key, ws) in &self.workspace {
+            let Some(color_str) = &ws.border_focused else {
+                continue;
+            };
+            let index = match key.parse::<usize>() {
+                Ok(n @ 1..=9) => n - 1,
+                _ => {
+                    log::warn!("Ignoring [workspace.{}]: workspace must be 1-9", key);
+                    continue;
+                }
+            };
+            match parse_color(color_str) {
+                Some(color) => {
+                    colors.insert(index, color);
+                }
+                None => log::warn!("Invalid border_focused '{}' for workspace {}", color_str, key),
+            }
+        }
+        colors
+    }
+
     /// Parse keybindings into action -> ParsedBinding map
     pub fn parse_keybindings(&self) -> HashMap<WmAction, ParsedBinding> {
         let mut bindings = HashMap::new();
@@ -373,10 +1269,32 @@ impl Config {
         insert(WmAction::UntagAll, &self.keybindings.untag_all);
         insert(WmAction::ToggleFloat, &self.keybindings.toggle_float);
         insert(WmAction::ToggleFullscreen, &self.keybindings.toggle_fullscreen);
+        insert(WmAction::ToggleZoomAllMonitors, &self.keybindings.toggle_zoom_all_monitors);
         insert(WmAction::ToggleVerticalTabs, &self.keybindings.toggle_vertical_tabs);
         insert(WmAction::FocusUrgent, &self.keybindings.focus_urgent);
         insert(WmAction::FocusMonitorLeft, &self.keybindings.focus_monitor_left);
         insert(WmAction::FocusMonitorRight, &self.keybindings.focus_monitor_right);
+        insert(WmAction::PromoteToFirst, &self.keybindings.promote_to_first);
+        insert(WmAction::FocusDirection(Direction::Left), &self.keybindings.focus_left);
+        insert(WmAction::FocusDirection(Direction::Right), &self.keybindings.focus_right);
+        insert(WmAction::FocusDirection(Direction::Up), &self.keybindings.focus_up);
+        insert(WmAction::FocusDirection(Direction::Down), &self.keybindings.focus_down);
+        insert(WmAction::NewFrame(Direction::Left), &self.keybindings.new_frame_left);
+        insert(WmAction::NewFrame(Direction::Right), &self.keybindings.new_frame_right);
+        insert(WmAction::NewFrame(Direction::Up), &self.keybindings.new_frame_up);
+        insert(WmAction::NewFrame(Direction::Down), &self.keybindings.new_frame_down);
+        insert(WmAction::ToggleFrameLock, &self.keybindings.toggle_frame_lock);
+        insert(WmAction::SwapWithSibling, &self.keybindings.swap_with_sibling);
+        insert(WmAction::UndoLayout, &self.keybindings.undo_layout);
+        insert(WmAction::TogglePresentationMode, &self.keybindings.toggle_presentation_mode);
+        insert(WmAction::SetMark, &self.keybindings.set_mark);
+        insert(WmAction::GotoMark, &self.keybindings.goto_mark);
+        insert(WmAction::PullWindow, &self.keybindings.pull_window);
+        insert(WmAction::Launcher, &self.keybindings.launcher);
+        insert(WmAction::CloseFrame, &self.keybindings.close_frame);
+        insert(WmAction::ToggleStacked, &self.keybindings.toggle_stacked);
+        insert(WmAction::FlattenWorkspace, &self.keybindings.flatten_workspace);
+        insert(WmAction::ShowHelp, &self.keybindings.show_help);
 
         // Parse exec bindings (key combo -> command)
         for (key_combo, command) in &self.exec.bindings {
@@ -391,6 +1309,16 @@ impl Config {
     }
 }
 
+/// The keybindings that stay live: all of them, or in presentation mode only
+/// the presentation toggle (so nothing else fires during a recording)
+pub fn active_bindings(
+    bindings: &HashMap<WmAction, ParsedBinding>,
+    presentation_mode: bool,
+) -> impl Iterator<Item = (&WmAction, &ParsedBinding)> {
+    bindings.iter()
+        .filter(move |(action, _)| !presentation_mode || **action == WmAction::TogglePresentationMode)
+}
+
 /// Parse a key binding string like "Mod4+Shift+h" into keysym and modifiers
 pub fn parse_key_binding(s: &str) -> Option<ParsedBinding> {
     let parts: Vec<&str> = s.split('+').collect();
@@ -401,19 +1329,10 @@ pub fn parse_key_binding(s: &str) -> Option<ParsedBinding> {
     let mut modifiers: u16 = 0;
     let key_part = parts.last()?;
 
-    // X11 modifier masks
-    const SHIFT_MASK: u16 = 1;
-    const CONTROL_MASK: u16 = 4;
-    const MOD1_MASK: u16 = 8; // Alt
-    const MOD4_MASK: u16 = 64; // Super/Win
-
     for part in &parts[..parts.len() - 1] {
-        match part.to_lowercase().as_str() {
-            "mod4" | "super" | "win" => modifiers |= MOD4_MASK,
-            "shift" => modifiers |= SHIFT_MASK,
-            "control" | "ctrl" => modifiers |= CONTROL_MASK,
-            "mod1" | "alt" => modifiers |= MOD1_MASK,
-            _ => {
+        match modifier_mask(part) {
+            Some(mask) => modifiers |= mask,
+            None => {
                 log::warn!("Unknown modifier: {}", part);
             }
         }
@@ -423,7 +1342,48 @@ pub fn parse_key_binding(s: &str) -> Option<ParsedBinding> {
     Some(ParsedBinding { keysym, modifiers })
 }
 
-/// Convert key name to X11 keysym
+/// Format a parsed binding for display, e.g. "Mod4+Shift+Return"
+pub fn format_binding(binding: &ParsedBinding) -> String {
+    let mut parts: Vec<String> = [(64, "Mod4"), (4, "Control"), (8, "Alt"), (1, "Shift")]
+        .iter()
+        .filter(|&&(mask, _)| binding.modifiers & mask != 0)
+        .map(|&(_, name)| name.to_string())
+        .collect();
+    parts.push(match keysyms::name(binding.keysym) {
+        Some(name) => name.to_string(),
+        None => format!("0x{:x}", binding.keysym),
+    });
+    parts.join("+")
+}
+
+/// X11 modifier masks that bindings can use (Shift, Control, Alt, Super)
+pub const BINDABLE_MODIFIERS: u16 = 1 | 4 | 8 | 64;
+
+/// Convert a modifier name to its X11 mask
+fn modifier_mask(name: &str) -> Option<u16> {
+    match name.to_lowercase().as_str() {
+        "mod4" | "super" | "win" => Some(64),
+        "shift" => Some(1),
+        "control" | "ctrl" => Some(4),
+        "mod1" | "alt" => Some(8),
+        _ => None,
+    }
+}
+
+/// Parse a modifier-only combo like "Control" or "Mod4+Shift" into an X11 mask.
+/// An empty string means no modifier. Returns None if any name is unknown.
+pub fn parse_modifiers(s: &str) -> Option<u16> {
+    s.split('+')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .try_fold(0u16, |mask, part| Some(mask | modifier_mask(part)?))
+}
+
+/// Convert key name to X11 keysym.
+///
+/// The friendly names below are matched case-insensitively; anything else is
+/// looked up as an X keysym name (`comma`, `F13`, `XF86AudioPlay`, see `xev`)
+/// or taken as a single printable character (`,`, `;`).
 fn key_to_keysym(key: &str) -> Option<u32> {
     match key.to_lowercase().as_str() {
         "return" | "enter" => Some(0xff0d),
@@ -495,19 +1455,110 @@ fn key_to_keysym(key: &str) -> Option<u32> {
         "]" | "bracketright" => Some(0x5d),
         // Slash key
         "/" | "slash" => Some(0x2f),
+        "'" | "apostrophe" => Some(0x27),
         _ => {
-            log::warn!("Unknown key: {}", key);
-            None
+            let mut chars = key.chars();
+            let single = match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_graphic() => Some(c as u32),
+                _ => None,
+            };
+            let keysym = single.or_else(|| keysyms::lookup(key));
+            if keysym.is_none() {
+                log::warn!("Unknown key: {}", key);
+            }
+            keysym
         }
     }
 }
 
+/// `[appearance]` settings a theme file may set along with `[colors]`; the
+/// rest of `[appearance]` affects layout or behavior and stays in the config
+pub const THEME_APPEARANCE_KEYS: &[&str] = &["gap", "outer_gap", "border_width", "tab_corner_radius"];
+
+/// Resolve a `general.theme` path: `~/` is the home directory and relative
+/// paths are taken from the directory of the config file at `config_path`.
+pub fn theme_path(theme: &str, config_path: &Path) -> PathBuf {
+    if let Some(rest) = theme.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    config_path.parent().unwrap_or(Path::new(".")).join(theme)
+}
+
+/// Parse a theme file, keeping only `[colors]` and `THEME_APPEARANCE_KEYS`
+/// (anything else is logged and dropped)
+fn parse_theme(contents: &str) -> anyhow::Result<toml::Table> {
+    let mut theme = toml::Table::new();
+    for (section, values) in toml::from_str::<toml::Table>(contents)? {
+        let toml::Value::Table(values) = values else {
+            log::warn!("Ignoring '{}' in theme: not a [colors] or [appearance] setting", section);
+            continue;
+        };
+        let values: toml::Table = match section.as_str() {
+            "colors" => values,
+            "appearance" => values.into_iter()
+                .filter(|(key, _)| {
+                    let keep = THEME_APPEARANCE_KEYS.contains(&key.as_str());
+                    if !keep {
+                        log::warn!("Ignoring appearance.{} in theme: only {:?} can be themed", key, THEME_APPEARANCE_KEYS);
+                    }
+                    keep
+                })
+                .collect(),
+            _ => {
+                log::warn!("Ignoring [{}] in theme: only [colors] and [appearance] can be themed", section);
+                continue;
+            }
+        };
+        theme.insert(section, toml::Value::Table(values));
+    }
+    // Catch bad values here, where they can be blamed on the theme
+    theme.clone().try_into::<Config>()?;
+    Ok(theme)
+}
+
+/// Overlay a parsed theme onto a config table; the theme's values win
+fn overlay_theme(table: &mut toml::Table, theme: toml::Table) {
+    for (section, values) in theme {
+        let toml::Value::Table(values) = values else { continue };
+        let target = table.entry(section).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if let toml::Value::Table(target) = target {
+            target.extend(values);
+        }
+    }
+}
+
+/// Read the theme named by `general.theme` in a config table (if any) and
+/// overlay it
+fn overlay_theme_file(table: &mut toml::Table, config_path: &Path) -> anyhow::Result<()> {
+    let Some(theme) = table.get("general").and_then(|g| g.get("theme")).and_then(|t| t.as_str()) else {
+        return Ok(());
+    };
+    let path = theme_path(theme, config_path);
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read theme {:?}", path))?;
+    let parsed = parse_theme(&contents).with_context(|| format!("Failed to parse theme {:?}", path))?;
+    overlay_theme(table, parsed);
+    log::info!("Loaded theme from {:?}", path);
+    Ok(())
+}
+
 /// Parse hex color string (e.g., "#5294e2" or "5294e2") to u32
 pub fn parse_color(s: &str) -> Option<u32> {
     let s = s.trim_start_matches('#');
     u32::from_str_radix(s, 16).ok()
 }
 
+/// Parse a color given over IPC, rejecting anything but 6 hex digits
+pub fn parse_rgb_color(s: &str) -> Option<u32> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 {
+        return None;
+    }
+    parse_color(hex)
+}
+
 impl Default for ExecConfig {
     fn default() -> Self {
         let mut bindings = HashMap::new();
@@ -523,11 +1574,34 @@ impl Default for AppearanceConfig {
             gap: 8,
             outer_gap: 8,
             border_width: 2,
-            tab_bar_height: 28,
-            vertical_tab_width: 28,
+            tab_bar_height: TabBarSize::Pixels(28),
+            vertical_tab_width: TabBarSize::Pixels(28),
+            min_frame_size: 0,
             tab_font: "monospace".to_string(),
             tab_font_size: 11,
             show_tab_icons: true,
+            tab_compact: false,
+            tab_icon_size: 20,
+            tab_icon_padding: 4,
+            icon_cache_size: 128,
+            tab_corner_radius: 6,
+            tab_label: TabLabel::Title,
+            tab_label_by_class: HashMap::new(),
+            tab_sizing: TabSizing::Content,
+            fixed_tab_width: 160,
+            tab_doubleclick_action: TabDoubleClickAction::Fullscreen,
+            tab_modifier_actions: HashMap::from([
+                ("Shift".to_string(), TabDoubleClickAction::Close),
+                ("Control".to_string(), TabDoubleClickAction::Float),
+            ]),
+            middle_click_close_modifier: "Control".to_string(),
+            tab_bar_click_through: false,
+            drag_feedback: true,
+            drag_threshold: 5,
+            spawn_animation: SpawnAnimation::None,
+            spawn_animation_ms: 120,
+            resize_gravity: ResizeGravity::TopLeft,
+            scale: UiScale::default(),
         }
     }
 }
@@ -544,6 +1618,7 @@ impl Default for ColorConfig {
             tab_text: "#ffffff".to_string(),
             tab_text_unfocused: "#888888".to_string(),
             tab_separator: "#4a4a4a".to_string(),
+            empty_frame_bg: String::new(),
             border_focused: "#5294e2".to_string(),
             border_unfocused: "#3a3a3a".to_string(),
         }
@@ -585,10 +1660,32 @@ impl Default for KeybindingConfig {
             untag_all: Some("Mod4+Shift+t".to_string()),
             toggle_float: Some("Mod4+f".to_string()),
             toggle_fullscreen: Some("Mod4+Return".to_string()),
+            toggle_zoom_all_monitors: Some("Mod4+Shift+f".to_string()),
             toggle_vertical_tabs: Some("Mod4+slash".to_string()),
             focus_urgent: Some("Mod4+space".to_string()),
             focus_monitor_left: Some("Mod4+Control+Left".to_string()),
             focus_monitor_right: Some("Mod4+Control+Right".to_string()),
+            promote_to_first: Some("Mod4+Shift+Return".to_string()),
+            focus_left: Some("Mod4+Mod1+Left".to_string()),
+            focus_right: Some("Mod4+Mod1+Right".to_string()),
+            focus_up: Some("Mod4+Mod1+Up".to_string()),
+            focus_down: Some("Mod4+Mod1+Down".to_string()),
+            new_frame_left: None,
+            new_frame_right: None,
+            new_frame_up: None,
+            new_frame_down: None,
+            toggle_frame_lock: Some("Mod4+Shift+l".to_string()),
+            swap_with_sibling: Some("Mod4+Shift+s".to_string()),
+            undo_layout: Some("Mod4+z".to_string()),
+            toggle_presentation_mode: None,
+            set_mark: Some("Mod4+m".to_string()),
+            goto_mark: Some("Mod4+apostrophe".to_string()),
+            pull_window: None,
+            launcher: None,
+            close_frame: None,
+            toggle_stacked: None,
+            flatten_workspace: None,
+            show_help: Some("Mod4+F1".to_string()),
         }
     }
 }
@@ -631,6 +1728,38 @@ mod tests {
         assert!(bindings.contains_key(&WmAction::FocusTab(1)));
     }
 
+    #[test]
+    fn test_default_focus_direction_bindings() {
+        let bindings = Config::default().parse_keybindings();
+        for (direction, key) in [
+            (Direction::Left, "Left"),
+            (Direction::Right, "Right"),
+            (Direction::Up, "Up"),
+            (Direction::Down, "Down"),
+        ] {
+            let binding = &bindings[&WmAction::FocusDirection(direction)];
+            assert_eq!(format_binding(binding), format!("Mod4+Alt+{}", key));
+        }
+    }
+
+    #[test]
+    fn test_presentation_mode_keeps_only_its_toggle() {
+        let mut config = Config::default();
+        config.keybindings.toggle_presentation_mode = Some("Mod4+Shift+p".to_string());
+        let bindings = config.parse_keybindings();
+
+        assert_eq!(active_bindings(&bindings, false).count(), bindings.len());
+        let active: Vec<_> = active_bindings(&bindings, true).collect();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].0, &WmAction::TogglePresentationMode);
+        assert_eq!(format_binding(active[0].1), "Mod4+Shift+p");
+
+        // Unbound by default: presentation mode is then only left over IPC
+        let bindings = Config::default().parse_keybindings();
+        assert!(!bindings.contains_key(&WmAction::TogglePresentationMode));
+        assert_eq!(active_bindings(&bindings, true).count(), 0);
+    }
+
     #[test]
     fn test_key_to_keysym() {
         assert_eq!(key_to_keysym("return"), Some(0xff0d));
@@ -640,6 +1769,443 @@ mod tests {
         assert_eq!(key_to_keysym("1"), Some(0x31));
     }
 
+    #[test]
+    fn test_action_names_round_trip() {
+        let mut seen = std::collections::HashSet::new();
+        for (name, action) in ACTION_NAMES {
+            assert!(seen.insert(*name), "{} is listed twice", name);
+            assert_eq!(WmAction::from_name(name).as_ref(), Some(action));
+            assert_eq!(action.name(), *name);
+        }
+        assert_eq!(WmAction::from_name("focus_tab_3"), Some(WmAction::FocusTab(3)));
+        assert_eq!(WmAction::FocusTab(3).name(), "focus_tab_3");
+        assert_eq!(WmAction::from_name("focus_tab_0"), None);
+
+        // Every bound action has a table entry
+        let config = Config::default();
+        for action in config.parse_keybindings().into_keys() {
+            if let WmAction::Spawn(ref command) = action {
+                assert_eq!(action.name(), format!("exec: {}", command));
+            } else {
+                assert_eq!(WmAction::from_name(&action.name()), Some(action));
+            }
+        }
+        assert_eq!(WmAction::ShowHelp.name(), "show_help");
+        assert_eq!(WmAction::from_name("show_help"), Some(WmAction::ShowHelp));
+    }
+
+    #[test]
+    fn test_format_binding() {
+        let binding = parse_key_binding("Shift+Mod4+Return").unwrap();
+        assert_eq!(format_binding(&binding), "Mod4+Shift+Return");
+        let binding = parse_key_binding("Mod4+Control+Left").unwrap();
+        assert_eq!(format_binding(&binding), "Mod4+Control+Left");
+        let binding = parse_key_binding("Mod1+h").unwrap();
+        assert_eq!(format_binding(&binding), "Alt+h");
+    }
+
+    #[test]
+    fn test_key_to_keysym_by_x_name() {
+        assert_eq!(key_to_keysym("comma"), Some(0x2c));
+        assert_eq!(key_to_keysym("semicolon"), Some(0x3b));
+        assert_eq!(key_to_keysym("F13"), Some(0xffca));
+        assert_eq!(key_to_keysym("XF86AudioPlay"), Some(0x1008ff14));
+        assert_eq!(key_to_keysym("XF86MonBrightnessUp"), Some(0x1008ff02));
+        assert_eq!(key_to_keysym("Print"), Some(0xff61));
+        // Case-insensitive fallback and single characters
+        assert_eq!(key_to_keysym("Comma"), Some(0x2c));
+        assert_eq!(key_to_keysym(";"), Some(0x3b));
+        // Letters keep resolving to lowercase keysyms
+        assert_eq!(key_to_keysym("H"), Some(0x68));
+        assert_eq!(key_to_keysym("NotAKey"), None);
+    }
+
+    #[test]
+    fn test_parse_modifiers() {
+        assert_eq!(parse_modifiers(""), Some(0));
+        assert_eq!(parse_modifiers("Control"), Some(4));
+        assert_eq!(parse_modifiers("Mod4+Shift"), Some(65));
+        assert_eq!(parse_modifiers("ctrl+alt"), Some(12));
+        assert_eq!(parse_modifiers("Hyper"), None);
+    }
+
+    #[test]
+    fn test_resolve_path_precedence() {
+        let cli = Some(PathBuf::from("/tmp/cli.toml"));
+        let env = Some(OsString::from("/tmp/env.toml"));
+
+        assert_eq!(
+            Config::resolve_path_with_env(cli.clone(), env.clone()),
+            PathBuf::from("/tmp/cli.toml")
+        );
+        assert_eq!(
+            Config::resolve_path_with_env(None, env),
+            PathBuf::from("/tmp/env.toml")
+        );
+        assert_eq!(
+            Config::resolve_path_with_env(None, Some(OsString::new())),
+            Config::default_path()
+        );
+        assert_eq!(Config::resolve_path_with_env(None, None), Config::default_path());
+    }
+
+    #[test]
+    fn test_option_defaults_and_overrides() {
+        // (TOML setting the option, how to read it back, default, value when set)
+        type Getter = fn(&Config) -> String;
+        let cases: &[(&str, Getter, &str, &str)] = &[
+            ("[general]\nauto_group_by_class = true", |c| format!("{:?}", c.general.auto_group_by_class), "false", "true"),
+            ("[general]\nbezel_gap = 24", |c| format!("{:?}", c.general.bezel_gap), "0", "24"),
+            ("[general]\nfloat_at_tiled_position = true", |c| format!("{:?}", c.general.float_at_tiled_position), "false", "true"),
+            ("[general]\nworkspace_wrap = false", |c| format!("{:?}", c.general.workspace_wrap), "true", "false"),
+            ("[general]\nworkspace_count = 4", |c| format!("{:?}", c.general.workspace_count), "9", "4"),
+            ("[general]\nworkspace_indicator_ms = 600", |c| format!("{:?}", c.general.workspace_indicator_ms), "0", "600"),
+            ("[general]\nfocus_wrap = false", |c| format!("{:?}", c.general.focus_wrap), "true", "false"),
+            ("[general]\ncycle_scope = \"visible\"", |c| format!("{:?}", c.general.cycle_scope), "All", "Visible"),
+            ("[general]\ntab_cycle_warps = true", |c| format!("{:?}", c.general.tab_cycle_warps), "false", "true"),
+            ("[general]\nurgent_order = \"lifo\"", |c| format!("{:?}", c.general.urgent_order), "Fifo", "Lifo"),
+            ("[general]\ncycle_monitor_scope = \"all\"", |c| format!("{:?}", c.general.cycle_monitor_scope), "Current", "All"),
+            ("[general]\nclose_focus = \"mru\"", |c| format!("{:?}", c.general.close_focus), "Next", "Mru"),
+            ("[general]\nmax_tabs_per_frame = 5", |c| format!("{:?}", c.general.max_tabs_per_frame), "0", "5"),
+            ("[general]\nexit_behavior = \"leave\"", |c| format!("{:?}", c.general.exit_behavior), "Remap", "Leave"),
+            ("[general]\noverride_redirect_flip = \"ignore\"", |c| format!("{:?}", c.general.override_redirect_flip), "Release", "Ignore"),
+            ("[general]\nrestore_session = true", |c| format!("{:?}", c.general.restore_session), "false", "true"),
+            ("[general]\nunmap_grace_ms = 150", |c| format!("{:?}", c.general.unmap_grace_ms), "0", "150"),
+            ("[general]\nresize_button = 3", |c| format!("{:?}", c.general.resize_button), "1", "3"),
+            ("[general]\nresize_from_border = true", |c| format!("{:?}", c.general.resize_from_border), "false", "true"),
+            ("[general]\npointer_barriers = true", |c| format!("{:?}", c.general.pointer_barriers), "false", "true"),
+            ("[general]\npointer_barrier_push = 400", |c| format!("{:?}", c.general.pointer_barrier_push), "150", "400"),
+            ("[general]\nauto_balance = true", |c| format!("{:?}", c.general.auto_balance), "false", "true"),
+            ("[general]\nraise_tabbar_always = true", |c| format!("{:?}", c.general.raise_tabbar_always), "false", "true"),
+            ("[general]\nmove_follows = true", |c| format!("{:?}", c.general.move_follows), "false", "true"),
+            ("[general]\nmonitor_focus_follows_mouse = true", |c| format!("{:?}", c.general.monitor_focus_follows_mouse), "false", "true"),
+            ("[general]\nopen_mode = \"split\"", |c| format!("{:?}", c.general.open_mode), "Tab", "Split"),
+            ("[colors]\nempty_frame_bg = \"#202020\"", |c| c.colors.empty_frame_bg.clone(), "", "#202020"),
+            ("[appearance]\nspawn_animation = \"slide\"", |c| format!("{:?}", c.appearance.spawn_animation), "None", "Slide"),
+            ("[appearance]\nspawn_animation_ms = 200", |c| format!("{:?}", c.appearance.spawn_animation_ms), "120", "200"),
+            ("[appearance]\nresize_gravity = \"center\"", |c| format!("{:?}", c.appearance.resize_gravity), "TopLeft", "Center"),
+            ("[appearance]\ntab_sizing = \"fixed\"", |c| format!("{:?}", c.appearance.tab_sizing), "Content", "Fixed"),
+            ("[appearance]\nfixed_tab_width = 120", |c| format!("{:?}", c.appearance.fixed_tab_width), "160", "120"),
+            ("[appearance]\ntab_doubleclick_action = \"close\"", |c| format!("{:?}", c.appearance.tab_doubleclick_action), "Fullscreen", "Close"),
+            ("[appearance]\ntab_doubleclick_action = \"maximize\"", |c| format!("{:?}", c.appearance.tab_doubleclick_action), "Fullscreen", "Maximize"),
+            ("[appearance]\nicon_cache_size = 0", |c| format!("{:?}", c.appearance.icon_cache_size), "128", "0"),
+            ("[appearance]\ntab_compact = true", |c| format!("{:?}", c.appearance.tab_compact), "false", "true"),
+            ("[appearance]\ntab_bar_click_through = true", |c| format!("{:?}", c.appearance.tab_bar_click_through), "false", "true"),
+            ("[bar]\nenabled = true", |c| format!("{:?}", c.bar.enabled), "false", "true"),
+            ("[bar]\nposition = \"bottom\"", |c| format!("{:?}", c.bar.position), "Top", "Bottom"),
+            ("[bar]\nheight = 24", |c| format!("{:?}", c.bar.height), "20", "24"),
+        ];
+        let defaults: Config = toml::from_str("").unwrap();
+        for (toml, get, default, set) in cases {
+            let config: Config = toml::from_str(toml).unwrap_or_else(|e| panic!("{:?}: {}", toml, e));
+            assert_eq!(get(&defaults), *default, "default for {:?}", toml);
+            assert_eq!(get(&config), *set, "{:?}", toml);
+        }
+
+        // Enum options reject unknown names
+        for toml in [
+            "[general]\noverride_redirect_flip = \"fight\"",
+            "[appearance]\nspawn_animation = \"bounce\"",
+            "[appearance]\nresize_gravity = \"south\"",
+        ] {
+            assert!(toml::from_str::<Config>(toml).is_err(), "{:?}", toml);
+        }
+    }
+
+    #[test]
+    fn test_unmanaged_classes() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.general.unmanaged_classes.is_empty());
+        assert!(!config.general.is_unmanaged_class("conky", "Conky"));
+
+        let config: Config = toml::from_str("[general]\nunmanaged_classes = [\"Conky\", \"trayer\"]").unwrap();
+        assert!(config.general.is_unmanaged_class("conky", "Conky"));
+        assert!(config.general.is_unmanaged_class("trayer", "Panel"));
+        assert!(!config.general.is_unmanaged_class("xterm", "XTerm"));
+        // Names are matched exactly
+        assert!(!config.general.is_unmanaged_class("conky", "conky"));
+    }
+
+    #[test]
+    fn test_idle_action() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.general.idle_timeout_s, 0);
+        assert!(config.general.idle_action.is_empty());
+
+        let config: Config = toml::from_str("[general]\nidle_timeout_s = 300\nidle_action = \"workspace:1\"").unwrap();
+        assert_eq!(config.general.idle_timeout_s, 300);
+        assert_eq!(IdleAction::parse(&config.general.idle_action), Some(IdleAction::Workspace(0)));
+
+        assert_eq!(IdleAction::parse("exec: xset dpms force off"), Some(IdleAction::Exec("xset dpms force off".to_string())));
+        assert_eq!(IdleAction::parse("workspace:10"), None);
+        assert_eq!(IdleAction::parse("exec:"), None);
+        assert_eq!(IdleAction::parse("sleep"), None);
+    }
+
+    #[test]
+    fn test_parse_rgb_color() {
+        assert_eq!(parse_rgb_color("#334455"), Some(0x334455));
+        assert_eq!(parse_rgb_color("a0b0c0"), Some(0xa0b0c0));
+        assert_eq!(parse_rgb_color("#fff"), None);
+        assert_eq!(parse_rgb_color("#ffffffff"), None);
+        assert_eq!(parse_rgb_color("#gg0000"), None);
+        assert_eq!(parse_rgb_color(""), None);
+    }
+
+    #[test]
+    fn test_theme_overrides_config() {
+        let mut table: toml::Table = toml::from_str(
+            "[general]\ntheme = \"dark.toml\"\n\
+             [appearance]\ngap = 8\nborder_width = 2\ntab_font_size = 12\n\
+             [colors]\nborder_focused = \"#5294e2\"\ntab_text = \"#ffffff\"",
+        ).unwrap();
+        let theme = parse_theme(
+            "[colors]\nborder_focused = \"#e06c75\"\n\
+             [appearance]\ngap = 2\ntab_font_size = 20\n\
+             [keybindings]\nquit = \"Mod4+q\"",
+        ).unwrap();
+        overlay_theme(&mut table, theme);
+        let config: Config = table.try_into().unwrap();
+
+        // Theme values win; what the theme leaves out comes from the config
+        assert_eq!(config.colors.border_focused, "#e06c75");
+        assert_eq!(config.colors.tab_text, "#ffffff");
+        assert_eq!(config.appearance.gap, 2);
+        assert_eq!(config.appearance.border_width, 2);
+        // Only colors and THEME_APPEARANCE_KEYS can be themed
+        assert_eq!(config.appearance.tab_font_size, 12);
+        assert_eq!(config.keybindings.quit.as_deref(), Some("Mod4+Control+F4"));
+
+        let mut layout = LayoutConfig::default();
+        layout.apply_theme(&config);
+        assert_eq!(layout.border_focused, 0xe06c75);
+        assert_eq!(layout.gap, 2);
+
+        // Bad values are the theme's fault, not the config's
+        assert!(parse_theme("[appearance]\ngap = \"wide\"").is_err());
+    }
+
+    #[test]
+    fn test_theme_path() {
+        let config = Path::new("/etc/ttwm/config.toml");
+        assert_eq!(theme_path("themes/dark.toml", config), PathBuf::from("/etc/ttwm/themes/dark.toml"));
+        assert_eq!(theme_path("/opt/dark.toml", config), PathBuf::from("/opt/dark.toml"));
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(theme_path("~/dark.toml", config), home.join("dark.toml"));
+        }
+    }
+
+    #[test]
+    fn test_workspace_border_colors() {
+        let config: Config = toml::from_str(
+            "[colors]\nborder_focused = \"#5294e2\"\n\
+             [workspace.3]\nborder_focused = \"#e06c75\"\n\
+             [workspace.12]\nborder_focused = \"#ffffff\"\n\
+             [workspace.4]\nborder_focused = \"nope\"",
+        ).unwrap();
+        let colors = config.workspace_border_colors();
+        assert_eq!(colors.len(), 1);
+        assert_eq!(colors.get(&2), Some(&0xe06c75));
+
+        // Switching workspaces picks the override, others fall back to the global color
+        let layout = LayoutConfig {
+            border_focused: 0x5294e2,
+            workspace_border_focused: colors,
+            ..LayoutConfig::default()
+        };
+        assert_eq!(layout.border_focused_for(2), 0xe06c75);
+        assert_eq!(layout.border_focused_for(0), 0x5294e2);
+        assert_eq!(layout.border_focused_for(3), 0x5294e2);
+    }
+
+    #[test]
+    fn test_monitor_font_sizes() {
+        let config: Config = toml::from_str(
+            "[appearance]\ntab_font_size = 12\n\
+             [monitor.\"DP-1\"]\ntab_font_size = 20\n\
+             [monitor.\"HDMI-1\"]",
+        ).unwrap();
+        let sizes = config.monitor_font_sizes(1.0);
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes.get("DP-1"), Some(&20));
+
+        // Scaled like the global tab font size
+        assert_eq!(config.monitor_font_sizes(1.5).get("DP-1"), Some(&30));
+        assert!(toml::from_str::<Config>("").unwrap().monitor_font_sizes(1.0).is_empty());
+    }
+
+    #[test]
+    fn test_workspace_gaps() {
+        let config: Config = toml::from_str(
+            "[appearance]\ngap = 8\n\
+             [workspace.2]\ngap = 0\n\
+             [workspace.5]\ngap = 20\n\
+             [workspace.12]\ngap = 4",
+        ).unwrap();
+        let gaps = config.workspace_gaps();
+        assert_eq!(gaps.len(), 2);
+
+        let layout = LayoutConfig {
+            gap: 8,
+            workspace_gap: gaps,
+            ..LayoutConfig::default()
+        };
+        assert_eq!(layout.gap_for(1), 0);
+        assert_eq!(layout.gap_for(4), 20);
+        assert_eq!(layout.gap_for(0), 8);
+
+        // Switching between workspaces changes the frame geometry accordingly
+        let mut tree = crate::layout::LayoutTree::new();
+        tree.add_window(1);
+        tree.split_focused(crate::layout::SplitDirection::Horizontal);
+        let screen = crate::types::Rect::new(0, 0, 1000, 500);
+        let gapless = tree.calculate_geometries(screen, layout.gap_for(1));
+        let gapped = tree.calculate_geometries(screen, layout.gap_for(4));
+        assert_eq!(gapless[0].1.width + gapless[1].1.width, 1000);
+        assert_eq!(gapped[0].1.width + gapped[1].1.width, 980);
+    }
+
+    #[test]
+    fn test_action_from_name() {
+        assert_eq!(WmAction::from_name("cycle_tab_forward"), Some(WmAction::CycleTabForward));
+        assert_eq!(WmAction::from_name("focus_left"), Some(WmAction::FocusDirection(Direction::Left)));
+        assert_eq!(WmAction::from_name("new_frame_up"), Some(WmAction::NewFrame(Direction::Up)));
+        assert_eq!(WmAction::from_name("focus_tab_3"), Some(WmAction::FocusTab(3)));
+        assert_eq!(WmAction::from_name("focus_tab_0"), None);
+        assert_eq!(WmAction::from_name("focus_tab_10"), None);
+        assert_eq!(WmAction::from_name("spawn"), None);
+        assert_eq!(WmAction::from_name("CycleTabForward"), None);
+    }
+
+    #[test]
+    fn test_terminal_fallbacks() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.general.spawn_candidates("xterm"), vec!["xterm", "alacritty", "kitty"]);
+
+        // A primary that isn't in the list still gets every fallback
+        assert_eq!(config.general.spawn_candidates("urxvt"), vec!["urxvt", "alacritty", "kitty", "xterm"]);
+
+        // Arguments after the program name carry over to the fallbacks
+        assert_eq!(
+            config.general.spawn_candidates("kitty -e htop"),
+            vec!["kitty -e htop", "alacritty -e htop", "xterm -e htop"]
+        );
+
+        let config: Config = toml::from_str("[general]\nterminal_fallbacks = [\"foot\", \"st -f Mono\"]").unwrap();
+        assert_eq!(config.general.spawn_candidates("foot"), vec!["foot", "st -f Mono"]);
+        assert_eq!(config.general.spawn_candidates("foot -e top"), vec!["foot -e top", "st -f Mono -e top"]);
+
+        let config: Config = toml::from_str("[general]\nterminal_fallbacks = []").unwrap();
+        assert_eq!(config.general.spawn_candidates("urxvt"), vec!["urxvt"]);
+    }
+
+    #[test]
+    fn test_tab_bar_size() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.appearance.tab_bar_height, TabBarSize::Pixels(28));
+
+        let config: Config = toml::from_str(
+            "[appearance]\ntab_bar_height = \"auto\"\nvertical_tab_width = 32",
+        ).unwrap();
+        assert_eq!(config.appearance.tab_bar_height, TabBarSize::Auto);
+        assert_eq!(config.appearance.vertical_tab_width, TabBarSize::Pixels(32));
+        assert_eq!(config.appearance.tab_bar_height.resolve(40), 40);
+        assert_eq!(config.appearance.vertical_tab_width.resolve(40), 32);
+
+        assert!(toml::from_str::<Config>("[appearance]\ntab_bar_height = \"big\"").is_err());
+    }
+
+    #[test]
+    fn test_ui_scale() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.appearance.scale, UiScale::Factor(1.0));
+
+        let config: Config = toml::from_str("[appearance]\nscale = \"auto\"").unwrap();
+        assert_eq!(config.appearance.scale, UiScale::Auto);
+        assert_eq!(config.appearance.scale.resolve(|| Some(192.0)), 2.0);
+        assert_eq!(config.appearance.scale.resolve(|| None), 1.0);
+
+        let config: Config = toml::from_str("[appearance]\nscale = 1.5").unwrap();
+        assert_eq!(config.appearance.scale.resolve(|| panic!("DPI not needed")), 1.5);
+        assert_eq!(UiScale::Factor(10.0).resolve(|| None), 4.0);
+        assert!(toml::from_str::<Config>("[appearance]\nscale = \"huge\"").is_err());
+    }
+
+    #[test]
+    fn test_scale_doubles_gaps_in_geometries() {
+        use crate::layout::{LayoutTree, SplitDirection};
+        use crate::types::Rect;
+
+        let mut config: Config = toml::from_str(
+            "[appearance]\nscale = 2\ngap = 8\nborder_width = 2\ntab_bar_height = 26\ntab_font_size = 11\n\
+             min_frame_size = 100\n[bar]\nheight = 20\n[workspace.3]\ngap = 5",
+        ).unwrap();
+        let scale = config.appearance.scale.resolve(|| None);
+        config.apply_scale(scale);
+        assert_eq!(config.appearance.border_width, 4);
+        assert_eq!(config.appearance.tab_bar_height, TabBarSize::Pixels(52));
+        assert_eq!(config.appearance.tab_font_size, 22);
+        assert_eq!(config.appearance.tab_icon_size, 40);
+        assert_eq!(config.appearance.min_frame_size, 200);
+        assert_eq!(config.bar.height, 40);
+        assert_eq!(config.workspace_gaps().get(&2), Some(&10));
+
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Horizontal);
+        let screen = Rect::new(0, 0, 1000, 500);
+        let gap_between = |gap| {
+            let geometries = tree.calculate_geometries(screen, gap);
+            let (left, right) = (geometries[0].1, geometries[1].1);
+            right.x - (left.x + left.width as i32)
+        };
+        assert_eq!(gap_between(config.appearance.gap), 2 * gap_between(8));
+    }
+
+    #[test]
+    fn test_tab_label() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.appearance.tab_label, TabLabel::Title);
+        assert!(config.appearance.tab_label_by_class.is_empty());
+
+        let config: Config = toml::from_str(
+            "[appearance]\ntab_label = \"class_title\"\n\n[appearance.tab_label_by_class]\nEmacs = \"class\"",
+        ).unwrap();
+        assert_eq!(config.appearance.tab_label, TabLabel::ClassTitle);
+        assert_eq!(config.appearance.tab_label_by_class.get("Emacs"), Some(&TabLabel::Class));
+    }
+
+    #[test]
+    fn test_tab_modifier_actions() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(
+            config.appearance.tab_modifier_masks(),
+            vec![(1, TabDoubleClickAction::Close), (4, TabDoubleClickAction::Float)]
+        );
+
+        // A table replaces the defaults; invalid or empty modifiers are skipped
+        let toml = r#"
+[appearance.tab_modifier_actions]
+"Mod4+Shift" = "fullscreen"
+Hyper = "close"
+"" = "float"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.appearance.tab_modifier_masks(), vec![(65, TabDoubleClickAction::Fullscreen)]);
+
+        // Two spellings of the same modifiers resolve the same way every time
+        let toml = r#"
+[appearance.tab_modifier_actions]
+"Shift+Control" = "close"
+"Control+Shift" = "float"
+Mod4 = "fullscreen"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.appearance.tab_modifier_masks(),
+            vec![(5, TabDoubleClickAction::Float), (64, TabDoubleClickAction::Fullscreen)]
+        );
+    }
+
     #[test]
     fn test_startup_config_simple_frame() {
         let toml = r#"
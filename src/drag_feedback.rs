@@ -0,0 +1,176 @@
+//! Drop target feedback for tab drags.
+//!
+//! While a tab is being dragged, an overlay shows where it will land: a thin
+//! insertion bar over the target tab, or an outline around the target frame
+//! when dropping onto its content area.
+
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::types::Rect;
+
+/// Thickness of the insertion bar and frame outline in pixels.
+pub const INDICATOR_THICKNESS: u32 = 3;
+
+/// Overlay windows used to draw the drop indicator.
+///
+/// Four thin override-redirect windows are used so that a frame outline can be
+/// drawn without covering the frame's contents. An insertion bar only uses the
+/// first one.
+pub struct DropIndicator {
+    /// Edge windows (top, bottom, left, right), created lazily
+    windows: Option<[Window; 4]>,
+    /// Whether the indicator is currently mapped
+    visible: bool,
+}
+
+impl DropIndicator {
+    /// Create a new, not-yet-realized drop indicator.
+    pub fn new() -> Self {
+        Self {
+            windows: None,
+            visible: false,
+        }
+    }
+
+    /// Create the overlay windows on first use.
+    fn ensure_windows(&mut self, conn: &impl Connection, root: Window, color: u32) -> Result<[Window; 4]> {
+        if let Some(windows) = self.windows {
+            return Ok(windows);
+        }
+
+        let mut windows = [0; 4];
+        for window in windows.iter_mut() {
+            *window = conn.generate_id()?;
+            conn.create_window(
+                x11rb::COPY_DEPTH_FROM_PARENT,
+                *window,
+                root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                x11rb::COPY_FROM_PARENT,
+                &CreateWindowAux::new()
+                    .background_pixel(color)
+                    .override_redirect(1), // Don't manage this window
+            )?;
+        }
+        self.windows = Some(windows);
+        Ok(windows)
+    }
+
+    /// Show a single insertion bar covering `rect`.
+    pub fn show_bar(&mut self, conn: &impl Connection, root: Window, color: u32, rect: Rect) -> Result<()> {
+        let windows = self.ensure_windows(conn, root, color)?;
+        place(conn, windows[0], color, rect)?;
+        for &window in &windows[1..] {
+            conn.unmap_window(window)?;
+        }
+        self.visible = true;
+        conn.flush()?;
+        Ok(())
+    }
+
+    /// Show an outline around `rect`.
+    pub fn show_outline(&mut self, conn: &impl Connection, root: Window, color: u32, rect: Rect) -> Result<()> {
+        let windows = self.ensure_windows(conn, root, color)?;
+        for (window, edge) in windows.iter().zip(outline_edges(rect, INDICATOR_THICKNESS)) {
+            place(conn, *window, color, edge)?;
+        }
+        self.visible = true;
+        conn.flush()?;
+        Ok(())
+    }
+
+    /// Hide the indicator if it is showing.
+    pub fn hide(&mut self, conn: &impl Connection) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        if let Some(windows) = self.windows {
+            for window in windows {
+                conn.unmap_window(window)?;
+            }
+            conn.flush()?;
+        }
+        self.visible = false;
+        Ok(())
+    }
+}
+
+impl Default for DropIndicator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Move, recolor, map and raise one overlay window.
+fn place(conn: &impl Connection, window: Window, color: u32, rect: Rect) -> Result<()> {
+    conn.change_window_attributes(window, &ChangeWindowAttributesAux::new().background_pixel(color))?;
+    conn.configure_window(
+        window,
+        &ConfigureWindowAux::new()
+            .x(rect.x)
+            .y(rect.y)
+            .width(rect.width.max(1))
+            .height(rect.height.max(1))
+            .stack_mode(StackMode::ABOVE),
+    )?;
+    conn.map_window(window)?;
+    conn.clear_area(false, window, 0, 0, 0, 0)?;
+    Ok(())
+}
+
+/// Whether the pointer has moved far enough from where a tab was pressed
+/// (more than `threshold` pixels on either axis) to count as a drag.
+pub fn past_threshold(start: (i32, i32), pos: (i32, i32), threshold: u32) -> bool {
+    let threshold = threshold as i32;
+    (pos.0 - start.0).abs() > threshold || (pos.1 - start.1).abs() > threshold
+}
+
+/// Split a rectangle's outline into top, bottom, left and right edge strips.
+pub fn outline_edges(rect: Rect, thickness: u32) -> [Rect; 4] {
+    let t = thickness.min(rect.width / 2).min(rect.height / 2).max(1);
+    let inner_height = rect.height.saturating_sub(2 * t);
+    [
+        Rect::new(rect.x, rect.y, rect.width, t),
+        Rect::new(rect.x, rect.y + rect.height as i32 - t as i32, rect.width, t),
+        Rect::new(rect.x, rect.y + t as i32, t, inner_height),
+        Rect::new(rect.x + rect.width as i32 - t as i32, rect.y + t as i32, t, inner_height),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline_edges() {
+        let edges = outline_edges(Rect::new(10, 20, 100, 50), 3);
+        assert_eq!(edges[0], Rect::new(10, 20, 100, 3));
+        assert_eq!(edges[1], Rect::new(10, 67, 100, 3));
+        assert_eq!(edges[2], Rect::new(10, 23, 3, 44));
+        assert_eq!(edges[3], Rect::new(107, 23, 3, 44));
+    }
+
+    #[test]
+    fn test_past_threshold() {
+        assert!(!past_threshold((100, 100), (100, 100), 5));
+        assert!(!past_threshold((100, 100), (105, 95), 5));
+        assert!(past_threshold((100, 100), (106, 100), 5));
+        assert!(past_threshold((100, 100), (100, 94), 5));
+        // A zero threshold drags on any movement
+        assert!(past_threshold((100, 100), (101, 100), 0));
+    }
+
+    #[test]
+    fn test_outline_edges_clamps_thickness() {
+        let edges = outline_edges(Rect::new(0, 0, 4, 4), 3);
+        assert_eq!(edges[0].height, 2);
+        assert_eq!(edges[2].height, 0);
+    }
+}
@@ -8,10 +8,35 @@ use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 use x11rb::protocol::Event;
 
-use crate::layout::{NodeId, SplitDirection};
+use crate::config::{active_bindings, TabDoubleClickAction, BINDABLE_MODIFIERS};
+use crate::drag_feedback;
+use crate::layout::{FrameStyle, NodeId, Rect, SplitDirection};
+use crate::launcher;
+use crate::marks;
+use crate::state::UnmanageReason;
+use crate::tab_bar;
 use crate::window_query;
+use crate::workspaces::MoveOrigin;
 use crate::Wm;
 
+/// Maximum interval between two clicks on a tab to count as a double-click (ms)
+const DOUBLE_CLICK_MS: u32 = 400;
+
+/// Server timestamp carried by an event, if it has one
+fn event_time(event: &Event) -> Option<Timestamp> {
+    match event {
+        Event::KeyPress(e) => Some(e.time),
+        Event::KeyRelease(e) => Some(e.time),
+        Event::ButtonPress(e) => Some(e.time),
+        Event::ButtonRelease(e) => Some(e.time),
+        Event::MotionNotify(e) => Some(e.time),
+        Event::EnterNotify(e) => Some(e.time),
+        Event::LeaveNotify(e) => Some(e.time),
+        Event::PropertyNotify(e) => Some(e.time),
+        _ => None,
+    }
+}
+
 /// Edge or corner of a floating window for resizing
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResizeEdge {
@@ -35,6 +60,12 @@ pub enum DragState {
         source_frame: NodeId,
         /// Original tab index
         source_index: usize,
+        /// Pointer position when the tab was pressed (root coordinates)
+        start_x: i32,
+        start_y: i32,
+        /// Set once the pointer moves past `appearance.drag_threshold`;
+        /// releasing before that is just a click
+        active: bool,
     },
     /// Resizing a split by dragging the gap
     Resize {
@@ -129,7 +160,7 @@ impl Wm {
             let desktop = event.data.as_data32()[0] as usize;
             log::info!("ClientMessage: _NET_WM_DESKTOP move 0x{:x} to {}", window, desktop);
 
-            self.move_window_to_workspace(window, desktop)?;
+            self.move_window_to_workspace(window, desktop, MoveOrigin::Client)?;
         } else if msg_type == self.atoms.net_wm_state {
             // _NET_WM_STATE: Change window state (fullscreen, etc.)
             // data[0]: action (0=remove, 1=add, 2=toggle)
@@ -167,13 +198,35 @@ impl Wm {
 
     /// Handle an X11 event
     pub fn handle_event(&mut self, event: Event) -> Result<()> {
+        if let Some(time) = event_time(&event) {
+            self.last_event_time = time;
+        }
         match event {
             Event::MapRequest(e) => {
                 self.tracer.trace_x11_event("MapRequest", Some(e.window), "");
                 log::debug!("MapRequest for window 0x{:x}", e.window);
+                if self.cancel_pending_unmanage(e.window) {
+                    // Still managed; only the visible tab goes back on screen
+                    if !self.hidden_windows.contains(&e.window) {
+                        self.conn.map_window(e.window)?;
+                        self.conn.flush()?;
+                    }
+                    return Ok(());
+                }
                 self.manage_window(e.window)?;
             }
 
+            Event::MapNotify(e) => {
+                self.cancel_pending_unmanage(e.window);
+                if e.override_redirect {
+                    self.handle_override_redirect_flip(e.window)?;
+                }
+            }
+
+            Event::ConfigureNotify(e) if e.override_redirect && e.event == self.root => {
+                self.handle_override_redirect_flip(e.window)?;
+            }
+
             Event::UnmapNotify(e) => {
                 self.tracer.trace_x11_event("UnmapNotify", Some(e.window), "");
                 log::debug!("UnmapNotify for window 0x{:x}", e.window);
@@ -181,7 +234,12 @@ impl Wm {
                 // and not from a reparent operation
                 // Also skip if we intentionally hid this window (it's a hidden tab)
                 if e.event == self.root && !self.hidden_windows.contains(&e.window) {
-                    if let Err(e) = self.unmanage_window(e.window) {
+                    let grace_ms = self.user_config.general.unmap_grace_ms;
+                    if grace_ms > 0 && self.find_window_workspace(e.window).is_some() {
+                        // Give flickery clients a chance to map again before unmanaging
+                        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(grace_ms);
+                        self.pending_unmanage.insert(e.window, deadline);
+                    } else if let Err(e) = self.unmanage_window_with_reason(e.window, UnmanageReason::ClientUnmapped) {
                         log::error!("Failed to unmanage window: {}", e);
                     }
                 }
@@ -197,12 +255,82 @@ impl Wm {
 
             Event::ConfigureRequest(e) => {
                 self.tracer.trace_x11_event("ConfigureRequest", Some(e.window), "");
-                // For now, allow all configure requests
                 log::debug!("ConfigureRequest for window 0x{:x}", e.window);
 
-                // If we're managing this window, re-apply layout (ignore client's request)
-                if self.workspaces().current().layout.find_window(e.window).is_some() {
-                    self.apply_layout()?;
+                // Look on every monitor: a window held on an unfocused one
+                // must not escape either
+                let bezel_gap = self.user_config.general.bezel_gap;
+                let takeover = self.monitors.takeover_of(e.window, bezel_gap, |id| self.usable_area(id));
+                if let Some((_, geom)) = takeover {
+                    // Fullscreen/zoomed: put it back exactly over the monitor
+                    // (or span) so a client can't shrink itself out of place
+                    self.conn.configure_window(
+                        e.window,
+                        &ConfigureWindowAux::new()
+                            .x(geom.x)
+                            .y(geom.y)
+                            .width(geom.width)
+                            .height(geom.height)
+                            .border_width(0),
+                    )?;
+                    window_query::send_configure_notify(
+                        &self.conn,
+                        e.window,
+                        geom.x as i16,
+                        geom.y as i16,
+                        geom.width as u16,
+                        geom.height as u16,
+                        0,
+                    )?;
+                    self.conn.flush()?;
+                } else if let Some((monitor_id, ws_idx)) = self.find_window_global(e.window) {
+                    // Managed on some monitor's workspace, shown or not
+                    let Some(monitor) = self.monitors.get_mut(monitor_id) else {
+                        return Ok(());
+                    };
+                    let bounds = monitor.geometry;
+                    let shown = monitor.workspaces.current_index() == ws_idx;
+                    let workspace = &mut monitor.workspaces.workspaces[ws_idx];
+                    if workspace.layout.find_window(e.window).is_some() {
+                        // Tiled: the layout decides the geometry. Deny the
+                        // request, but tell the client where it really is
+                        let geom = self.conn.get_geometry(e.window)?.reply()?;
+                        window_query::send_configure_notify(
+                            &self.conn,
+                            e.window,
+                            geom.x,
+                            geom.y,
+                            geom.width,
+                            geom.height,
+                            geom.border_width,
+                        )?;
+                    } else {
+                        // Floating: honor the request within its own monitor
+                        let border = self.config.border_width;
+                        let mask = e.value_mask;
+                        let x = mask.contains(ConfigWindow::X).then_some(e.x as i32);
+                        let y = mask.contains(ConfigWindow::Y).then_some(e.y as i32);
+                        let width = mask.contains(ConfigWindow::WIDTH).then_some(e.width as u32 + border * 2);
+                        let height = mask.contains(ConfigWindow::HEIGHT).then_some(e.height as u32 + border * 2);
+                        workspace.apply_configure_request(e.window, x, y, width, height, bounds);
+                        let float = workspace.find_floating(e.window).copied();
+                        if monitor_id == self.monitors.focused_id() && shown {
+                            self.apply_floating_layout()?;
+                        } else if let Some(float) = float.filter(|_| shown) {
+                            // Shown on another monitor: move just this window.
+                            // On a hidden workspace the stored geometry is
+                            // used the next time it's shown
+                            self.conn.configure_window(
+                                e.window,
+                                &ConfigureWindowAux::new()
+                                    .x(float.x)
+                                    .y(float.y)
+                                    .width(float.width.saturating_sub(border * 2))
+                                    .height(float.height.saturating_sub(border * 2)),
+                            )?;
+                        }
+                    }
+                    self.conn.flush()?;
                 } else {
                     // Unmanaged window - allow the configure
                     let aux = ConfigureWindowAux::from_configure_request(&e);
@@ -213,8 +341,10 @@ impl Wm {
 
             Event::EnterNotify(e) => {
                 self.tracer.trace_x11_event("EnterNotify", Some(e.event), "");
-                // Focus follows mouse (unless suppressed after explicit focus)
-                if !self.suppress_enter_focus {
+                // Switch monitors first so window lookups below see the monitor under the pointer
+                self.focus_monitor_at_pointer(e.root_x as i32, e.root_y as i32)?;
+                // Focus follows mouse (unless disabled, or suppressed after explicit focus)
+                if self.focus_follows_mouse && !self.suppress_enter_focus {
                     // Check if window is tiled or floating
                     let is_tiled = self.workspaces().current().layout.find_window(e.event).is_some();
                     let is_floating = self.workspaces().current().is_floating(e.event);
@@ -232,12 +362,27 @@ impl Wm {
             }
 
             Event::KeyPress(e) => {
+                self.note_input();
                 self.tracer.trace_x11_event("KeyPress", None, &format!("keycode={}", e.detail));
                 self.handle_key_press(e)?;
             }
 
             Event::Expose(e) => {
                 self.tracer.trace_x11_event("Expose", Some(e.window), "");
+                #[cfg(feature = "mirror")]
+                self.redraw_mirror_view(e.window)?;
+                if self.launcher.owns_window(e.window) {
+                    self.launcher.draw(&self.conn, &self.tab_bars.font_renderer, self.tab_bars.gc, &self.config)?;
+                }
+                if e.count == 0 && self.workspace_indicator.owns_window(e.window) {
+                    self.workspace_indicator.draw(&self.conn, &self.tab_bars.font_renderer, self.tab_bars.gc, &self.config)?;
+                }
+                if e.count == 0 && self.help.owns_window(e.window) {
+                    self.help.draw(&self.conn, &self.tab_bars.font_renderer, self.tab_bars.gc, &self.config)?;
+                }
+                if e.count == 0 && self.status_bar.owns_window(e.window) {
+                    self.update_bar()?;
+                }
                 // Redraw tab bar if it's one of ours
                 self.handle_expose(e)?;
             }
@@ -253,6 +398,9 @@ impl Wm {
                 // Redraw tab bar if title changed
                 if e.atom == self.atoms.net_wm_name || e.atom == u32::from(AtomEnum::WM_NAME) {
                     self.redraw_tabs_for_window(e.window)?;
+                    if self.focused_window == Some(e.window) {
+                        self.update_bar()?;
+                    }
                 }
                 // Handle urgent state changes (EWMH _NET_WM_STATE or legacy WM_HINTS)
                 if e.atom == self.atoms.net_wm_state || e.atom == u32::from(AtomEnum::WM_HINTS) {
@@ -271,20 +419,21 @@ impl Wm {
                     }
                 }
                 // Handle strut changes for dock windows
-                if e.atom == self.atoms.net_wm_strut || e.atom == self.atoms.net_wm_strut_partial {
-                    if self.dock_windows.contains_key(&e.window) {
-                        let new_struts = window_query::read_struts(&self.conn, &self.atoms, e.window);
-                        log::info!(
-                            "Dock 0x{:x} struts changed: top={}, bottom={}, left={}, right={}",
-                            e.window, new_struts.top, new_struts.bottom, new_struts.left, new_struts.right
-                        );
-                        self.dock_windows.insert(e.window, new_struts);
-                        self.apply_layout()?;
-                    }
+                if (e.atom == self.atoms.net_wm_strut || e.atom == self.atoms.net_wm_strut_partial)
+                    && self.dock_windows.contains_key(&e.window)
+                {
+                    let new_struts = window_query::read_struts(&self.conn, &self.atoms, e.window);
+                    log::info!(
+                        "Dock 0x{:x} struts changed: top={}, bottom={}, left={}, right={}",
+                        e.window, new_struts.top, new_struts.bottom, new_struts.left, new_struts.right
+                    );
+                    self.dock_windows.insert(e.window, new_struts);
+                    self.apply_layout()?;
                 }
             }
 
             Event::ButtonPress(e) => {
+                self.note_input();
                 self.tracer.trace_x11_event("ButtonPress", Some(e.event), &format!("button={}", e.detail));
                 // Handle clicks on tab bars
                 self.handle_button_press(e)?;
@@ -297,6 +446,7 @@ impl Wm {
             }
 
             Event::MotionNotify(e) => {
+                self.note_input();
                 // Handle resize drag - update split ratio in real-time
                 if let Some(DragState::Resize { split_id, direction, split_start, total_size }) = &self.drag_state {
                     // Copy values to avoid borrow conflict
@@ -383,8 +533,21 @@ impl Wm {
                     self.apply_floating_layout()?;
                     self.conn.flush()?;
                 }
-                // Tab drags don't need motion processing - drop target determined at release
+                // Handle tab drag - highlight the drop target under the cursor
+                else if let Some(DragState::Tab { start_x, start_y, active, .. }) = &mut self.drag_state {
+                    if !*active && drag_feedback::past_threshold(
+                        (*start_x, *start_y),
+                        (e.root_x as i32, e.root_y as i32),
+                        self.config.drag_threshold,
+                    ) {
+                        *active = true;
+                    }
+                    if *active && self.config.drag_feedback {
+                        self.update_drop_indicator(e.root_x, e.root_y)?;
+                    }
+                }
                 else if self.drag_state.is_none() {
+                    self.focus_monitor_at_pointer(e.root_x as i32, e.root_y as i32)?;
                     // No drag in progress - update cursor based on hover position
                     self.update_hover_cursor(e.root_x as i32, e.root_y as i32)?;
                 }
@@ -394,6 +557,23 @@ impl Wm {
                 self.handle_client_message(e)?;
             }
 
+            Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) => {
+                self.tracer.trace_x11_event("RandrNotify", None, "");
+                self.handle_monitor_change()?;
+            }
+
+            Event::XinputBarrierHit(e) => {
+                if let Some(barriers) = &mut self.barriers {
+                    barriers.handle_hit(&self.conn, &e)?;
+                }
+            }
+
+            Event::XinputBarrierLeave(e) => {
+                if let Some(barriers) = &mut self.barriers {
+                    barriers.handle_leave(&e);
+                }
+            }
+
             Event::MappingNotify(e) => {
                 self.tracer.trace_x11_event("MappingNotify", None, &format!("request={:?}", e.request));
                 // Re-grab keys when keyboard mapping changes (Modifier or Keyboard, not Pointer)
@@ -403,6 +583,13 @@ impl Wm {
                 }
             }
 
+            #[cfg(feature = "mirror")]
+            Event::DamageNotify(e) => {
+                if let Some(m) = self.mirrors.find_by_damage(e.damage) {
+                    crate::mirror::redraw(&self.conn, self.tab_bars.gc, m)?;
+                }
+            }
+
             _ => {
                 // Ignore other events for now
             }
@@ -426,7 +613,7 @@ impl Wm {
 
                 // Get frame geometry to redraw
                 let screen_rect = self.usable_screen();
-                let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.config.gap);
+                let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
                 for (fid, rect) in geometries {
                     if fid == frame_id {
                         self.draw_tab_bar(frame_id, tab_window, &rect, vertical)?;
@@ -443,40 +630,16 @@ impl Wm {
     /// Try to handle a gap resize drag initiation.
     /// Returns Ok(true) if the click started a resize operation, Ok(false) otherwise.
     fn try_handle_gap_resize(&mut self, event: &ButtonPressEvent) -> Result<bool> {
-        // Only handle left-clicks on root window
-        if event.event != self.root || event.detail != 1 {
+        // Only handle the configured resize button on the root window
+        if event.event != self.root || event.detail != self.user_config.general.resize_button {
             return Ok(false);
         }
 
         let screen = self.usable_screen();
         if let Some((split_id, direction, split_start, total_size)) =
-            self.workspaces().current().layout.find_split_at_gap(screen, self.config.gap, event.root_x as i32, event.root_y as i32)
+            self.workspaces().current().layout.find_split_at_gap(screen, self.gap(), event.root_x as i32, event.root_y as i32)
         {
-            // Select the appropriate resize cursor based on split direction
-            let resize_cursor = match direction {
-                SplitDirection::Horizontal => self.cursor_resize_h,
-                SplitDirection::Vertical => self.cursor_resize_v,
-            };
-
-            // Start resize drag - grab pointer to track motion
-            self.conn.grab_pointer(
-                false,
-                self.root,
-                EventMask::BUTTON_RELEASE | EventMask::POINTER_MOTION,
-                GrabMode::ASYNC,
-                GrabMode::ASYNC,
-                x11rb::NONE,  // confine_to
-                resize_cursor,
-                x11rb::CURRENT_TIME,
-            )?;
-
-            self.drag_state = Some(DragState::Resize {
-                split_id,
-                direction,
-                split_start,
-                total_size,
-            });
-
+            self.start_split_resize(split_id, direction, split_start, total_size)?;
             log::info!("Started gap resize for {:?} split", direction);
             return Ok(true);
         }
@@ -484,6 +647,96 @@ impl Wm {
         Ok(false)
     }
 
+    /// Try to start a split resize from a press on a tiled window's border
+    /// (general.resize_from_border). Presses on client windows reach ttwm
+    /// through the synchronous button grab set up in manage_window, which
+    /// freezes the pointer until the press is taken here or replayed to the
+    /// client, so this runs before anything that could drop the press.
+    /// Only presses on the border pixels themselves are taken, and none
+    /// while presentation mode blocks the mouse.
+    /// Returns Ok(true) if the press was on a managed window.
+    fn try_handle_border_resize(&mut self, event: &ButtonPressEvent) -> Result<bool> {
+        if !self.user_config.general.resize_from_border
+            || event.event == self.root
+            || event.detail != self.user_config.general.resize_button
+        {
+            return Ok(false);
+        }
+        let window = event.event;
+        let managed = self.monitors.iter().any(|(_, monitor)| {
+            monitor.workspaces.workspaces.iter()
+                .any(|ws| ws.is_floating(window) || ws.layout.find_window(window).is_some())
+        });
+        let blocked = self.presentation_mode && self.user_config.general.presentation_blocks_mouse;
+
+        let frame = self.workspaces().current().layout.find_window(window).filter(|_| !blocked);
+        let split = match frame {
+            Some(frame_id) => {
+                // Event coordinates are relative to the inside of the border
+                let geometry = self.conn.get_geometry(window)?.reply()?;
+                let screen = self.usable_screen();
+                let gap = self.gap();
+                self.workspaces().current().layout.find_split_at_border(
+                    screen,
+                    gap,
+                    frame_id,
+                    (event.event_x as i32, event.event_y as i32),
+                    (geometry.width as u32, geometry.height as u32),
+                )
+            }
+            None => None,
+        };
+
+        match split {
+            Some((split_id, direction, split_start, total_size)) => {
+                self.conn.allow_events(Allow::ASYNC_POINTER, event.time)?;
+                self.start_split_resize(split_id, direction, split_start, total_size)?;
+                log::info!("Started border resize for {:?} split", direction);
+            }
+            None => {
+                // Also for windows no longer managed; a press that wasn't
+                // grabbed (tab bars, ...) isn't frozen and this is a no-op
+                self.conn.allow_events(Allow::REPLAY_POINTER, event.time)?;
+            }
+        }
+        Ok(managed)
+    }
+
+    /// Grab the pointer and start dragging a split's divider
+    fn start_split_resize(
+        &mut self,
+        split_id: NodeId,
+        direction: SplitDirection,
+        split_start: i32,
+        total_size: u32,
+    ) -> Result<()> {
+        // Select the appropriate resize cursor based on split direction
+        let resize_cursor = match direction {
+            SplitDirection::Horizontal => self.cursor_resize_h,
+            SplitDirection::Vertical => self.cursor_resize_v,
+        };
+
+        // Grab pointer to track motion
+        self.conn.grab_pointer(
+            false,
+            self.root,
+            EventMask::BUTTON_RELEASE | EventMask::POINTER_MOTION,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+            x11rb::NONE,  // confine_to
+            resize_cursor,
+            x11rb::CURRENT_TIME,
+        )?;
+
+        self.drag_state = Some(DragState::Resize {
+            split_id,
+            direction,
+            split_start,
+            total_size,
+        });
+        Ok(())
+    }
+
     /// Try to handle a click on an empty frame area.
     /// Returns Ok(true) if an empty frame was focused, Ok(false) otherwise.
     fn try_handle_empty_frame_click(&mut self, event: &ButtonPressEvent) -> Result<bool> {
@@ -493,7 +746,7 @@ impl Wm {
         }
 
         let screen = self.usable_screen();
-        let geometries = self.workspaces().current().layout.calculate_geometries(screen, self.config.gap);
+        let geometries = self.workspaces().current().layout.calculate_geometries(screen, self.gap());
 
         for (frame_id, rect) in &geometries {
             if let Some(frame) = self.workspaces().current().layout.get(*frame_id).and_then(|n| n.as_frame()) {
@@ -521,6 +774,9 @@ impl Wm {
 
         // Handle middle click - remove empty frame
         if event.detail == 2 {
+            if !self.is_middle_click_close(event) {
+                return Ok(());
+            }
             if let Some(frame) = self.workspaces().current().layout.get(frame_id).and_then(|n| n.as_frame()) {
                 if frame.is_empty() {
                     // Remove tab bar window and its pixmap buffer
@@ -531,7 +787,9 @@ impl Wm {
                         self.conn.destroy_window(tab_window)?;
                     }
                     // Remove this specific empty frame from layout
+                    self.workspaces_mut().current_mut().push_layout_history();
                     self.workspaces_mut().current_mut().layout.remove_frame_by_id(frame_id);
+                    self.auto_balance(self.workspaces().current_index());
                     self.apply_layout()?;
                     log::info!("Removed empty frame via middle-click");
                 }
@@ -548,6 +806,7 @@ impl Wm {
         if let Some(frame) = self.workspaces().current().layout.get(frame_id).and_then(|n| n.as_frame()) {
             let num_tabs = frame.windows.len();
             let is_vertical = frame.vertical_tabs;
+            let is_stacked = frame.style == FrameStyle::Stacked;
             if num_tabs == 0 {
                 // Focus the empty frame
                 self.workspaces_mut().current_mut().layout.focused = frame_id;
@@ -556,7 +815,10 @@ impl Wm {
             }
 
             // Calculate which tab was clicked
-            let clicked_tab = if is_vertical {
+            let clicked_tab = if is_stacked {
+                // Stacked titles: one full-width row per tab
+                tab_bar::tab_at_offset(event.event_y as i32, self.config.tab_bar_height, num_tabs)
+            } else if is_vertical {
                 // Vertical tabs: each tab is a square of vertical_tab_width size
                 let tab_size = self.config.vertical_tab_width;
                 let click_y = event.event_y as u32;
@@ -569,7 +831,7 @@ impl Wm {
             } else {
                 // Horizontal tabs: use content-based layout
                 let tab_layout = self.calculate_tab_layout(frame_id);
-                let click_x = event.event_x as i16;
+                let click_x = event.event_x;
                 tab_layout.iter().enumerate()
                     .find(|(_, (x, w))| click_x >= *x && click_x < *x + *w as i16)
                     .map(|(i, _)| i)
@@ -579,6 +841,19 @@ impl Wm {
                 // Get the window at this tab
                 let window = frame.windows[clicked_tab];
 
+                // Modifier+click runs the configured action on the tab without
+                // focusing it or starting a drag
+                let modifiers = u16::from(event.state) & BINDABLE_MODIFIERS;
+                let modifier_action = self.config.tab_modifier_actions.iter()
+                    .find(|&&(mask, _)| mask == modifiers)
+                    .map(|&(_, action)| action);
+                if let Some(action) = modifier_action {
+                    log::info!("Modifier-click on tab {} (window 0x{:x}): {:?}", clicked_tab + 1, window, action);
+                    self.last_tab_click = None;
+                    self.run_tab_action(action, window)?;
+                    return Ok(());
+                }
+
                 // Focus this tab immediately
                 if let Some(w) = self.workspaces_mut().current_mut().layout.focus_tab(clicked_tab) {
                     self.apply_layout()?;
@@ -588,6 +863,24 @@ impl Wm {
                     self.skip_focus_tab_bar_redraw = false;
                 }
 
+                // A second click on the same tab in quick succession is a double-click:
+                // run the configured action instead of starting another drag
+                let is_double_click = matches!(
+                    self.last_tab_click,
+                    Some((w, t)) if w == window && event.time.wrapping_sub(t) <= DOUBLE_CLICK_MS
+                );
+                if is_double_click {
+                    self.last_tab_click = None;
+                    let action = self.user_config.appearance.tab_doubleclick_action;
+                    if action != TabDoubleClickAction::None {
+                        log::info!("Double-click on tab {} (window 0x{:x}): {:?}", clicked_tab + 1, window, action);
+                        self.run_tab_action(action, window)?;
+                        return Ok(());
+                    }
+                } else {
+                    self.last_tab_click = Some((window, event.time));
+                }
+
                 // Start drag operation - grab pointer to track motion
                 self.conn.grab_pointer(
                     false,
@@ -600,21 +893,77 @@ impl Wm {
                     x11rb::CURRENT_TIME,
                 )?;
 
+                // Watch for Escape to cancel the drag
+                let reply = self.conn.grab_keyboard(
+                    false,
+                    self.root,
+                    x11rb::CURRENT_TIME,
+                    GrabMode::ASYNC,
+                    GrabMode::ASYNC,
+                )?.reply()?;
+                if reply.status != GrabStatus::SUCCESS {
+                    log::debug!("Could not grab keyboard for tab drag: {:?}", reply.status);
+                }
+
                 self.drag_state = Some(DragState::Tab {
                     window,
                     source_frame: frame_id,
                     source_index: clicked_tab,
+                    start_x: event.root_x as i32,
+                    start_y: event.root_y as i32,
+                    active: false,
                 });
 
                 log::info!("Started drag for tab {} (window 0x{:x})", clicked_tab + 1, window);
+            } else if self.user_config.appearance.tab_bar_click_through {
+                // Bar background past the last tab. Tab hits (modifier actions,
+                // double-clicks, drags) were handled above, so this never
+                // swallows a click meant for a tab
+                let current = frame.focused_window();
+                self.last_tab_click = None;
+                self.workspaces_mut().current_mut().layout.focused = frame_id;
+                self.apply_layout()?;
+                if let Some(window) = current {
+                    self.focus_window(window)?;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Run a double-click or modifier-click action on a tab's window
+    fn run_tab_action(&mut self, action: TabDoubleClickAction, window: Window) -> Result<()> {
+        match action {
+            TabDoubleClickAction::Fullscreen => self.toggle_fullscreen(Some(window)),
+            TabDoubleClickAction::Maximize => self.toggle_maximize(Some(window)),
+            TabDoubleClickAction::Float => self.toggle_float(Some(window)),
+            TabDoubleClickAction::Close => {
+                log::info!("Closing window 0x{:x}", window);
+                self.note_close_requested(&[window]);
+                self.request_close(window)
+            }
+            TabDoubleClickAction::None => Ok(()),
+        }
+    }
+
+    /// Check that a middle-click carries exactly the configured close modifier,
+    /// so a stray middle-click paste can't remove frames
+    fn is_middle_click_close(&self, event: &ButtonPressEvent) -> bool {
+        u16::from(event.state) & BINDABLE_MODIFIERS == self.config.middle_click_close_mask
+    }
+
     /// Handle button press event (click on tab bar or gap for resize)
     fn handle_button_press(&mut self, event: ButtonPressEvent) -> Result<()> {
+        // First, so a press frozen by the border grab is always released
+        if self.try_handle_border_resize(&event)? {
+            return Ok(());
+        }
+
+        if self.presentation_mode && self.user_config.general.presentation_blocks_mouse {
+            return Ok(());
+        }
+
         // Check for gap resize or empty frame click on root window
         if self.try_handle_gap_resize(&event)? {
             return Ok(());
@@ -639,6 +988,9 @@ impl Wm {
         if let Some(frame_id) = clicked_empty_frame {
             if event.detail == 2 {
                 // Middle-click: remove empty frame
+                if !self.is_middle_click_close(&event) {
+                    return Ok(());
+                }
                 if let Some(empty_window) = self.tab_bars.empty_frame_windows.remove(&(mon_id, ws_idx, frame_id)) {
                     self.conn.destroy_window(empty_window)?;
                 }
@@ -648,7 +1000,9 @@ impl Wm {
                     }
                     self.conn.destroy_window(tab_window)?;
                 }
+                self.workspaces_mut().current_mut().push_layout_history();
                 self.workspaces_mut().current_mut().layout.remove_frame_by_id(frame_id);
+                self.auto_balance(self.workspaces().current_index());
                 self.apply_layout()?;
                 log::info!("Removed empty frame via middle-click on content area");
                 return Ok(());
@@ -785,8 +1139,8 @@ impl Wm {
             let geom = self.conn.get_geometry(tab_window)?.reply()?;
             let coords = self.conn.translate_coordinates(tab_window, self.root, 0, 0)?.reply()?;
 
-            let tab_x = coords.dst_x as i16;
-            let tab_y = coords.dst_y as i16;
+            let tab_x = coords.dst_x;
+            let tab_y = coords.dst_y;
 
             if root_x >= tab_x && root_x < tab_x + geom.width as i16 &&
                root_y >= tab_y && root_y < tab_y + geom.height as i16 {
@@ -798,7 +1152,15 @@ impl Wm {
                     .map(|f| f.vertical_tabs)
                     .unwrap_or(false);
 
-                let target_index = if is_vertical {
+                let target_index = if self.frame_is_stacked(frame_id) {
+                    // Stacked titles: use y position, one row per tab
+                    let num_tabs = self.workspaces().current().layout
+                        .get(frame_id)
+                        .and_then(|n| n.as_frame())
+                        .map(|f| f.windows.len())
+                        .unwrap_or(0);
+                    tab_bar::tab_at_offset((root_y - tab_y) as i32, self.config.tab_bar_height, num_tabs)
+                } else if is_vertical {
                     // Vertical tabs: use y position
                     let local_y = root_y - tab_y;
                     let tab_size = self.config.vertical_tab_width;
@@ -835,7 +1197,7 @@ impl Wm {
 
         // Check frame content areas (for dropping into single-window frames or frames without visible tab bars)
         let screen_rect = self.usable_screen();
-        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.config.gap);
+        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
 
         for (frame_id, rect) in geometries {
             if (root_x as i32) >= rect.x && (root_x as i32) < rect.x + rect.width as i32 &&
@@ -847,10 +1209,63 @@ impl Wm {
         Ok((None, None))
     }
 
+    /// Show the drop indicator for the drop target at the given root position.
+    ///
+    /// Drops onto a tab get an insertion bar at that tab's leading edge; drops
+    /// onto a frame's content area (or past the last tab) outline the frame.
+    fn update_drop_indicator(&mut self, root_x: i16, root_y: i16) -> Result<()> {
+        let (target_frame, target_index) = self.find_drop_target(root_x, root_y)?;
+        let frame_id = match target_frame {
+            Some(f) => f,
+            None => return self.drop_indicator.hide(&self.conn),
+        };
+        let color = self.config.tab_focused_bg;
+
+        if let Some(index) = target_index {
+            let key = (self.monitors.focused_id(), self.workspaces().current_index(), frame_id);
+            if let Some(&tab_window) = self.tab_bars.windows.get(&key) {
+                let geom = self.conn.get_geometry(tab_window)?.reply()?;
+                let coords = self.conn.translate_coordinates(tab_window, self.root, 0, 0)?.reply()?;
+                let is_vertical = self.workspaces().current().layout
+                    .get(frame_id)
+                    .and_then(|n| n.as_frame())
+                    .map(|f| f.vertical_tabs)
+                    .unwrap_or(false);
+
+                let bar = if self.frame_is_stacked(frame_id) {
+                    let y = coords.dst_y as i32 + (index as u32 * self.config.tab_bar_height) as i32;
+                    Rect::new(coords.dst_x as i32, y, geom.width as u32, drag_feedback::INDICATOR_THICKNESS)
+                } else if is_vertical {
+                    let y = coords.dst_y as i32 + (index as u32 * self.config.vertical_tab_width) as i32;
+                    Rect::new(coords.dst_x as i32, y, geom.width as u32, drag_feedback::INDICATOR_THICKNESS)
+                } else {
+                    let tab_x = self.calculate_tab_layout(frame_id)
+                        .get(index)
+                        .map(|&(x, _)| x)
+                        .unwrap_or(0);
+                    let x = coords.dst_x as i32 + tab_x as i32;
+                    Rect::new(x, coords.dst_y as i32, drag_feedback::INDICATOR_THICKNESS, geom.height as u32)
+                };
+                return self.drop_indicator.show_bar(&self.conn, self.root, color, bar);
+            }
+        }
+
+        let screen_rect = self.usable_screen();
+        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
+        match geometries.into_iter().find(|(id, _)| *id == frame_id) {
+            Some((_, rect)) => self.drop_indicator.show_outline(&self.conn, self.root, color, rect),
+            None => self.drop_indicator.hide(&self.conn),
+        }
+    }
+
     /// Handle button release event (end of drag)
     fn handle_button_release(&mut self, event: ButtonReleaseEvent) -> Result<()> {
-        // Only handle left button
-        if event.detail != 1 {
+        // Only handle the button that started the drag (left, or resize_button for gap resizes)
+        let drag_button = match self.drag_state {
+            Some(DragState::Resize { .. }) => self.user_config.general.resize_button,
+            _ => 1,
+        };
+        if event.detail != drag_button {
             return Ok(());
         }
 
@@ -864,7 +1279,15 @@ impl Wm {
         };
 
         match drag {
-            DragState::Tab { window, source_frame, source_index } => {
+            DragState::Tab { active: false, window, .. } => {
+                self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+                self.conn.flush()?;
+                log::debug!("Tab press on 0x{:x} released below drag threshold", window);
+            }
+            DragState::Tab { window, source_frame, source_index, .. } => {
+                self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+                self.drop_indicator.hide(&self.conn)?;
+
                 // Find what's under the cursor at root coordinates
                 let (target_frame, target_index) = self.find_drop_target(event.root_x, event.root_y)?;
 
@@ -873,13 +1296,16 @@ impl Wm {
                         // Reorder within same frame
                         if let Some(target_idx) = target_index {
                             if target_idx != source_index {
+                                self.workspaces_mut().current_mut().push_layout_history();
                                 self.workspaces_mut().current_mut().layout.reorder_tab(target_frame, source_index, target_idx);
                                 log::info!("Reordered tab from {} to {}", source_index + 1, target_idx + 1);
                             }
                         }
                     } else {
                         // Move to different frame
+                        self.workspaces_mut().current_mut().push_layout_history();
                         self.workspaces_mut().current_mut().layout.move_window_to_frame(window, source_frame, target_frame);
+                        self.auto_normalize(self.workspaces().current_index());
 
                         log::info!("Moved window 0x{:x} to different frame", window);
                     }
@@ -907,6 +1333,18 @@ impl Wm {
         Ok(())
     }
 
+    /// Abandon a tab drag in progress, leaving the tab where it was
+    fn cancel_tab_drag(&mut self) -> Result<()> {
+        if let Some(DragState::Tab { window, .. }) = self.drag_state.take() {
+            self.conn.ungrab_pointer(x11rb::CURRENT_TIME)?;
+            self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+            self.drop_indicator.hide(&self.conn)?;
+            self.conn.flush()?;
+            log::info!("Tab drag of window 0x{:x} cancelled", window);
+        }
+        Ok(())
+    }
+
     /// Handle a key press event
     fn handle_key_press(&mut self, event: KeyPressEvent) -> Result<()> {
         // Convert state to u16 and mask out NumLock and CapsLock for comparison
@@ -935,9 +1373,46 @@ impl Wm {
             clean_state
         );
 
+        // Escape cancels a tab drag; other keys are swallowed by its grab
+        if matches!(self.drag_state, Some(DragState::Tab { .. })) {
+            if keysym == 0xff1b {
+                self.cancel_tab_drag()?;
+            }
+            return Ok(());
+        }
+
+        // The launcher takes all keys while open (using the Shift level for text)
+        if self.launcher.is_visible() {
+            let shifted = state_u16 & u16::from(ModMask::SHIFT) != 0;
+            let keysym = match mapping.keysyms.get(idx + 1) {
+                Some(&upper) if shifted && keysyms_per_keycode > 1 && upper != 0 => upper,
+                _ => keysym,
+            };
+            if let Some(key) = launcher::key_from_keysym(keysym) {
+                self.launcher_key(key)?;
+            }
+            return Ok(());
+        }
+
+        // Any key (other than a bare modifier) closes the cheat sheet
+        if self.help.is_visible() {
+            if !marks::is_modifier_keysym(keysym) {
+                self.hide_help()?;
+            }
+            return Ok(());
+        }
+
+        // The key after a mark prefix names the mark (modifiers alone don't count)
+        if let Some(prompt) = self.mark_prompt {
+            if !marks::is_modifier_keysym(keysym) {
+                self.finish_mark_prompt(prompt, marks::mark_from_keysym(keysym))?;
+            }
+            return Ok(());
+        }
+
         // Find matching action from configured keybindings
         let mut matched_action = None;
-        for (action, binding) in &self.keybindings {
+        for (action, binding) in active_bindings(&self.keybindings, self.presentation_mode) {
             if binding.keysym == keysym && binding.modifiers == clean_state {
                 matched_action = Some(action.clone());
                 break;
@@ -16,6 +16,7 @@ pub struct Atoms {
     // ICCCM atoms
     pub wm_protocols: Atom,
     pub wm_delete_window: Atom,
+    pub wm_take_focus: Atom,
 
     // Core EWMH atoms
     pub net_supported: Atom,
@@ -29,6 +30,7 @@ pub struct Atoms {
     pub net_current_desktop: Atom,
     pub net_number_of_desktops: Atom,
     pub net_desktop_names: Atom,
+    pub net_desktop_viewport: Atom,
     pub net_wm_desktop: Atom,
 
     // Icon atom
@@ -44,6 +46,7 @@ pub struct Atoms {
 
     // Window type atoms (for auto-float detection)
     pub net_wm_window_type: Atom,
+    pub net_wm_window_opacity: Atom,
     pub net_wm_window_type_dialog: Atom,
     pub net_wm_window_type_splash: Atom,
     pub net_wm_window_type_toolbar: Atom,
@@ -58,6 +61,11 @@ pub struct Atoms {
     // Strut atoms (for dock/panel space reservation)
     pub net_wm_strut: Atom,
     pub net_wm_strut_partial: Atom,
+    pub xrootpmap_id: Atom,
+    pub esetroot_pmap_id: Atom,
+
+    // Marker for windows created by ttwm itself
+    pub ttwm_internal: Atom,
 }
 
 impl Atoms {
@@ -66,6 +74,7 @@ impl Atoms {
         Ok(Self {
             wm_protocols: Self::intern(conn, b"WM_PROTOCOLS")?,
             wm_delete_window: Self::intern(conn, b"WM_DELETE_WINDOW")?,
+            wm_take_focus: Self::intern(conn, b"WM_TAKE_FOCUS")?,
             net_supported: Self::intern(conn, b"_NET_SUPPORTED")?,
             net_client_list: Self::intern(conn, b"_NET_CLIENT_LIST")?,
             net_active_window: Self::intern(conn, b"_NET_ACTIVE_WINDOW")?,
@@ -75,6 +84,7 @@ impl Atoms {
             net_current_desktop: Self::intern(conn, b"_NET_CURRENT_DESKTOP")?,
             net_number_of_desktops: Self::intern(conn, b"_NET_NUMBER_OF_DESKTOPS")?,
             net_desktop_names: Self::intern(conn, b"_NET_DESKTOP_NAMES")?,
+            net_desktop_viewport: Self::intern(conn, b"_NET_DESKTOP_VIEWPORT")?,
             net_wm_desktop: Self::intern(conn, b"_NET_WM_DESKTOP")?,
             net_wm_icon: Self::intern(conn, b"_NET_WM_ICON")?,
             net_close_window: Self::intern(conn, b"_NET_CLOSE_WINDOW")?,
@@ -82,6 +92,7 @@ impl Atoms {
             net_wm_state_demands_attention: Self::intern(conn, b"_NET_WM_STATE_DEMANDS_ATTENTION")?,
             net_wm_state_fullscreen: Self::intern(conn, b"_NET_WM_STATE_FULLSCREEN")?,
             net_wm_window_type: Self::intern(conn, b"_NET_WM_WINDOW_TYPE")?,
+            net_wm_window_opacity: Self::intern(conn, b"_NET_WM_WINDOW_OPACITY")?,
             net_wm_window_type_dialog: Self::intern(conn, b"_NET_WM_WINDOW_TYPE_DIALOG")?,
             net_wm_window_type_splash: Self::intern(conn, b"_NET_WM_WINDOW_TYPE_SPLASH")?,
             net_wm_window_type_toolbar: Self::intern(conn, b"_NET_WM_WINDOW_TYPE_TOOLBAR")?,
@@ -94,9 +105,54 @@ impl Atoms {
             net_wm_window_type_dock: Self::intern(conn, b"_NET_WM_WINDOW_TYPE_DOCK")?,
             net_wm_strut: Self::intern(conn, b"_NET_WM_STRUT")?,
             net_wm_strut_partial: Self::intern(conn, b"_NET_WM_STRUT_PARTIAL")?,
+            xrootpmap_id: Self::intern(conn, b"_XROOTPMAP_ID")?,
+            esetroot_pmap_id: Self::intern(conn, b"ESETROOT_PMAP_ID")?,
+            ttwm_internal: Self::intern(conn, b"_TTWM_INTERNAL")?,
         })
     }
 
+    /// All interned atoms by field name, in declaration order
+    pub fn entries(&self) -> Vec<(&'static str, Atom)> {
+        vec![
+            ("wm_protocols", self.wm_protocols),
+            ("wm_delete_window", self.wm_delete_window),
+            ("wm_take_focus", self.wm_take_focus),
+            ("net_supported", self.net_supported),
+            ("net_client_list", self.net_client_list),
+            ("net_active_window", self.net_active_window),
+            ("net_wm_name", self.net_wm_name),
+            ("net_supporting_wm_check", self.net_supporting_wm_check),
+            ("utf8_string", self.utf8_string),
+            ("net_current_desktop", self.net_current_desktop),
+            ("net_number_of_desktops", self.net_number_of_desktops),
+            ("net_desktop_names", self.net_desktop_names),
+            ("net_desktop_viewport", self.net_desktop_viewport),
+            ("net_wm_desktop", self.net_wm_desktop),
+            ("net_wm_icon", self.net_wm_icon),
+            ("net_close_window", self.net_close_window),
+            ("net_wm_state", self.net_wm_state),
+            ("net_wm_state_demands_attention", self.net_wm_state_demands_attention),
+            ("net_wm_state_fullscreen", self.net_wm_state_fullscreen),
+            ("net_wm_window_type", self.net_wm_window_type),
+            ("net_wm_window_opacity", self.net_wm_window_opacity),
+            ("net_wm_window_type_dialog", self.net_wm_window_type_dialog),
+            ("net_wm_window_type_splash", self.net_wm_window_type_splash),
+            ("net_wm_window_type_toolbar", self.net_wm_window_type_toolbar),
+            ("net_wm_window_type_utility", self.net_wm_window_type_utility),
+            ("net_wm_window_type_menu", self.net_wm_window_type_menu),
+            ("net_wm_window_type_popup_menu", self.net_wm_window_type_popup_menu),
+            ("net_wm_window_type_dropdown_menu", self.net_wm_window_type_dropdown_menu),
+            ("net_wm_window_type_tooltip", self.net_wm_window_type_tooltip),
+            ("net_wm_window_type_notification", self.net_wm_window_type_notification),
+            ("net_wm_window_type_dock", self.net_wm_window_type_dock),
+            ("net_wm_strut", self.net_wm_strut),
+            ("net_wm_strut_partial", self.net_wm_strut_partial),
+            ("xrootpmap_id", self.xrootpmap_id),
+            ("esetroot_pmap_id", self.esetroot_pmap_id),
+            ("ttwm_internal", self.ttwm_internal),
+        ]
+    }
+
     /// Intern an atom name
     fn intern(conn: &RustConnection, name: &[u8]) -> Result<Atom> {
         Ok(conn.intern_atom(false, name)?.reply()?.atom)
@@ -107,6 +163,23 @@ impl Atoms {
 // EWMH Property Update Functions
 // =============================================================================
 
+/// Mark a window as created by ttwm (tab bars, empty-frame placeholders, the
+/// check window) so that a later instance never manages it as a client.
+/// The value is the PID of the creating process.
+pub fn mark_internal(conn: &impl Connection, atoms: &Atoms, window: Window) -> Result<()> {
+    conn.change_property32(
+        PropMode::REPLACE,
+        window,
+        atoms.ttwm_internal,
+        AtomEnum::CARDINAL,
+        &[std::process::id()],
+    )?;
+    Ok(())
+}
+
+/// _NET_WM_DESKTOP value for windows shown on every desktop
+pub const ALL_DESKTOPS: usize = 0xFFFFFFFF;
+
 /// Set _NET_WM_DESKTOP property for a window.
 pub fn set_window_desktop(
     conn: &impl Connection,
@@ -142,6 +215,27 @@ pub fn update_current_desktop(
     Ok(())
 }
 
+/// Update _NET_DESKTOP_VIEWPORT property on root window.
+///
+/// ttwm's desktops are never larger than the screen, so every desktop's
+/// viewport sits at (0, 0). Pagers that lay out desktops by viewport misdraw
+/// them when the property is missing or stale.
+pub fn update_desktop_viewport(
+    conn: &impl Connection,
+    atoms: &Atoms,
+    root: Window,
+    desktops: usize,
+) -> Result<()> {
+    conn.change_property32(
+        PropMode::REPLACE,
+        root,
+        atoms.net_desktop_viewport,
+        AtomEnum::CARDINAL,
+        &vec![0u32; desktops * 2],
+    )?;
+    Ok(())
+}
+
 /// Update _NET_ACTIVE_WINDOW property on root window.
 pub fn update_active_window(
     conn: &impl Connection,
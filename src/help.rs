@@ -0,0 +1,229 @@
+//! Keybinding cheat-sheet overlay.
+//!
+//! `show_help` opens a centered override-redirect window listing every active
+//! keybinding (key combo and action name). The keyboard is grabbed while it
+//! is shown and the next key press closes it. Long lists are split into
+//! columns so the overlay fits on the monitor; the window is sized to its
+//! content and only created the first time it is opened.
+
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::config::LayoutConfig;
+use crate::render::FontRenderer;
+use crate::types::Rect;
+
+/// Padding around text inside each row
+const ROW_PADDING: u32 = 4;
+
+/// Space between a combo and its action, and between columns
+const COLUMN_GAP: u32 = 24;
+
+/// Largest share of the monitor height the overlay may use
+const MAX_HEIGHT_PERCENT: u32 = 80;
+
+/// Split `count` rows into columns of at most `max_rows` rows.
+/// Returns (columns, rows per column); rows are spread evenly.
+pub fn grid(count: usize, max_rows: usize) -> (usize, usize) {
+    if count == 0 {
+        return (1, 0);
+    }
+    let columns = count.div_ceil(max_rows.max(1));
+    (columns, count.div_ceil(columns))
+}
+
+/// Cheat-sheet state and its overlay window
+pub struct HelpOverlay {
+    /// Overlay window, created on first use
+    window: Option<Window>,
+    /// Area the overlay occupies while shown
+    rect: Rect,
+    visible: bool,
+    /// (key combo, action) rows in display order
+    entries: Vec<(String, String)>,
+    /// Rows per column for the current entries
+    rows: usize,
+    /// Widths of the combo text and of a whole column
+    combo_width: u32,
+    column_width: u32,
+}
+
+impl HelpOverlay {
+    /// Create a hidden overlay (no window yet).
+    pub fn new() -> Self {
+        Self {
+            window: None,
+            rect: Rect::new(0, 0, 1, 1),
+            visible: false,
+            entries: Vec::new(),
+            rows: 0,
+            combo_width: 0,
+            column_width: 0,
+        }
+    }
+
+    /// Whether the overlay is shown
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Check whether a window is the overlay
+    pub fn owns_window(&self, window: Window) -> bool {
+        self.window == Some(window)
+    }
+
+    /// Show `entries` centered on `monitor`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn show(
+        &mut self,
+        conn: &impl Connection,
+        root: Window,
+        monitor: Rect,
+        entries: Vec<(String, String)>,
+        font: &FontRenderer,
+        gc: Gcontext,
+        config: &LayoutConfig,
+    ) -> Result<()> {
+        let row_height = font.char_height() + ROW_PADDING * 2;
+        let max_rows = (monitor.height * MAX_HEIGHT_PERCENT / 100 / row_height) as usize;
+        let (columns, rows) = grid(entries.len(), max_rows);
+
+        self.combo_width = entries.iter().map(|(combo, _)| font.measure_text(combo)).max().unwrap_or(0);
+        let action_width = entries.iter().map(|(_, action)| font.measure_text(action)).max().unwrap_or(0);
+        self.column_width = self.combo_width + COLUMN_GAP + action_width;
+        self.entries = entries;
+        self.rows = rows;
+        self.visible = true;
+
+        let width = (columns as u32 * (self.column_width + ROW_PADDING * 2)
+            + (columns as u32 - 1) * COLUMN_GAP)
+            .clamp(1, monitor.width);
+        let height = (rows as u32 * row_height).clamp(1, monitor.height);
+        self.rect = Rect::new(
+            monitor.x + (monitor.width as i32 - width as i32) / 2,
+            monitor.y + (monitor.height as i32 - height as i32) / 2,
+            width,
+            height,
+        );
+
+        let window = match self.window {
+            Some(window) => window,
+            None => {
+                let window = conn.generate_id()?;
+                conn.create_window(
+                    x11rb::COPY_DEPTH_FROM_PARENT,
+                    window,
+                    root,
+                    0,
+                    0,
+                    1,
+                    1,
+                    0,
+                    WindowClass::INPUT_OUTPUT,
+                    x11rb::COPY_FROM_PARENT,
+                    &CreateWindowAux::new()
+                        .background_pixel(config.tab_unfocused_bg)
+                        .border_pixel(config.border_focused)
+                        .override_redirect(1) // Don't manage this window
+                        .event_mask(EventMask::EXPOSURE),
+                )?;
+                self.window = Some(window);
+                window
+            }
+        };
+
+        conn.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(self.rect.x)
+                .y(self.rect.y)
+                .width(self.rect.width)
+                .height(self.rect.height)
+                .border_width(config.border_width)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        conn.map_window(window)?;
+        self.draw(conn, font, gc, config)?;
+        Ok(())
+    }
+
+    /// Hide the overlay (the window is kept for next time).
+    pub fn hide(&mut self, conn: &impl Connection) -> Result<()> {
+        self.visible = false;
+        self.entries.clear();
+        if let Some(window) = self.window {
+            conn.unmap_window(window)?;
+            conn.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Redraw the list: combos in the normal text color, actions dimmed.
+    pub fn draw(&self, conn: &impl Connection, font: &FontRenderer, gc: Gcontext, config: &LayoutConfig) -> Result<()> {
+        let Some(window) = self.window.filter(|_| self.visible) else {
+            return Ok(());
+        };
+        let row_height = font.char_height() + ROW_PADDING * 2;
+        let bg = config.tab_unfocused_bg;
+
+        conn.clear_area(false, window, 0, 0, 0, 0)?;
+        for (i, (combo, action)) in self.entries.iter().enumerate() {
+            let column = (i / self.rows.max(1)) as u32;
+            let row = (i % self.rows.max(1)) as u32;
+            let x = column * (self.column_width + ROW_PADDING * 2 + COLUMN_GAP) + ROW_PADDING;
+            let y = row * row_height + ROW_PADDING;
+            put_text(conn, window, gc, font, combo, config.tab_text_color, bg, x, y)?;
+            put_text(conn, window, gc, font, action, config.tab_text_unfocused, bg, x + self.combo_width + COLUMN_GAP, y)?;
+        }
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+/// Render `text` and copy it into `window` at (x, y)
+#[allow(clippy::too_many_arguments)]
+fn put_text(
+    conn: &impl Connection,
+    window: Window,
+    gc: Gcontext,
+    font: &FontRenderer,
+    text: &str,
+    fg: u32,
+    bg: u32,
+    x: u32,
+    y: u32,
+) -> Result<()> {
+    let (pixels, w, h) = font.render_text(text, fg, bg);
+    if !pixels.is_empty() && w > 0 && h > 0 {
+        conn.put_image(
+            ImageFormat::Z_PIXMAP,
+            window,
+            gc,
+            w as u16,
+            h as u16,
+            x as i16,
+            y as i16,
+            0,
+            24, // depth (24-bit color, will be padded to 32)
+            &pixels,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_splits_long_lists_into_columns() {
+        assert_eq!(grid(10, 20), (1, 10));
+        assert_eq!(grid(20, 20), (1, 20));
+        // 45 rows at 20 per column: 3 columns of 15
+        assert_eq!(grid(45, 20), (3, 15));
+        assert_eq!(grid(0, 20), (1, 0));
+        // A monitor too small for even one row still gets one row per column
+        assert_eq!(grid(3, 0), (3, 1));
+    }
+}
@@ -7,6 +7,7 @@
 //! - Capture screenshots
 //! - Validate state invariants
 
+use std::collections::BTreeMap;
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::PathBuf;
@@ -14,8 +15,15 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::TabSizing;
+
 pub use crate::types::LayoutSnapshot;
 
+/// Version of the JSON shapes in IPC responses, sent as `schema_version`
+/// with every response. Bumped when a field is removed, renamed or changes
+/// type; new fields and responses don't change it.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// Get the socket path for this display
 pub fn socket_path() -> PathBuf {
     let display = std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
@@ -38,29 +46,83 @@ pub enum IpcCommand {
     GetFocused,
     /// Validate state invariants
     ValidateState,
-    /// Get recent event log
+    /// Dump the WM's internal maps and cross-check them for orphans
+    VerifyState,
+    /// Get recent event log, optionally only entries after sequence number
+    /// `since_seq` and of one type (see `EventTracer::query`); `count`
+    /// keeps the newest matches
     GetEventLog {
         #[serde(default)]
         count: Option<usize>,
+        #[serde(default)]
+        since_seq: Option<u64>,
+        #[serde(default)]
+        event_type: Option<String>,
     },
+    /// List processes spawned by ttwm (running and recently exited)
+    GetChildren,
+    /// List interned atoms and the atoms advertised in _NET_SUPPORTED
+    GetAtoms,
+    /// List the protocols in a managed window's WM_PROTOCOLS
+    /// (WM_DELETE_WINDOW, WM_TAKE_FOCUS, ...)
+    GetProtocols { window: u32 },
+    /// Get tab bar / layout render timings (requires `ttwm --profile`)
+    GetRenderStats,
+    /// Describe the fields of every response and the types they use
+    GetSchema,
 
     // Actions
     /// Focus a specific window
     FocusWindow { window: u32 },
     /// Focus a specific tab by index (1-based)
     FocusTab { index: usize },
+    /// Focus tab `index` (1-based) of a named frame, switching monitor and
+    /// workspace if the frame lives elsewhere
+    FocusTabInFrame { frame: String, index: usize },
     /// Focus frame in direction (left, right, up, down)
     FocusFrame { direction: String },
     /// Split the focused frame
     Split { direction: String },
+    /// Add an empty, focused frame on a side (left, right, up, down) of the
+    /// focused frame; its windows stay where they are
+    NewFrame { direction: String },
     /// Move window to adjacent frame
     MoveWindow { forward: bool },
     /// Resize the focused split
     ResizeSplit { delta: f32 },
+    /// Size the first child of the focused split to exactly `pixels`
+    /// in the split direction (clamped like other resizes)
+    SetSplitPixels { pixels: u32 },
     /// Close the focused window
     CloseWindow,
+    /// Ask every window in the focused frame to close (the frame is kept)
+    CloseFrame,
     /// Cycle tabs in focused frame
     CycleTab { forward: bool },
+    /// Move the focused tab to the first position in its frame
+    Promote,
+    /// Swap the focused frame with its sibling in the parent split
+    SwapWithSibling,
+    /// Collapse splits with an empty frame on one side (the focused frame and
+    /// named or locked frames are kept)
+    NormalizeTree,
+    /// Move every window on the current workspace into one frame, discarding
+    /// all splits (focus is kept)
+    FlattenWorkspace,
+    /// Reset split ratios so frames in the same direction get equal space
+    BalanceSplits,
+    /// Re-apply border widths and focused/unfocused border colors to every
+    /// managed window from the current focus and config
+    RepairBorders,
+    /// Restore the layout from before the last layout operation
+    Undo,
+    /// Run a keybinding action by name (e.g. "cycle_tab_forward"), `repeat`
+    /// times in a row (default 1)
+    SendAction {
+        action: String,
+        #[serde(default)]
+        repeat: Option<u32>,
+    },
 
     // Tagging
     /// Tag a window (uses focused window if not specified)
@@ -76,17 +138,31 @@ pub enum IpcCommand {
     /// Get list of tagged window IDs
     GetTagged,
 
+    // Marks
+    /// Mark a window with a single character (uses focused window if not specified)
+    Mark { window: Option<u32>, name: String },
+    /// Focus the window with a mark, switching monitor/workspace as needed
+    GotoMark { name: String },
+    /// Move a tiled window on the current workspace into the focused frame
+    /// as a new tab and focus it (no-op if it is already there)
+    PullWindow { window: u32 },
+
     // Floating
     /// Toggle floating state for a window (uses focused window if not specified)
     ToggleFloat { window: Option<u32> },
     /// Get list of floating window IDs
     GetFloating,
+    /// Toggle pinning a floating window so it stays visible on every workspace
+    /// of its monitor (uses focused window if not specified)
+    PinFloatGlobal { window: Option<u32> },
 
     // Fullscreen
     /// Toggle fullscreen state for a window (uses focused window if not specified)
     ToggleFullscreen { window: Option<u32> },
     /// Get fullscreen window ID (if any)
     GetFullscreen,
+    /// Toggle zoom across all monitors for a window (uses focused window if not specified)
+    ToggleZoom { window: Option<u32> },
 
     // Urgent
     /// Get list of urgent window IDs (ordered oldest first)
@@ -113,16 +189,87 @@ pub enum IpcCommand {
     GetCurrentMonitor,
     /// Focus a specific monitor by name or direction (left/right)
     FocusMonitor { target: String },
+    /// Focus a monitor by its exact (case-sensitive) output name
+    FocusMonitorByName { name: String },
+    /// Get the focused monitor with its geometry
+    GetFocusedMonitor,
+    /// Merge all outputs into one logical monitor spanning them (`merged`),
+    /// or restore the separate monitors and their windows
+    SetMonitorMode { merged: bool },
 
     // Frame naming
     /// Set the name of the focused frame (None or empty string to clear)
     SetFrameName { name: Option<String> },
     /// Find a frame by name (searches all workspaces/monitors)
     GetFrameByName { name: String },
+    /// Toggle the locked flag on the focused frame (locked frames don't receive new windows)
+    ToggleFrameLock,
+    /// Show tabs vertically (true) or horizontally (false) on the focused frame
+    SetVerticalTabs { enabled: bool },
+    /// Get whether the focused frame shows vertical tabs
+    GetVerticalTabs,
+    /// Switch the focused frame between tabbed and stacked titles
+    ToggleStacked,
+    /// Override tab sizing (content, equal or fixed) for the focused frame;
+    /// omit the mode to go back to `appearance.tab_sizing`
+    SetFrameTabMode {
+        #[serde(default)]
+        mode: Option<TabSizing>,
+    },
+    /// Tint a named frame's empty placeholder and tab bar background
+    /// ("#rrggbb"); omit the color to go back to `colors.empty_frame_bg`
+    SetFrameColor {
+        name: String,
+        #[serde(default)]
+        color: Option<String>,
+    },
+    /// Show a view-only copy of a window in a named frame (needs the `mirror` feature)
+    MirrorWindow { window: u32, frame: String },
+
+    // Startup config
+    /// Export a workspace's layout as a `[startup.workspace.N]` TOML snippet
+    /// (0-based workspace index, defaults to the current workspace)
+    ExportStartupConfig {
+        #[serde(default)]
+        workspace: Option<usize>,
+    },
+
+    // Focus behavior
+    /// Enable or disable focus-follows-mouse at runtime
+    SetFocusFollowsMouse { enabled: bool },
+    /// Get whether focus-follows-mouse is enabled
+    GetFocusFollowsMouse,
+    /// Enter or leave presentation mode (all keybindings but its toggle disabled)
+    TogglePresentationMode,
+
+    // Appearance
+    /// Change the inner and/or outer gap in place (omitted values are kept)
+    SetGaps {
+        #[serde(default)]
+        gap: Option<u32>,
+        #[serde(default)]
+        outer_gap: Option<u32>,
+    },
+    /// Switch to a theme file (`[colors]` and themable `[appearance]` settings,
+    /// as with `general.theme`); relative paths are taken from the config's directory
+    SetTheme { path: String },
+    /// Set the root window wallpaper from an image file
+    /// (mode: center, tile, stretch or fill; defaults to fill)
+    SetWallpaper {
+        path: String,
+        #[serde(default)]
+        mode: Option<String>,
+    },
+    /// Save which workspace each monitor is showing (also done on exit)
+    SaveSession,
+    /// Restore per-monitor workspaces from the saved session (also done at startup)
+    RestoreSession,
 
     // Debug
     /// Capture screenshot to file
     Screenshot { path: String },
+    /// Re-apply layout, redraw tab bars and re-publish EWMH properties
+    Refresh,
 
     // Control
     /// Quit the window manager
@@ -148,14 +295,26 @@ pub enum IpcResponse {
         valid: bool,
         violations: Vec<String>,
     },
+    /// Internal map dump and orphan check (for VerifyState)
+    Verification { valid: bool, data: VerifyReport },
     /// Event log
     EventLog { entries: Vec<EventLogEntry> },
+    /// Spawned processes
+    Children { children: Vec<ChildInfo> },
+    /// Interned atoms (keyed by `Atoms` field name) and `_NET_SUPPORTED` contents
+    Atoms { interned: BTreeMap<String, u32>, supported: Vec<AtomInfo> },
+    /// A window's WM_PROTOCOLS atoms
+    Protocols { window: u32, protocols: Vec<AtomInfo> },
+    /// Render timings collected since startup
+    RenderStats { data: RenderStatsSnapshot },
     /// Screenshot saved
     Screenshot { path: String },
     /// List of tagged window IDs
     Tagged { windows: Vec<u32> },
     /// List of floating window IDs
     Floating { windows: Vec<u32> },
+    /// Pinned state of a floating window after PinFloatGlobal
+    FloatPinned { window: u32, pinned: bool },
     /// Fullscreen window (if any)
     Fullscreen { window: Option<u32> },
     /// List of urgent window IDs (ordered oldest first)
@@ -166,6 +325,8 @@ pub enum IpcResponse {
     Monitors { data: Vec<MonitorInfo> },
     /// Current monitor info
     Monitor { name: String, is_primary: bool },
+    /// Focused monitor details (for GetFocusedMonitor and FocusMonitorByName)
+    FocusedMonitor { monitor: MonitorInfo },
     /// Frame info (for GetFrameByName)
     Frame {
         id: String,
@@ -173,7 +334,31 @@ pub enum IpcResponse {
         monitor: String,
         workspace: usize,
         window_count: usize,
+        /// Selected tab (1-based); None for an empty frame
+        focused_tab: Option<usize>,
     },
+    /// Startup config TOML snippet
+    StartupConfig { toml: String },
+    /// Tab orientation of the focused frame
+    VerticalTabs { enabled: bool },
+    /// Whether the focused frame shows stacked titles (after ToggleStacked)
+    Stacked { enabled: bool },
+    /// Focus-follows-mouse state
+    FocusFollowsMouse { enabled: bool },
+    /// First child size in effect after SetSplitPixels
+    SplitPixels { pixels: u32 },
+    /// Gaps in effect on the current workspace after SetGaps
+    Gaps { gap: u32, outer_gap: u32 },
+    /// Number of windows asked to close by CloseFrame
+    FrameClosed { requested: usize },
+    /// Number of empty frames removed by NormalizeTree
+    Normalized { removed: usize },
+    /// Number of frames removed by FlattenWorkspace
+    Flattened { removed: usize },
+    /// Number of windows whose borders RepairBorders re-applied
+    BordersRepaired { windows: usize },
+    /// Description of the response shapes (for GetSchema)
+    Schema { data: SchemaDescription },
     /// Error response
     Error { code: String, message: String },
 }
@@ -181,6 +366,9 @@ pub enum IpcResponse {
 /// Snapshot of the full WM state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WmStateSnapshot {
+    /// SCHEMA_VERSION of the snapshot, for tools that store snapshots
+    #[serde(default)]
+    pub schema_version: u32,
     pub focused_window: Option<u32>,
     pub focused_frame: String,
     pub window_count: usize,
@@ -203,11 +391,89 @@ pub struct WindowInfo {
     pub is_urgent: bool,
 }
 
+/// A process spawned by ttwm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildInfo {
+    pub pid: u32,
+    pub command: String,
+    /// Spawn time in seconds since the Unix epoch
+    pub spawned_at: u64,
+    pub alive: bool,
+    /// Exit code once exited (None while running or if killed by a signal)
+    pub exit_code: Option<i32>,
+}
+
+/// Contents of one internal map or set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MapReport {
+    pub count: usize,
+    pub keys: Vec<String>,
+}
+
+/// Frames in one workspace's layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceFrames {
+    pub monitor: String,
+    pub workspace: usize,
+    pub frames: Vec<String>,
+}
+
+/// Everything VerifyState looked at, and what didn't add up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyReport {
+    /// Internal maps keyed by name (tab_bars, pixmaps, icon_cache, ...)
+    pub maps: BTreeMap<String, MapReport>,
+    pub workspaces: Vec<WorkspaceFrames>,
+    /// Violations also reported by ValidateState
+    pub violations: Vec<String>,
+    /// Entries referring to windows, frames or monitors that no longer exist
+    pub orphans: Vec<String>,
+}
+
+/// An X11 atom and its name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AtomInfo {
+    pub name: String,
+    pub atom: u32,
+}
+
+/// Number of calls and their cumulative duration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingStat {
+    pub count: u64,
+    /// Total time spent in microseconds
+    pub total_us: u64,
+}
+
+/// Render timings since startup (see `ttwm --profile`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenderStatsSnapshot {
+    pub tab_bar_redraws: TimingStat,
+    /// Root window `get_image` samples for pseudo-transparent tab bars
+    pub background_samples: TimingStat,
+    pub layout_applications: TimingStat,
+}
+
+/// Where an event log entry came from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// An X11 event as received
+    #[default]
+    X11,
+    /// A WM state transition (window managed, frame split, ...)
+    Transition,
+    /// An IPC command and its result
+    Ipc,
+}
+
 /// Entry in the event log
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventLogEntry {
     pub sequence: u64,
     pub timestamp_ms: u64,
+    #[serde(default)]
+    pub kind: EventKind,
     pub event_type: String,
     pub window: Option<u32>,
     pub details: String,
@@ -226,6 +492,181 @@ pub struct MonitorInfo {
     pub current_workspace: usize,
 }
 
+/// Machine-readable description of the IPC response shapes. Each shape
+/// maps field names to types: Rust scalar names (`u32`, `usize`, `bool`,
+/// ...), `string`, `[T]` for arrays, `{string: T}` for objects, a trailing
+/// `?` for fields that may be null or absent, and the names of other
+/// entries in `types`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDescription {
+    pub version: u32,
+    /// Fields of each response, keyed by its `status`
+    pub responses: BTreeMap<String, BTreeMap<String, String>>,
+    /// Fields of the objects nested in responses
+    pub types: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Fields of each IpcResponse variant, keyed by `status`.
+/// Keep in sync with IpcResponse.
+const RESPONSE_SHAPES: &[(&str, &[(&str, &str)])] = &[
+    ("ok", &[]),
+    ("state", &[("data", "WmStateSnapshot")]),
+    ("layout", &[("data", "LayoutSnapshot")]),
+    ("windows", &[("data", "[WindowInfo]")]),
+    ("focused", &[("window", "u32?")]),
+    ("validation", &[("valid", "bool"), ("violations", "[string]")]),
+    ("verification", &[("valid", "bool"), ("data", "VerifyReport")]),
+    ("event_log", &[("entries", "[EventLogEntry]")]),
+    ("children", &[("children", "[ChildInfo]")]),
+    ("atoms", &[("interned", "{string: u32}"), ("supported", "[AtomInfo]")]),
+    ("protocols", &[("window", "u32"), ("protocols", "[AtomInfo]")]),
+    ("render_stats", &[("data", "RenderStatsSnapshot")]),
+    ("screenshot", &[("path", "string")]),
+    ("tagged", &[("windows", "[u32]")]),
+    ("floating", &[("windows", "[u32]")]),
+    ("float_pinned", &[("window", "u32"), ("pinned", "bool")]),
+    ("fullscreen", &[("window", "u32?")]),
+    ("urgent", &[("windows", "[u32]")]),
+    ("workspace", &[("index", "usize"), ("total", "usize")]),
+    ("monitors", &[("data", "[MonitorInfo]")]),
+    ("monitor", &[("name", "string"), ("is_primary", "bool")]),
+    ("focused_monitor", &[("monitor", "MonitorInfo")]),
+    ("frame", &[
+        ("id", "string"),
+        ("name", "string?"),
+        ("monitor", "string"),
+        ("workspace", "usize"),
+        ("window_count", "usize"),
+        ("focused_tab", "usize?"),
+    ]),
+    ("startup_config", &[("toml", "string")]),
+    ("vertical_tabs", &[("enabled", "bool")]),
+    ("stacked", &[("enabled", "bool")]),
+    ("focus_follows_mouse", &[("enabled", "bool")]),
+    ("split_pixels", &[("pixels", "u32")]),
+    ("gaps", &[("gap", "u32"), ("outer_gap", "u32")]),
+    ("frame_closed", &[("requested", "usize")]),
+    ("normalized", &[("removed", "usize")]),
+    ("flattened", &[("removed", "usize")]),
+    ("borders_repaired", &[("windows", "usize")]),
+    ("schema", &[("data", "SchemaDescription")]),
+    ("error", &[("code", "string"), ("message", "string")]),
+];
+
+/// Fields of the types nested in responses. Keep in sync with the structs.
+const TYPE_SHAPES: &[(&str, &[(&str, &str)])] = &[
+    ("WmStateSnapshot", &[
+        ("schema_version", "u32"),
+        ("focused_window", "u32?"),
+        ("focused_frame", "string"),
+        ("window_count", "usize"),
+        ("frame_count", "usize"),
+        ("layout", "LayoutSnapshot"),
+        ("windows", "[WindowInfo]"),
+    ]),
+    ("LayoutSnapshot", &[("root", "NodeSnapshot.frame | NodeSnapshot.split")]),
+    ("NodeSnapshot.frame", &[
+        ("type", "\"frame\""),
+        ("id", "string"),
+        ("name", "string?"),
+        ("windows", "[u32]"),
+        ("focused_tab", "usize"),
+        ("locked", "bool?"),
+        ("geometry", "RectSnapshot?"),
+    ]),
+    ("NodeSnapshot.split", &[
+        ("type", "\"split\""),
+        ("id", "string"),
+        ("direction", "string"),
+        ("ratio", "f32"),
+        ("first", "NodeSnapshot.frame | NodeSnapshot.split"),
+        ("second", "NodeSnapshot.frame | NodeSnapshot.split"),
+    ]),
+    ("RectSnapshot", &[("x", "i32"), ("y", "i32"), ("width", "u32"), ("height", "u32")]),
+    ("WindowInfo", &[
+        ("id", "u32"),
+        ("title", "string"),
+        ("frame", "string"),
+        ("tab_index", "usize"),
+        ("is_focused", "bool"),
+        ("is_visible", "bool"),
+        ("is_tagged", "bool"),
+        ("is_floating", "bool"),
+        ("is_urgent", "bool"),
+    ]),
+    ("ChildInfo", &[
+        ("pid", "u32"),
+        ("command", "string"),
+        ("spawned_at", "u64"),
+        ("alive", "bool"),
+        ("exit_code", "i32?"),
+    ]),
+    ("MapReport", &[("count", "usize"), ("keys", "[string]")]),
+    ("WorkspaceFrames", &[("monitor", "string"), ("workspace", "usize"), ("frames", "[string]")]),
+    ("VerifyReport", &[
+        ("maps", "{string: MapReport}"),
+        ("workspaces", "[WorkspaceFrames]"),
+        ("violations", "[string]"),
+        ("orphans", "[string]"),
+    ]),
+    ("AtomInfo", &[("name", "string"), ("atom", "u32")]),
+    ("TimingStat", &[("count", "u64"), ("total_us", "u64")]),
+    ("RenderStatsSnapshot", &[
+        ("tab_bar_redraws", "TimingStat"),
+        ("background_samples", "TimingStat"),
+        ("layout_applications", "TimingStat"),
+    ]),
+    ("EventLogEntry", &[
+        ("sequence", "u64"),
+        ("timestamp_ms", "u64"),
+        ("kind", "\"x11\" | \"transition\" | \"ipc\""),
+        ("event_type", "string"),
+        ("window", "u32?"),
+        ("details", "string"),
+    ]),
+    ("MonitorInfo", &[
+        ("name", "string"),
+        ("x", "i32"),
+        ("y", "i32"),
+        ("width", "u32"),
+        ("height", "u32"),
+        ("is_primary", "bool"),
+        ("is_focused", "bool"),
+        ("current_workspace", "usize"),
+    ]),
+    ("SchemaDescription", &[
+        ("version", "u32"),
+        ("responses", "{string: {string: string}}"),
+        ("types", "{string: {string: string}}"),
+    ]),
+];
+
+/// Describe the response shapes for GetSchema
+pub fn schema() -> SchemaDescription {
+    fn shapes(table: &[(&str, &[(&str, &str)])]) -> BTreeMap<String, BTreeMap<String, String>> {
+        table.iter()
+            .map(|(name, fields)| {
+                let fields = fields.iter().map(|(f, t)| (f.to_string(), t.to_string())).collect();
+                (name.to_string(), fields)
+            })
+            .collect()
+    }
+    SchemaDescription {
+        version: SCHEMA_VERSION,
+        responses: shapes(RESPONSE_SHAPES),
+        types: shapes(TYPE_SHAPES),
+    }
+}
+
+/// A response as written to the socket, with the schema version alongside
+/// the `status` tag
+#[derive(Serialize)]
+struct VersionedResponse<'a> {
+    #[serde(flatten)]
+    response: &'a IpcResponse,
+    schema_version: u32,
+}
+
 /// IPC server that listens on a Unix socket
 pub struct IpcServer {
     listener: UnixListener,
@@ -326,7 +767,7 @@ pub struct IpcClient {
 impl IpcClient {
     /// Send a response to the client
     pub fn respond(&mut self, response: IpcResponse) -> std::io::Result<()> {
-        let json = serde_json::to_string(&response)?;
+        let json = serde_json::to_string(&VersionedResponse { response: &response, schema_version: SCHEMA_VERSION })?;
         writeln!(self.stream, "{}", json)?;
         self.stream.flush()?;
         Ok(())
@@ -389,6 +830,316 @@ mod tests {
         assert!(json.contains("horizontal"));
     }
 
+    #[test]
+    fn test_focus_follows_mouse_serialization() {
+        let cmd = IpcCommand::SetFocusFollowsMouse { enabled: false };
+        let json = serde_json::to_string(&cmd).unwrap();
+        assert_eq!(json, r#"{"command":"set_focus_follows_mouse","enabled":false}"#);
+        let cmd: IpcCommand = serde_json::from_str(&json).unwrap();
+        assert!(matches!(cmd, IpcCommand::SetFocusFollowsMouse { enabled: false }));
+
+        let cmd = IpcCommand::GetFocusFollowsMouse;
+        let json = serde_json::to_string(&cmd).unwrap();
+        assert_eq!(json, r#"{"command":"get_focus_follows_mouse"}"#);
+        let cmd: IpcCommand = serde_json::from_str(&json).unwrap();
+        assert!(matches!(cmd, IpcCommand::GetFocusFollowsMouse));
+    }
+
+    #[test]
+    fn test_toggle_presentation_mode_serialization() {
+        let cmd = IpcCommand::TogglePresentationMode;
+        let json = serde_json::to_string(&cmd).unwrap();
+        assert_eq!(json, r#"{"command":"toggle_presentation_mode"}"#);
+        let cmd: IpcCommand = serde_json::from_str(&json).unwrap();
+        assert!(matches!(cmd, IpcCommand::TogglePresentationMode));
+    }
+
+    #[test]
+    fn test_focused_monitor_serialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"focus_monitor_by_name","name":"DP-1"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::FocusMonitorByName { ref name } if name == "DP-1"));
+
+        let resp = IpcResponse::FocusedMonitor {
+            monitor: MonitorInfo {
+                name: "DP-1".to_string(),
+                x: 1920,
+                y: 0,
+                width: 2560,
+                height: 1440,
+                is_primary: false,
+                is_focused: true,
+                current_workspace: 2,
+            },
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""status":"focused_monitor""#));
+        assert!(json.contains(r#""x":1920"#));
+    }
+
+    #[test]
+    fn test_set_frame_tab_mode_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"set_frame_tab_mode","mode":"equal"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::SetFrameTabMode { mode: Some(TabSizing::Equal) }));
+
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"set_frame_tab_mode"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::SetFrameTabMode { mode: None }));
+    }
+
+    #[test]
+    fn test_focus_tab_in_frame_deserialization() {
+        let cmd: IpcCommand =
+            serde_json::from_str(r#"{"command":"focus_tab_in_frame","frame":"editor","index":2}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::FocusTabInFrame { ref frame, index: 2 } if frame == "editor"));
+    }
+
+    #[test]
+    fn test_set_monitor_mode_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"set_monitor_mode","merged":true}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::SetMonitorMode { merged: true }));
+    }
+
+    #[test]
+    fn test_repair_borders_serialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"repair_borders"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::RepairBorders));
+
+        let json = serde_json::to_string(&IpcResponse::BordersRepaired { windows: 4 }).unwrap();
+        assert_eq!(json, r#"{"status":"borders_repaired","windows":4}"#);
+    }
+
+    #[test]
+    fn test_new_frame_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"new_frame","direction":"left"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::NewFrame { ref direction } if direction == "left"));
+    }
+
+    #[test]
+    fn test_pull_window_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"pull_window","window":4194305}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::PullWindow { window: 4194305 }));
+    }
+
+    #[test]
+    fn test_set_frame_color_deserialization() {
+        let cmd: IpcCommand =
+            serde_json::from_str(r##"{"command":"set_frame_color","name":"editor","color":"#334455"}"##).unwrap();
+        assert!(matches!(cmd, IpcCommand::SetFrameColor { ref name, color: Some(ref c) } if name == "editor" && c == "#334455"));
+
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"set_frame_color","name":"editor"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::SetFrameColor { color: None, .. }));
+    }
+
+    #[test]
+    fn test_send_action_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"send_action","action":"cycle_tab_forward","repeat":3}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::SendAction { ref action, repeat: Some(3) } if action == "cycle_tab_forward"));
+
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"send_action","action":"undo_layout"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::SendAction { repeat: None, .. }));
+    }
+
+    #[test]
+    fn test_atoms_response_serialization() {
+        let mut interned = BTreeMap::new();
+        interned.insert("net_supported".to_string(), 301);
+        let resp = IpcResponse::Atoms {
+            interned,
+            supported: vec![AtomInfo { name: "_NET_SUPPORTED".to_string(), atom: 301 }],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""status":"atoms""#));
+        assert!(json.contains(r#""interned":{"net_supported":301}"#));
+        assert!(json.contains(r#""supported":[{"name":"_NET_SUPPORTED","atom":301}]"#));
+    }
+
+    #[test]
+    fn test_protocols_serialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"get_protocols","window":4194307}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::GetProtocols { window: 4194307 }));
+
+        let resp = IpcResponse::Protocols {
+            window: 4194307,
+            protocols: vec![AtomInfo { name: "WM_DELETE_WINDOW".to_string(), atom: 312 }],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""status":"protocols""#));
+        assert!(json.contains(r#""protocols":[{"name":"WM_DELETE_WINDOW","atom":312}]"#));
+    }
+
+    #[test]
+    fn test_render_stats_response_serialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"get_render_stats"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::GetRenderStats));
+
+        let resp = IpcResponse::RenderStats {
+            data: RenderStatsSnapshot {
+                tab_bar_redraws: TimingStat { count: 12, total_us: 3400 },
+                ..Default::default()
+            },
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""status":"render_stats""#));
+        assert!(json.contains(r#""tab_bar_redraws":{"count":12,"total_us":3400}"#));
+        assert!(json.contains(r#""layout_applications":{"count":0,"total_us":0}"#));
+    }
+
+    #[test]
+    fn test_versioned_response() {
+        let json = serde_json::to_string(&VersionedResponse { response: &IpcResponse::Ok, schema_version: SCHEMA_VERSION }).unwrap();
+        assert_eq!(json, format!(r#"{{"status":"ok","schema_version":{}}}"#, SCHEMA_VERSION));
+
+        let resp = IpcResponse::Gaps { gap: 4, outer_gap: 8 };
+        let json = serde_json::to_string(&VersionedResponse { response: &resp, schema_version: SCHEMA_VERSION }).unwrap();
+        assert_eq!(json, format!(r#"{{"status":"gaps","gap":4,"outer_gap":8,"schema_version":{}}}"#, SCHEMA_VERSION));
+
+        // Clients that predate the version field still parse responses
+        let parsed: IpcResponse = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, IpcResponse::Gaps { gap: 4, outer_gap: 8 }));
+    }
+
+    #[test]
+    fn test_schema_matches_serialized_fields() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"get_schema"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::GetSchema));
+
+        let schema = schema();
+        assert_eq!(schema.version, SCHEMA_VERSION);
+
+        // Field names in the schema are the ones serde writes
+        let keys = |value: serde_json::Value| -> Vec<String> {
+            value.as_object().unwrap().keys().cloned().collect()
+        };
+        let fields = |shape: &BTreeMap<String, String>| -> Vec<String> {
+            let mut names: Vec<String> = shape.keys().cloned().collect();
+            names.sort();
+            names
+        };
+        let monitor = MonitorInfo {
+            name: "DP-1".to_string(),
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+            is_primary: true,
+            is_focused: true,
+            current_workspace: 0,
+        };
+        let mut serialized = keys(serde_json::to_value(&monitor).unwrap());
+        serialized.sort();
+        assert_eq!(serialized, fields(&schema.types["MonitorInfo"]));
+
+        let window = WindowInfo {
+            id: 1,
+            title: String::new(),
+            frame: String::new(),
+            tab_index: 0,
+            is_focused: false,
+            is_visible: false,
+            is_tagged: false,
+            is_floating: false,
+            is_urgent: false,
+        };
+        let mut serialized = keys(serde_json::to_value(&window).unwrap());
+        serialized.sort();
+        assert_eq!(serialized, fields(&schema.types["WindowInfo"]));
+
+        let mut serialized = keys(serde_json::to_value(RenderStatsSnapshot::default()).unwrap());
+        serialized.sort();
+        assert_eq!(serialized, fields(&schema.types["RenderStatsSnapshot"]));
+
+        // Every response has a status entry with its fields
+        let resp = IpcResponse::Frame {
+            id: "1v1".to_string(),
+            name: None,
+            monitor: "DP-1".to_string(),
+            workspace: 0,
+            window_count: 0,
+            focused_tab: None,
+        };
+        let value = serde_json::to_value(&resp).unwrap();
+        let status = value["status"].as_str().unwrap().to_string();
+        let mut serialized: Vec<String> = keys(value).into_iter().filter(|k| k != "status").collect();
+        serialized.sort();
+        assert_eq!(serialized, fields(&schema.responses[&status]));
+
+        // Nested type names all resolve
+        let known = |t: &str| {
+            let t = t.trim_end_matches('?').trim_start_matches('[').trim_end_matches(']');
+            let t = t.strip_prefix("{string: ").and_then(|t| t.strip_suffix('}')).unwrap_or(t);
+            t.split(" | ").all(|part| {
+                part.starts_with('"')
+                    || part.starts_with('{')
+                    || schema.types.contains_key(part)
+                    || ["string", "bool", "u32", "u64", "usize", "i32", "f32"].contains(&part)
+            })
+        };
+        for shape in schema.responses.values().chain(schema.types.values()) {
+            for t in shape.values() {
+                assert!(known(t), "unknown type {}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_gaps_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"set_gaps","gap":4}"#).unwrap();
+        match cmd {
+            IpcCommand::SetGaps { gap, outer_gap } => {
+                assert_eq!(gap, Some(4));
+                assert_eq!(outer_gap, None);
+            }
+            _ => panic!("Expected SetGaps"),
+        }
+
+        let resp = IpcResponse::Gaps { gap: 4, outer_gap: 8 };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""gap":4"#));
+        assert!(json.contains(r#""outer_gap":8"#));
+    }
+
+    #[test]
+    fn test_set_theme_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"set_theme","path":"themes/dark.toml"}"#).unwrap();
+        match cmd {
+            IpcCommand::SetTheme { path } => assert_eq!(path, "themes/dark.toml"),
+            _ => panic!("Expected SetTheme"),
+        }
+    }
+
+    #[test]
+    fn test_get_event_log_deserialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"get_event_log"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::GetEventLog { count: None, since_seq: None, event_type: None }));
+
+        let cmd: IpcCommand =
+            serde_json::from_str(r#"{"command":"get_event_log","since_seq":42,"event_type":"ipc"}"#).unwrap();
+        assert!(matches!(
+            cmd,
+            IpcCommand::GetEventLog { count: None, since_seq: Some(42), event_type: Some(ref t) } if t == "ipc"
+        ));
+    }
+
+    #[test]
+    fn test_verify_state_serialization() {
+        let cmd: IpcCommand = serde_json::from_str(r#"{"command":"verify_state"}"#).unwrap();
+        assert!(matches!(cmd, IpcCommand::VerifyState));
+
+        let mut maps = BTreeMap::new();
+        maps.insert("pixmaps".to_string(), MapReport { count: 1, keys: vec!["0x400001".to_string()] });
+        let resp = IpcResponse::Verification {
+            valid: false,
+            data: VerifyReport {
+                maps,
+                workspaces: Vec::new(),
+                violations: Vec::new(),
+                orphans: vec!["Pixmap for unknown tab bar 0x400001".to_string()],
+            },
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""status":"verification""#));
+        assert!(json.contains(r#""pixmaps":{"count":1,"keys":["0x400001"]}"#));
+    }
+
     #[test]
     fn test_response_serialization() {
         let resp = IpcResponse::Ok;
@@ -467,6 +1218,18 @@ mod tests {
         assert!(matches!(cmd, IpcCommand::GetFloating));
     }
 
+    #[test]
+    fn test_pin_float_global_round_trip() {
+        let json = r#"{"command": "pin_float_global"}"#;
+        let cmd: IpcCommand = serde_json::from_str(json).unwrap();
+        assert!(matches!(cmd, IpcCommand::PinFloatGlobal { window: None }));
+
+        let resp = IpcResponse::FloatPinned { window: 42, pinned: true };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""status":"float_pinned""#));
+        assert!(json.contains(r#""pinned":true"#));
+    }
+
     #[test]
     fn test_floating_response_serialization() {
         let resp = IpcResponse::Floating {
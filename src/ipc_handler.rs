@@ -2,13 +2,24 @@
 //!
 //! Contains the handler for all IPC commands from ttwmctl and other clients.
 
-use x11rb::protocol::xproto::Window;
+use std::collections::BTreeMap;
 
-use crate::ipc::{self, IpcCommand, IpcResponse, WmStateSnapshot, WindowInfo};
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, Window};
+
+use crate::config;
+use crate::ipc::{self, IpcCommand, IpcResponse, MapReport, VerifyReport, WmStateSnapshot, WindowInfo, WorkspaceFrames};
 use crate::layout::{Direction, SplitDirection};
+use crate::marks;
+use crate::monitor::{Monitor, MonitorId};
+use crate::tab_bar::TabBarKey;
+use crate::wallpaper::WallpaperMode;
 use crate::window_query;
+use crate::workspaces::{MoveOrigin, NUM_WORKSPACES};
 use crate::Wm;
 
+/// Upper bound for `SendAction` repetitions
+const MAX_ACTION_REPEAT: u32 = 100;
+
 impl Wm {
     /// Handle an IPC command and return a response
     pub fn handle_ipc(&mut self, cmd: IpcCommand) -> IpcResponse {
@@ -26,7 +37,7 @@ impl Wm {
             IpcCommand::GetLayout => {
                 let geometries = self.workspaces().current().layout.calculate_geometries(
                     self.usable_screen(),
-                    self.config.gap,
+                    self.gap(),
                 );
                 IpcResponse::Layout {
                     data: self.workspaces().current().layout.snapshot(Some(&geometries)),
@@ -49,13 +60,56 @@ impl Wm {
                     violations,
                 }
             }
-            IpcCommand::GetEventLog { count } => {
-                let entries = match count {
-                    Some(n) => self.tracer.get_last(n),
-                    None => self.tracer.get_all(),
-                };
+            IpcCommand::VerifyState => {
+                let data = self.verify_state();
+                IpcResponse::Verification {
+                    valid: data.violations.is_empty() && data.orphans.is_empty(),
+                    data,
+                }
+            }
+            IpcCommand::GetEventLog { count, since_seq, event_type } => {
+                let entries = self.tracer.query(count, since_seq, event_type.as_deref());
                 IpcResponse::EventLog { entries }
             }
+            IpcCommand::GetAtoms => {
+                match self.query_atoms() {
+                    Ok((interned, supported)) => IpcResponse::Atoms { interned, supported },
+                    Err(e) => IpcResponse::Error {
+                        code: "atoms_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::GetProtocols { window } => {
+                if self.find_window_global(window).is_none() {
+                    return IpcResponse::Error {
+                        code: "not_found".to_string(),
+                        message: format!("Window 0x{:x} is not managed", window),
+                    };
+                }
+                match self.query_protocols(window) {
+                    Ok(protocols) => IpcResponse::Protocols { window, protocols },
+                    Err(e) => IpcResponse::Error {
+                        code: "protocols_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::GetRenderStats => {
+                if self.render_stats.enabled() {
+                    IpcResponse::RenderStats { data: self.render_stats.snapshot() }
+                } else {
+                    IpcResponse::Error {
+                        code: "profiling_disabled".to_string(),
+                        message: "Render stats are only collected when ttwm is started with --profile".to_string(),
+                    }
+                }
+            }
+            IpcCommand::GetSchema => IpcResponse::Schema { data: crate::ipc::schema() },
+            IpcCommand::GetChildren => {
+                self.children.reap();
+                IpcResponse::Children { children: self.children.list() }
+            }
             IpcCommand::FocusWindow { window } => {
                 match self.focus_window(window) {
                     Ok(()) => IpcResponse::Ok,
@@ -74,6 +128,32 @@ impl Wm {
                     },
                 }
             }
+            IpcCommand::FocusTabInFrame { frame, index } => {
+                let Some((monitor_id, ws_idx, node_id)) = self.find_frame_by_name_global(&frame) else {
+                    return IpcResponse::Error {
+                        code: "frame_not_found".to_string(),
+                        message: format!("No frame found with name '{}'", frame),
+                    };
+                };
+                let windows = self.monitors.get(monitor_id)
+                    .and_then(|m| m.workspaces.workspaces[ws_idx].layout.get(node_id))
+                    .and_then(|n| n.as_frame())
+                    .map(|f| f.windows.clone())
+                    .unwrap_or_default();
+                let Some(&window) = index.checked_sub(1).and_then(|i| windows.get(i)) else {
+                    return IpcResponse::Error {
+                        code: "index_out_of_range".to_string(),
+                        message: format!("Frame '{}' has {} tab(s), no tab {}", frame, windows.len(), index),
+                    };
+                };
+                match self.goto_window(window) {
+                    Ok(_) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "focus_tab_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::FocusFrame { direction } => {
                 let dir = match direction.to_lowercase().as_str() {
                     "left" | "l" => Direction::Left,
@@ -106,6 +186,15 @@ impl Wm {
                         }
                     }
                 };
+                if !self.can_split_focused(dir) {
+                    return IpcResponse::Error {
+                        code: "frame_too_small".to_string(),
+                        message: format!(
+                            "Splitting would create frames smaller than {}px",
+                            self.config.min_frame_size
+                        ),
+                    };
+                }
                 match self.split_focused(dir) {
                     Ok(()) => IpcResponse::Ok,
                     Err(e) => IpcResponse::Error {
@@ -114,6 +203,40 @@ impl Wm {
                     },
                 }
             }
+            IpcCommand::NewFrame { direction } => {
+                let side = match direction.to_lowercase().as_str() {
+                    "left" | "l" => Direction::Left,
+                    "right" | "r" => Direction::Right,
+                    "up" | "u" => Direction::Up,
+                    "down" | "d" => Direction::Down,
+                    _ => {
+                        return IpcResponse::Error {
+                            code: "invalid_direction".to_string(),
+                            message: format!("Unknown direction: {}. Use left, right, up, or down.", direction),
+                        };
+                    }
+                };
+                let axis = match side {
+                    Direction::Left | Direction::Right => SplitDirection::Horizontal,
+                    Direction::Up | Direction::Down => SplitDirection::Vertical,
+                };
+                if !self.can_split_focused(axis) {
+                    return IpcResponse::Error {
+                        code: "frame_too_small".to_string(),
+                        message: format!(
+                            "Adding a frame would create frames smaller than {}px",
+                            self.config.min_frame_size
+                        ),
+                    };
+                }
+                match self.new_frame(side) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "new_frame_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::MoveWindow { forward } => {
                 match self.move_window(forward) {
                     Ok(()) => IpcResponse::Ok,
@@ -132,6 +255,19 @@ impl Wm {
                     },
                 }
             }
+            IpcCommand::SetSplitPixels { pixels } => {
+                match self.set_split_pixels(pixels) {
+                    Ok(Some(pixels)) => IpcResponse::SplitPixels { pixels },
+                    Ok(None) => IpcResponse::Error {
+                        code: "no_split".to_string(),
+                        message: "Focused frame is not in a split".to_string(),
+                    },
+                    Err(e) => IpcResponse::Error {
+                        code: "resize_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::CloseWindow => {
                 match self.close_focused_window() {
                     Ok(()) => IpcResponse::Ok,
@@ -141,6 +277,15 @@ impl Wm {
                     },
                 }
             }
+            IpcCommand::CloseFrame => {
+                match self.close_focused_frame() {
+                    Ok(requested) => IpcResponse::FrameClosed { requested },
+                    Err(e) => IpcResponse::Error {
+                        code: "close_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::CycleTab { forward } => {
                 match self.cycle_tab(forward) {
                     Ok(()) => IpcResponse::Ok,
@@ -150,6 +295,108 @@ impl Wm {
                     },
                 }
             }
+            IpcCommand::Promote => {
+                match self.promote_focused_tab() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "promote_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::SwapWithSibling => {
+                let layout = &self.workspaces().current().layout;
+                if layout.parent(layout.focused).is_none() {
+                    return IpcResponse::Error {
+                        code: "no_sibling".to_string(),
+                        message: "Focused frame is not part of a split".to_string(),
+                    };
+                }
+                match self.swap_with_sibling() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "swap_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::BalanceSplits => {
+                match self.balance_splits() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "balance_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::NormalizeTree => {
+                match self.normalize_tree() {
+                    Ok(removed) => IpcResponse::Normalized { removed },
+                    Err(e) => IpcResponse::Error {
+                        code: "normalize_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::FlattenWorkspace => {
+                match self.flatten_workspace() {
+                    Ok(removed) => IpcResponse::Flattened { removed },
+                    Err(e) => IpcResponse::Error {
+                        code: "flatten_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::RepairBorders => {
+                match self.repair_borders() {
+                    Ok(windows) => IpcResponse::BordersRepaired { windows },
+                    Err(e) => IpcResponse::Error {
+                        code: "repair_borders_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::Undo => {
+                if !self.workspaces().current().has_layout_history() {
+                    return IpcResponse::Error {
+                        code: "nothing_to_undo".to_string(),
+                        message: "No layout operation to undo".to_string(),
+                    };
+                }
+                match self.undo_layout() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "undo_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::SendAction { action, repeat } => {
+                let Some(wm_action) = config::WmAction::from_name(&action) else {
+                    return IpcResponse::Error {
+                        code: "invalid_action".to_string(),
+                        message: format!("Unknown action '{}'", action),
+                    };
+                };
+                let repeat = repeat.unwrap_or(1);
+                if !(1..=MAX_ACTION_REPEAT).contains(&repeat) {
+                    return IpcResponse::Error {
+                        code: "invalid_repeat".to_string(),
+                        message: format!("Repeat must be between 1 and {}", MAX_ACTION_REPEAT),
+                    };
+                }
+                let mut response = IpcResponse::Ok;
+                for i in 0..repeat {
+                    if let Err(e) = self.execute_action(wm_action.clone()) {
+                        response = IpcResponse::Error {
+                            code: "action_failed".to_string(),
+                            message: format!("'{}' failed on repetition {}: {}", action, i + 1, e),
+                        };
+                        break;
+                    }
+                }
+                response
+            }
             IpcCommand::TagWindow { window } => {
                 let target = window.or(self.focused_window);
                 if let Some(w) = target {
@@ -230,6 +477,51 @@ impl Wm {
                     },
                 }
             }
+            IpcCommand::Mark { window, name } => {
+                let Some(mark) = marks::parse_mark(&name) else {
+                    return IpcResponse::Error {
+                        code: "invalid_mark".to_string(),
+                        message: format!("Mark must be a single printable character, got '{}'", name),
+                    };
+                };
+                let Some(window) = window.or(self.focused_window) else {
+                    return IpcResponse::Error {
+                        code: "no_window".to_string(),
+                        message: "No window specified and no focused window".to_string(),
+                    };
+                };
+                match self.set_mark(mark, window) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "mark_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::PullWindow { window } => {
+                match self.pull_window(window) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "pull_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::GotoMark { name } => {
+                let Some(mark) = marks::parse_mark(&name) else {
+                    return IpcResponse::Error {
+                        code: "invalid_mark".to_string(),
+                        message: format!("Mark must be a single printable character, got '{}'", name),
+                    };
+                };
+                match self.goto_mark(mark) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "mark_not_found".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::GetTagged => {
                 let tagged: Vec<u32> = self.tagged_windows.iter().copied().collect();
                 IpcResponse::Tagged { windows: tagged }
@@ -247,6 +539,15 @@ impl Wm {
                 let floating: Vec<u32> = self.workspaces().current().floating_window_ids();
                 IpcResponse::Floating { windows: floating }
             }
+            IpcCommand::PinFloatGlobal { window } => {
+                match self.toggle_float_pin(window.map(|w| w as Window)) {
+                    Ok((window, pinned)) => IpcResponse::FloatPinned { window, pinned },
+                    Err(e) => IpcResponse::Error {
+                        code: "pin_float_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::ToggleFullscreen { window } => {
                 match self.toggle_fullscreen(window.map(|w| w as Window)) {
                     Ok(()) => IpcResponse::Ok,
@@ -257,11 +558,20 @@ impl Wm {
                 }
             }
             IpcCommand::GetFullscreen => {
-                let fullscreen = self.workspaces().current().fullscreen_window.map(|w| w as u32);
+                let fullscreen = self.workspaces().current().fullscreen_window;
                 IpcResponse::Fullscreen { window: fullscreen }
             }
+            IpcCommand::ToggleZoom { window } => {
+                match self.toggle_zoom(window) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "toggle_zoom_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::GetUrgent => {
-                let urgent: Vec<u32> = self.urgent.windows().iter().map(|&w| w as u32).collect();
+                let urgent: Vec<u32> = self.urgent.windows().to_vec();
                 IpcResponse::Urgent { windows: urgent }
             }
             IpcCommand::FocusUrgent => {
@@ -313,7 +623,7 @@ impl Wm {
             IpcCommand::MoveToWorkspace { window, workspace } => {
                 let target_window = window.or(self.focused_window);
                 if let Some(w) = target_window {
-                    match self.move_window_to_workspace(w, workspace) {
+                    match self.move_window_to_workspace(w, workspace, MoveOrigin::User) {
                         Ok(()) => IpcResponse::Ok,
                         Err(e) => IpcResponse::Error {
                             code: "move_to_workspace_failed".to_string(),
@@ -329,18 +639,41 @@ impl Wm {
             }
             IpcCommand::GetMonitors => {
                 let monitors: Vec<_> = self.monitors.iter()
-                    .map(|(id, monitor)| {
-                        ipc::MonitorInfo {
-                            name: monitor.name.clone(),
-                            x: monitor.geometry.x,
-                            y: monitor.geometry.y,
-                            width: monitor.geometry.width,
-                            height: monitor.geometry.height,
-                            is_primary: monitor.primary,
-                            is_focused: id == self.monitors.focused_id(),
-                            current_workspace: monitor.workspaces.current_index(),
-                        }
-                    })
+                    .map(|(id, monitor)| self.monitor_info(id, monitor))
+                    .collect();
+                IpcResponse::Monitors { data: monitors }
+            }
+            IpcCommand::GetFocusedMonitor => {
+                let monitor = self.monitor_info(self.monitors.focused_id(), self.monitors.focused());
+                IpcResponse::FocusedMonitor { monitor }
+            }
+            IpcCommand::FocusMonitorByName { name } => {
+                let Some(monitor_id) = self.monitors.find_by_name(&name) else {
+                    return IpcResponse::Error {
+                        code: "monitor_not_found".to_string(),
+                        message: format!("No monitor named '{}'", name),
+                    };
+                };
+                match self.focus_monitor(monitor_id) {
+                    Ok(()) => {
+                        let monitor = self.monitor_info(monitor_id, self.monitors.focused());
+                        IpcResponse::FocusedMonitor { monitor }
+                    }
+                    Err(e) => IpcResponse::Error {
+                        code: "focus_monitor_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::SetMonitorMode { merged } => {
+                if let Err(e) = self.set_monitor_mode(merged) {
+                    return IpcResponse::Error {
+                        code: "monitor_mode_failed".to_string(),
+                        message: e.to_string(),
+                    };
+                }
+                let monitors: Vec<_> = self.monitors.iter()
+                    .map(|(id, monitor)| self.monitor_info(id, monitor))
                     .collect();
                 IpcResponse::Monitors { data: monitors }
             }
@@ -422,10 +755,9 @@ impl Wm {
                 if let Some((monitor_id, ws_idx, node_id)) = self.find_frame_by_name_global(&name) {
                     let monitor = self.monitors.get(monitor_id).unwrap();
                     let ws = &monitor.workspaces.workspaces[ws_idx];
-                    let window_count = if let Some(frame) = ws.layout.get(node_id).and_then(|n| n.as_frame()) {
-                        frame.windows.len()
-                    } else {
-                        0
+                    let (window_count, focused_tab) = match ws.layout.get(node_id).and_then(|n| n.as_frame()) {
+                        Some(frame) if !frame.windows.is_empty() => (frame.windows.len(), Some(frame.focused + 1)),
+                        _ => (0, None),
                     };
                     let frame_name = ws.layout.get_frame_name(node_id).map(|s| s.to_string());
 
@@ -435,6 +767,7 @@ impl Wm {
                         monitor: monitor.name.clone(),
                         workspace: ws_idx + 1, // 1-indexed for user display
                         window_count,
+                        focused_tab,
                     }
                 } else {
                     IpcResponse::Error {
@@ -443,6 +776,104 @@ impl Wm {
                     }
                 }
             }
+            IpcCommand::SetVerticalTabs { enabled } => {
+                match self.set_vertical_tabs(enabled) {
+                    Ok(enabled) => IpcResponse::VerticalTabs { enabled },
+                    Err(e) => IpcResponse::Error {
+                        code: "vertical_tabs_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::ToggleStacked => {
+                match self.toggle_stacked() {
+                    Ok(enabled) => IpcResponse::Stacked { enabled },
+                    Err(e) => IpcResponse::Error {
+                        code: "toggle_stacked_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::GetVerticalTabs => {
+                let enabled = self.workspaces().current().layout.focused_frame()
+                    .map(|f| f.vertical_tabs)
+                    .unwrap_or(false);
+                IpcResponse::VerticalTabs { enabled }
+            }
+            IpcCommand::SetFrameTabMode { mode } => {
+                match self.set_frame_tab_sizing(mode) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "tab_mode_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::SetFrameColor { name, color } => {
+                let color = match color.as_deref().map(crate::config::parse_rgb_color) {
+                    Some(None) => {
+                        return IpcResponse::Error {
+                            code: "invalid_color".to_string(),
+                            message: format!("Invalid color '{}', expected #rrggbb", color.unwrap_or_default()),
+                        };
+                    }
+                    Some(Some(c)) => Some(c),
+                    None => None,
+                };
+                if self.find_frame_by_name_global(&name).is_none() {
+                    return IpcResponse::Error {
+                        code: "not_found".to_string(),
+                        message: format!("No frame named '{}'", name),
+                    };
+                }
+                match self.set_frame_color(&name, color) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "frame_color_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::ToggleFrameLock => {
+                match self.toggle_frame_lock() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "toggle_frame_lock_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            #[cfg(feature = "mirror")]
+            IpcCommand::MirrorWindow { window, frame } => {
+                match self.mirror_window(window, &frame) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "mirror_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            #[cfg(not(feature = "mirror"))]
+            IpcCommand::MirrorWindow { .. } => IpcResponse::Error {
+                code: "unsupported".to_string(),
+                message: "ttwm was built without the `mirror` feature".to_string(),
+            },
+            IpcCommand::ExportStartupConfig { workspace } => {
+                let ws_idx = workspace.unwrap_or_else(|| self.workspaces().current_index());
+                let Some(ws) = self.workspaces().workspaces.get(ws_idx) else {
+                    return IpcResponse::Error {
+                        code: "invalid_workspace".to_string(),
+                        message: format!("Workspace index must be 0-{}, got {}", NUM_WORKSPACES - 1, ws_idx),
+                    };
+                };
+                match config::startup_config_snippet(ws_idx + 1, ws.layout.to_config()) {
+                    Ok(toml) => IpcResponse::StartupConfig { toml },
+                    Err(e) => IpcResponse::Error {
+                        code: "export_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::Screenshot { path } => {
                 match self.capture_screenshot(&path) {
                     Ok(()) => IpcResponse::Screenshot { path },
@@ -452,6 +883,91 @@ impl Wm {
                     },
                 }
             }
+            IpcCommand::SetFocusFollowsMouse { enabled } => {
+                self.focus_follows_mouse = enabled;
+                log::info!("Focus follows mouse {}", if enabled { "enabled" } else { "disabled" });
+                IpcResponse::Ok
+            }
+            IpcCommand::GetFocusFollowsMouse => {
+                IpcResponse::FocusFollowsMouse { enabled: self.focus_follows_mouse }
+            }
+            IpcCommand::SetGaps { gap, outer_gap } => {
+                match self.set_gaps(gap, outer_gap) {
+                    Ok((gap, outer_gap)) => IpcResponse::Gaps { gap, outer_gap },
+                    Err(e) => IpcResponse::Error {
+                        code: "set_gaps_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::SetTheme { path } => {
+                match self.set_theme(&path) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "theme_failed".to_string(),
+                        message: format!("{:#}", e),
+                    },
+                }
+            }
+            IpcCommand::SetWallpaper { path, mode } => {
+                let mode = match mode.as_deref().map(WallpaperMode::parse) {
+                    None => WallpaperMode::default(),
+                    Some(Some(mode)) => mode,
+                    Some(None) => {
+                        return IpcResponse::Error {
+                            code: "invalid_mode".to_string(),
+                            message: "Mode must be center, tile, stretch or fill".to_string(),
+                        };
+                    }
+                };
+                match self.set_wallpaper(&path, mode) {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "wallpaper_failed".to_string(),
+                        message: format!("{:#}", e),
+                    },
+                }
+            }
+            IpcCommand::SaveSession => {
+                match self.save_session() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "session_failed".to_string(),
+                        message: format!("{:#}", e),
+                    },
+                }
+            }
+            IpcCommand::RestoreSession => {
+                match self.restore_session() {
+                    Ok(true) => IpcResponse::Ok,
+                    Ok(false) => IpcResponse::Error {
+                        code: "no_session".to_string(),
+                        message: "No saved session".to_string(),
+                    },
+                    Err(e) => IpcResponse::Error {
+                        code: "session_failed".to_string(),
+                        message: format!("{:#}", e),
+                    },
+                }
+            }
+            IpcCommand::TogglePresentationMode => {
+                match self.toggle_presentation_mode() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "presentation_mode_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
+            IpcCommand::Refresh => {
+                match self.refresh() {
+                    Ok(()) => IpcResponse::Ok,
+                    Err(e) => IpcResponse::Error {
+                        code: "refresh_failed".to_string(),
+                        message: e.to_string(),
+                    },
+                }
+            }
             IpcCommand::Quit => {
                 log::info!("Quit requested via IPC");
                 self.running = false;
@@ -474,11 +990,12 @@ impl Wm {
     fn snapshot_state(&self) -> WmStateSnapshot {
         let geometries = self.workspaces().current().layout.calculate_geometries(
             self.usable_screen(),
-            self.config.gap,
+            self.gap(),
         );
         let tiled_count = self.workspaces().current().layout.all_windows().len();
         let floating_count = self.workspaces().current().floating_windows.len();
         WmStateSnapshot {
+            schema_version: crate::ipc::SCHEMA_VERSION,
             focused_window: self.focused_window,
             focused_frame: self.workspaces().current().layout.focused_frame_id(),
             window_count: tiled_count + floating_count,
@@ -532,6 +1049,59 @@ impl Wm {
         windows
     }
 
+    /// Describe a monitor for IPC responses
+    fn monitor_info(&self, id: MonitorId, monitor: &Monitor) -> ipc::MonitorInfo {
+        ipc::MonitorInfo {
+            name: monitor.name.clone(),
+            x: monitor.geometry.x,
+            y: monitor.geometry.y,
+            width: monitor.geometry.width,
+            height: monitor.geometry.height,
+            is_primary: monitor.primary,
+            is_focused: id == self.monitors.focused_id(),
+            current_workspace: monitor.workspaces.current_index(),
+        }
+    }
+
+    /// Interned atoms by field name, and the atoms currently in `_NET_SUPPORTED`
+    /// on the root window (names resolved by the server)
+    fn query_atoms(&self) -> anyhow::Result<(BTreeMap<String, u32>, Vec<ipc::AtomInfo>)> {
+        let interned = self.atoms.entries().into_iter()
+            .map(|(name, atom)| (name.to_string(), atom))
+            .collect();
+
+        let reply = self.conn.get_property(
+            false,
+            self.root,
+            self.atoms.net_supported,
+            AtomEnum::ATOM,
+            0,
+            u32::MAX,
+        )?.reply()?;
+        let mut supported = Vec::new();
+        for atom in reply.value32().into_iter().flatten() {
+            let name = self.conn.get_atom_name(atom)?.reply()?;
+            supported.push(ipc::AtomInfo {
+                name: String::from_utf8_lossy(&name.name).into_owned(),
+                atom,
+            });
+        }
+        Ok((interned, supported))
+    }
+
+    /// A window's WM_PROTOCOLS atoms with their names
+    fn query_protocols(&self, window: Window) -> anyhow::Result<Vec<ipc::AtomInfo>> {
+        let mut protocols = Vec::new();
+        for atom in window_query::read_protocols(&self.conn, &self.atoms, window) {
+            let name = self.conn.get_atom_name(atom)?.reply()?;
+            protocols.push(ipc::AtomInfo {
+                name: String::from_utf8_lossy(&name.name).into_owned(),
+                atom,
+            });
+        }
+        Ok(protocols)
+    }
+
     /// Validate WM state invariants
     fn validate_state(&self) -> Vec<String> {
         let mut violations = Vec::new();
@@ -557,6 +1127,9 @@ impl Wm {
             }
         }
 
+        // Check: every tiled window is in exactly one frame of the layout
+        violations.extend(self.workspaces().current().layout.window_violations());
+
         // Check: tab bar windows should correspond to existing frames
         let mon_id = self.monitors.focused_id();
         let ws_idx = self.workspaces().current_index();
@@ -568,4 +1141,80 @@ impl Wm {
 
         violations
     }
+
+    /// Dump the internal maps and cross-check them against the monitors'
+    /// layouts, on top of `validate_state`
+    fn verify_state(&self) -> VerifyReport {
+        fn report<T>(keys: impl Iterator<Item = T>, fmt: impl Fn(T) -> String) -> MapReport {
+            let mut keys: Vec<String> = keys.map(fmt).collect();
+            keys.sort();
+            MapReport { count: keys.len(), keys }
+        }
+        let bar_key = |&(m, ws, frame): &TabBarKey| format!("{:?}/{}/{:?}", m, ws + 1, frame);
+        let hex = |w: &Window| format!("0x{:x}", w);
+
+        let mut maps = BTreeMap::new();
+        maps.insert("tab_bars".to_string(), report(self.tab_bars.windows.keys(), bar_key));
+        maps.insert("pixmaps".to_string(), report(self.tab_bars.pixmaps.keys(), hex));
+        maps.insert("empty_frame_windows".to_string(), report(self.tab_bars.empty_frame_windows.keys(), bar_key));
+        maps.insert("icon_cache".to_string(), report(self.tab_bars.icon_cache.keys(), |(w, size)| format!("0x{:x}@{}", w, size)));
+        maps.insert("hidden_windows".to_string(), report(self.hidden_windows.iter(), hex));
+        maps.insert("tagged_windows".to_string(), report(self.tagged_windows.iter(), hex));
+        maps.insert("urgent".to_string(), report(self.urgent.iter(), hex));
+
+        let workspaces = self.monitors.iter()
+            .flat_map(|(_, mon)| {
+                mon.workspaces.workspaces.iter().enumerate().map(|(idx, ws)| WorkspaceFrames {
+                    monitor: mon.name.clone(),
+                    workspace: idx + 1,
+                    frames: ws.layout.all_frames().iter().map(|id| format!("{:?}", id)).collect(),
+                })
+            })
+            .collect();
+
+        let mut orphans = Vec::new();
+        let frame_exists = |&(m, ws, frame): &TabBarKey| {
+            self.monitors.get(m).is_some_and(|mon| {
+                mon.workspaces.workspaces.get(ws).is_some_and(|w| w.layout.get(frame).is_some())
+            })
+        };
+        for (key, &window) in &self.tab_bars.windows {
+            if !frame_exists(key) {
+                orphans.push(format!("Tab bar 0x{:x} for non-existent frame {}", window, bar_key(key)));
+            }
+        }
+        for (key, &window) in &self.tab_bars.empty_frame_windows {
+            if !frame_exists(key) {
+                orphans.push(format!("Empty frame window 0x{:x} for non-existent frame {}", window, bar_key(key)));
+            }
+        }
+        for &window in self.tab_bars.pixmaps.keys() {
+            if !self.tab_bars.windows.values().any(|&w| w == window) {
+                orphans.push(format!("Pixmap for unknown tab bar 0x{:x}", window));
+            }
+        }
+        let managed = |w: Window| self.find_window_global(w).is_some();
+        for &(window, size) in self.tab_bars.icon_cache.keys() {
+            if !managed(window) {
+                orphans.push(format!("Cached {}px icon for unmanaged window 0x{:x}", size, window));
+            }
+        }
+        for (name, windows) in [
+            ("Hidden", self.hidden_windows.iter().copied().collect::<Vec<_>>()),
+            ("Tagged", self.tagged_windows.iter().copied().collect()),
+            ("Urgent", self.urgent.windows().to_vec()),
+        ] {
+            for window in windows.into_iter().filter(|&w| !managed(w)) {
+                orphans.push(format!("{} window 0x{:x} is not managed", name, window));
+            }
+        }
+        orphans.sort();
+
+        VerifyReport {
+            maps,
+            workspaces,
+            violations: self.validate_state(),
+            orphans,
+        }
+    }
 }
@@ -0,0 +1,368 @@
+//! Built-in application launcher.
+//!
+//! A small override-redirect window with an input line and a list of matching
+//! executables from `$PATH`. While it is open the keyboard is grabbed; typing
+//! filters the list, Up/Down (or Tab) move the selection, Enter runs the
+//! selected program and Escape closes it. The window is only created the first
+//! time the launcher is opened.
+
+use std::collections::BTreeSet;
+use std::os::unix::fs::PermissionsExt;
+
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::config::LayoutConfig;
+use crate::render::FontRenderer;
+use crate::types::Rect;
+
+/// Maximum number of matches listed below the input line
+pub const MAX_MATCHES: usize = 8;
+
+/// Launcher width in pixels (capped to the monitor width)
+const LAUNCHER_WIDTH: u32 = 600;
+
+/// Padding around text inside each row
+const ROW_PADDING: u32 = 6;
+
+/// A key press, as far as the launcher is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LauncherKey {
+    Char(char),
+    Backspace,
+    Up,
+    Down,
+    Enter,
+    Escape,
+}
+
+/// Map a keysym to a launcher key; None for keys the launcher ignores.
+pub fn key_from_keysym(keysym: u32) -> Option<LauncherKey> {
+    match keysym {
+        0xff1b => Some(LauncherKey::Escape),
+        0xff0d | 0xff8d => Some(LauncherKey::Enter), // Return, KP_Enter
+        0xff08 => Some(LauncherKey::Backspace),
+        0xff52 => Some(LauncherKey::Up),
+        0xff54 | 0xff09 => Some(LauncherKey::Down), // Down, Tab
+        0x20..=0x7e => char::from_u32(keysym).map(LauncherKey::Char),
+        _ => None,
+    }
+}
+
+/// Result of feeding a key to the launcher
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LauncherOutcome {
+    /// Still open; redraw
+    Continue,
+    /// Closed without running anything
+    Cancel,
+    /// Closed; run this command
+    Launch(String),
+}
+
+/// Collect the names of executable files in `$PATH`, sorted and deduplicated.
+pub fn path_executables() -> Vec<String> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut names = BTreeSet::new();
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let is_executable = entry.metadata()
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false);
+            if is_executable {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.insert(name.to_string());
+                }
+            }
+        }
+    }
+    names.into_iter().collect()
+}
+
+/// Filter entries by query: prefix matches first, then other substring matches.
+pub fn filter_matches(entries: &[String], query: &str) -> Vec<String> {
+    let query = query.trim();
+    let prefix = entries.iter().filter(|e| e.starts_with(query));
+    let substring = entries.iter().filter(|e| !e.starts_with(query) && e.contains(query));
+    prefix.chain(substring).take(MAX_MATCHES).cloned().collect()
+}
+
+/// Launcher state and its overlay window
+pub struct Launcher {
+    /// Overlay window, created on first use
+    window: Option<Window>,
+    /// Area the launcher occupies while open
+    rect: Rect,
+    visible: bool,
+    query: String,
+    /// All executables found in `$PATH` when the launcher was opened
+    entries: Vec<String>,
+    matches: Vec<String>,
+    selected: usize,
+}
+
+impl Launcher {
+    /// Create a closed launcher (no window yet).
+    pub fn new() -> Self {
+        Self {
+            window: None,
+            rect: Rect::new(0, 0, 1, 1),
+            visible: false,
+            query: String::new(),
+            entries: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    /// Whether the launcher is open
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Reset the input and load the candidates.
+    fn open_with(&mut self, entries: Vec<String>) {
+        self.entries = entries;
+        self.query.clear();
+        self.selected = 0;
+        self.matches = filter_matches(&self.entries, "");
+        self.visible = true;
+    }
+
+    /// Feed a key press to the launcher.
+    pub fn handle_key(&mut self, key: LauncherKey) -> LauncherOutcome {
+        match key {
+            LauncherKey::Escape => return LauncherOutcome::Cancel,
+            LauncherKey::Enter => {
+                // A query with arguments runs as typed; otherwise run the selection
+                let query = self.query.trim();
+                let command = if query.contains(char::is_whitespace) {
+                    Some(query.to_string())
+                } else {
+                    self.matches.get(self.selected).cloned()
+                        .or_else(|| (!query.is_empty()).then(|| query.to_string()))
+                };
+                return match command {
+                    Some(command) => LauncherOutcome::Launch(command),
+                    None => LauncherOutcome::Cancel,
+                };
+            }
+            LauncherKey::Up => self.selected = self.selected.saturating_sub(1),
+            LauncherKey::Down => {
+                if self.selected + 1 < self.matches.len() {
+                    self.selected += 1;
+                }
+            }
+            LauncherKey::Backspace => {
+                self.query.pop();
+                self.refilter();
+            }
+            LauncherKey::Char(c) => {
+                self.query.push(c);
+                self.refilter();
+            }
+        }
+        LauncherOutcome::Continue
+    }
+
+    fn refilter(&mut self) {
+        // Once arguments are typed, keep matching on the program name
+        let program = self.query.split_whitespace().next().unwrap_or("");
+        self.matches = filter_matches(&self.entries, program);
+        self.selected = 0;
+    }
+
+    /// Open the launcher centered horizontally near the top of `monitor`.
+    pub fn show(
+        &mut self,
+        conn: &impl Connection,
+        root: Window,
+        monitor: Rect,
+        font: &FontRenderer,
+        gc: Gcontext,
+        config: &LayoutConfig,
+    ) -> Result<()> {
+        self.open_with(path_executables());
+
+        let row_height = font.char_height() + ROW_PADDING * 2;
+        let width = LAUNCHER_WIDTH.min(monitor.width);
+        let height = row_height * (MAX_MATCHES as u32 + 1);
+        self.rect = Rect::new(
+            monitor.x + (monitor.width as i32 - width as i32) / 2,
+            monitor.y + monitor.height as i32 / 5,
+            width,
+            height,
+        );
+
+        let window = match self.window {
+            Some(window) => window,
+            None => {
+                let window = conn.generate_id()?;
+                conn.create_window(
+                    x11rb::COPY_DEPTH_FROM_PARENT,
+                    window,
+                    root,
+                    0,
+                    0,
+                    1,
+                    1,
+                    0,
+                    WindowClass::INPUT_OUTPUT,
+                    x11rb::COPY_FROM_PARENT,
+                    &CreateWindowAux::new()
+                        .background_pixel(config.tab_unfocused_bg)
+                        .border_pixel(config.border_focused)
+                        .override_redirect(1) // Don't manage this window
+                        .event_mask(EventMask::EXPOSURE),
+                )?;
+                self.window = Some(window);
+                window
+            }
+        };
+
+        conn.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(self.rect.x)
+                .y(self.rect.y)
+                .width(self.rect.width)
+                .height(self.rect.height)
+                .border_width(config.border_width)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        conn.map_window(window)?;
+        self.draw(conn, font, gc, config)?;
+        Ok(())
+    }
+
+    /// Close the launcher (the window is kept for next time).
+    pub fn hide(&mut self, conn: &impl Connection) -> Result<()> {
+        self.visible = false;
+        self.entries.clear();
+        self.matches.clear();
+        if let Some(window) = self.window {
+            conn.unmap_window(window)?;
+            conn.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Check whether a window is the launcher's overlay
+    pub fn owns_window(&self, window: Window) -> bool {
+        self.window == Some(window)
+    }
+
+    /// Redraw the input line and the match list.
+    pub fn draw(&self, conn: &impl Connection, font: &FontRenderer, gc: Gcontext, config: &LayoutConfig) -> Result<()> {
+        let Some(window) = self.window.filter(|_| self.visible) else {
+            return Ok(());
+        };
+        let row_height = font.char_height() + ROW_PADDING * 2;
+        let text_width = self.rect.width.saturating_sub(ROW_PADDING * 2);
+
+        let input = format!("> {}_", self.query);
+        let rows = std::iter::once((input, config.tab_focused_bg, config.tab_text_color))
+            .chain(self.matches.iter().enumerate().map(|(i, name)| {
+                if i == self.selected {
+                    (name.clone(), config.tab_visible_unfocused_bg, config.tab_text_color)
+                } else {
+                    (name.clone(), config.tab_unfocused_bg, config.tab_text_unfocused)
+                }
+            }));
+
+        // Clear everything below the last row, then draw each row over it
+        conn.clear_area(false, window, 0, 0, 0, 0)?;
+        for (i, (text, bg, fg)) in rows.enumerate() {
+            let y = (i as u32 * row_height) as i16;
+            conn.change_gc(gc, &ChangeGCAux::new().foreground(bg))?;
+            conn.poly_fill_rectangle(window, gc, &[Rectangle {
+                x: 0,
+                y,
+                width: self.rect.width as u16,
+                height: row_height as u16,
+            }])?;
+
+            let text = font.truncate_text_to_width(&text, text_width);
+            let (pixels, w, h) = font.render_text(&text, fg, bg);
+            if !pixels.is_empty() && w > 0 && h > 0 {
+                conn.put_image(
+                    ImageFormat::Z_PIXMAP,
+                    window,
+                    gc,
+                    w as u16,
+                    h as u16,
+                    ROW_PADDING as i16,
+                    y + ROW_PADDING as i16,
+                    0,
+                    24, // depth (24-bit color, will be padded to 32)
+                    &pixels,
+                )?;
+            }
+        }
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<String> {
+        ["alacritty", "firefox", "fish", "xterm"].iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_filter_prefers_prefix_matches() {
+        assert_eq!(filter_matches(&entries(), "f"), vec!["firefox", "fish"]);
+        assert_eq!(filter_matches(&entries(), "te"), vec!["xterm"]);
+        assert_eq!(filter_matches(&entries(), "r"), vec!["alacritty", "firefox", "xterm"]);
+        assert_eq!(filter_matches(&entries(), "").len(), 4);
+    }
+
+    #[test]
+    fn test_navigate_and_launch() {
+        let mut launcher = Launcher::new();
+        launcher.open_with(entries());
+
+        assert_eq!(launcher.handle_key(LauncherKey::Char('f')), LauncherOutcome::Continue);
+        launcher.handle_key(LauncherKey::Down);
+        launcher.handle_key(LauncherKey::Down); // stays on the last match
+        assert_eq!(launcher.handle_key(LauncherKey::Enter), LauncherOutcome::Launch("fish".to_string()));
+
+        launcher.handle_key(LauncherKey::Up);
+        assert_eq!(launcher.handle_key(LauncherKey::Enter), LauncherOutcome::Launch("firefox".to_string()));
+    }
+
+    #[test]
+    fn test_arguments_and_unknown_commands_run_as_typed() {
+        let mut launcher = Launcher::new();
+        launcher.open_with(entries());
+        for c in "xterm -e top".chars() {
+            launcher.handle_key(LauncherKey::Char(c));
+        }
+        assert_eq!(launcher.matches, vec!["xterm"]);
+        assert_eq!(launcher.handle_key(LauncherKey::Enter), LauncherOutcome::Launch("xterm -e top".to_string()));
+
+        launcher.open_with(entries());
+        launcher.handle_key(LauncherKey::Char('q'));
+        assert_eq!(launcher.handle_key(LauncherKey::Enter), LauncherOutcome::Launch("q".to_string()));
+        launcher.handle_key(LauncherKey::Backspace);
+        assert_eq!(launcher.handle_key(LauncherKey::Escape), LauncherOutcome::Cancel);
+    }
+
+    #[test]
+    fn test_key_from_keysym() {
+        assert_eq!(key_from_keysym(0x61), Some(LauncherKey::Char('a')));
+        assert_eq!(key_from_keysym(0x20), Some(LauncherKey::Char(' ')));
+        assert_eq!(key_from_keysym(0xff0d), Some(LauncherKey::Enter));
+        assert_eq!(key_from_keysym(0xff1b), Some(LauncherKey::Escape));
+        assert_eq!(key_from_keysym(0xffe1), None); // Shift_L
+    }
+}
@@ -9,7 +9,7 @@ use slotmap::{new_key_type, SlotMap};
 use std::collections::HashMap;
 use x11rb::protocol::xproto::Window;
 
-use crate::config::{FrameConfig, LayoutNodeConfig, SplitConfig, SplitDirectionConfig};
+use crate::config::{CloseFocus, FrameConfig, LayoutNodeConfig, SplitConfig, SplitDirectionConfig, TabSizing};
 pub use crate::types::Rect;
 
 // Generate unique key types for our arena
@@ -18,6 +18,20 @@ new_key_type! {
     pub struct NodeId;
 }
 
+/// Index reached by stepping once from `current` through `len` items.
+/// Past either end this wraps around, or returns None when `wrap` is false.
+pub fn step_index(current: usize, len: usize, forward: bool, wrap: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    match (forward, current + 1 >= len, current == 0) {
+        (true, false, _) => Some(current + 1),
+        (true, true, _) => wrap.then_some(0),
+        (false, _, false) => Some(current - 1),
+        (false, _, true) => wrap.then_some(len - 1),
+    }
+}
+
 /// Direction of a split
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -29,7 +43,7 @@ pub enum SplitDirection {
 }
 
 /// Direction for spatial navigation
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Direction {
     Left,
     Right,
@@ -37,6 +51,16 @@ pub enum Direction {
     Down,
 }
 
+/// How a frame presents its windows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrameStyle {
+    /// A single tab bar; only the focused tab's window is shown
+    #[default]
+    Tabbed,
+    /// One full-width title row per window, stacked above the focused window
+    Stacked,
+}
+
 /// A frame is a leaf node that contains windows
 #[derive(Debug, Clone)]
 pub struct Frame {
@@ -48,6 +72,15 @@ pub struct Frame {
     pub vertical_tabs: bool,
     /// Optional user-assigned name for window placement rules
     pub name: Option<String>,
+    /// Locked frames are skipped when placing newly managed windows
+    pub locked: bool,
+    /// Per-frame override of `appearance.tab_sizing`
+    pub tab_sizing: Option<TabSizing>,
+    /// Tabbed or stacked titles (stacked takes precedence over vertical_tabs)
+    pub style: FrameStyle,
+    /// Background tint for the empty-frame placeholder and the unused part
+    /// of the tab bar (overrides `colors.empty_frame_bg`)
+    pub color: Option<u32>,
 }
 
 impl Frame {
@@ -57,6 +90,10 @@ impl Frame {
             focused: 0,
             vertical_tabs: false,
             name: None,
+            locked: false,
+            tab_sizing: None,
+            style: FrameStyle::Tabbed,
+            color: None,
         }
     }
 
@@ -67,6 +104,10 @@ impl Frame {
             focused: 0,
             vertical_tabs: false,
             name: None,
+            locked: false,
+            tab_sizing: None,
+            style: FrameStyle::Tabbed,
+            color: None,
         }
     }
 
@@ -82,7 +123,11 @@ impl Frame {
     pub fn remove_window(&mut self, window: Window) -> bool {
         if let Some(idx) = self.windows.iter().position(|&w| w == window) {
             self.windows.remove(idx);
-            if self.focused >= self.windows.len() && !self.windows.is_empty() {
+            // A background tab before the focused one shifts it down a slot;
+            // follow it so the visible window stays visible
+            if idx < self.focused {
+                self.focused -= 1;
+            } else if self.focused >= self.windows.len() && !self.windows.is_empty() {
                 self.focused = self.windows.len() - 1;
             }
             true
@@ -91,6 +136,34 @@ impl Frame {
         }
     }
 
+    /// Remove a window, choosing the next focused tab by `policy` if it was focused.
+    ///
+    /// `mru` lists recently focused windows, most recent last.
+    pub fn remove_window_with_focus(&mut self, window: Window, policy: CloseFocus, mru: &[Window]) -> bool {
+        let Some(idx) = self.windows.iter().position(|&w| w == window) else {
+            return false;
+        };
+        if idx != self.focused {
+            return self.remove_window(window);
+        }
+
+        self.windows.remove(idx);
+        if self.windows.is_empty() {
+            self.focused = 0;
+            return true;
+        }
+        let last = self.windows.len() - 1;
+        self.focused = match policy {
+            CloseFocus::Next => idx.min(last),
+            CloseFocus::Prev => idx.saturating_sub(1),
+            CloseFocus::Mru => mru.iter()
+                .rev()
+                .find_map(|w| self.windows.iter().position(|x| x == w))
+                .unwrap_or(idx.min(last)),
+        };
+        true
+    }
+
     pub fn is_empty(&self) -> bool {
         self.windows.is_empty()
     }
@@ -156,7 +229,7 @@ impl Node {
 }
 
 /// The layout tree manages the tiling structure
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LayoutTree {
     /// Arena storage for all nodes (each node contains its own parent pointer)
     nodes: SlotMap<NodeId, Node>,
@@ -215,15 +288,89 @@ impl LayoutTree {
         self.get_mut(self.focused).and_then(|n| n.as_frame_mut())
     }
 
-    /// Add a window to the focused frame
+    /// Make sure `focused` points at a frame in the tree.
+    ///
+    /// After unusual frame removal sequences `focused` (or even `root`) can be
+    /// left pointing at a node that no longer exists. Focus falls back to the
+    /// first frame in the tree, or to a fresh root frame if the tree has none.
+    /// Returns true if anything had to be repaired.
+    pub fn ensure_focused_frame(&mut self) -> bool {
+        if self.focused_frame().is_some() && self.all_frames().contains(&self.focused) {
+            return false;
+        }
+
+        if let Some(&frame_id) = self.all_frames().first() {
+            log::warn!("Focused node {:?} is not a frame in the tree, focusing {:?}", self.focused, frame_id);
+            self.focused = frame_id;
+        } else {
+            log::warn!("Layout has no frames, creating a new root frame");
+            self.root = self.nodes.insert(Node::Frame {
+                frame: Frame::new(),
+                parent: None,
+            });
+            self.focused = self.root;
+        }
+        true
+    }
+
+    /// Add a window to the focused frame.
+    ///
+    /// If the focused frame is locked, the window goes to the next unlocked
+    /// frame instead (or a new frame split off the focused one if every frame
+    /// is locked), and that frame becomes focused.
     pub fn add_window(&mut self, window: Window) {
+        self.ensure_focused_frame();
+        if self.focused_frame().map(|f| f.locked).unwrap_or(false) {
+            let frames = self.all_frames();
+            let start = frames.iter().position(|&id| id == self.focused).unwrap_or(0);
+            let unlocked = frames.iter()
+                .cycle()
+                .skip(start + 1)
+                .take(frames.len())
+                .copied()
+                .find(|&id| !self.get(id).and_then(|n| n.as_frame()).map(|f| f.locked).unwrap_or(true));
+            match unlocked {
+                Some(frame_id) => self.focused = frame_id,
+                None => {
+                    self.split_focused(SplitDirection::Horizontal);
+                }
+            }
+        }
         if let Some(frame) = self.focused_frame_mut() {
             frame.add_window(window);
         }
     }
 
+    /// Frame a new window should join to be grouped with windows of its kind
+    /// (`matches` says whether an existing window is one). Prefers the
+    /// focused frame; locked frames never take grouped windows.
+    pub fn group_frame(&self, matches: impl Fn(Window) -> bool) -> Option<NodeId> {
+        let mut frames = self.all_frames();
+        if let Some(pos) = frames.iter().position(|&id| id == self.focused) {
+            frames.remove(pos);
+            frames.insert(0, self.focused);
+        }
+        frames.into_iter().find(|&id| {
+            self.get(id)
+                .and_then(|n| n.as_frame())
+                .is_some_and(|f| !f.locked && f.windows.iter().any(|&w| matches(w)))
+        })
+    }
+
     /// Add a window to a specific frame (for cross-workspace moves)
+    /// If `frame_id` is stale or not a frame, the window goes to the focused
+    /// frame instead of being dropped.
     pub fn add_window_to_frame(&mut self, window: Window, frame_id: NodeId) {
+        let frame_id = if self.get(frame_id).and_then(|n| n.as_frame()).is_some() {
+            frame_id
+        } else {
+            self.ensure_focused_frame();
+            log::warn!(
+                "Frame {:?} no longer exists, adding window 0x{:x} to {:?}",
+                frame_id, window, self.focused
+            );
+            self.focused
+        };
         if let Some(Node::Frame { frame, .. }) = self.nodes.get_mut(frame_id) {
             frame.add_window(window);
         }
@@ -244,6 +391,16 @@ impl LayoutTree {
         found_frame
     }
 
+    /// Remove a window from whichever frame contains it, picking that frame's
+    /// next focused tab by `policy` (see `Frame::remove_window_with_focus`)
+    pub fn remove_window_with_focus(&mut self, window: Window, policy: CloseFocus, mru: &[Window]) -> Option<NodeId> {
+        let frame_id = self.find_window(window)?;
+        if let Some(Node::Frame { frame, .. }) = self.nodes.get_mut(frame_id) {
+            frame.remove_window_with_focus(window, policy, mru);
+        }
+        Some(frame_id)
+    }
+
     /// Find which frame contains a window
     pub fn find_window(&self, window: Window) -> Option<NodeId> {
         for (id, node) in &self.nodes {
@@ -258,6 +415,7 @@ impl LayoutTree {
 
     /// Split the focused frame
     pub fn split_focused(&mut self, direction: SplitDirection) -> NodeId {
+        self.ensure_focused_frame();
         let old_focused = self.focused;
         let old_focused_parent = self.parent(old_focused);
 
@@ -303,6 +461,119 @@ impl LayoutTree {
         new_frame_id
     }
 
+    /// Add an empty frame on the given side of the focused frame.
+    ///
+    /// Like `split_focused`, every window stays in the original frame and the
+    /// new frame gets focus, but the new frame can go left of or above the
+    /// original as well as right of or below it. Returns the new frame.
+    pub fn new_frame(&mut self, side: Direction) -> NodeId {
+        let direction = match side {
+            Direction::Left | Direction::Right => SplitDirection::Horizontal,
+            Direction::Up | Direction::Down => SplitDirection::Vertical,
+        };
+        let new_frame = self.split_focused(direction);
+        if matches!(side, Direction::Left | Direction::Up) {
+            self.swap_with_sibling();
+        }
+        new_frame
+    }
+
+    /// Swap the focused frame with its sibling subtree in the parent split.
+    ///
+    /// The two children trade places while the split ratio stays as-is, so each
+    /// side keeps its size and the contents flip. Focus stays on the same frame.
+    /// Returns false if the focused frame is the root.
+    pub fn swap_with_sibling(&mut self) -> bool {
+        let Some(parent_id) = self.parent(self.focused) else {
+            return false;
+        };
+        if let Some(Node::Split { split, .. }) = self.nodes.get_mut(parent_id) {
+            std::mem::swap(&mut split.first, &mut split.second);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Check whether splitting the focused frame would leave both halves
+    /// at least `min_size` pixels along the split axis
+    pub fn can_split_focused(&self, direction: SplitDirection, screen: Rect, gap: u32, min_size: u32) -> bool {
+        let geometries = self.calculate_geometries(screen, gap);
+        let Some(&(_, rect)) = geometries.iter().find(|(id, _)| *id == self.focused) else {
+            return false;
+        };
+        let (first, second) = Self::split_rect(rect, direction, 0.5, gap);
+        match direction {
+            SplitDirection::Horizontal => first.width >= min_size && second.width >= min_size,
+            SplitDirection::Vertical => first.height >= min_size && second.height >= min_size,
+        }
+    }
+
+    /// Add a window in a new frame split off the focused frame.
+    ///
+    /// The split direction follows the focused frame's aspect ratio (wide frames
+    /// split side-by-side, tall frames top-to-bottom). Falls back to `add_window`
+    /// when the focused frame is empty, locked, or too small to split.
+    pub fn add_window_as_split(&mut self, window: Window, screen: Rect, gap: u32, min_size: u32) {
+        let splittable = self.focused_frame().map(|f| !f.is_empty() && !f.locked).unwrap_or(false);
+        if splittable {
+            self.split_focused_by_shape(screen, gap, min_size);
+        }
+        self.add_window(window);
+    }
+
+    /// Add a window to the focused frame without taking it past `max_tabs`
+    /// windows (0 = no limit).
+    ///
+    /// A full focused frame hands the window to the next unlocked frame with
+    /// room, or else to a new frame split off it (as in `add_window_as_split`).
+    /// If the frame is too small to split, the limit gives way rather than
+    /// the window.
+    pub fn add_window_capped(&mut self, window: Window, max_tabs: usize, screen: Rect, gap: u32, min_size: u32) {
+        self.ensure_focused_frame();
+        let full = |f: &Frame| f.locked || f.windows.len() >= max_tabs;
+        if max_tabs > 0 && self.focused_frame().is_some_and(full) {
+            let frames = self.all_frames();
+            let start = frames.iter().position(|&id| id == self.focused).unwrap_or(0);
+            let roomy = frames.iter()
+                .cycle()
+                .skip(start + 1)
+                .take(frames.len())
+                .copied()
+                .find(|&id| self.get(id).and_then(|n| n.as_frame()).is_some_and(|f| !full(f)));
+            match roomy {
+                Some(frame_id) => self.focused = frame_id,
+                None => {
+                    self.split_focused_by_shape(screen, gap, min_size);
+                }
+            }
+        }
+        self.add_window(window);
+    }
+
+    /// Split the focused frame along its longer side (wide frames
+    /// side-by-side, tall frames top-to-bottom), focusing the new frame.
+    /// Returns false if either half would be smaller than `min_size`.
+    fn split_focused_by_shape(&mut self, screen: Rect, gap: u32, min_size: u32) -> bool {
+        let Some(rect) = self.calculate_geometries(screen, gap)
+            .into_iter()
+            .find(|(id, _)| *id == self.focused)
+            .map(|(_, rect)| rect)
+        else {
+            return false;
+        };
+        let direction = if rect.width >= rect.height {
+            SplitDirection::Horizontal
+        } else {
+            SplitDirection::Vertical
+        };
+        if !self.can_split_focused(direction, screen, gap, min_size) {
+            return false;
+        }
+        self.split_focused(direction);
+        true
+    }
+
     /// Get all frame IDs in the tree (in-order traversal)
     pub fn all_frames(&self) -> Vec<NodeId> {
         let mut frames = Vec::new();
@@ -381,6 +652,53 @@ impl LayoutTree {
         best.map(|(id, _)| id)
     }
 
+    /// Find the frame that focus should land on when moving in `direction` from
+    /// `from` into another set of frames (e.g. the adjacent monitor's layout).
+    ///
+    /// Frames overlapping `from` on the perpendicular axis are preferred; ties
+    /// are broken by which frame's near edge is closest to `from`.
+    pub fn nearest_frame_from(
+        from: Rect,
+        direction: Direction,
+        geometries: &[(NodeId, Rect)],
+    ) -> Option<NodeId> {
+        // Distance from a point to a span (0 if inside it)
+        fn span_dist(p: i32, start: i32, len: u32) -> i32 {
+            if p < start {
+                start - p
+            } else if p >= start + len as i32 {
+                p - (start + len as i32 - 1)
+            } else {
+                0
+            }
+        }
+
+        geometries.iter()
+            .map(|(id, rect)| {
+                let (perpendicular, edge) = match direction {
+                    Direction::Left => (
+                        span_dist(from.center_y(), rect.y, rect.height),
+                        (from.x - (rect.x + rect.width as i32)).abs(),
+                    ),
+                    Direction::Right => (
+                        span_dist(from.center_y(), rect.y, rect.height),
+                        (rect.x - (from.x + from.width as i32)).abs(),
+                    ),
+                    Direction::Up => (
+                        span_dist(from.center_x(), rect.x, rect.width),
+                        (from.y - (rect.y + rect.height as i32)).abs(),
+                    ),
+                    Direction::Down => (
+                        span_dist(from.center_x(), rect.x, rect.width),
+                        (rect.y - (from.y + from.height as i32)).abs(),
+                    ),
+                };
+                (*id, (perpendicular, edge))
+            })
+            .min_by_key(|&(_, key)| key)
+            .map(|(id, _)| id)
+    }
+
     /// Focus the frame in the given spatial direction
     pub fn focus_spatial(&mut self, direction: Direction, geometries: &[(NodeId, Rect)]) -> bool {
         if let Some(target) = self.find_frame_in_direction(direction, geometries) {
@@ -474,6 +792,17 @@ impl LayoutTree {
         windows
     }
 
+    /// Get the focused (on-screen) window of each frame, in frame order
+    pub fn visible_windows(&self) -> Vec<Window> {
+        self.all_frames()
+            .into_iter()
+            .filter_map(|id| match self.get(id) {
+                Some(Node::Frame { frame, .. }) => frame.focused_window(),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Resize the split containing the focused frame
     /// delta > 0 grows the focused frame, delta < 0 shrinks it
     pub fn resize_focused_split(&mut self, delta: f32) -> bool {
@@ -506,6 +835,175 @@ impl LayoutTree {
         }
     }
 
+    /// Reset split ratios so that frames side by side in the same direction get
+    /// equal space (three columns each get a third, however they are nested).
+    /// Perpendicular splits count as one unit. Returns true if any ratio changed.
+    pub fn balance(&mut self) -> bool {
+        let splits: Vec<(NodeId, f32)> = self.nodes.iter()
+            .filter_map(|(id, node)| match node {
+                Node::Split { split, .. } => {
+                    let first = self.balance_weight(split.first, split.direction) as f32;
+                    let second = self.balance_weight(split.second, split.direction) as f32;
+                    Some((id, (first / (first + second)).clamp(0.1, 0.9)))
+                }
+                Node::Frame { .. } => None,
+            })
+            .collect();
+
+        let mut changed = false;
+        for (id, ratio) in splits {
+            if let Some(Node::Split { split, .. }) = self.nodes.get_mut(id) {
+                if (split.ratio - ratio).abs() > f32::EPSILON {
+                    split.ratio = ratio;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Number of equal shares a node takes along `direction`
+    fn balance_weight(&self, node_id: NodeId, direction: SplitDirection) -> usize {
+        match self.nodes.get(node_id) {
+            Some(Node::Split { split, .. }) if split.direction == direction => {
+                self.balance_weight(split.first, direction) + self.balance_weight(split.second, direction)
+            }
+            _ => 1,
+        }
+    }
+
+    /// Check that every window is in exactly one frame reachable from the root.
+    /// Returns a description of each violation (duplicates or windows stranded
+    /// in detached frames).
+    pub fn window_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        for frame_id in self.all_frames() {
+            if let Some(frame) = self.get(frame_id).and_then(|n| n.as_frame()) {
+                for &window in &frame.windows {
+                    if !seen.insert(window) {
+                        violations.push(format!("Window 0x{:x} is in more than one frame", window));
+                    }
+                }
+            }
+        }
+        for window in self.all_windows() {
+            if !seen.contains(&window) {
+                violations.push(format!("Window 0x{:x} is in a frame detached from the layout", window));
+            }
+        }
+        violations
+    }
+
+    /// Calculate the area allocated to any node (frame or split)
+    pub fn node_rect(&self, screen: Rect, gap: u32, target: NodeId) -> Option<Rect> {
+        self.node_rect_recursive(self.root, screen, gap, target)
+    }
+
+    fn node_rect_recursive(&self, node_id: NodeId, available: Rect, gap: u32, target: NodeId) -> Option<Rect> {
+        if node_id == target {
+            return Some(available);
+        }
+        match self.get(node_id) {
+            Some(Node::Split { split, .. }) => {
+                let (first_rect, second_rect) = Self::split_rect(available, split.direction, split.ratio, gap);
+                self.node_rect_recursive(split.first, first_rect, gap, target)
+                    .or_else(|| self.node_rect_recursive(split.second, second_rect, gap, target))
+            }
+            _ => None,
+        }
+    }
+
+    /// Set the split containing the focused frame so its first child is
+    /// `pixels` wide (horizontal split) or tall (vertical split).
+    /// The ratio is clamped like any other resize, so the result may differ;
+    /// returns the first child's resulting size, or None if there is no split.
+    pub fn set_focused_split_pixels(&mut self, screen: Rect, gap: u32, pixels: u32) -> Option<u32> {
+        let split_id = self.parent(self.focused)?;
+        let rect = self.node_rect(screen, gap, split_id)?;
+        let direction = self.get(split_id)?.as_split()?.direction;
+        let total = match direction {
+            SplitDirection::Horizontal => rect.width,
+            SplitDirection::Vertical => rect.height,
+        };
+        if total == 0 {
+            return None;
+        }
+
+        // split_rect() gives the first child floor(total * ratio) - gap / 2; the
+        // extra half pixel keeps float rounding from landing one pixel short
+        let ratio = ((pixels + gap / 2) as f32 + 0.5) / total as f32;
+        self.set_split_ratio(split_id, ratio);
+
+        let ratio = self.get(split_id)?.as_split()?.ratio;
+        let (first, _) = Self::split_rect(rect, direction, ratio, gap);
+        Some(match direction {
+            SplitDirection::Horizontal => first.width,
+            SplitDirection::Vertical => first.height,
+        })
+    }
+
+    /// Find the split whose divider runs along the `side` edge of a frame,
+    /// i.e. the split a drag on that edge resizes. Returns the same tuple
+    /// as `find_split_at_gap`, or None for an edge on the screen border.
+    pub fn find_split_at_edge(
+        &self,
+        screen: Rect,
+        gap: u32,
+        frame: NodeId,
+        side: Direction,
+    ) -> Option<(NodeId, SplitDirection, i32, u32)> {
+        let (direction, from_second) = match side {
+            Direction::Left => (SplitDirection::Horizontal, true),
+            Direction::Right => (SplitDirection::Horizontal, false),
+            Direction::Up => (SplitDirection::Vertical, true),
+            Direction::Down => (SplitDirection::Vertical, false),
+        };
+
+        // Walk up until the node is on the matching side of a split in
+        // that direction; otherwise its edge is also its parent's edge
+        let mut child = frame;
+        while let Some(split_id) = self.parent(child) {
+            let split = self.get(split_id)?.as_split()?;
+            if split.direction == direction && (split.second == child) == from_second {
+                let rect = self.node_rect(screen, gap, split_id)?;
+                let (split_start, total_size) = match direction {
+                    SplitDirection::Horizontal => (rect.x, rect.width),
+                    SplitDirection::Vertical => (rect.y, rect.height),
+                };
+                return Some((split_id, direction, split_start, total_size));
+            }
+            child = split_id;
+        }
+        None
+    }
+
+    /// Find the split a press on a tiled window's border resizes. `press` is
+    /// relative to the inside of the border and `size` is the window's, so a
+    /// press on the border falls outside `0..width` or `0..height`. None for
+    /// presses inside the window or on a border along the screen edge.
+    pub fn find_split_at_border(
+        &self,
+        screen: Rect,
+        gap: u32,
+        frame: NodeId,
+        (x, y): (i32, i32),
+        (width, height): (u32, u32),
+    ) -> Option<(NodeId, SplitDirection, i32, u32)> {
+        let side = if x < 0 {
+            Direction::Left
+        } else if x >= width as i32 {
+            Direction::Right
+        } else if y < 0 {
+            Direction::Up
+        } else if y >= height as i32 {
+            Direction::Down
+        } else {
+            return None;
+        };
+        self.find_split_at_edge(screen, gap, frame, side)
+    }
+
     /// Find a split whose gap contains the given mouse coordinates
     /// Returns (split_id, direction, gap_start_position, total_size_in_split_direction)
     pub fn find_split_at_gap(
@@ -585,16 +1083,17 @@ impl LayoutTree {
         }
     }
 
-    /// Remove a specific empty frame by ID
+    /// Remove a specific frame by ID (normally an empty one)
+    /// Windows still in the frame are moved to the nearest frame in its
+    /// sibling subtree rather than being dropped.
     /// Returns true if the frame was removed
     pub fn remove_frame_by_id(&mut self, frame_id: NodeId) -> bool {
-        // Check if frame exists and is empty
-        let is_empty = self.get(frame_id)
-            .and_then(|n| n.as_frame())
-            .map(|f| f.is_empty())
-            .unwrap_or(false);
+        let orphans = match self.get(frame_id).and_then(|n| n.as_frame()) {
+            Some(frame) => frame.windows.clone(),
+            None => return false,
+        };
 
-        if !is_empty || frame_id == self.root {
+        if frame_id == self.root {
             return false;
         }
 
@@ -636,10 +1135,25 @@ impl LayoutTree {
             self.set_parent(sibling_id, None);
         }
 
-        // Remove the empty frame and the parent split
+        // Remove the frame and the parent split
         self.nodes.remove(frame_id);
         self.nodes.remove(parent_id);
 
+        // Re-home any windows the frame still held
+        if !orphans.is_empty() {
+            let mut frames = Vec::new();
+            self.collect_frames(sibling_id, &mut frames);
+            if let Some(&target) = frames.first() {
+                log::warn!(
+                    "Removed frame {:?} still had {} window(s), moving them to {:?}",
+                    frame_id, orphans.len(), target
+                );
+                for window in orphans {
+                    self.add_window_to_frame(window, target);
+                }
+            }
+        }
+
         // Update focused if needed
         if self.focused == frame_id {
             // Focus the first frame we can find
@@ -649,21 +1163,60 @@ impl LayoutTree {
         true
     }
 
-    /// Cycle to the next/previous tab in the focused frame
-    /// Returns the newly focused window (if any)
-    pub fn cycle_tab(&mut self, forward: bool) -> Option<Window> {
-        let frame = self.focused_frame_mut()?;
-        if frame.windows.is_empty() {
-            return None;
+    /// Collapse every split that has an empty frame on one side into its other child.
+    /// The focused frame and named or locked frames are kept, since those are
+    /// deliberate placeholders. Returns the number of frames removed.
+    pub fn normalize(&mut self) -> usize {
+        let mut removed = 0;
+        loop {
+            let collapsible = self.all_frames().into_iter().find(|&id| {
+                id != self.root
+                    && id != self.focused
+                    && self.get(id).and_then(|n| n.as_frame()).is_some_and(|f| {
+                        f.is_empty() && f.name.is_none() && !f.locked
+                    })
+            });
+            match collapsible {
+                Some(frame_id) if self.remove_frame_by_id(frame_id) => removed += 1,
+                _ => return removed,
+            }
         }
+    }
 
-        let len = frame.windows.len();
-        frame.focused = if forward {
-            (frame.focused + 1) % len
-        } else {
-            if frame.focused == 0 { len - 1 } else { frame.focused - 1 }
-        };
-
+    /// Gather every window into a single frame, discarding all splits.
+    /// Windows keep their frame/tab traversal order and the focused window
+    /// stays focused; the focused frame's settings (name, lock, tab style)
+    /// carry over. Returns the number of frames removed.
+    pub fn flatten(&mut self) -> usize {
+        let frames = self.all_frames();
+        if frames.len() <= 1 {
+            return 0;
+        }
+        let windows: Vec<Window> = frames
+            .iter()
+            .filter_map(|&id| self.get(id).and_then(|n| n.as_frame()))
+            .flat_map(|f| f.windows.iter().copied())
+            .collect();
+        let focused_window = self.focused_frame().and_then(|f| f.focused_window());
+
+        let mut frame = self.focused_frame().cloned().unwrap_or_else(Frame::new);
+        frame.focused = focused_window
+            .and_then(|w| windows.iter().position(|&x| x == w))
+            .unwrap_or(0);
+        frame.windows = windows;
+
+        self.nodes.clear();
+        self.root = self.nodes.insert(Node::Frame { frame, parent: None });
+        self.focused = self.root;
+        frames.len() - 1
+    }
+
+    /// Cycle to the next/previous tab in the focused frame, wrapping at the
+    /// ends only if `wrap` is set (see `step_index`)
+    /// Returns the newly focused window, or None if there was no tab to go to
+    pub fn cycle_tab(&mut self, forward: bool, wrap: bool) -> Option<Window> {
+        let frame = self.focused_frame_mut()?;
+        frame.focused = step_index(frame.focused, frame.windows.len(), forward, wrap)?;
         frame.focused_window()
     }
 
@@ -679,6 +1232,17 @@ impl LayoutTree {
         }
     }
 
+    /// Toggle the locked flag on the focused frame
+    /// Returns the new locked state
+    pub fn toggle_frame_lock(&mut self) -> bool {
+        if let Some(frame) = self.focused_frame_mut() {
+            frame.locked = !frame.locked;
+            frame.locked
+        } else {
+            false
+        }
+    }
+
     /// Toggle vertical tabs on the focused frame
     /// Returns the new vertical_tabs state
     pub fn toggle_vertical_tabs(&mut self) -> bool {
@@ -690,6 +1254,31 @@ impl LayoutTree {
         }
     }
 
+    /// Toggle the focused frame between tabbed and stacked titles
+    /// Returns true if the frame is now stacked
+    pub fn toggle_stacked(&mut self) -> bool {
+        if let Some(frame) = self.focused_frame_mut() {
+            frame.style = match frame.style {
+                FrameStyle::Tabbed => FrameStyle::Stacked,
+                FrameStyle::Stacked => FrameStyle::Tabbed,
+            };
+            frame.style == FrameStyle::Stacked
+        } else {
+            false
+        }
+    }
+
+    /// Set vertical tabs on the focused frame
+    /// Returns false if there is no focused frame
+    pub fn set_vertical_tabs(&mut self, enabled: bool) -> bool {
+        if let Some(frame) = self.focused_frame_mut() {
+            frame.vertical_tabs = enabled;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Set the name of a frame
     /// Does not check for uniqueness - caller is responsible for that
     pub fn set_frame_name(&mut self, node_id: NodeId, name: Option<String>) -> bool {
@@ -751,6 +1340,18 @@ impl LayoutTree {
         }
     }
 
+    /// Move the focused tab of the focused frame to the first position
+    /// Returns the promoted window if the order changed
+    pub fn promote_focused_tab(&mut self) -> Option<Window> {
+        let frame_id = self.focused;
+        let index = self.focused_frame()?.focused;
+        if self.reorder_tab(frame_id, index, 0) {
+            self.focused_frame()?.focused_window()
+        } else {
+            None
+        }
+    }
+
     /// Move a window from source frame to target frame
     pub fn move_window_to_frame(
         &mut self,
@@ -758,6 +1359,12 @@ impl LayoutTree {
         source_frame: NodeId,
         target_frame: NodeId,
     ) -> bool {
+        // Check the target first so a stale id can't strand the window
+        if self.get(target_frame).and_then(|n| n.as_frame()).is_none() {
+            log::warn!("Cannot move window 0x{:x} to missing frame {:?}", window, target_frame);
+            return false;
+        }
+
         // Remove from source
         if let Some(Node::Frame { frame, .. }) = self.nodes.get_mut(source_frame) {
             if !frame.remove_window(window) {
@@ -770,8 +1377,6 @@ impl LayoutTree {
         // Add to target
         if let Some(Node::Frame { frame, .. }) = self.nodes.get_mut(target_frame) {
             frame.add_window(window);
-        } else {
-            return false;
         }
 
         // Update focused frame
@@ -779,15 +1384,29 @@ impl LayoutTree {
         true
     }
 
+    /// Pull a window from another frame into the focused frame as its last
+    /// tab. Returns false if the window is not in this tree or is already in
+    /// the focused frame.
+    pub fn pull_window_to_focused(&mut self, window: Window) -> bool {
+        let target_frame = self.focused;
+        match self.find_window(window) {
+            Some(source_frame) if source_frame != target_frame => {
+                self.move_window_to_frame(window, source_frame, target_frame)
+            }
+            _ => false,
+        }
+    }
+
     /// Get number of tabs in the focused frame
     #[allow(dead_code)]
     pub fn tab_count(&self) -> usize {
         self.focused_frame().map(|f| f.windows.len()).unwrap_or(0)
     }
 
-    /// Move the focused window to an adjacent frame
+    /// Move the focused window to an adjacent frame, wrapping from the last
+    /// frame to the first (and back) only if `wrap` is set
     /// Returns the window that was moved (if any)
-    pub fn move_window_to_adjacent(&mut self, forward: bool) -> Option<Window> {
+    pub fn move_window_to_adjacent(&mut self, forward: bool, wrap: bool) -> Option<Window> {
         let frames = self.all_frames();
         if frames.len() <= 1 {
             return None;
@@ -800,28 +1419,15 @@ impl LayoutTree {
         let current_idx = frames.iter().position(|&f| f == self.focused)?;
 
         // Find adjacent frame
-        let adjacent_idx = if forward {
-            (current_idx + 1) % frames.len()
-        } else {
-            if current_idx == 0 { frames.len() - 1 } else { current_idx - 1 }
-        };
+        let adjacent_idx = step_index(current_idx, frames.len(), forward, wrap)?;
 
         let adjacent_frame_id = frames[adjacent_idx];
 
-        // Remove window from current frame
-        if let Some(Node::Frame { frame, .. }) = self.nodes.get_mut(self.focused) {
-            frame.remove_window(window);
-        }
-
-        // Add window to adjacent frame
-        if let Some(Node::Frame { frame, .. }) = self.nodes.get_mut(adjacent_frame_id) {
-            frame.add_window(window);
+        if self.move_window_to_frame(window, self.focused, adjacent_frame_id) {
+            Some(window)
+        } else {
+            None
         }
-
-        // Focus the adjacent frame
-        self.focused = adjacent_frame_id;
-
-        Some(window)
     }
 
     /// Create a snapshot of the layout tree for IPC serialization
@@ -846,6 +1452,7 @@ impl LayoutTree {
                         name: frame.name.clone(),
                         windows: frame.windows.clone(),
                         focused_tab: frame.focused,
+                        locked: frame.locked,
                         geometry,
                     }
                 }
@@ -867,6 +1474,7 @@ impl LayoutTree {
                     name: None,
                     windows: vec![],
                     focused_tab: 0,
+                    locked: false,
                     geometry: None,
                 },
             }
@@ -930,6 +1538,10 @@ impl LayoutTree {
             focused: 0,
             vertical_tabs: config.vertical_tabs,
             name: config.name.clone().filter(|s| !s.is_empty()),
+            locked: false,
+            tab_sizing: None,
+            style: FrameStyle::Tabbed,
+            color: None,
         };
         let node_id = nodes.insert(Node::Frame { frame, parent });
 
@@ -997,6 +1609,35 @@ impl LayoutTree {
         self.focused = new_tree.focused;
         pending_apps
     }
+
+    /// Convert the tree back into a startup layout config (inverse of `from_config`).
+    /// Windows are not included, so frames have no apps.
+    pub fn to_config(&self) -> LayoutNodeConfig {
+        self.node_to_config(self.root)
+    }
+
+    fn node_to_config(&self, node_id: NodeId) -> LayoutNodeConfig {
+        match self.get(node_id) {
+            Some(Node::Split { split, .. }) => {
+                let direction = match split.direction {
+                    SplitDirection::Horizontal => SplitDirectionConfig::Horizontal,
+                    SplitDirection::Vertical => SplitDirectionConfig::Vertical,
+                };
+                LayoutNodeConfig::Split(SplitConfig {
+                    direction,
+                    ratio: split.ratio,
+                    first: Box::new(self.node_to_config(split.first)),
+                    second: Box::new(self.node_to_config(split.second)),
+                })
+            }
+            Some(Node::Frame { frame, .. }) => LayoutNodeConfig::Frame(FrameConfig {
+                name: frame.name.clone(),
+                vertical_tabs: frame.vertical_tabs,
+                apps: Vec::new(),
+            }),
+            None => LayoutNodeConfig::Frame(FrameConfig::default()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1071,6 +1712,33 @@ mod tests {
         assert_ne!(tree.focused, original_focused);
     }
 
+    #[test]
+    fn test_new_frame_keeps_windows_in_original() {
+        let mut tree = LayoutTree::new();
+        let original = tree.focused;
+        tree.add_window(1);
+        tree.add_window(2);
+
+        let new_frame = tree.new_frame(Direction::Left);
+
+        assert_eq!(tree.focused, new_frame);
+        assert!(tree.focused_frame().unwrap().is_empty());
+        assert_eq!(tree.get(original).unwrap().as_frame().unwrap().windows, vec![1, 2]);
+        // The new frame goes on the requested side
+        let split = tree.get(tree.root).unwrap().as_split().unwrap();
+        assert_eq!(split.direction, SplitDirection::Horizontal);
+        assert_eq!((split.first, split.second), (new_frame, original));
+
+        // The next window lands in the new frame
+        tree.add_window(3);
+        assert_eq!(tree.get(new_frame).unwrap().as_frame().unwrap().windows, vec![3]);
+
+        let below = tree.new_frame(Direction::Down);
+        let split = tree.get(tree.parent(below).unwrap()).unwrap().as_split().unwrap();
+        assert_eq!(split.direction, SplitDirection::Vertical);
+        assert_eq!((split.first, split.second), (new_frame, below));
+    }
+
     #[test]
     fn test_nested_splits() {
         let mut tree = LayoutTree::new();
@@ -1093,48 +1761,137 @@ mod tests {
         assert_eq!(split.ratio, 0.5);
     }
 
-    // ==================== Geometry Tests ====================
-
     #[test]
-    fn test_single_frame_fills_screen() {
-        let tree = LayoutTree::new();
-        let screen = Rect::new(0, 0, 1920, 1080);
+    fn test_can_split_focused_respects_min_size() {
+        let mut tree = LayoutTree::new();
+        let screen = Rect::new(0, 0, 800, 600);
 
-        let geometries = tree.calculate_geometries(screen, 0);
+        // 800 -> 396 -> 194 -> 93: the third split would go below 100
+        let mut splits = 0;
+        while tree.can_split_focused(SplitDirection::Horizontal, screen, 8, 100) {
+            tree.split_focused(SplitDirection::Horizontal);
+            splits += 1;
+        }
+        assert_eq!(splits, 2);
+        assert_eq!(tree.all_frames().len(), 3);
 
-        assert_eq!(geometries.len(), 1);
-        let (_, rect) = &geometries[0];
-        assert_eq!(rect.x, 0);
-        assert_eq!(rect.y, 0);
-        assert_eq!(rect.width, 1920);
-        assert_eq!(rect.height, 1080);
+        // Vertical axis is unaffected
+        assert!(tree.can_split_focused(SplitDirection::Vertical, screen, 8, 100));
+
+        // Zero disables the limit
+        assert!(tree.can_split_focused(SplitDirection::Horizontal, screen, 8, 0));
     }
 
     #[test]
-    fn test_horizontal_split_divides_width() {
+    fn test_add_window_as_split_grows_frames() {
         let mut tree = LayoutTree::new();
-        tree.split_focused(SplitDirection::Horizontal);
-
-        let screen = Rect::new(0, 0, 1000, 500);
-        let geometries = tree.calculate_geometries(screen, 0);
+        let screen = Rect::new(0, 0, 1600, 1000);
 
-        assert_eq!(geometries.len(), 2);
+        // First window goes into the empty root frame
+        tree.add_window_as_split(1, screen, 0, 100);
+        assert_eq!(tree.all_frames().len(), 1);
 
-        // Both should have same height
-        assert_eq!(geometries[0].1.height, 500);
-        assert_eq!(geometries[1].1.height, 500);
+        // Each further window gets its own frame
+        for (i, window) in (2..=4).enumerate() {
+            tree.add_window_as_split(window, screen, 0, 100);
+            assert_eq!(tree.all_frames().len(), i + 2);
+            assert_eq!(tree.focused_frame().unwrap().windows, vec![window]);
+        }
 
-        // Widths should roughly add up (allowing for gap calculations)
-        let total_width = geometries[0].1.width + geometries[1].1.width;
-        assert!(total_width <= 1000);
+        // Wide screen splits side-by-side first, then the tall half top-to-bottom
+        let root_split = tree.get(tree.root).unwrap().as_split().unwrap();
+        assert_eq!(root_split.direction, SplitDirection::Horizontal);
+        let second = tree.get(root_split.second).unwrap().as_split().unwrap();
+        assert_eq!(second.direction, SplitDirection::Vertical);
     }
 
     #[test]
-    fn test_vertical_split_divides_height() {
+    fn test_add_window_as_split_falls_back_to_tab() {
         let mut tree = LayoutTree::new();
-        tree.split_focused(SplitDirection::Vertical);
+        let screen = Rect::new(0, 0, 150, 150);
 
-        let screen = Rect::new(0, 0, 1000, 500);
+        tree.add_window_as_split(1, screen, 0, 100);
+        tree.add_window_as_split(2, screen, 0, 100);
+
+        // Too small to split: the second window becomes a tab
+        assert_eq!(tree.all_frames().len(), 1);
+        assert_eq!(tree.focused_frame().unwrap().windows, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_add_window_capped() {
+        let mut tree = LayoutTree::new();
+        let screen = Rect::new(0, 0, 1600, 1000);
+
+        for window in 1..=3 {
+            tree.add_window_capped(window, 3, screen, 0, 100);
+        }
+        assert_eq!(tree.all_frames().len(), 1);
+
+        // Past the limit a new frame is split off for the window
+        tree.add_window_capped(4, 3, screen, 0, 100);
+        let frames = tree.all_frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(tree.get(frames[0]).unwrap().as_frame().unwrap().windows, vec![1, 2, 3]);
+        assert_eq!(tree.focused_frame().unwrap().windows, vec![4]);
+
+        // A full frame passes windows on to a frame with room before splitting
+        tree.focused = frames[0];
+        tree.add_window_capped(5, 3, screen, 0, 100);
+        assert_eq!(tree.all_frames().len(), 2);
+        assert_eq!(tree.focused, frames[1]);
+        assert_eq!(tree.focused_frame().unwrap().windows, vec![4, 5]);
+
+        // 0 means no limit
+        let mut tree = LayoutTree::new();
+        for window in 1..=10 {
+            tree.add_window_capped(window, 0, screen, 0, 100);
+        }
+        assert_eq!(tree.all_frames().len(), 1);
+    }
+
+    // ==================== Geometry Tests ====================
+
+    #[test]
+    fn test_single_frame_fills_screen() {
+        let tree = LayoutTree::new();
+        let screen = Rect::new(0, 0, 1920, 1080);
+
+        let geometries = tree.calculate_geometries(screen, 0);
+
+        assert_eq!(geometries.len(), 1);
+        let (_, rect) = &geometries[0];
+        assert_eq!(rect.x, 0);
+        assert_eq!(rect.y, 0);
+        assert_eq!(rect.width, 1920);
+        assert_eq!(rect.height, 1080);
+    }
+
+    #[test]
+    fn test_horizontal_split_divides_width() {
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Horizontal);
+
+        let screen = Rect::new(0, 0, 1000, 500);
+        let geometries = tree.calculate_geometries(screen, 0);
+
+        assert_eq!(geometries.len(), 2);
+
+        // Both should have same height
+        assert_eq!(geometries[0].1.height, 500);
+        assert_eq!(geometries[1].1.height, 500);
+
+        // Widths should roughly add up (allowing for gap calculations)
+        let total_width = geometries[0].1.width + geometries[1].1.width;
+        assert!(total_width <= 1000);
+    }
+
+    #[test]
+    fn test_vertical_split_divides_height() {
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Vertical);
+
+        let screen = Rect::new(0, 0, 1000, 500);
         let geometries = tree.calculate_geometries(screen, 0);
 
         assert_eq!(geometries.len(), 2);
@@ -1148,6 +1905,57 @@ mod tests {
         assert!(total_height <= 500);
     }
 
+    #[test]
+    fn test_find_split_at_edge() {
+        let screen = Rect::new(0, 0, 1000, 800);
+        let mut tree = LayoutTree::new();
+        let left = tree.focused;
+        // [left | [top / bottom]]
+        tree.split_focused(SplitDirection::Horizontal);
+        let top = tree.focused;
+        tree.split_focused(SplitDirection::Vertical);
+        let bottom = tree.focused;
+        let outer = tree.parent(left).unwrap();
+        let inner = tree.parent(top).unwrap();
+
+        let (split, direction, start, total) =
+            tree.find_split_at_edge(screen, 0, left, Direction::Right).unwrap();
+        assert_eq!((split, direction, start, total), (outer, SplitDirection::Horizontal, 0, 1000));
+        // The bottom frame's left edge is the outer divider too
+        assert_eq!(tree.find_split_at_edge(screen, 0, bottom, Direction::Left).map(|r| r.0), Some(outer));
+        assert_eq!(tree.find_split_at_edge(screen, 0, top, Direction::Down).map(|r| r.0), Some(inner));
+        let (_, _, start, total) = tree.find_split_at_edge(screen, 0, bottom, Direction::Up).unwrap();
+        assert_eq!((start, total), (0, 800));
+
+        // Screen edges have no split
+        assert!(tree.find_split_at_edge(screen, 0, left, Direction::Left).is_none());
+        assert!(tree.find_split_at_edge(screen, 0, left, Direction::Up).is_none());
+        assert!(tree.find_split_at_edge(screen, 0, top, Direction::Up).is_none());
+        assert!(tree.find_split_at_edge(screen, 0, bottom, Direction::Right).is_none());
+    }
+
+    #[test]
+    fn test_find_split_at_border() {
+        let screen = Rect::new(0, 0, 1000, 800);
+        let mut tree = LayoutTree::new();
+        let left = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        let right = tree.focused;
+        let split = tree.parent(left).unwrap();
+        let size = (496, 796);
+
+        // Presses on the inner borders resize the split
+        assert_eq!(tree.find_split_at_border(screen, 0, left, (497, 300), size).map(|r| r.0), Some(split));
+        assert_eq!(tree.find_split_at_border(screen, 0, right, (-1, 300), size).map(|r| r.0), Some(split));
+
+        // Presses inside the window, or on a border along the screen edge, don't
+        assert!(tree.find_split_at_border(screen, 0, left, (0, 0), size).is_none());
+        assert!(tree.find_split_at_border(screen, 0, left, (495, 795), size).is_none());
+        assert!(tree.find_split_at_border(screen, 0, left, (-2, 300), size).is_none());
+        assert!(tree.find_split_at_border(screen, 0, right, (200, 796), size).is_none());
+        assert!(tree.find_split_at_border(screen, 0, right, (496, 300), size).is_none());
+    }
+
     #[test]
     fn test_gaps_reduce_available_space() {
         let mut tree = LayoutTree::new();
@@ -1232,6 +2040,51 @@ mod tests {
         assert_ne!(tree.focused, top_frame);
     }
 
+    #[test]
+    fn test_nearest_frame_from_hops_to_near_edge() {
+        // Right monitor at x=1000 split left/right; hopping right lands on its left frame
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Horizontal);
+        let right_monitor = Rect::new(1000, 0, 1000, 500);
+        let geometries = tree.calculate_geometries(right_monitor, 0);
+        let left_frame = geometries.iter().min_by_key(|(_, r)| r.x).unwrap().0;
+
+        let from = Rect::new(500, 0, 500, 500);
+        assert_eq!(LayoutTree::nearest_frame_from(from, Direction::Right, &geometries), Some(left_frame));
+    }
+
+    #[test]
+    fn test_nearest_frame_from_matches_perpendicular_position() {
+        // Right monitor split top/bottom; hopping right from a bottom frame lands on the bottom one
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Vertical);
+        let right_monitor = Rect::new(1000, 0, 1000, 500);
+        let geometries = tree.calculate_geometries(right_monitor, 0);
+        let bottom_frame = geometries.iter().max_by_key(|(_, r)| r.y).unwrap().0;
+        let top_frame = geometries.iter().min_by_key(|(_, r)| r.y).unwrap().0;
+
+        let from_bottom = Rect::new(0, 250, 1000, 250);
+        assert_eq!(LayoutTree::nearest_frame_from(from_bottom, Direction::Right, &geometries), Some(bottom_frame));
+
+        // Hopping left back from a monitor to the right, starting at the top
+        let from_top = Rect::new(2000, 0, 1000, 250);
+        assert_eq!(LayoutTree::nearest_frame_from(from_top, Direction::Left, &geometries), Some(top_frame));
+    }
+
+    #[test]
+    fn test_nearest_frame_from_vertical_hop() {
+        // Monitor below split left/right; hopping down from the right half lands on the right frame
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Horizontal);
+        let lower_monitor = Rect::new(0, 500, 1000, 500);
+        let geometries = tree.calculate_geometries(lower_monitor, 0);
+        let right_frame = geometries.iter().max_by_key(|(_, r)| r.x).unwrap().0;
+
+        let from = Rect::new(600, 0, 400, 500);
+        assert_eq!(LayoutTree::nearest_frame_from(from, Direction::Down, &geometries), Some(right_frame));
+        assert_eq!(LayoutTree::nearest_frame_from(from, Direction::Down, &[]), None);
+    }
+
     #[test]
     fn test_spatial_focus_no_frame_in_direction() {
         let mut tree = LayoutTree::new();
@@ -1337,6 +2190,116 @@ mod tests {
         assert!(tree.find_window(9999).is_none());
     }
 
+    #[test]
+    fn test_add_window_skips_locked_frame() {
+        let mut tree = LayoutTree::new();
+        let left = tree.focused;
+        tree.add_window(1);
+        let right = tree.split_focused(SplitDirection::Horizontal);
+
+        // Lock the left frame and focus it: new windows go to the right frame
+        tree.focused = left;
+        assert!(tree.toggle_frame_lock());
+        tree.add_window(2);
+        assert_eq!(tree.focused, right);
+        assert_eq!(tree.get(left).unwrap().as_frame().unwrap().windows, vec![1]);
+        assert_eq!(tree.get(right).unwrap().as_frame().unwrap().windows, vec![2]);
+
+        // Explicit moves still land in the locked frame
+        assert!(tree.move_window_to_frame(2, right, left));
+        assert_eq!(tree.get(left).unwrap().as_frame().unwrap().windows, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_group_frame_collects_same_class() {
+        // 1 and 3 are the same application, 2 is another one
+        let same_app = |w: Window| w == 1 || w == 3;
+        let mut tree = LayoutTree::new();
+        let left = tree.focused;
+        tree.add_window(1);
+        let right = tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(2);
+        assert_eq!(tree.focused, right);
+
+        // The new window joins the frame holding its sibling, not the focused one
+        let target = tree.group_frame(same_app).unwrap();
+        assert_eq!(target, left);
+        tree.focused = target;
+        tree.add_window(3);
+        assert_eq!(tree.get(left).unwrap().as_frame().unwrap().windows, vec![1, 3]);
+        assert_eq!(tree.get(right).unwrap().as_frame().unwrap().windows, vec![2]);
+
+        // No match anywhere: normal placement
+        assert_eq!(tree.group_frame(|w| w == 99), None);
+
+        // Locked frames don't take grouped windows
+        tree.focused = left;
+        tree.toggle_frame_lock();
+        assert_eq!(tree.group_frame(same_app), None);
+    }
+
+    #[test]
+    fn test_add_window_all_locked_creates_frame() {
+        let mut tree = LayoutTree::new();
+        let only = tree.focused;
+        tree.add_window(1);
+        tree.toggle_frame_lock();
+
+        tree.add_window(2);
+        assert_eq!(tree.all_frames().len(), 2);
+        assert_ne!(tree.focused, only);
+        assert_eq!(tree.focused_frame().unwrap().windows, vec![2]);
+
+        // Unlocking restores normal tab behavior
+        tree.focused = only;
+        assert!(!tree.toggle_frame_lock());
+        tree.add_window(3);
+        assert_eq!(tree.get(only).unwrap().as_frame().unwrap().windows, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_close_focused_tab_policies() {
+        let setup = || {
+            let mut tree = LayoutTree::new();
+            for w in 1..=4 {
+                tree.add_window(w);
+            }
+            tree.focus_tab(1); // focus window 2
+            tree
+        };
+        let focused = |tree: &LayoutTree| tree.focused_frame().unwrap().focused_window();
+
+        let mut tree = setup();
+        tree.remove_window_with_focus(2, CloseFocus::Next, &[]);
+        assert_eq!(focused(&tree), Some(3));
+
+        let mut tree = setup();
+        tree.remove_window_with_focus(2, CloseFocus::Prev, &[]);
+        assert_eq!(focused(&tree), Some(1));
+
+        // MRU: 4 was used before 2, then 1 long ago
+        let mut tree = setup();
+        tree.remove_window_with_focus(2, CloseFocus::Mru, &[1, 4, 2]);
+        assert_eq!(focused(&tree), Some(4));
+
+        // MRU with no history falls back to the next tab
+        let mut tree = setup();
+        tree.remove_window_with_focus(2, CloseFocus::Mru, &[]);
+        assert_eq!(focused(&tree), Some(3));
+
+        // Closing the last tab with Next focuses the new last tab
+        let mut tree = setup();
+        tree.focus_tab(3);
+        tree.remove_window_with_focus(4, CloseFocus::Next, &[]);
+        assert_eq!(focused(&tree), Some(3));
+
+        // Closing the first tab with Prev stays at the start
+        let mut tree = setup();
+        tree.focus_tab(0);
+        tree.remove_window_with_focus(1, CloseFocus::Prev, &[]);
+        assert_eq!(focused(&tree), Some(2));
+    }
+
     // ==================== Tab Cycling Tests ====================
 
     #[test]
@@ -1347,10 +2310,10 @@ mod tests {
         tree.add_window(1003);
 
         // Currently focused on 1003 (last added)
-        let next = tree.cycle_tab(true);
+        let next = tree.cycle_tab(true, true);
         assert_eq!(next, Some(1001)); // Wraps around
 
-        let next = tree.cycle_tab(true);
+        let next = tree.cycle_tab(true, true);
         assert_eq!(next, Some(1002));
     }
 
@@ -1362,13 +2325,13 @@ mod tests {
         tree.add_window(1003);
 
         // Currently focused on 1003 (index 2)
-        let prev = tree.cycle_tab(false);
+        let prev = tree.cycle_tab(false, true);
         assert_eq!(prev, Some(1002));
 
-        let prev = tree.cycle_tab(false);
+        let prev = tree.cycle_tab(false, true);
         assert_eq!(prev, Some(1001));
 
-        let prev = tree.cycle_tab(false);
+        let prev = tree.cycle_tab(false, true);
         assert_eq!(prev, Some(1003)); // Wraps around
     }
 
@@ -1376,7 +2339,7 @@ mod tests {
     fn test_cycle_tab_empty_frame() {
         let mut tree = LayoutTree::new();
 
-        let result = tree.cycle_tab(true);
+        let result = tree.cycle_tab(true, true);
         assert!(result.is_none());
     }
 
@@ -1385,8 +2348,30 @@ mod tests {
         let mut tree = LayoutTree::new();
         tree.add_window(1001);
 
-        let result = tree.cycle_tab(true);
+        let result = tree.cycle_tab(true, true);
         assert_eq!(result, Some(1001)); // Stays on same window
+
+        // Without wrapping there is nowhere to go
+        assert_eq!(tree.cycle_tab(true, false), None);
+        assert_eq!(tree.cycle_tab(false, false), None);
+    }
+
+    #[test]
+    fn test_cycle_tab_without_wrap() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1001);
+        tree.add_window(1002);
+        tree.add_window(1003);
+
+        // Focused on the last tab: forward stops, backward still moves
+        assert_eq!(tree.cycle_tab(true, false), None);
+        assert_eq!(tree.focused_frame().unwrap().focused, 2);
+        assert_eq!(tree.cycle_tab(false, false), Some(1002));
+        assert_eq!(tree.cycle_tab(false, false), Some(1001));
+
+        // At the first tab backward stops
+        assert_eq!(tree.cycle_tab(false, false), None);
+        assert_eq!(tree.focused_frame().unwrap().focused, 0);
     }
 
     #[test]
@@ -1426,7 +2411,7 @@ mod tests {
         tree.focus_spatial(Direction::Left, &geometries);
 
         // Move window forward
-        let moved = tree.move_window_to_adjacent(true);
+        let moved = tree.move_window_to_adjacent(true, true);
         assert_eq!(moved, Some(1001));
 
         // Window should now be in the second frame
@@ -1439,10 +2424,33 @@ mod tests {
         tree.add_window(1001);
 
         // Can't move with only one frame
-        let moved = tree.move_window_to_adjacent(true);
+        let moved = tree.move_window_to_adjacent(true, true);
         assert!(moved.is_none());
     }
 
+    #[test]
+    fn test_move_window_to_adjacent_wrap() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1001);
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(1002);
+        let last = tree.focused;
+
+        // From the last frame, forward only wraps to the first when allowed
+        assert_eq!(tree.move_window_to_adjacent(true, false), None);
+        assert_eq!(tree.focused, last);
+        assert_eq!(tree.focused_frame().unwrap().windows, vec![1002]);
+
+        assert_eq!(tree.move_window_to_adjacent(true, true), Some(1002));
+        assert_ne!(tree.focused, last);
+        assert_eq!(tree.focused_frame().unwrap().windows, vec![1001, 1002]);
+
+        // Now at the first frame, backward without wrap stays put
+        assert_eq!(tree.move_window_to_adjacent(false, false), None);
+        assert_eq!(tree.move_window_to_adjacent(false, true), Some(1002));
+        assert_eq!(tree.focused, last);
+    }
+
     // ==================== Resize Tests ====================
 
     #[test]
@@ -1463,6 +2471,30 @@ mod tests {
         assert!(new_ratio > original_ratio);
     }
 
+    #[test]
+    fn test_balance_equalizes_columns() {
+        // Splitting the right frame twice gives [a | [b | c]] with 0.5 everywhere
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.split_focused(SplitDirection::Horizontal);
+        let inner = tree.parent(tree.focused).unwrap();
+        let screen = Rect::new(0, 0, 900, 600);
+        let widths = |tree: &LayoutTree| -> Vec<u32> {
+            tree.calculate_geometries(screen, 0).iter().map(|(_, r)| r.width).collect()
+        };
+        assert_eq!(widths(&tree), vec![450, 225, 225]);
+
+        assert!(tree.balance());
+        assert_eq!(widths(&tree), vec![300, 300, 300]);
+        assert_eq!(tree.get(inner).unwrap().as_split().unwrap().ratio, 0.5);
+        assert!(!tree.balance());
+
+        // A perpendicular split inside a column counts as one column
+        tree.split_focused(SplitDirection::Vertical);
+        assert!(!tree.balance());
+        assert_eq!(widths(&tree), vec![300, 300, 300, 300]);
+    }
+
     #[test]
     fn test_resize_clamps_ratio() {
         let mut tree = LayoutTree::new();
@@ -1487,6 +2519,32 @@ mod tests {
         assert!(!resized);
     }
 
+    #[test]
+    fn test_set_focused_split_pixels() {
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.split_focused(SplitDirection::Vertical);
+
+        let screen = Rect::new(0, 0, 1366, 768);
+        let gap = 8;
+        for pixels in [300, 301, 333, 500] {
+            let first_size = tree.set_focused_split_pixels(screen, gap, pixels).unwrap();
+            assert_eq!(first_size, pixels);
+        }
+
+        // Outer split: first child width matches in the actual geometry
+        let geometries = tree.calculate_geometries(screen, gap);
+        tree.focus_spatial(Direction::Left, &geometries);
+        assert_eq!(tree.set_focused_split_pixels(screen, gap, 300), Some(300));
+        let geometries = tree.calculate_geometries(screen, gap);
+        assert_eq!(geometries[0].1.width, 300);
+
+        // Clamped to the usual ratio limits
+        let clamped = tree.set_focused_split_pixels(screen, gap, 5000).unwrap();
+        assert!(clamped < 1366);
+        assert!(LayoutTree::new().set_focused_split_pixels(screen, gap, 300).is_none());
+    }
+
     // ==================== Frame Operations Tests ====================
 
     #[test]
@@ -1522,6 +2580,68 @@ mod tests {
         assert_eq!(frame.focused_window(), Some(1001));
     }
 
+    #[test]
+    fn test_frame_remove_background_tab_keeps_focus() {
+        let mut frame = Frame::new();
+        frame.add_window(1001);
+        frame.add_window(1002);
+        frame.add_window(1003);
+        assert_eq!(frame.focused, 2);
+
+        // Closing a background tab before the focused one keeps 1003 visible
+        frame.remove_window(1001);
+        assert_eq!(frame.focused, 1);
+        assert_eq!(frame.focused_window(), Some(1003));
+
+        // Same through the tree and the close-focus policy path
+        let mut tree = LayoutTree::new();
+        for window in [1, 2, 3] {
+            tree.add_window(window);
+        }
+        tree.remove_window_with_focus(1, CloseFocus::Prev, &[]);
+        assert_eq!(tree.focused_frame().unwrap().focused_window(), Some(3));
+    }
+
+    #[test]
+    fn test_swap_with_sibling() {
+        let mut tree = LayoutTree::new();
+        let left = tree.focused;
+        tree.add_window(1);
+        let right = tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(2);
+        tree.add_window(3);
+        tree.resize_focused_split(0.1); // make the split asymmetric
+        let ratio = tree.get(tree.root).unwrap().as_split().unwrap().ratio;
+
+        let screen = Rect::new(0, 0, 1000, 500);
+        let before: HashMap<_, _> = tree.calculate_geometries(screen, 0).into_iter().collect();
+
+        assert!(tree.swap_with_sibling());
+
+        // Frames trade positions, keeping their windows; the ratio is untouched,
+        // so the right frame now occupies the left-hand share of the split
+        let split = tree.get(tree.root).unwrap().as_split().unwrap();
+        assert_eq!((split.first, split.second), (right, left));
+        assert_eq!(split.ratio, ratio);
+        assert_eq!(tree.focused, right);
+        assert_eq!(tree.get(right).unwrap().as_frame().unwrap().windows, vec![2, 3]);
+
+        let after: HashMap<_, _> = tree.calculate_geometries(screen, 0).into_iter().collect();
+        assert_eq!(after[&right], before[&left]);
+        assert_eq!(after[&left], before[&right]);
+
+        // Swapping again restores the original arrangement
+        assert!(tree.swap_with_sibling());
+        assert_eq!(tree.calculate_geometries(screen, 0).into_iter().collect::<HashMap<_, _>>(), before);
+    }
+
+    #[test]
+    fn test_swap_with_sibling_root_frame() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        assert!(!tree.swap_with_sibling());
+    }
+
     // ==================== All Windows Tests ====================
 
     #[test]
@@ -1540,6 +2660,19 @@ mod tests {
         assert!(all.contains(&1003));
     }
 
+    #[test]
+    fn test_visible_windows_skips_background_tabs() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1001);
+        tree.add_window(1002);
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(1003);
+        tree.split_focused(SplitDirection::Vertical);
+
+        // 1001 is a background tab, the last frame is empty
+        assert_eq!(tree.visible_windows(), vec![1002, 1003]);
+    }
+
     // ==================== Tab Reorder Tests ====================
 
     #[test]
@@ -1617,6 +2750,35 @@ mod tests {
         assert_eq!(frame.focused_window(), Some(1003));
     }
 
+    #[test]
+    fn test_promote_focused_tab() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1001);
+        tree.add_window(1002);
+        tree.add_window(1003);
+
+        // Focus the middle tab and promote it
+        tree.focus_tab(1);
+        assert_eq!(tree.promote_focused_tab(), Some(1002));
+
+        let frame = tree.focused_frame().unwrap();
+        assert_eq!(frame.windows, vec![1002, 1001, 1003]);
+        assert_eq!(frame.focused_window(), Some(1002));
+    }
+
+    #[test]
+    fn test_promote_first_tab_is_noop() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1001);
+        tree.add_window(1002);
+
+        tree.focus_tab(0);
+        assert_eq!(tree.promote_focused_tab(), None);
+
+        let frame = tree.focused_frame().unwrap();
+        assert_eq!(frame.windows, vec![1001, 1002]);
+    }
+
     // ==================== Move Window to Frame Tests ====================
 
     #[test]
@@ -1671,6 +2833,30 @@ mod tests {
         assert!(!tree.move_window_to_frame(9999, source_frame, target_frame));
     }
 
+    #[test]
+    fn test_pull_window_to_focused() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1001);
+        let source_frame = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        let target_frame = tree.focused;
+        tree.add_window(1002);
+
+        assert!(tree.pull_window_to_focused(1001));
+        assert_eq!(tree.focused, target_frame);
+        let frame = tree.get(target_frame).and_then(|n| n.as_frame()).unwrap();
+        assert_eq!(frame.windows, vec![1002, 1001]);
+        assert_eq!(frame.focused_window(), Some(1001));
+        let source = tree.get(source_frame).and_then(|n| n.as_frame()).unwrap();
+        assert!(source.windows.is_empty());
+
+        // Already in the focused frame, or not in the tree: no-op
+        assert!(!tree.pull_window_to_focused(1002));
+        assert!(!tree.pull_window_to_focused(9999));
+        let frame = tree.get(target_frame).and_then(|n| n.as_frame()).unwrap();
+        assert_eq!(frame.windows, vec![1002, 1001]);
+    }
+
     // ==================== From Config Tests ====================
 
     #[test]
@@ -1802,4 +2988,319 @@ mod tests {
         // Windows should be gone (replaced tree has no windows)
         assert!(frame.windows.is_empty());
     }
+
+    #[test]
+    fn test_to_config_round_trip() {
+        use crate::config::{startup_config_snippet, Config};
+
+        // Build a tree interactively: [editor | [term / browser(vertical tabs)]]
+        let mut tree = LayoutTree::new();
+        tree.set_frame_name(tree.focused, Some("editor".to_string()));
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.set_frame_name(tree.focused, Some("term".to_string()));
+        tree.resize_focused_split(-0.1);
+        tree.split_focused(SplitDirection::Vertical);
+        tree.set_frame_name(tree.focused, Some("browser".to_string()));
+        tree.toggle_vertical_tabs();
+
+        // Export as a TOML snippet and parse it back as a full config
+        let snippet = startup_config_snippet(3, tree.to_config()).unwrap();
+        let config: Config = toml::from_str(&snippet).unwrap();
+        let layout = &config.startup.workspace["3"].layout;
+        let (rebuilt, pending_apps) = LayoutTree::from_config(layout);
+        assert!(pending_apps.is_empty());
+
+        // Same shape: frame names, tab orientation, split directions and ratios
+        let describe = |t: &LayoutTree| -> Vec<(Option<String>, bool)> {
+            t.all_frames().into_iter()
+                .map(|id| {
+                    let f = t.get(id).unwrap().as_frame().unwrap();
+                    (f.name.clone(), f.vertical_tabs)
+                })
+                .collect()
+        };
+        assert_eq!(describe(&rebuilt), describe(&tree));
+
+        let root = rebuilt.get(rebuilt.root).unwrap().as_split().unwrap();
+        assert_eq!(root.direction, SplitDirection::Horizontal);
+        assert!((root.ratio - 0.6).abs() < 0.001);
+        let inner = rebuilt.get(root.second).unwrap().as_split().unwrap();
+        assert_eq!(inner.direction, SplitDirection::Vertical);
+        assert!((inner.ratio - 0.5).abs() < 0.001);
+    }
+
+    // ==================== Focus Repair Tests ====================
+
+    #[test]
+    fn test_add_window_after_all_frames_removed() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(2);
+
+        // Simulate a removal sequence that leaves no nodes at all
+        tree.nodes.clear();
+        assert!(tree.focused_frame().is_none());
+
+        tree.add_window(3);
+        assert_eq!(tree.all_windows(), vec![3]);
+        assert_eq!(tree.focused, tree.root);
+        assert_eq!(tree.focused_frame().unwrap().focused_window(), Some(3));
+        assert!(!tree.ensure_focused_frame());
+    }
+
+    #[test]
+    fn test_stale_focus_falls_back_to_existing_frame() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        let first = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+
+        // Point focus at a node that no longer exists
+        let stale = tree.focused;
+        tree.nodes.remove(stale);
+        tree.focused = stale;
+
+        let new_frame = tree.split_focused(SplitDirection::Vertical);
+        let split = tree.parent(new_frame).unwrap();
+        assert_eq!(tree.parent(first), Some(split));
+        assert_eq!(tree.get(split).unwrap().as_split().unwrap().direction, SplitDirection::Vertical);
+        assert_eq!(tree.all_windows(), vec![1]);
+    }
+
+    // ==================== Vertical Tabs Tests ====================
+
+    #[test]
+    fn test_set_vertical_tabs() {
+        let mut tree = LayoutTree::new();
+        tree.split_focused(SplitDirection::Horizontal);
+        let second = tree.focused;
+
+        assert!(tree.set_vertical_tabs(true));
+        assert!(tree.focused_frame().unwrap().vertical_tabs);
+        // Setting the same value again is a no-op, not a toggle
+        assert!(tree.set_vertical_tabs(true));
+        assert!(tree.focused_frame().unwrap().vertical_tabs);
+
+        // Only the focused frame changes
+        let first = tree.all_frames().into_iter().find(|&id| id != second).unwrap();
+        assert!(!tree.get(first).unwrap().as_frame().unwrap().vertical_tabs);
+
+        assert!(tree.set_vertical_tabs(false));
+        assert!(!tree.focused_frame().unwrap().vertical_tabs);
+    }
+
+    #[test]
+    fn test_toggle_stacked() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        let first = tree.focused;
+        assert_eq!(tree.focused_frame().unwrap().style, FrameStyle::Tabbed);
+
+        assert!(tree.toggle_stacked());
+        assert_eq!(tree.focused_frame().unwrap().style, FrameStyle::Stacked);
+
+        // Only the focused frame changes
+        tree.split_focused(SplitDirection::Horizontal);
+        assert_eq!(tree.focused_frame().unwrap().style, FrameStyle::Tabbed);
+
+        tree.focused = first;
+        assert!(!tree.toggle_stacked());
+        assert_eq!(tree.focused_frame().unwrap().style, FrameStyle::Tabbed);
+    }
+
+    #[test]
+    fn test_flatten_collects_windows_in_traversal_order() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        tree.add_window(2);
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(3);
+        tree.split_focused(SplitDirection::Vertical);
+        tree.add_window(4);
+        tree.add_window(5);
+        // Focus window 4 in the last frame
+        tree.focus_tab(0);
+        assert_eq!(tree.all_frames().len(), 3);
+
+        assert_eq!(tree.flatten(), 2);
+        assert_eq!(tree.all_frames(), vec![tree.root]);
+        assert_eq!(tree.focused, tree.root);
+        let frame = tree.focused_frame().unwrap();
+        assert_eq!(frame.windows, vec![1, 2, 3, 4, 5]);
+        assert_eq!(frame.focused_window(), Some(4));
+
+        // Already flat: nothing to do
+        assert_eq!(tree.flatten(), 0);
+        assert_eq!(tree.focused_frame().unwrap().windows, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_step_index_wraps_at_the_ends() {
+        assert_eq!(step_index(0, 3, true, true), Some(1));
+        assert_eq!(step_index(2, 3, true, true), Some(0));
+        assert_eq!(step_index(0, 3, false, true), Some(2));
+        assert_eq!(step_index(1, 3, false, true), Some(0));
+        assert_eq!(step_index(0, 0, true, true), None);
+    }
+
+    #[test]
+    fn test_step_index_without_wrap_stops_at_the_ends() {
+        assert_eq!(step_index(1, 3, true, false), Some(2));
+        assert_eq!(step_index(2, 3, true, false), None);
+        assert_eq!(step_index(1, 3, false, false), Some(0));
+        assert_eq!(step_index(0, 3, false, false), None);
+        assert_eq!(step_index(0, 1, true, false), None);
+    }
+
+    // ==================== Orphaned Window Tests ====================
+
+    #[test]
+    fn test_remove_non_empty_frame_rehomes_windows() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        let first = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(2);
+        tree.add_window(3);
+        let second = tree.focused;
+
+        assert!(tree.remove_frame_by_id(second));
+        assert_eq!(tree.root, first);
+        let frame = tree.get(first).unwrap().as_frame().unwrap();
+        assert_eq!(frame.windows, vec![1, 2, 3]);
+        assert!(tree.window_violations().is_empty());
+    }
+
+    #[test]
+    fn test_rehomed_windows_go_into_sibling_subtree() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        let first = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        let right_top = tree.focused;
+        tree.add_window(2);
+        tree.split_focused(SplitDirection::Vertical);
+        tree.add_window(3);
+
+        // Removing the left frame moves its window into the right-hand subtree
+        assert!(tree.remove_frame_by_id(first));
+        assert!(tree.get(right_top).unwrap().as_frame().unwrap().windows.contains(&1));
+        let mut windows = tree.all_windows();
+        windows.sort();
+        assert_eq!(windows, vec![1, 2, 3]);
+        assert!(tree.window_violations().is_empty());
+    }
+
+    #[test]
+    fn test_add_window_to_stale_frame_uses_focused_frame() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        let first = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        let second = tree.focused;
+        assert!(tree.remove_frame_by_id(second));
+
+        tree.add_window_to_frame(2, second);
+        assert_eq!(tree.get(first).unwrap().as_frame().unwrap().windows, vec![1, 2]);
+        assert!(tree.window_violations().is_empty());
+    }
+
+    #[test]
+    fn test_move_window_to_stale_frame_keeps_window() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        tree.add_window(2);
+        let first = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        let second = tree.focused;
+        assert!(tree.remove_frame_by_id(second));
+
+        assert!(!tree.move_window_to_frame(1, first, second));
+        assert_eq!(tree.get(first).unwrap().as_frame().unwrap().windows, vec![1, 2]);
+        assert_eq!(tree.focused, first);
+        assert!(tree.window_violations().is_empty());
+
+        // A split node is not a valid target either
+        tree.split_focused(SplitDirection::Vertical);
+        let split = tree.root;
+        assert!(!tree.move_window_to_frame(1, first, split));
+        assert_eq!(tree.find_window(1), Some(first));
+    }
+
+    #[test]
+    fn test_window_violations() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        let first = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(2);
+        assert!(tree.window_violations().is_empty());
+
+        // Duplicate
+        tree.add_window_to_frame(2, first);
+        assert_eq!(tree.window_violations().len(), 1);
+
+        // Window in a frame that isn't reachable from the root
+        let mut detached = Frame::new();
+        detached.add_window(9);
+        tree.nodes.insert(Node::Frame { frame: detached, parent: None });
+        let violations = tree.window_violations();
+        assert_eq!(violations.len(), 2);
+        assert!(violations[1].contains("0x9"));
+    }
+
+    // ==================== Normalize Tests ====================
+
+    #[test]
+    fn test_normalize_collapses_empty_sides() {
+        // [1 | [empty / [empty | 2]]] with focus on 2
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        let first = tree.focused;
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.split_focused(SplitDirection::Vertical);
+        tree.split_focused(SplitDirection::Horizontal);
+        tree.add_window(2);
+        let last = tree.focused;
+        assert_eq!(tree.all_frames().len(), 4);
+
+        assert_eq!(tree.normalize(), 2);
+
+        // Minimal shape: a single split of the two non-empty frames
+        let split = tree.get(tree.root).unwrap().as_split().unwrap();
+        assert_eq!(split.direction, SplitDirection::Horizontal);
+        assert_eq!((split.first, split.second), (first, last));
+        assert_eq!(tree.parent(last), Some(tree.root));
+        assert_eq!(tree.focused, last);
+        assert_eq!(tree.focused_frame().unwrap().focused_window(), Some(2));
+        assert!(tree.window_violations().is_empty());
+
+        // Already minimal
+        assert_eq!(tree.normalize(), 0);
+    }
+
+    #[test]
+    fn test_normalize_keeps_focused_named_and_locked_frames() {
+        let mut tree = LayoutTree::new();
+        tree.add_window(1);
+        tree.split_focused(SplitDirection::Horizontal);
+        let named = tree.focused;
+        tree.set_frame_name(named, Some("editor".to_string()));
+        tree.split_focused(SplitDirection::Vertical);
+        let locked = tree.focused;
+        tree.toggle_frame_lock();
+        tree.split_focused(SplitDirection::Horizontal);
+        let focused = tree.focused;
+
+        assert_eq!(tree.normalize(), 0);
+        assert_eq!(tree.all_frames().len(), 4);
+        assert!(tree.all_frames().contains(&named));
+        assert!(tree.all_frames().contains(&locked));
+        assert_eq!(tree.focused, focused);
+
+        // A single empty root frame is left alone
+        let mut tree = LayoutTree::new();
+        assert_eq!(tree.normalize(), 0);
+    }
 }
@@ -4,47 +4,68 @@
 //! Milestone 5: Tabs with tab bar rendering.
 //! Milestone 6: IPC interface for debugability and scriptability.
 
+mod animation;
+mod bar;
+mod barriers;
+mod children;
 mod config;
+mod drag_feedback;
 mod event;
 mod ewmh;
+mod help;
 mod icon;
 mod ipc;
 mod ipc_handler;
+mod keysyms;
+mod launcher;
 mod layout;
+mod marks;
+#[cfg(feature = "mirror")]
+mod mirror;
 mod monitor;
+mod profile;
 mod render;
+mod session;
 mod startup;
 mod state;
 mod tab_bar;
 mod tracing;
 mod types;
 mod urgent;
+mod wallpaper;
 mod window_query;
+mod workspace_indicator;
 mod workspaces;
 
 pub use event::{DragState, ResizeEdge};
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Command;
 
 use anyhow::{Context, Result};
+use clap::Parser;
 use x11rb::connection::Connection;
+use x11rb::protocol::screensaver::ConnectionExt as _;
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 use x11rb::wrapper::ConnectionExt as _;
 
-use config::{parse_color, Config, ParsedBinding, WmAction};
+use config::{parse_modifiers, CloseFocus, Config, CycleMonitorScope, CycleScope, ExitBehavior, IdleAction, OpenMode, OverrideRedirectFlip, ParsedBinding, ResizeGravity, SpawnAnimation, TabBarSize, TabSizing, WmAction};
+use drag_feedback::DropIndicator;
 use ewmh::Atoms;
 use ipc::IpcServer;
-use layout::{Direction, NodeId, Rect, SplitDirection};
+use layout::{Direction, FrameStyle, NodeId, Rect, SplitDirection};
 use monitor::{MonitorId, MonitorManager};
-use workspaces::{WorkspaceManager, NUM_WORKSPACES};
+use workspaces::{MoveOrigin, WorkspaceManager, NUM_WORKSPACES};
 use render::{CachedIcon, FontRenderer, blend_icon_with_background, lighten_color, darken_color};
 use state::{StateTransition, UnmanageReason};
 use tab_bar::TabBarManager;
 use tracing::EventTracer;
 use types::StrutPartial;
 use urgent::UrgentManager;
+use marks::{MarkPrompt, Marks};
+use launcher::{Launcher, LauncherKey, LauncherOutcome};
 
 // Re-export LayoutConfig from config module
 use config::LayoutConfig;
@@ -59,7 +80,8 @@ struct Wm {
     monitors: MonitorManager,
     /// Currently focused window (if any)
     focused_window: Option<Window>,
-    /// WM check window for EWMH
+    /// WM check window for EWMH; holds the input focus while the focused
+    /// window has input=False
     check_window: Window,
     /// Layout configuration
     config: LayoutConfig,
@@ -77,6 +99,15 @@ struct Wm {
     keybindings: HashMap<WmAction, ParsedBinding>,
     /// Current drag operation (if any)
     drag_state: Option<DragState>,
+    /// Last left-click on a tab (window, server time) for double-click detection
+    last_tab_click: Option<(Window, Timestamp)>,
+    /// Overlay highlighting the drop target during tab drags
+    drop_indicator: DropIndicator,
+    /// View-only window mirrors (Composite/Damage)
+    #[cfg(feature = "mirror")]
+    mirrors: mirror::MirrorManager,
+    /// Pointer barriers between monitors (general.pointer_barriers)
+    barriers: Option<barriers::PointerBarriers>,
     /// Horizontal resize cursor
     cursor_resize_h: Cursor,
     /// Vertical resize cursor
@@ -97,21 +128,73 @@ struct Wm {
     tagged_windows: std::collections::HashSet<Window>,
     /// Suppress EnterNotify focus changes (set after explicit focus operations)
     suppress_enter_focus: bool,
+    /// Focus windows on EnterNotify (toggled at runtime via IPC)
+    focus_follows_mouse: bool,
+    /// Presentation mode: only the presentation toggle keybinding is grabbed
+    presentation_mode: bool,
+    /// Managed windows in focus order (most recently focused last)
+    focus_history: Vec<Window>,
+    /// Window marks (single-character bookmarks)
+    marks: Marks,
+    /// Set while the keyboard is grabbed waiting for a mark key
+    mark_prompt: Option<MarkPrompt>,
+    /// Built-in application launcher (its window is created on first use)
+    launcher: Launcher,
+    /// Keybinding cheat sheet (its window is created on first use)
+    help: help::HelpOverlay,
+    /// Workspace number shown briefly after a switch (general.workspace_indicator_ms)
+    workspace_indicator: workspace_indicator::WorkspaceIndicator,
+    /// Built-in status bar windows (only created when `[bar] enabled`)
+    status_bar: bar::StatusBar,
+    /// Windows that unmapped themselves, with the time they get unmanaged
+    /// unless they map again first (see general.unmap_grace_ms)
+    pending_unmanage: HashMap<Window, std::time::Instant>,
+    /// Windows asked to close, keyed by close request; the first of a request
+    /// to actually go pushes the undo entry for all of it
+    close_requested: HashMap<Window, u32>,
+    /// Id for the next entry in `close_requested`
+    next_close_request: u32,
+    /// Tiled windows whose first placement should be animated
+    spawn_pending: std::collections::HashSet<Window>,
+    /// Running spawn animations (see appearance.spawn_animation)
+    animator: animation::Animator,
+    /// Floating windows that follow their monitor across workspace switches
+    pinned_floats: std::collections::HashSet<Window>,
+    /// Action run after general.idle_timeout_s without input
+    idle_action: Option<IdleAction>,
+    /// Time of the last key/button/pointer event
+    last_input: std::time::Instant,
+    /// When the server's idle time (MIT-SCREEN-SAVER) was last queried
+    idle_polled: std::time::Instant,
+    /// Server timestamp of the latest event that carried one (for WM_TAKE_FOCUS)
+    last_event_time: Timestamp,
+    /// Set once the idle action has run, until input arrives again
+    idle_fired: bool,
+    /// Pixmap currently installed as the root background by set_wallpaper()
+    wallpaper_pixmap: Option<Pixmap>,
     /// Skip tab bar redraw in focus_window() when apply_layout() just did it
     skip_focus_tab_bar_redraw: bool,
     /// Urgent window manager (tracks urgent windows and indicator)
     urgent: UrgentManager,
     /// Dock windows (polybar, etc.) and their strut reservations
     dock_windows: HashMap<Window, StrutPartial>,
+    /// Processes spawned by exec bindings, the launcher and startup apps
+    children: children::ChildTracker,
+    /// Render timings (only collected with `--profile`)
+    render_stats: profile::RenderStats,
     /// Startup manager for initial layout and app spawning
     startup_manager: startup::StartupManager,
     /// User configuration (kept for startup config reference)
     user_config: Config,
+    /// Config file in use (re-read by set_theme)
+    config_path: PathBuf,
+    /// HiDPI multiplier resolved from appearance.scale at startup
+    scale: f64,
 }
 
 impl Wm {
     /// Connect to X11 and set up the window manager
-    fn new() -> Result<Self> {
+    fn new(config_path: Option<PathBuf>, profile: bool) -> Result<Self> {
         // Connect to X11 server
         let (conn, screen_num) = RustConnection::connect(None)
             .context("Failed to connect to X11 server")?;
@@ -131,7 +214,9 @@ impl Wm {
         // Create atoms for EWMH
         let atoms = Atoms::new(&conn)?;
 
-        // Create a small check window for EWMH _NET_SUPPORTING_WM_CHECK
+        // Create a small check window for EWMH _NET_SUPPORTING_WM_CHECK.
+        // It is mapped off-screen so it can also hold the input focus while
+        // a window that takes no input is focused
         let check_window = conn.generate_id()?;
         conn.create_window(
             0, // depth (copy from parent)
@@ -140,8 +225,10 @@ impl Wm {
             -1, -1, 1, 1, 0, // x, y, w, h, border
             WindowClass::INPUT_ONLY,
             0, // visual (copy from parent)
-            &CreateWindowAux::new(),
+            &CreateWindowAux::new().override_redirect(1),
         )?;
+        ewmh::mark_internal(&conn, &atoms, check_window)?;
+        conn.map_window(check_window)?;
 
         // Create graphics context for drawing tab bars
         let gc = conn.generate_id()?;
@@ -163,34 +250,88 @@ impl Wm {
         };
 
         // Load user configuration
-        let user_config = Config::load();
+        let config_path = Config::resolve_path(config_path);
+        let mut user_config = Config::load_from_path(config_path.clone());
+        if !(1..=3).contains(&user_config.general.resize_button) {
+            log::warn!("Invalid resize_button {}, using 1", user_config.general.resize_button);
+            user_config.general.resize_button = 1;
+        }
         let keybindings = user_config.parse_keybindings();
 
+        // Scale pixel sizes for HiDPI before anything is derived from them
+        let scale = user_config.appearance.scale.resolve(|| window_query::read_dpi(&conn, screen));
+        if scale != 1.0 {
+            log::info!("Scaling gaps, borders, tab bars, the bar and fonts by {:.2}", scale);
+            user_config.apply_scale(scale);
+        }
+
         // Initialize font renderer
         let font_renderer = FontRenderer::new(
             &user_config.appearance.tab_font,
             user_config.appearance.tab_font_size,
         ).context("Failed to initialize font renderer")?;
+        let mut tab_bars = TabBarManager::new(font_renderer, gc, screen_depth, user_config.appearance.icon_cache_size);
+        tab_bars.load_monitor_fonts(&user_config.appearance.tab_font, user_config.monitor_font_sizes(scale));
+
+        // Resolve "auto" tab bar sizes from the fonts and icon size. Per-monitor
+        // fonts can be taller than the global one, so the tallest sets the height
+        let tab_icon_size = user_config.appearance.tab_icon_size.max(1);
+        let tab_bar_height = user_config.appearance.tab_bar_height.resolve(tab_bar::auto_tab_bar_height(
+            tab_bars.max_char_height(),
+            tab_icon_size,
+            user_config.appearance.show_tab_icons,
+        ));
+        let vertical_tab_width = user_config.appearance.vertical_tab_width
+            .resolve(tab_bar::auto_vertical_tab_width(tab_icon_size));
+        if user_config.appearance.tab_bar_height == TabBarSize::Auto {
+            log::info!("Auto tab bar height: {}px", tab_bar_height);
+        }
+        if user_config.appearance.vertical_tab_width == TabBarSize::Auto {
+            log::info!("Auto vertical tab width: {}px", vertical_tab_width);
+        }
 
         // Build LayoutConfig from user config
-        let config = LayoutConfig {
-            gap: user_config.appearance.gap,
-            outer_gap: user_config.appearance.outer_gap,
-            border_width: user_config.appearance.border_width,
-            tab_bar_height: user_config.appearance.tab_bar_height,
-            vertical_tab_width: user_config.appearance.vertical_tab_width,
-            tab_bar_bg: parse_color(&user_config.colors.tab_bar_bg).unwrap_or(0x2e2e2e),
-            tab_focused_bg: parse_color(&user_config.colors.tab_focused_bg).unwrap_or(0x5294e2),
-            tab_unfocused_bg: parse_color(&user_config.colors.tab_unfocused_bg).unwrap_or(0x3a3a3a),
-            tab_visible_unfocused_bg: parse_color(&user_config.colors.tab_visible_unfocused_bg).unwrap_or(0x4a6a9a),
-            tab_tagged_bg: parse_color(&user_config.colors.tab_tagged_bg).unwrap_or(0xe06c75),
-            tab_urgent_bg: parse_color(&user_config.colors.tab_urgent_bg).unwrap_or(0xd19a66),
-            tab_text_color: parse_color(&user_config.colors.tab_text).unwrap_or(0xffffff),
-            tab_text_unfocused: parse_color(&user_config.colors.tab_text_unfocused).unwrap_or(0x888888),
-            tab_separator: parse_color(&user_config.colors.tab_separator).unwrap_or(0x4a4a4a),
-            border_focused: parse_color(&user_config.colors.border_focused).unwrap_or(0x5294e2),
-            border_unfocused: parse_color(&user_config.colors.border_unfocused).unwrap_or(0x3a3a3a),
+        let mut config = LayoutConfig {
+            tab_bar_height,
+            vertical_tab_width,
+            min_frame_size: user_config.appearance.min_frame_size,
+            workspace_border_focused: user_config.workspace_border_colors(),
+            workspace_gap: user_config.workspace_gaps(),
             show_tab_icons: user_config.appearance.show_tab_icons,
+            tab_compact: user_config.appearance.tab_compact,
+            tab_icon_size,
+            tab_icon_padding: user_config.appearance.tab_icon_padding,
+            tab_label: user_config.appearance.tab_label,
+            tab_label_by_class: user_config.appearance.tab_label_by_class.clone(),
+            tab_sizing: user_config.appearance.tab_sizing,
+            fixed_tab_width: user_config.appearance.fixed_tab_width.max(1),
+            middle_click_close_mask: parse_modifiers(&user_config.appearance.middle_click_close_modifier)
+                .unwrap_or_else(|| {
+                    log::warn!(
+                        "Invalid middle_click_close_modifier '{}', using Control",
+                        user_config.appearance.middle_click_close_modifier
+                    );
+                    4
+                }),
+            tab_modifier_actions: user_config.appearance.tab_modifier_masks(),
+            drag_feedback: user_config.appearance.drag_feedback,
+            drag_threshold: user_config.appearance.drag_threshold,
+            spawn_animation: user_config.appearance.spawn_animation,
+            spawn_animation_ms: user_config.appearance.spawn_animation_ms,
+            resize_gravity: user_config.appearance.resize_gravity,
+            ..LayoutConfig::default()
+        };
+        // Colors, gaps, borders and corner radius, as a theme switch redoes them
+        config.apply_theme(&user_config);
+
+        let idle_action = if user_config.general.idle_timeout_s > 0 {
+            let action = IdleAction::parse(&user_config.general.idle_action);
+            if action.is_none() {
+                log::warn!("Invalid idle_action '{}', idle timeout disabled", user_config.general.idle_action);
+            }
+            action
+        } else {
+            None
         };
 
         // Create resize cursors from the cursor font
@@ -298,6 +439,12 @@ impl Wm {
         monitors.refresh(&conn, root)?;
         log::info!("Initialized {} monitor(s)", monitors.count());
 
+        #[cfg(feature = "mirror")]
+        let mirrors = mirror::MirrorManager::new(&conn);
+
+        let barriers = user_config.general.pointer_barriers
+            .then(|| barriers::PointerBarriers::new(&conn, root, user_config.general.pointer_barrier_push));
+
         Ok(Self {
             conn,
             screen_num,
@@ -307,13 +454,18 @@ impl Wm {
             focused_window: None,
             check_window,
             config,
-            tab_bars: TabBarManager::new(font_renderer, gc, screen_depth),
+            tab_bars,
             hidden_windows: std::collections::HashSet::new(),
             running: true,
             ipc,
             tracer: EventTracer::new(),
             keybindings,
             drag_state: None,
+            last_tab_click: None,
+            drop_indicator: DropIndicator::new(),
+            #[cfg(feature = "mirror")]
+            mirrors,
+            barriers,
             cursor_resize_h,
             cursor_resize_v,
             cursor_default,
@@ -324,11 +476,36 @@ impl Wm {
             current_cursor: cursor_default,
             tagged_windows: std::collections::HashSet::new(),
             suppress_enter_focus: false,
+            focus_follows_mouse: true,
+            presentation_mode: false,
+            focus_history: Vec::new(),
+            marks: Marks::new(),
+            launcher: Launcher::new(),
+            help: help::HelpOverlay::new(),
+            workspace_indicator: workspace_indicator::WorkspaceIndicator::new(),
+            status_bar: bar::StatusBar::new(),
+            mark_prompt: None,
+            pending_unmanage: HashMap::new(),
+            close_requested: HashMap::new(),
+            next_close_request: 0,
+            spawn_pending: std::collections::HashSet::new(),
+            animator: animation::Animator::new(),
+            pinned_floats: std::collections::HashSet::new(),
+            idle_action,
+            last_input: std::time::Instant::now(),
+            idle_polled: std::time::Instant::now(),
+            last_event_time: x11rb::CURRENT_TIME,
+            idle_fired: false,
+            wallpaper_pixmap: None,
             skip_focus_tab_bar_redraw: false,
             urgent: UrgentManager::new(),
             dock_windows: HashMap::new(),
+            children: children::ChildTracker::new(),
+            render_stats: profile::RenderStats::new(profile),
             startup_manager: startup::StartupManager::new(),
             user_config,
+            config_path,
+            scale,
         })
     }
 
@@ -360,6 +537,15 @@ impl Wm {
         None
     }
 
+    /// Find the monitor and workspace holding a managed window (tiled or floating)
+    fn find_window_global(&self, window: Window) -> Option<(MonitorId, usize)> {
+        self.monitors.iter().find_map(|(monitor_id, monitor)| {
+            monitor.workspaces.workspaces.iter()
+                .position(|ws| ws.is_floating(window) || ws.layout.find_window(window).is_some())
+                .map(|ws_idx| (monitor_id, ws_idx))
+        })
+    }
+
     /// Get the appropriate cursor for a resize edge
     fn cursor_for_edge(&self, edge: ResizeEdge) -> Cursor {
         match edge {
@@ -375,7 +561,7 @@ impl Wm {
     /// Update cursor based on what's under the mouse (for hover feedback)
     fn update_hover_cursor(&mut self, x: i32, y: i32) -> Result<()> {
         let screen = self.usable_screen();
-        let gap = self.config.gap;
+        let gap = self.gap();
 
         // Check if over a split gap
         let new_cursor = if let Some((_, direction, _, _)) =
@@ -444,6 +630,7 @@ impl Wm {
             self.atoms.net_current_desktop,
             self.atoms.net_number_of_desktops,
             self.atoms.net_desktop_names,
+            self.atoms.net_desktop_viewport,
             self.atoms.net_wm_desktop,
         ];
         self.conn.change_property32(
@@ -516,13 +703,17 @@ impl Wm {
             names.as_bytes(),
         )?;
 
+        // Set _NET_DESKTOP_VIEWPORT
+        ewmh::update_desktop_viewport(&self.conn, &self.atoms, self.root, NUM_WORKSPACES)?;
+
         self.conn.flush()?;
         log::info!("EWMH properties set up");
         Ok(())
     }
 
-    /// Update _NET_CURRENT_DESKTOP
+    /// Update _NET_CURRENT_DESKTOP (and _NET_DESKTOP_VIEWPORT alongside it)
     fn update_current_desktop(&self) -> Result<()> {
+        ewmh::update_desktop_viewport(&self.conn, &self.atoms, self.root, NUM_WORKSPACES)?;
         ewmh::update_current_desktop(
             &self.conn,
             &self.atoms,
@@ -536,17 +727,23 @@ impl Wm {
         ewmh::set_window_desktop(&self.conn, &self.atoms, window, desktop)
     }
 
-    /// Switch to the next workspace
+    /// Switch to the next workspace (general.workspace_wrap/workspace_count)
     fn workspace_next(&mut self) -> Result<()> {
-        let old_idx = self.workspaces_mut().next();
-        self.perform_workspace_switch(old_idx)?;
+        let general = &self.user_config.general;
+        let (count, wrap) = (general.workspace_count, general.workspace_wrap);
+        if let Some(old_idx) = self.workspaces_mut().next(count, wrap) {
+            self.perform_workspace_switch(old_idx)?;
+        }
         Ok(())
     }
 
-    /// Switch to the previous workspace
+    /// Switch to the previous workspace (general.workspace_wrap/workspace_count)
     fn workspace_prev(&mut self) -> Result<()> {
-        let old_idx = self.workspaces_mut().prev();
-        self.perform_workspace_switch(old_idx)?;
+        let general = &self.user_config.general;
+        let (count, wrap) = (general.workspace_count, general.workspace_wrap);
+        if let Some(old_idx) = self.workspaces_mut().prev(count, wrap) {
+            self.perform_workspace_switch(old_idx)?;
+        }
         Ok(())
     }
 
@@ -605,6 +802,7 @@ impl Wm {
                     self.set_window_desktop(window, current_ws)?;
                     last_moved = Some(window);
                 }
+                self.auto_normalize(source_ws);
             }
         }
 
@@ -635,15 +833,24 @@ impl Wm {
         let new_idx = self.workspaces().current_index();
         log::info!("Switching from workspace {} to workspace {}", old_idx + 1, new_idx + 1);
 
-        // Save current workspace's focused window
-        self.monitors.focused_mut().workspaces.workspaces[old_idx].last_focused_window = self.focused_window;
-
         // Hide all tiled windows from old workspace
         for window in self.monitors.focused_mut().workspaces.workspaces[old_idx].layout.all_windows() {
             self.hidden_windows.insert(window);
             self.conn.unmap_window(window)?;
         }
 
+        // Pinned floats move along to the new workspace and stay mapped
+        let pinned: Vec<Window> = self.monitors.focused().workspaces.workspaces[old_idx].floating_window_ids()
+            .into_iter()
+            .filter(|w| self.pinned_floats.contains(w))
+            .collect();
+        for window in pinned {
+            let workspaces = &mut self.monitors.focused_mut().workspaces.workspaces;
+            if let Some(floating) = workspaces[old_idx].remove_floating(window) {
+                workspaces[new_idx].floating_windows.push(floating);
+            }
+        }
+
         // Hide all floating windows from old workspace
         for floating in &self.monitors.focused_mut().workspaces.workspaces[old_idx].floating_windows {
             self.hidden_windows.insert(floating.window);
@@ -679,17 +886,15 @@ impl Wm {
         // Clear focused window (will be restored below)
         self.focused_window = None;
 
+        // Select the window focused when this workspace was last visible, so
+        // apply_layout maps its tab
+        let restored = self.workspaces_mut().restore_focus();
+
         // Apply layout for new workspace (handles both tiled and floating)
         self.apply_layout()?;
 
-        // Restore focus to last focused window in new workspace
-        if let Some(w) = self.workspaces().current().last_focused_window {
-            // Check if window exists (either tiled or floating)
-            let is_tiled = self.workspaces().current().layout.find_window(w).is_some();
-            let is_floating = self.workspaces().current().is_floating(w);
-            if is_tiled || is_floating {
-                self.focus_window(w)?;
-            }
+        if let Some(w) = restored {
+            self.focus_window(w)?;
         }
 
         // If no focus restored, try to focus something
@@ -703,6 +908,22 @@ impl Wm {
         // Update urgent indicator (may need to show/hide based on new workspace)
         self.update_urgent_indicator()?;
 
+        let indicator_ms = self.user_config.general.workspace_indicator_ms;
+        if indicator_ms > 0 {
+            let monitor = self.monitors.focused().geometry;
+            self.workspace_indicator.show(
+                &self.conn,
+                self.root,
+                self.atoms.net_wm_window_opacity,
+                monitor,
+                format!("Workspace {}", new_idx + 1),
+                std::time::Duration::from_millis(indicator_ms),
+                &self.tab_bars.font_renderer,
+                self.tab_bars.gc,
+                &self.config,
+            )?;
+        }
+
         self.conn.flush()?;
         Ok(())
     }
@@ -721,7 +942,54 @@ impl Wm {
 
     /// Update _NET_ACTIVE_WINDOW
     fn update_active_window(&self) -> Result<()> {
-        ewmh::update_active_window(&self.conn, &self.atoms, self.root, self.focused_window)
+        ewmh::update_active_window(&self.conn, &self.atoms, self.root, self.focused_window)?;
+        self.update_bar()
+    }
+
+    /// Create the built-in bar on every monitor if `[bar] enabled` is set
+    fn setup_bar(&mut self) -> Result<()> {
+        let bar = &self.user_config.bar;
+        if !bar.enabled {
+            return Ok(());
+        }
+        let rects: Vec<(MonitorId, Rect)> = self.monitors.iter()
+            .map(|(id, monitor)| (id, bar::bar_rect(monitor.geometry, bar.position, bar.height)))
+            .collect();
+        for (id, rect) in rects {
+            let window = self.status_bar.create(&self.conn, self.root, id, rect, &self.config)?;
+            ewmh::mark_internal(&self.conn, &self.atoms, window)?;
+        }
+        log::info!("Built-in bar enabled ({:?}, {}px)", bar.position, bar.height);
+        self.update_bar()
+    }
+
+    /// Redraw the built-in bar on every monitor: workspace indicators and the
+    /// focused window's title (no-op when the bar is disabled)
+    fn update_bar(&self) -> Result<()> {
+        if !self.user_config.bar.enabled {
+            return Ok(());
+        }
+        let focused_monitor = self.monitors.focused_id();
+        for (id, monitor) in self.monitors.iter() {
+            let workspaces = &monitor.workspaces;
+            let window = if id == focused_monitor {
+                self.focused_window
+            } else {
+                workspaces.current().layout.focused_frame().and_then(|f| f.focused_window())
+            };
+            let content = bar::BarContent {
+                current: workspaces.current_index(),
+                occupied: std::array::from_fn(|i| {
+                    let ws = &workspaces.workspaces[i];
+                    !ws.layout.all_windows().is_empty() || !ws.floating_windows.is_empty()
+                }),
+                title: window
+                    .map(|w| window_query::get_window_title(&self.conn, &self.atoms, w))
+                    .unwrap_or_default(),
+            };
+            self.status_bar.draw(&self.conn, id, &content, &self.tab_bars.font_renderer, self.tab_bars.gc, &self.config)?;
+        }
+        Ok(())
     }
 
     /// Get the usable screen area for the focused monitor (with outer gaps)
@@ -732,13 +1000,15 @@ impl Wm {
     /// Get the usable area for a specific monitor (with outer gaps and struts)
     fn usable_area(&self, monitor_id: MonitorId) -> Rect {
         let gap = self.config.outer_gap;
-        let base = if let Some(monitor) = self.monitors.get(monitor_id) {
-            monitor.geometry
+        let base = if let Some(geometry) = self.monitors.bezel_adjusted(monitor_id, self.user_config.general.bezel_gap) {
+            geometry
         } else {
             // Fallback to full screen if monitor not found
             let screen = self.screen();
             Rect::new(0, 0, screen.width_in_pixels as u32, screen.height_in_pixels as u32)
         };
+        let bar = &self.user_config.bar;
+        let base = if bar.enabled { bar::reserve(base, bar.position, bar.height) } else { base };
 
         // Aggregate struts from all dock windows (take max of each edge)
         let (strut_left, strut_right, strut_top, strut_bottom) =
@@ -764,7 +1034,34 @@ impl Wm {
         let mon_id = self.monitors.focused_id();
         let ws_idx = self.workspaces().current_index();
         let key = (mon_id, ws_idx, frame_id);
-        self.tab_bars.get_or_create_window(&self.conn, self.root, &self.config, key, rect, vertical)
+        let is_new = !self.tab_bars.windows.contains_key(&key);
+        let size = self.tab_bar_size(frame_id, rect, vertical);
+        let window = self.tab_bars.get_or_create_window(&self.conn, self.root, &self.config, key, rect, size)?;
+        if is_new {
+            ewmh::mark_internal(&self.conn, &self.atoms, window)?;
+        }
+        Ok(window)
+    }
+
+    /// Whether a frame on the current workspace shows stacked titles
+    fn frame_is_stacked(&self, frame_id: NodeId) -> bool {
+        self.workspaces().current().layout.get(frame_id)
+            .and_then(|n| n.as_frame())
+            .is_some_and(|f| f.style == FrameStyle::Stacked)
+    }
+
+    /// Size of a frame's tab bar: a column for vertical tabs, one row per
+    /// window for stacked frames, otherwise a single row
+    fn tab_bar_size(&self, frame_id: NodeId, rect: &Rect, vertical: bool) -> (u32, u32) {
+        let stacked_tabs = self.workspaces().current().layout.get(frame_id)
+            .and_then(|n| n.as_frame())
+            .filter(|f| f.style == FrameStyle::Stacked)
+            .map(|f| f.windows.len());
+        match stacked_tabs {
+            Some(tabs) => (rect.width, tab_bar::stacked_bar_height(self.config.tab_bar_height, tabs, rect.height)),
+            None if vertical => (self.config.vertical_tab_width, rect.height),
+            None => (rect.width, self.config.tab_bar_height),
+        }
     }
 
     /// Get or create a pixmap buffer for double-buffered tab bar rendering
@@ -777,7 +1074,13 @@ impl Wm {
         let mon_id = self.monitors.focused_id();
         let ws_idx = self.workspaces().current_index();
         let key = (mon_id, ws_idx, frame_id);
-        self.tab_bars.get_or_create_empty_frame(&self.conn, self.root, &self.config, key, rect, is_focused)
+        let is_new = !self.tab_bars.empty_frame_windows.contains_key(&key);
+        let background = self.frame_color(frame_id).unwrap_or(self.config.empty_frame_bg);
+        let window = self.tab_bars.get_or_create_empty_frame(&self.conn, self.root, &self.config, key, rect, is_focused, background)?;
+        if is_new {
+            ewmh::mark_internal(&self.conn, &self.atoms, window)?;
+        }
+        Ok(window)
     }
 
     /// Destroy an empty frame placeholder window if it exists
@@ -796,20 +1099,69 @@ impl Wm {
         self.tab_bars.cleanup_empty_frames(&self.conn, mon_id, ws_idx, &valid_frames);
     }
 
-    /// Calculate tab widths based on window titles (Chrome-style content-based sizing)
+    /// Calculate tab positions using the frame's tab sizing (or the global default)
     /// Returns a vector of (x_position, width) for each tab
     fn calculate_tab_layout(&self, frame_id: NodeId) -> Vec<(i16, u32)> {
-        let frame = match self.workspaces().current().layout.get(frame_id).and_then(|n| n.as_frame()) {
+        let layout = &self.workspaces().current().layout;
+        let frame = match layout.get(frame_id).and_then(|n| n.as_frame()) {
             Some(f) => f,
             None => return Vec::new(),
         };
-        self.tab_bars.calculate_tab_layout(&self.conn, &self.atoms, &self.config, &frame.windows)
+        let sizing = frame.tab_sizing.unwrap_or(self.config.tab_sizing);
+        let bar_width = if sizing == TabSizing::Equal {
+            layout.calculate_geometries(self.usable_screen(), self.gap())
+                .into_iter()
+                .find(|(id, _)| *id == frame_id)
+                .map(|(_, rect)| rect.width)
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let monitor = &self.monitors.focused().name;
+        self.tab_bars.calculate_tab_layout(&self.conn, &self.atoms, &self.config, monitor, &frame.windows, sizing, bar_width)
+    }
+
+    /// Per-frame background tint on the current workspace, if one is set
+    fn frame_color(&self, frame_id: NodeId) -> Option<u32> {
+        self.workspaces().current().layout.get(frame_id)
+            .and_then(|n| n.as_frame())
+            .and_then(|f| f.color)
+    }
+
+    /// Tint a named frame on any monitor/workspace (`None` clears the tint).
+    fn set_frame_color(&mut self, name: &str, color: Option<u32>) -> Result<()> {
+        let (monitor_id, ws_idx, node_id) = self.find_frame_by_name_global(name)
+            .with_context(|| format!("No frame named '{}'", name))?;
+        let frame = self.monitors.get_mut(monitor_id)
+            .and_then(|m| m.workspaces.workspaces.get_mut(ws_idx))
+            .and_then(|ws| ws.layout.get_mut(node_id))
+            .and_then(|n| n.as_frame_mut())
+            .context("Frame disappeared")?;
+        frame.color = color;
+        log::info!("Frame '{}' color set to {:?}", name, color.map(|c| format!("#{:06x}", c)));
+        self.apply_layout()?;
+        Ok(())
+    }
+
+    /// Override tab sizing for the focused frame (`None` reverts to the global default)
+    fn set_frame_tab_sizing(&mut self, sizing: Option<TabSizing>) -> Result<()> {
+        let frame_id = self.workspaces().current().layout.focused;
+        let frame = self.workspaces_mut().current_mut().layout.get_mut(frame_id)
+            .and_then(|n| n.as_frame_mut())
+            .context("Focused node is not a frame")?;
+        frame.tab_sizing = sizing;
+        self.apply_layout()?;
+        log::info!("Tab sizing for frame {:?} set to {:?}", frame_id, sizing);
+        Ok(())
     }
 
     /// Sample the root window background at the given position
     /// Returns the pixel data that can be drawn with put_image
-    fn sample_root_background(&self, x: i16, y: i16, width: u16, height: u16) -> Option<Vec<u8>> {
-        TabBarManager::sample_root_background(&self.conn, self.root, x, y, width, height)
+    fn sample_root_background(&mut self, x: i16, y: i16, width: u16, height: u16) -> Option<Vec<u8>> {
+        let started = self.render_stats.start();
+        let pixels = TabBarManager::sample_root_background(&self.conn, self.root, x, y, width, height);
+        self.render_stats.record(profile::Stat::BackgroundSample, started);
+        pixels
     }
 
     /// Draw the pseudo-transparent background for a tab bar (horizontal or vertical).
@@ -859,7 +1211,11 @@ impl Wm {
     ) -> Result<()> {
         let width = tab_size;
         let height = tab_size;
-        let corner_radius: u32 = 4; // Smaller radius for vertical tabs (vs 6px for horizontal)
+        let corner_radius = tab_bar::clamp_corner_radius(
+            tab_bar::vertical_corner_radius(self.config.tab_corner_radius),
+            width,
+            height,
+        );
 
         // Determine background color (same priority as horizontal)
         let is_urgent = self.urgent.contains(client_window);
@@ -939,18 +1295,18 @@ impl Wm {
         }
 
         // Draw icon centered in tab
-        const ICON_SIZE: u32 = 20;
+        let icon_size = self.config.tab_icon_size;
         let icon = self.get_window_icon(client_window);
-        let blended = blend_icon_with_background(&icon.pixels, bg_color, ICON_SIZE);
-        let icon_x = ((width - ICON_SIZE) / 2) as i16;
-        let icon_y = y + ((height - ICON_SIZE) / 2) as i16;
+        let blended = blend_icon_with_background(&icon.pixels, bg_color, icon_size);
+        let icon_x = (width.saturating_sub(icon_size) / 2) as i16;
+        let icon_y = y + (height.saturating_sub(icon_size) / 2) as i16;
 
         self.conn.put_image(
             ImageFormat::Z_PIXMAP,
             window,
             self.tab_bars.gc,
-            ICON_SIZE as u16,
-            ICON_SIZE as u16,
+            icon_size as u16,
+            icon_size as u16,
             icon_x,
             icon_y,
             0,
@@ -967,6 +1323,7 @@ impl Wm {
         &mut self,
         window: Window,
         x: i16,
+        y: i16,
         tab_width: u32,
         client_window: Window,
         is_focused: bool,
@@ -974,12 +1331,13 @@ impl Wm {
         is_tagged: bool,
         is_focused_frame: bool,
         show_icons: bool,
+        compact: bool,
     ) -> Result<()> {
         let height = self.config.tab_bar_height;
         let h_padding: i16 = 12;    // Horizontal text padding
-        let corner_radius: u32 = 6; // Rounded corner radius
-        let icon_size: u32 = 20;    // Icon size in pixels
-        let icon_padding: i16 = 4;  // Padding after icon
+        let corner_radius = tab_bar::clamp_corner_radius(self.config.tab_corner_radius, tab_width, height);
+        let icon_size = self.config.tab_icon_size;
+        let icon_padding = self.config.tab_icon_padding as i16;
 
         // Tab background color (5 states: tagged, focused, urgent, visible-unfocused, background)
         // Priority: tagged > focused > urgent > visible-unfocused > background
@@ -1005,7 +1363,7 @@ impl Wm {
                 self.tab_bars.gc,
                 &[Rectangle {
                     x: x + 2,
-                    y: (height - 2) as i16,
+                    y: y + (height - 2) as i16,
                     width: tab_width as u16,
                     height: 3,
                 }],
@@ -1014,7 +1372,7 @@ impl Wm {
 
         // Draw tab background with rounded top corners
         self.conn.change_gc(self.tab_bars.gc, &ChangeGCAux::new().foreground(bg_color))?;
-        tab_bar::draw_rounded_top_rect(&self.conn, self.tab_bars.gc, window, x, 0, tab_width, height, corner_radius)?;
+        tab_bar::draw_rounded_top_rect(&self.conn, self.tab_bars.gc, window, x, y, tab_width, height, corner_radius)?;
 
         // Draw bevel effect for 3D raised appearance
         let bevel_light = lighten_color(bg_color, 0x20);
@@ -1027,7 +1385,7 @@ impl Wm {
             self.tab_bars.gc,
             &[Rectangle {
                 x: x + corner_radius as i16,
-                y: 1,
+                y: y + 1,
                 width: (tab_width - corner_radius * 2) as u16,
                 height: 1,
             }],
@@ -1039,8 +1397,8 @@ impl Wm {
             window,
             self.tab_bars.gc,
             &[Rectangle {
-                x: x,
-                y: (height - 1) as i16,
+                x,
+                y: y + (height - 1) as i16,
                 width: tab_width as u16,
                 height: 1,
             }],
@@ -1054,7 +1412,7 @@ impl Wm {
                 self.tab_bars.gc,
                 window,
                 x + tab_width as i16 - 1,
-                4,
+                y + 4,
                 (height - 8) as u16,
             )?;
         }
@@ -1062,14 +1420,18 @@ impl Wm {
         // Calculate content offset (shifts right if icon is present)
         let mut content_offset: i16 = 0;
 
-        // Draw icon if enabled
-        if show_icons {
+        // Draw icon if enabled (compact tabs are nothing but the icon)
+        if show_icons || compact {
             let icon = self.get_window_icon(client_window);
             // Blend icon with tab background and render
             let blended = blend_icon_with_background(&icon.pixels, bg_color, icon_size);
 
-            let icon_x = x + h_padding;
-            let icon_y = ((height - icon_size) / 2) as i16;
+            let icon_x = if compact {
+                x + (tab_width.saturating_sub(icon_size) / 2) as i16
+            } else {
+                x + h_padding
+            };
+            let icon_y = y + (height.saturating_sub(icon_size) / 2) as i16;
 
             self.conn.put_image(
                 ImageFormat::Z_PIXMAP,
@@ -1086,11 +1448,15 @@ impl Wm {
 
             content_offset = icon_size as i16 + icon_padding;
         }
+        if compact {
+            return Ok(());
+        }
 
-        // Get window title and truncate if needed
-        let title = window_query::get_window_title(&self.conn, &self.atoms, client_window);
+        // Get tab label (title and/or class) and truncate if needed
+        let title = tab_bar::window_tab_label(&self.conn, &self.atoms, &self.config, client_window);
         let available_width = (tab_width as i32 - h_padding as i32 * 2 - content_offset as i32).max(0) as u32;
-        let display_title = self.tab_bars.font_renderer.truncate_text_to_width(&title, available_width);
+        let font = self.tab_bars.font_for_monitor(&self.monitors.focused().name);
+        let display_title = font.truncate_text_to_width(&title, available_width);
 
         // Text color (dimmer for background tabs)
         let text_color = if is_focused {
@@ -1100,7 +1466,7 @@ impl Wm {
         };
 
         // Render text with FreeType
-        let (pixels, text_width, text_height) = self.tab_bars.font_renderer.render_text(
+        let (pixels, text_width, text_height) = font.render_text(
             &display_title,
             text_color,
             bg_color,
@@ -1109,7 +1475,7 @@ impl Wm {
         if !pixels.is_empty() && text_width > 0 && text_height > 0 {
             // Calculate text position (vertically centered, after icon)
             let text_x = x + h_padding + content_offset;
-            let text_y = ((height - text_height) / 2) as i16;
+            let text_y = y + ((height - text_height) / 2) as i16;
 
             // Draw text using put_image
             self.conn.put_image(
@@ -1131,25 +1497,31 @@ impl Wm {
 
     /// Draw the tab bar for a frame (Chrome-style with content-based tab widths)
     fn draw_tab_bar(&mut self, frame_id: NodeId, window: Window, rect: &Rect, vertical: bool) -> Result<()> {
+        let started = self.render_stats.start();
+        let result = self.draw_tab_bar_inner(frame_id, window, rect, vertical);
+        self.render_stats.record(profile::Stat::TabBarRedraw, started);
+        result
+    }
+
+    fn draw_tab_bar_inner(&mut self, frame_id: NodeId, window: Window, rect: &Rect, vertical: bool) -> Result<()> {
         // Calculate pixmap dimensions based on orientation
-        let (pix_width, pix_height) = if vertical {
-            (self.config.vertical_tab_width as u16, rect.height as u16)
-        } else {
-            (rect.width as u16, self.config.tab_bar_height as u16)
-        };
+        let (pix_width, pix_height) = self.tab_bar_size(frame_id, rect, vertical);
+        let (pix_width, pix_height) = (pix_width as u16, pix_height as u16);
+        let stacked = self.frame_is_stacked(frame_id);
 
         // Get or create pixmap buffer for double-buffered rendering
         // (pixmap is always recreated fresh, and background fill covers entire area)
         let pixmap = self.get_or_create_tab_bar_pixmap(window, pix_width, pix_height)?;
 
         // Extract all needed data from frame before any mutable calls
-        let (windows, focused_tab, is_empty) = {
+        let (windows, focused_tab, is_empty, locked) = {
             let frame = match self.workspaces().current().layout.get(frame_id).and_then(|n| n.as_frame()) {
                 Some(f) => f,
                 None => return Ok(()),
             };
-            (frame.windows.clone(), frame.focused, frame.windows.is_empty())
+            (frame.windows.clone(), frame.focused, frame.windows.is_empty(), frame.locked)
         };
+        let bar_bg = self.frame_color(frame_id).unwrap_or(self.config.tab_bar_bg);
 
         // Draw background to pixmap (same for horizontal and vertical)
         self.draw_pixmap_background(pixmap, rect, pix_width, pix_height)?;
@@ -1157,13 +1529,40 @@ impl Wm {
         // Empty frame - just copy the background pixmap
         if is_empty {
             self.conn.copy_area(pixmap, window, self.tab_bars.gc, 0, 0, 0, 0, pix_width, pix_height)?;
+            if locked {
+                self.draw_frame_lock_glyph(window, pix_width, pix_height, vertical)?;
+            }
             return Ok(());
         }
 
         // Check if this frame is the focused frame
         let is_focused_frame = frame_id == self.workspaces().current().layout.focused;
 
-        if vertical {
+        if stacked {
+            // Draw one full-width title row per window to pixmap
+            let row_height = self.config.tab_bar_height;
+            let show_icons = self.config.show_tab_icons;
+            for (i, &client_window) in windows.iter().enumerate() {
+                let y = (i as u32 * row_height) as i16;
+                let is_focused = i == focused_tab;
+                let is_tagged = self.tagged_windows.contains(&client_window);
+
+                // Rows are separated by their bevels, so no right-edge separator
+                self.draw_single_tab(
+                    pixmap,
+                    0,
+                    y,
+                    rect.width,
+                    client_window,
+                    is_focused,
+                    true,
+                    is_tagged,
+                    is_focused_frame,
+                    show_icons,
+                    false,
+                )?;
+            }
+        } else if vertical {
             // Draw vertical tabs (icon-only) to pixmap
             let tab_size = self.config.vertical_tab_width;
             let num_tabs = windows.len();
@@ -1190,12 +1589,15 @@ impl Wm {
             let clear_start = (num_tabs as u32 * tab_size) as i16;
             if (clear_start as u16) < pix_height {
                 self.conn.copy_area(pixmap, window, self.tab_bars.gc, 0, 0, 0, 0, pix_width, pix_height)?;
-                self.conn.change_gc(self.tab_bars.gc, &ChangeGCAux::new().foreground(self.config.tab_bar_bg))?;
+                self.conn.change_gc(self.tab_bars.gc, &ChangeGCAux::new().foreground(bar_bg))?;
                 tab_bar::clear_area(&self.conn, self.tab_bars.gc, window, 0, clear_start, pix_width, pix_height - clear_start as u16)?;
+                if locked {
+                    self.draw_frame_lock_glyph(window, pix_width, pix_height, vertical)?;
+                }
                 return Ok(());
             }
         } else {
-            // Draw horizontal tabs (with text) to pixmap
+            // Draw horizontal tabs (with text unless compact) to pixmap
             let tab_layout = self.calculate_tab_layout(frame_id);
             let show_icons = self.config.show_tab_icons;
             let num_tabs = windows.len();
@@ -1209,6 +1611,7 @@ impl Wm {
                 self.draw_single_tab(
                     pixmap,
                     x,
+                    0,
                     tab_width,
                     client_window,
                     is_focused,
@@ -1216,6 +1619,7 @@ impl Wm {
                     is_tagged,
                     is_focused_frame,
                     show_icons,
+                    self.config.tab_compact,
                 )?;
             }
 
@@ -1226,8 +1630,11 @@ impl Wm {
                     // Copy pixmap to window first
                     self.conn.copy_area(pixmap, window, self.tab_bars.gc, 0, 0, 0, 0, pix_width, pix_height)?;
                     // Then clear the empty area on the WINDOW to remove ghost tabs
-                    self.conn.change_gc(self.tab_bars.gc, &ChangeGCAux::new().foreground(self.config.tab_bar_bg))?;
+                    self.conn.change_gc(self.tab_bars.gc, &ChangeGCAux::new().foreground(bar_bg))?;
                     tab_bar::clear_area(&self.conn, self.tab_bars.gc, window, clear_start, 0, pix_width - clear_start as u16, pix_height)?;
+                    if locked {
+                        self.draw_frame_lock_glyph(window, pix_width, pix_height, vertical)?;
+                    }
                     return Ok(());
                 }
             }
@@ -1235,14 +1642,35 @@ impl Wm {
 
         // Copy the rendered pixmap to window (double buffering)
         self.conn.copy_area(pixmap, window, self.tab_bars.gc, 0, 0, 0, 0, pix_width, pix_height)?;
+        if locked {
+            self.draw_frame_lock_glyph(window, pix_width, pix_height, vertical)?;
+        }
+
+        Ok(())
+    }
 
+    /// Draw the lock glyph on a locked frame's tab bar window.
+    ///
+    /// Horizontal bars get it at the right end, vertical bars at the bottom,
+    /// so it sits past the tabs in the common case.
+    fn draw_frame_lock_glyph(&self, window: Window, width: u16, height: u16, vertical: bool) -> Result<()> {
+        let size = tab_bar::LOCK_GLYPH_SIZE as i16;
+        let (x, y) = if vertical {
+            ((width as i16 - size) / 2, height as i16 - size - 6)
+        } else {
+            // Centered in the first row (stacked bars have several)
+            let row = height.min(self.config.tab_bar_height as u16) as i16;
+            (width as i16 - size - 6, (row - size) / 2)
+        };
+        self.conn.change_gc(self.tab_bars.gc, &ChangeGCAux::new().foreground(self.config.tab_text_color))?;
+        tab_bar::draw_lock_glyph(&self.conn, self.tab_bars.gc, window, x, y)?;
         Ok(())
     }
 
-    /// Get window icon from _NET_WM_ICON property, scaled to 20x20 BGRA.
-    /// Returns a static default icon if the window has no icon.
+    /// Get window icon from _NET_WM_ICON property, scaled to the configured icon size (BGRA).
+    /// Returns a default icon if the window has no icon.
     fn get_window_icon(&mut self, window: Window) -> &CachedIcon {
-        self.tab_bars.get_icon(&self.conn, &self.atoms, window)
+        self.tab_bars.get_icon(&self.conn, &self.atoms, window, self.config.tab_icon_size)
     }
 
     /// Redraw tab bars that contain a specific window (used when icon changes)
@@ -1264,10 +1692,10 @@ impl Wm {
                 let screen_rect = self.usable_screen();
                 let geometries = self.workspaces().current().layout.calculate_geometries(
                     screen_rect,
-                    self.config.gap,
+                    self.gap(),
                 );
 
-                if let Some(rect) = geometries.iter().find(|(fid, _)| *fid == frame_id).map(|(_, r)| r.clone()) {
+                if let Some(rect) = geometries.iter().find(|(fid, _)| *fid == frame_id).map(|(_, r)| *r) {
                     self.draw_tab_bar(frame_id, tab_window, &rect, vertical)?;
                     self.conn.flush()?;
                 }
@@ -1287,11 +1715,18 @@ impl Wm {
 
     /// Apply the current layout to all windows
     fn apply_layout(&mut self) -> Result<()> {
-        // Check for fullscreen window first - it takes over the entire screen
-        if let Some(fullscreen_window) = self.workspaces().current().fullscreen_window {
-            // Get the raw monitor geometry (no gaps, no struts)
-            let monitor = self.monitors.focused();
-            let geom = monitor.geometry;
+        let started = self.render_stats.start();
+        let result = self.apply_layout_inner();
+        self.render_stats.record(profile::Stat::LayoutApplication, started);
+        result
+    }
+
+    fn apply_layout_inner(&mut self) -> Result<()> {
+        // Check for zoomed/fullscreen window first - it takes over the entire screen.
+        // Zoom spans the bounding box of all monitors, fullscreen the raw monitor
+        // geometry (no gaps, no struts).
+        if let Some((fullscreen_window, geom)) = self.takeover() {
+            self.animator.cancel(fullscreen_window);
 
             // Configure fullscreen window to cover entire monitor
             self.conn.configure_window(
@@ -1322,11 +1757,15 @@ impl Wm {
             }
             self.conn.flush()?;
 
+            #[cfg(feature = "mirror")]
+            self.update_mirrors()?;
+
+            self.update_bar()?;
             return Ok(());
         }
 
         let screen_rect = self.usable_screen();
-        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.config.gap);
+        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
 
         // Get the focused frame id
         let focused_frame_id = self.workspaces().current().layout.focused;
@@ -1345,6 +1784,7 @@ impl Wm {
             windows: Vec<Window>,
             focused_idx: usize,
             vertical_tabs: bool,
+            stacked: bool,
         }
         let frame_data: Vec<FrameData> = geometries.iter()
             .filter_map(|(frame_id, rect)| {
@@ -1352,10 +1792,11 @@ impl Wm {
                     .and_then(|n| n.as_frame())
                     .map(|frame| FrameData {
                         frame_id: *frame_id,
-                        rect: rect.clone(),
+                        rect: *rect,
                         windows: frame.windows.clone(),
                         focused_idx: frame.focused,
                         vertical_tabs: frame.vertical_tabs,
+                        stacked: frame.style == FrameStyle::Stacked,
                     })
             })
             .collect();
@@ -1368,30 +1809,19 @@ impl Wm {
             // Calculate client area based on tab orientation
             // Only show tab bar for frames with windows
             let has_tabs = !fd.windows.is_empty();
-            let (client_x, client_y, client_width, client_height) = if !has_tabs {
-                // Empty frame: use full area (no tab bar)
-                (fd.rect.x, fd.rect.y, fd.rect.width, fd.rect.height)
-            } else if fd.vertical_tabs {
-                // Vertical tabs: client area is to the right of the tab bar
-                (
-                    fd.rect.x + vertical_tab_width as i32,
-                    fd.rect.y,
-                    fd.rect.width.saturating_sub(vertical_tab_width),
-                    fd.rect.height,
-                )
-            } else {
-                // Horizontal tabs: client area is below the tab bar
-                (
-                    fd.rect.x,
-                    fd.rect.y + tab_bar_height as i32,
-                    fd.rect.width,
-                    fd.rect.height.saturating_sub(tab_bar_height),
-                )
-            };
+            let client = tab_bar::client_area(
+                fd.rect,
+                fd.windows.len(),
+                fd.stacked,
+                fd.vertical_tabs,
+                tab_bar_height,
+                vertical_tab_width,
+            );
+            let (client_x, client_y, client_width, client_height) = (client.x, client.y, client.width, client.height);
 
             if has_tabs {
                 log::debug!("Frame {:?} has {} windows, will show tab bar (vertical={})", fd.frame_id, fd.windows.len(), fd.vertical_tabs);
-                frames_with_tabs.push((fd.frame_id, fd.rect.clone(), fd.windows.len(), fd.vertical_tabs));
+                frames_with_tabs.push((fd.frame_id, fd.rect, fd.windows.len(), fd.vertical_tabs));
             } else {
                 // Hide tab bar for single-window frames
                 let mon_id = self.monitors.focused_id();
@@ -1404,7 +1834,7 @@ impl Wm {
             // Track empty vs non-empty frames for placeholder window management
             if fd.windows.is_empty() {
                 let is_focused = fd.frame_id == focused_frame_id;
-                empty_frames.push((fd.frame_id, fd.rect.clone(), is_focused));
+                empty_frames.push((fd.frame_id, fd.rect, is_focused));
             } else {
                 non_empty_frames.push(fd.frame_id);
             }
@@ -1412,19 +1842,38 @@ impl Wm {
             // Map focused window FIRST to reduce flicker (show new before hiding old)
             for (i, &window) in fd.windows.iter().enumerate() {
                 if i == fd.focused_idx {
+                    // X rejects zero sizes, however small the frame gets
+                    let target = types::Rect::new(
+                        client_x,
+                        client_y,
+                        client_width.saturating_sub(border * 2).max(1),
+                        client_height.saturating_sub(border * 2).max(1),
+                    );
+                    let rect = self.spawn_animation_rect(window, target);
                     self.conn.configure_window(
                         window,
                         &ConfigureWindowAux::new()
-                            .x(client_x)
-                            .y(client_y)
-                            .width(client_width.saturating_sub(border * 2))
-                            .height(client_height.saturating_sub(border * 2))
+                            .x(rect.x)
+                            .y(rect.y)
+                            .width(rect.width)
+                            .height(rect.height)
                             .border_width(border),
                     )?;
+                    // A move without a resize produces no real ConfigureNotify,
+                    // so report the absolute geometry ourselves (ICCCM 4.1.5)
+                    window_query::send_configure_notify(
+                        &self.conn,
+                        window,
+                        rect.x as i16,
+                        rect.y as i16,
+                        rect.width as u16,
+                        rect.height as u16,
+                        border as u16,
+                    )?;
                     self.conn.change_window_attributes(
                         window,
                         &ChangeWindowAttributesAux::new()
-                            .border_pixel(self.config.border_focused),
+                            .border_pixel(self.border_focused()),
                     )?;
                     self.conn.map_window(window)?;
                     self.hidden_windows.remove(&window);
@@ -1434,6 +1883,8 @@ impl Wm {
             // Then unmap non-focused windows (hidden tabs)
             for (i, &window) in fd.windows.iter().enumerate() {
                 if i != fd.focused_idx {
+                    self.spawn_pending.remove(&window);
+                    self.animator.cancel(window);
                     self.hidden_windows.insert(window);
                     self.conn.unmap_window(window)?;
                 }
@@ -1443,11 +1894,7 @@ impl Wm {
         // Create/update tab bars for frames with multiple windows
         for (frame_id, rect, _, vertical) in frames_with_tabs {
             let tab_window = self.get_or_create_tab_bar(frame_id, &rect, vertical)?;
-            let (w, h) = if vertical {
-                (self.config.vertical_tab_width, rect.height)
-            } else {
-                (rect.width, self.config.tab_bar_height)
-            };
+            let (w, h) = self.tab_bar_size(frame_id, &rect, vertical);
             log::info!("Tab bar window 0x{:x} for frame {:?} at ({}, {}) {}x{} (vertical={})",
                 tab_window, frame_id, rect.x, rect.y, w, h, vertical);
             self.conn.map_window(tab_window)?;
@@ -1458,6 +1905,7 @@ impl Wm {
             )?;
             self.draw_tab_bar(frame_id, tab_window, &rect, vertical)?;
         }
+        self.raise_docks()?;
 
         // Create/update empty frame placeholder windows (with borders)
         for (frame_id, rect, is_focused) in empty_frames {
@@ -1475,10 +1923,336 @@ impl Wm {
         // Clean up empty frame windows for removed frames
         self.cleanup_empty_frame_windows();
 
+        // Position mirrors over their frames (below floating windows)
+        #[cfg(feature = "mirror")]
+        self.update_mirrors()?;
+
         // Apply floating window layout
         self.apply_floating_layout()?;
 
+        self.update_bar()?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Mirror a managed window's contents into a named frame (view-only).
+    #[cfg(feature = "mirror")]
+    fn mirror_window(&mut self, window: Window, frame_name: &str) -> Result<()> {
+        if self.find_window_global(window).is_none() {
+            anyhow::bail!("Window 0x{:x} is not managed", window);
+        }
+        let Some(target) = self.find_frame_by_name_global(frame_name) else {
+            anyhow::bail!("No frame found with name '{}'", frame_name);
+        };
+        if self.monitors.get(target.0)
+            .and_then(|m| m.workspaces.workspaces[target.1].layout.get(target.2))
+            .and_then(|n| n.as_frame())
+            .is_some_and(|f| f.windows.contains(&window))
+        {
+            anyhow::bail!("Window 0x{:x} is already in frame '{}'", window, frame_name);
+        }
+
+        self.mirrors.add(&self.conn, self.root, window, target, self.config.tab_bar_bg)?;
+        log::info!("Mirroring window 0x{:x} into frame '{}'", window, frame_name);
+        self.apply_layout()
+    }
+
+    /// Show mirrors whose frames are visible on the focused monitor, hide the rest,
+    /// and drop mirrors whose frames no longer exist.
+    #[cfg(feature = "mirror")]
+    fn update_mirrors(&mut self) -> Result<()> {
+        let monitors = &self.monitors;
+        self.mirrors.remove_where(&self.conn, |m| {
+            monitors.get(m.monitor)
+                .and_then(|mon| mon.workspaces.workspaces[m.workspace].layout.get(m.frame))
+                .and_then(|n| n.as_frame())
+                .is_none()
+        })?;
+
+        let mon_id = self.monitors.focused_id();
+        let ws_idx = self.workspaces().current_index();
+        let takeover = self.takeover().is_some();
+        let screen_rect = self.usable_screen();
+        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
+
+        let mut shown = Vec::new();
+        for mirror in self.mirrors.iter() {
+            if mirror.monitor != mon_id {
+                continue;
+            }
+            let rect = geometries.iter().find(|(id, _)| *id == mirror.frame).map(|(_, r)| *r);
+            match rect {
+                Some(rect) if mirror.workspace == ws_idx && !takeover => {
+                    self.conn.configure_window(
+                        mirror.view,
+                        &ConfigureWindowAux::new()
+                            .x(rect.x)
+                            .y(rect.y)
+                            .width(rect.width.max(1))
+                            .height(rect.height.max(1))
+                            .stack_mode(StackMode::ABOVE),
+                    )?;
+                    self.conn.map_window(mirror.view)?;
+                    shown.push(mirror.view);
+                }
+                _ => {
+                    self.conn.unmap_window(mirror.view)?;
+                }
+            }
+        }
+        for view in shown {
+            self.redraw_mirror_view(view)?;
+        }
+        Ok(())
+    }
+
+    /// Repaint a mirror view from its source window
+    #[cfg(feature = "mirror")]
+    fn redraw_mirror_view(&mut self, view: Window) -> Result<()> {
+        if let Some(m) = self.mirrors.find_by_view(view) {
+            mirror::redraw(&self.conn, self.tab_bars.gc, m)?;
+        }
+        Ok(())
+    }
+
+    /// Re-apply layout on every monitor, restoring the focused monitor afterwards.
+    fn apply_layout_all_monitors(&mut self) -> Result<()> {
+        let original_monitor = self.monitors.focused_id();
+        let monitor_ids: Vec<MonitorId> = self.monitors.iter().map(|(id, _)| id).collect();
+
+        // apply_layout() works on the focused monitor, so visit each in turn
+        let mut result = Ok(());
+        for monitor_id in monitor_ids {
+            self.monitors.set_focused(monitor_id);
+            result = self.apply_layout();
+            if result.is_err() {
+                break;
+            }
+        }
+        self.monitors.set_focused(original_monitor);
+        result
+    }
+
+    /// Re-layout one monitor (apply_layout() works on the focused monitor)
+    fn apply_layout_on(&mut self, monitor_id: MonitorId) -> Result<()> {
+        let original_monitor = self.monitors.focused_id();
+        self.monitors.set_focused(monitor_id);
+        let result = self.apply_layout();
+        self.monitors.set_focused(original_monitor);
+        result
+    }
+
+    /// Put pointer barriers on the edges the monitors share, replacing any
+    /// from before (general.pointer_barriers). Called whenever the monitor
+    /// arrangement changes.
+    fn update_pointer_barriers(&mut self) -> Result<()> {
+        let edges = self.monitors.shared_edges();
+        if let Some(barriers) = &mut self.barriers {
+            barriers.update(&self.conn, self.root, &edges)?;
+        }
+        Ok(())
+    }
+
+    /// RandR reported an output change: detect the monitors again (see
+    /// `MonitorManager::update_monitors`) and, if any moved, came or went,
+    /// rebuild what is kept per monitor and re-tile.
+    fn handle_monitor_change(&mut self) -> Result<()> {
+        let arrangement = |monitors: &MonitorManager| -> Vec<(MonitorId, Rect)> {
+            monitors.iter().map(|(id, monitor)| (id, monitor.geometry)).collect()
+        };
+        let before = arrangement(&self.monitors);
+        self.monitors.refresh(&self.conn, self.root)?;
+        if arrangement(&self.monitors) == before {
+            return Ok(());
+        }
+        log::info!("Monitors changed, now {} monitor(s)", self.monitors.count());
+        self.rebuild_for_monitors()
+    }
+
+    /// After the set of monitors changed: recreate the tab bars, built-in bar
+    /// and pointer barriers (kept per monitor), show and hide windows to match
+    /// the workspaces now shown, re-tile and keep focus on a visible window.
+    fn rebuild_for_monitors(&mut self) -> Result<()> {
+        // Tab bars and the built-in bar are keyed by monitor, so start them afresh
+        self.tab_bars.destroy_all(&self.conn);
+        self.status_bar.destroy_all(&self.conn)?;
+        self.setup_bar()?;
+        self.update_pointer_barriers()?;
+        #[cfg(feature = "mirror")]
+        {
+            let monitors = &self.monitors;
+            self.mirrors.remove_where(&self.conn, |m| monitors.get(m.monitor).is_none())?;
+        }
+
+        // Windows may now be on a workspace that isn't shown (or the other
+        // way round); those of disconnected monitors aren't on any
+        let mut shown = Vec::new();
+        let mut hidden = self.monitors.held_windows();
+        for (_, monitor) in self.monitors.iter() {
+            let current = monitor.workspaces.current_index();
+            for (idx, ws) in monitor.workspaces.workspaces.iter().enumerate() {
+                let windows = ws.layout.all_windows().into_iter().chain(ws.floating_window_ids());
+                if idx == current { shown.extend(windows) } else { hidden.extend(windows) }
+            }
+        }
+        for window in hidden {
+            self.hidden_windows.insert(window);
+            self.conn.unmap_window(window)?;
+        }
+        for window in shown {
+            self.hidden_windows.remove(&window);
+        }
+        self.apply_layout_all_monitors()?;
+
+        let ws = self.workspaces().current();
+        match self.focused_window.filter(|&w| ws.layout.find_window(w).is_some() || ws.is_floating(w)) {
+            Some(window) => self.focus_window(window)?,
+            None => {
+                self.focused_window = None;
+                self.focus_next_available_window()?;
+            }
+        }
+        self.update_current_desktop()
+    }
+
+    /// Merge all monitors into one logical screen spanning every output, or
+    /// restore the separate monitors. Windows keep their workspace; see
+    /// `MonitorManager::merge` for where they go.
+    fn set_monitor_mode(&mut self, merged: bool) -> Result<()> {
+        if merged == self.monitors.is_merged() {
+            return Ok(());
+        }
+        let changed = if merged { self.monitors.merge() } else { self.monitors.unmerge() };
+        if changed.is_empty() {
+            anyhow::bail!("Only one monitor, nothing to merge");
+        }
+
+        self.rebuild_for_monitors()?;
+        log::info!(
+            "Monitors {} ({} logical monitor(s))",
+            if merged { "merged" } else { "split" },
+            self.monitors.count()
+        );
+        Ok(())
+    }
+
+    /// Change gaps in place and re-tile every monitor. Values are clamped to
+    /// 0..=MAX_GAP; `None` keeps the current value. A new gap replaces the
+    /// `[workspace.N] gap` overrides so it applies everywhere. Returns the
+    /// gaps now in effect on the current workspace.
+    fn set_gaps(&mut self, gap: Option<u32>, outer_gap: Option<u32>) -> Result<(u32, u32)> {
+        const MAX_GAP: u32 = 200;
+
+        if let Some(gap) = gap {
+            self.config.gap = gap.min(MAX_GAP);
+            self.config.workspace_gap.clear();
+        }
+        if let Some(outer_gap) = outer_gap {
+            self.config.outer_gap = outer_gap.min(MAX_GAP);
+        }
+        self.apply_layout_all_monitors()?;
+        log::info!("Gaps set to {} (outer {})", self.gap(), self.config.outer_gap);
+        Ok((self.gap(), self.config.outer_gap))
+    }
+
+    /// Switch to the theme file at `path` (as `general.theme`), recoloring
+    /// borders, tab bars and the built-in bar and re-applying the theme's gaps,
+    /// border width and corner radius. On failure the current theme stays.
+    fn set_theme(&mut self, path: &str) -> Result<()> {
+        let mut themed = Config::load_with_theme(&self.config_path, path)?;
+        themed.appearance.apply_scale(self.scale);
+        self.config.apply_theme(&themed);
+        self.user_config.colors = themed.colors;
+        self.user_config.general.theme = themed.general.theme;
+
+        self.status_bar.destroy_all(&self.conn)?;
+        self.setup_bar()?;
+        self.apply_layout_all_monitors()?;
+        self.repair_borders()?;
+        log::info!("Theme set to {}", path);
+        Ok(())
+    }
+
+    /// Load an image and install it as the root background, fitted to each monitor.
+    /// On failure the current wallpaper is left untouched.
+    fn set_wallpaper(&mut self, path: &str, mode: wallpaper::WallpaperMode) -> Result<()> {
+        let img = wallpaper::load(path)?;
+        let monitors: Vec<types::Rect> = self.monitors.iter().map(|(_, m)| m.geometry).collect();
+        let depth = self.screen().root_depth;
+        let pixmap = wallpaper::apply(
+            &self.conn,
+            self.root,
+            depth,
+            &monitors,
+            &img,
+            mode,
+            (self.atoms.xrootpmap_id, self.atoms.esetroot_pmap_id),
+        )?;
+        if let Some(old) = self.wallpaper_pixmap.replace(pixmap) {
+            self.conn.free_pixmap(old)?;
+        }
+
+        // Tab bars sample the root background, so redraw them against the new image
+        self.apply_layout_all_monitors()?;
+        log::info!("Wallpaper set to {} ({:?})", path, mode);
+        Ok(())
+    }
+
+    /// Save which workspace each monitor is showing to the session file,
+    /// keeping the saved entries of monitors that aren't connected.
+    fn save_session(&self) -> Result<()> {
+        let path = session::Session::default_path();
+        let mut session = session::Session::load(&path).unwrap_or_default();
+        session.capture(&self.monitors);
+        session.save(&path)?;
+        log::info!("Session saved to {:?}", path);
+        Ok(())
+    }
+
+    /// Restore per-monitor workspaces from the session file.
+    ///
+    /// Monitors are matched by name: saved monitors that are no longer connected
+    /// are ignored and new monitors keep their current workspace.
+    /// Returns false if there is no session file.
+    fn restore_session(&mut self) -> Result<bool> {
+        let path = session::Session::default_path();
+        let Some(saved) = session::Session::load(&path) else {
+            return Ok(false);
+        };
+
+        let original_monitor = self.monitors.focused_id();
+        for (monitor_id, index) in saved.assignments(&self.monitors) {
+            self.focus_monitor(monitor_id)?;
+            if let Some(old_idx) = self.workspaces_mut().switch_to(index) {
+                self.perform_workspace_switch(old_idx)?;
+            }
+        }
+        self.focus_monitor(original_monitor)?;
+
+        log::info!("Session restored from {:?}", path);
+        Ok(true)
+    }
+
+    /// Re-apply layout on all monitors, redraw all tab bars and re-publish EWMH properties.
+    ///
+    /// Safe to call at any time; tab bar backgrounds are re-sampled from the root window.
+    fn refresh(&mut self) -> Result<()> {
+        self.apply_layout_all_monitors()?;
+
+        // setup_ewmh() resets dynamic properties, so publish them again afterwards
+        self.setup_ewmh()?;
+        self.update_client_list()?;
+        self.update_current_desktop()?;
+        self.update_active_window()?;
+        self.update_urgent_indicator()?;
+
+        // Restore focus borders and tab highlight
+        if let Some(window) = self.focused_window {
+            self.focus_window(window)?;
+        }
+
         self.conn.flush()?;
+        log::info!("Refreshed layout and EWMH state");
         Ok(())
     }
 
@@ -1494,6 +2268,8 @@ impl Wm {
             .collect();
 
         for (window, x, y, width, height) in floating_windows {
+            self.spawn_pending.remove(&window);
+            self.animator.cancel(window);
             // Configure window geometry
             self.conn.configure_window(
                 window,
@@ -1544,8 +2320,8 @@ impl Wm {
             }
         }
 
-        // Grab all configured keybindings
-        for (action, binding) in &self.keybindings {
+        // Grab all configured keybindings (only the toggle in presentation mode)
+        for (action, binding) in config::active_bindings(&self.keybindings, self.presentation_mode) {
             if let Some(&keycode) = keysym_to_keycode.get(&binding.keysym) {
                 let modmask = ModMask::from(binding.modifiers);
                 self.grab_key(keycode, modmask)?;
@@ -1568,6 +2344,20 @@ impl Wm {
         Ok(())
     }
 
+    /// Enter or leave presentation mode, re-grabbing keys to match
+    fn toggle_presentation_mode(&mut self) -> Result<()> {
+        self.presentation_mode = !self.presentation_mode;
+        self.conn.ungrab_key(Grab::ANY, self.root, ModMask::ANY)?;
+        self.grab_keys()?;
+
+        if self.presentation_mode {
+            log::warn!("=== Presentation mode ON: all keybindings except the toggle are disabled ===");
+        } else {
+            log::warn!("=== Presentation mode OFF: keybindings restored ===");
+        }
+        Ok(())
+    }
+
     /// Grab a single key combination
     fn grab_key(&self, keycode: Keycode, modifiers: ModMask) -> Result<()> {
         // Grab with and without NumLock/CapsLock to handle those states
@@ -1602,9 +2392,14 @@ impl Wm {
             // Skip windows that are:
             // - override_redirect (popups, menus, etc.)
             // - not viewable (unmapped)
+            // - created by ttwm (e.g. tab bars left over from a replaced instance)
             if attrs.override_redirect || attrs.map_state != MapState::VIEWABLE {
                 continue;
             }
+            if window_query::is_internal_window(&self.conn, &self.atoms, window) {
+                log::info!("Skipping ttwm-internal window 0x{:x}", window);
+                continue;
+            }
 
             log::info!("Found existing window 0x{:x}", window);
             self.manage_window(window)?;
@@ -1613,11 +2408,6 @@ impl Wm {
         Ok(())
     }
 
-    /// Check if a window is currently floating
-    fn is_floating(&self, window: Window) -> bool {
-        self.workspaces().current().is_floating(window)
-    }
-
     /// Find which workspace contains a window (including floating)
     fn find_window_workspace(&self, window: Window) -> Option<usize> {
         for (idx, ws) in self.monitors.focused().workspaces.workspaces.iter().enumerate() {
@@ -1677,10 +2467,16 @@ impl Wm {
         Ok(())
     }
 
-    /// Focus the oldest urgent window (FIFO order)
+    /// Focus the next urgent window in general.urgent_order (oldest first by
+    /// default). Focusing clears its urgent state, so repeated calls visit
+    /// every urgent window in turn; ones not on this monitor are skipped.
     fn focus_urgent(&mut self) -> Result<()> {
         log::info!("focus_urgent: called");
-        if let Some(window) = self.urgent.first() {
+        let order = self.user_config.general.urgent_order;
+        let next = self.urgent.ordered(order)
+            .find(|&w| self.find_window_workspace(w).is_some())
+            .or_else(|| self.urgent.next(order));
+        if let Some(window) = next {
             log::info!("focus_urgent: urgent window is 0x{:x}", window);
             // Find which workspace contains this window
             if let Some(workspace_idx) = self.find_window_workspace(window) {
@@ -1738,23 +2534,238 @@ impl Wm {
         Ok(())
     }
 
-    /// Start managing a window
-    fn manage_window(&mut self, window: Window) -> Result<()> {
-        // Check if already managed (either tiled or floating)
-        if self.workspaces().current().layout.find_window(window).is_some() {
-            return Ok(());
+    /// Switch to the monitor and workspace holding a window, select its tab and focus it.
+    /// Returns false if the window isn't managed.
+    fn goto_window(&mut self, window: Window) -> Result<bool> {
+        let Some((monitor_id, ws_idx)) = self.find_window_global(window) else {
+            return Ok(false);
+        };
+
+        self.focus_monitor(monitor_id)?;
+        if let Some(old_idx) = self.workspaces_mut().switch_to(ws_idx) {
+            self.perform_workspace_switch(old_idx)?;
         }
-        if self.workspaces().current().is_floating(window) {
-            return Ok(());
+
+        // apply_layout only maps the focused tab in each frame, so select it first
+        if let Some(frame_id) = self.workspaces().current().layout.find_window(window) {
+            let layout = &mut self.workspaces_mut().current_mut().layout;
+            let tab_idx = layout.get(frame_id)
+                .and_then(|n| n.as_frame())
+                .and_then(|frame| frame.windows.iter().position(|&w| w == window));
+            if let Some(tab_idx) = tab_idx {
+                layout.focused = frame_id;
+                layout.focus_tab(tab_idx);
+            }
+            self.apply_layout()?;
         }
 
-        log::info!("Managing window 0x{:x}", window);
+        self.suppress_enter_focus = true;
+        self.focus_window(window)?;
+        Ok(true)
+    }
+
+    /// Bind a mark to a window
+    fn set_mark(&mut self, mark: char, window: Window) -> Result<()> {
+        if self.find_window_global(window).is_none() {
+            anyhow::bail!("Window 0x{:x} is not managed", window);
+        }
+        self.marks.set(mark, window);
+        log::info!("Marked window 0x{:x} as '{}'", window, mark);
+        Ok(())
+    }
+
+    /// Focus the window bound to a mark, wherever it lives
+    fn goto_mark(&mut self, mark: char) -> Result<()> {
+        let Some(window) = self.marks.get(mark) else {
+            anyhow::bail!("No window marked '{}'", mark);
+        };
+        if !self.goto_window(window)? {
+            anyhow::bail!("Window 0x{:x} marked '{}' is no longer managed", window, mark);
+        }
+        log::info!("Jumped to mark '{}' (window 0x{:x})", mark, window);
+        Ok(())
+    }
+
+    /// Grab the keyboard so the next key press names a mark
+    fn start_mark_prompt(&mut self, prompt: MarkPrompt) -> Result<()> {
+        let reply = self.conn.grab_keyboard(
+            false,
+            self.root,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )?.reply()?;
+        if reply.status != GrabStatus::SUCCESS {
+            log::warn!("Could not grab keyboard for mark prompt: {:?}", reply.status);
+            return Ok(());
+        }
+        self.mark_prompt = Some(prompt);
+        log::debug!("Waiting for mark key ({:?})", prompt);
+        Ok(())
+    }
+
+    /// Finish a mark prompt with the typed key (None cancels it)
+    fn finish_mark_prompt(&mut self, prompt: MarkPrompt, mark: Option<char>) -> Result<()> {
+        self.mark_prompt = None;
+        self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        self.conn.flush()?;
+
+        let Some(mark) = mark else {
+            log::info!("Mark prompt cancelled");
+            return Ok(());
+        };
+        let result = match prompt {
+            MarkPrompt::Set => match self.focused_window {
+                Some(window) => self.set_mark(mark, window),
+                None => Ok(()),
+            },
+            MarkPrompt::Goto => self.goto_mark(mark),
+            MarkPrompt::Pull => match self.marks.get(mark) {
+                Some(window) => self.pull_window(window),
+                None => Err(anyhow::anyhow!("No window marked '{}'", mark)),
+            },
+        };
+        if let Err(e) = result {
+            log::info!("{}", e);
+        }
+        Ok(())
+    }
 
-        // Set border color
+    /// Open the launcher on the focused monitor and grab the keyboard for it
+    fn open_launcher(&mut self) -> Result<()> {
+        if self.launcher.is_visible() {
+            return Ok(());
+        }
+        let reply = self.conn.grab_keyboard(
+            false,
+            self.root,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )?.reply()?;
+        if reply.status != GrabStatus::SUCCESS {
+            log::warn!("Could not grab keyboard for launcher: {:?}", reply.status);
+            return Ok(());
+        }
+        let monitor = self.monitors.focused().geometry;
+        self.launcher.show(&self.conn, self.root, monitor, &self.tab_bars.font_renderer, self.tab_bars.gc, &self.config)?;
+        Ok(())
+    }
+
+    /// Show the keybinding cheat sheet on the focused monitor, grabbing the
+    /// keyboard so the next key press closes it
+    fn show_help(&mut self) -> Result<()> {
+        if self.help.is_visible() {
+            return self.hide_help();
+        }
+        let reply = self.conn.grab_keyboard(
+            false,
+            self.root,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )?.reply()?;
+        if reply.status != GrabStatus::SUCCESS {
+            log::warn!("Could not grab keyboard for help overlay: {:?}", reply.status);
+            return Ok(());
+        }
+        let mut entries: Vec<(String, String)> = self.keybindings.iter()
+            .map(|(action, binding)| (config::format_binding(binding), action.name()))
+            .collect();
+        entries.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        let monitor = self.monitors.focused().geometry;
+        self.help.show(&self.conn, self.root, monitor, entries, &self.tab_bars.font_renderer, self.tab_bars.gc, &self.config)?;
+        Ok(())
+    }
+
+    /// Close the cheat sheet and release the keyboard
+    fn hide_help(&mut self) -> Result<()> {
+        self.help.hide(&self.conn)?;
+        self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Feed a key to the open launcher, closing it and spawning on Enter
+    fn launcher_key(&mut self, key: LauncherKey) -> Result<()> {
+        match self.launcher.handle_key(key) {
+            LauncherOutcome::Continue => {
+                self.launcher.draw(&self.conn, &self.tab_bars.font_renderer, self.tab_bars.gc, &self.config)?;
+            }
+            outcome => {
+                self.launcher.hide(&self.conn)?;
+                self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+                self.conn.flush()?;
+                if let LauncherOutcome::Launch(command) = outcome {
+                    self.execute_action(WmAction::Spawn(command))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Spawn a command, falling back through `general.terminal_fallbacks`
+    /// if it fails to start
+    fn spawn(&mut self, command: &str) {
+        log::info!("Spawning: {}", command);
+        let candidates = self.user_config.general.spawn_candidates(command);
+        for (i, candidate) in candidates.iter().enumerate() {
+            let parts: Vec<&str> = candidate.split_whitespace().collect();
+            let Some((program, args)) = parts.split_first() else {
+                continue;
+            };
+            match Command::new(program).args(args).spawn() {
+                Ok(child) => {
+                    if i > 0 {
+                        log::info!("Spawned fallback terminal '{}' instead of '{}'", candidate, command);
+                    }
+                    self.children.track(candidate, child);
+                    return;
+                }
+                Err(e) => log::error!("Failed to spawn {}: {}", candidate, e),
+            }
+        }
+    }
+
+    /// Start managing a window
+    fn manage_window(&mut self, window: Window) -> Result<()> {
+        // Check if already managed (either tiled or floating)
+        if self.workspaces().current().layout.find_window(window).is_some() {
+            return Ok(());
+        }
+        if self.workspaces().current().is_floating(window) {
+            return Ok(());
+        }
+
+        // Never manage our own tab bars/placeholders (or a previous instance's)
+        if window_query::is_internal_window(&self.conn, &self.atoms, window) {
+            log::debug!("Ignoring ttwm-internal window 0x{:x}", window);
+            return Ok(());
+        }
+
+        // Excluded classes are mapped as requested and otherwise left alone
+        // (not tiled, floated or listed in _NET_CLIENT_LIST)
+        if let Some((instance, class)) = window_query::get_window_class(&self.conn, window) {
+            if self.user_config.general.is_unmanaged_class(&instance, &class) {
+                log::info!("Not managing window 0x{:x} ({}/{}): unmanaged_classes", window, instance, class);
+                self.conn.map_window(window)?;
+                return Ok(());
+            }
+        }
+
+        log::info!("Managing window 0x{:x}", window);
+
+        // Set border color, and the bit gravity that decides where the old
+        // contents stay while the client catches up with a frame resize
+        let bit_gravity = match self.config.resize_gravity {
+            ResizeGravity::TopLeft => Gravity::NORTH_WEST,
+            ResizeGravity::Center => Gravity::CENTER,
+        };
         self.conn.change_window_attributes(
             window,
             &ChangeWindowAttributesAux::new()
-                .border_pixel(self.config.border_focused),
+                .border_pixel(self.border_focused())
+                .bit_gravity(bit_gravity),
         )?;
 
         // Subscribe to events on this window
@@ -1764,6 +2775,22 @@ impl Wm {
                 .event_mask(EventMask::ENTER_WINDOW | EventMask::FOCUS_CHANGE | EventMask::PROPERTY_CHANGE),
         )?;
 
+        // Borders can only be clicked through a grab on the window itself;
+        // the pointer freezes until handle_button_press decides who gets it
+        if self.user_config.general.resize_from_border {
+            self.conn.grab_button(
+                false,
+                window,
+                EventMask::BUTTON_PRESS,
+                GrabMode::SYNC,
+                GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                ButtonIndex::from(self.user_config.general.resize_button),
+                ModMask::ANY,
+            )?;
+        }
+
         // Map the window (make it visible)
         self.conn.map_window(window)?;
 
@@ -1775,11 +2802,8 @@ impl Wm {
                 window, struts.top, struts.bottom, struts.left, struts.right
             );
             self.dock_windows.insert(window, struts);
-            // Keep dock windows above others
-            self.conn.configure_window(
-                window,
-                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-            )?;
+            // Keep dock windows above others, except a fullscreen window
+            self.raise_docks()?;
             self.apply_layout()?;
             return Ok(());
         }
@@ -1820,8 +2844,44 @@ impl Wm {
                 frame: "floating".to_string(),
             });
         } else {
-            // Add to the focused frame in our layout (tiled)
-            self.workspaces_mut().current_mut().layout.add_window(window);
+            // Join a frame already holding this application (auto_group_by_class)
+            let group = if self.user_config.general.auto_group_by_class {
+                window_query::get_window_class(&self.conn, window).and_then(|(_, class)| {
+                    self.workspaces().current().layout.group_frame(|w| {
+                        window_query::get_window_class(&self.conn, w).is_some_and(|(_, c)| c == class)
+                    })
+                })
+            } else {
+                None
+            };
+
+            // Add to the focused frame in our layout (tiled), or a new frame split off it
+            let screen_rect = self.usable_screen();
+            let (gap, min_size) = (self.gap(), self.config.min_frame_size);
+            let max_tabs = self.user_config.general.max_tabs_per_frame;
+            let frame_count = self.workspaces().current().layout.all_frames().len();
+            if let Some(frame_id) = group {
+                log::info!("Grouping window 0x{:x} with its class in frame {:?}", window, frame_id);
+                let layout = &mut self.workspaces_mut().current_mut().layout;
+                layout.focused = frame_id;
+                layout.add_window_capped(window, max_tabs, screen_rect, gap, min_size);
+            } else {
+                match self.user_config.general.open_mode {
+                    OpenMode::Tab => {
+                        self.workspaces_mut().current_mut().layout.add_window_capped(window, max_tabs, screen_rect, gap, min_size);
+                    }
+                    OpenMode::Split => {
+                        self.workspaces_mut().current_mut().layout.add_window_as_split(window, screen_rect, gap, min_size);
+                    }
+                }
+            }
+            if self.workspaces().current().layout.all_frames().len() != frame_count {
+                self.auto_balance(self.workspaces().current_index());
+            }
+
+            if self.config.spawn_animation != SpawnAnimation::None {
+                self.spawn_pending.insert(window);
+            }
 
             // Trace the window being managed
             if let Some(frame_id) = self.workspaces().current().layout.find_window(window) {
@@ -1845,13 +2905,41 @@ impl Wm {
         Ok(())
     }
 
-    /// Unmanage a window
+    /// Unmanage a window that was destroyed
     fn unmanage_window(&mut self, window: Window) -> Result<()> {
+        self.unmanage_window_with_reason(window, UnmanageReason::ClientDestroyed)
+    }
+
+    /// A managed window turned override-redirect (seen on its MapNotify or
+    /// ConfigureNotify). With `general.override_redirect_flip = "release"` it
+    /// leaves the layout but stays mapped, without ttwm's border.
+    fn handle_override_redirect_flip(&mut self, window: Window) -> Result<()> {
+        if self.find_window_workspace(window).is_none() {
+            return Ok(());
+        }
+        self.tracer.trace_x11_event("OverrideRedirect", Some(window), "managed window set override-redirect");
+        match self.user_config.general.override_redirect_flip {
+            OverrideRedirectFlip::Ignore => {
+                log::info!("Window 0x{:x} became override-redirect, still managing it", window);
+            }
+            OverrideRedirectFlip::Release => {
+                log::info!("Window 0x{:x} became override-redirect, releasing it", window);
+                self.unmanage_window_with_reason(window, UnmanageReason::OverrideRedirect)?;
+                self.conn.configure_window(window, &ConfigureWindowAux::new().border_width(0))?;
+                self.conn.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop managing a window, tracing why
+    fn unmanage_window_with_reason(&mut self, window: Window, reason: UnmanageReason) -> Result<()> {
         // Cancel drag if we're dragging this window
         if let Some(DragState::Tab { window: dragged_window, .. }) = self.drag_state {
             if dragged_window == window {
-                // Ungrab pointer and clear drag state
+                // Ungrab pointer/keyboard and clear drag state
                 self.conn.ungrab_pointer(x11rb::CURRENT_TIME)?;
+                self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?;
                 self.drag_state = None;
                 log::info!("Cancelled drag - dragged window was destroyed");
             }
@@ -1860,9 +2948,32 @@ impl Wm {
         // Remove from hidden set if present
         self.hidden_windows.remove(&window);
 
+        // Drop any deferred unmanage (e.g. destroyed during its grace period)
+        self.pending_unmanage.remove(&window);
+
+        // A close the client honoured is undoable; one it refused never got here
+        let close_request = self.close_requested.remove(&window);
+
+        // Stop any spawn animation
+        self.spawn_pending.remove(&window);
+        self.animator.cancel(window);
+
+        // Drop any mirrors of this window
+        #[cfg(feature = "mirror")]
+        self.mirrors.remove_where(&self.conn, |m| m.source == window)?;
+
         // Remove from tagged set if present
         self.tagged_windows.remove(&window);
 
+        // Drop the pin of a pinned float
+        self.pinned_floats.remove(&window);
+
+        // Remove from focus history
+        self.focus_history.retain(|&w| w != window);
+
+        // Drop marks pointing at this window
+        self.marks.remove_window(window);
+
         // Remove from icon cache to prevent stale icons when X11 reuses window IDs
         self.tab_bars.invalidate_icon(window);
 
@@ -1888,15 +2999,52 @@ impl Wm {
             }
         }
 
+        // Clear zoom if this window was zoomed, on whichever monitor zoomed it
+        let zoom_cleared_elsewhere = match self.monitors.clear_zoom(window) {
+            Some(monitor_id) => {
+                log::info!("Cleared zoom state for destroyed window 0x{:x}", window);
+                monitor_id != self.monitors.focused_id()
+            }
+            None => false,
+        };
+
+        // Clear maximize if this window was maximized, on whichever monitor
+        // maximized it
+        let maximize_cleared_on = self.monitors.clear_maximize(window);
+        if maximize_cleared_on.is_some() {
+            log::info!("Cleared maximize state for destroyed window 0x{:x}", window);
+        }
+
+        // Windows of disconnected monitors wait on held workspaces
+        if self.monitors.remove_held_window(window) {
+            log::info!("Unmanaging held window 0x{:x}", window);
+            return Ok(());
+        }
+
         // Find which workspace contains this window (search ALL workspaces)
         let ws_idx = self.find_window_workspace(window);
 
+        // A window that outlives being managed keeps the border grab from
+        // manage_window unless it's dropped, and its presses would freeze
+        // the pointer with nothing left to release them
+        if ws_idx.is_some()
+            && self.user_config.general.resize_from_border
+            && !matches!(reason, UnmanageReason::ClientDestroyed)
+        {
+            self.conn.ungrab_button(
+                ButtonIndex::from(self.user_config.general.resize_button),
+                window,
+                ModMask::ANY,
+            )?;
+        }
+
         if let Some(ws_idx) = ws_idx {
             // Check if floating on that workspace
-            if self.monitors.focused().workspaces.workspaces[ws_idx].is_floating(window) {
+            let was_floating = self.monitors.focused().workspaces.workspaces[ws_idx].is_floating(window);
+            if was_floating {
                 self.tracer.trace_transition(&StateTransition::WindowUnmanaged {
                     window,
-                    reason: UnmanageReason::ClientDestroyed,
+                    reason: reason.clone(),
                 });
 
                 self.monitors.focused_mut().workspaces.workspaces[ws_idx].remove_floating(window);
@@ -1905,26 +3053,56 @@ impl Wm {
                 // Tiled window
                 self.tracer.trace_transition(&StateTransition::WindowUnmanaged {
                     window,
-                    reason: UnmanageReason::ClientDestroyed,
+                    reason: reason.clone(),
                 });
 
-                self.monitors.focused_mut().workspaces.workspaces[ws_idx].layout.remove_window(window);
+                let close_focus = self.user_config.general.close_focus;
+                if let Some(request) = close_request {
+                    self.close_requested.retain(|_, r| *r != request);
+                    self.monitors.focused_mut().workspaces.workspaces[ws_idx].push_layout_history();
+                }
+                self.monitors.focused_mut().workspaces.workspaces[ws_idx].layout
+                    .remove_window_with_focus(window, close_focus, &self.focus_history);
+                self.auto_normalize(ws_idx);
                 log::info!("Unmanaging window 0x{:x} from workspace {}", window, ws_idx + 1);
             }
 
             // Update EWMH client list
             self.update_client_list()?;
 
-            // If this was focused, focus another window
+            // If this was focused, focus another window - preferring the tab
+            // chosen by close_focus in the same frame
             if self.focused_window == Some(window) {
                 self.focused_window = None;
-                self.focus_next_available_window()?;
+                let same_frame = if was_floating {
+                    None
+                } else {
+                    self.workspaces().current().layout.focused_frame().and_then(|f| f.focused_window())
+                };
+                let mru = if self.user_config.general.close_focus == CloseFocus::Mru {
+                    let ws = self.workspaces().current();
+                    self.focus_history.iter().rev().copied()
+                        .find(|&w| ws.layout.find_window(w).is_some() || ws.is_floating(w))
+                } else {
+                    None
+                };
+                match same_frame.or(mru) {
+                    Some(w) => self.focus_window(w)?,
+                    None => self.focus_next_available_window()?,
+                }
             }
 
             // Re-apply layout
             self.apply_layout()?;
         }
 
+        // The zoom covered every monitor; the one that held it gets its layout back
+        if zoom_cleared_elsewhere {
+            self.apply_layout_all_monitors()?;
+        } else if let Some(monitor_id) = maximize_cleared_on.filter(|&id| id != self.monitors.focused_id()) {
+            self.apply_layout_on(monitor_id)?;
+        }
+
         Ok(())
     }
 
@@ -1954,6 +3132,25 @@ impl Wm {
         Ok(())
     }
 
+    /// Where a tiled window on the current workspace sits on screen, border
+    /// included (see `Workspace::tiled_client_rect`).
+    fn tiled_client_rect(&self, window: Window) -> Option<Rect> {
+        self.workspaces().current().tiled_client_rect(
+            window,
+            self.usable_screen(),
+            self.gap(),
+            self.config.tab_bar_height,
+            self.config.vertical_tab_width,
+        )
+    }
+
+    /// Zoomed, fullscreen or maximized window on the current workspace and
+    /// the exact geometry it is held to (see `Workspace::takeover`)
+    fn takeover(&self) -> Option<(Window, Rect)> {
+        let span = self.monitors.bounding_box(self.user_config.general.bezel_gap);
+        self.workspaces().current().takeover(self.monitors.focused().geometry, span, self.usable_screen())
+    }
+
     /// Toggle a window between floating and tiled states
     /// If window is None, uses the focused window
     fn toggle_float(&mut self, window: Option<Window>) -> Result<()> {
@@ -1968,6 +3165,10 @@ impl Wm {
         if self.workspaces().current().is_floating(window) {
             // Currently floating -> make it tiled
             if let Some(float_info) = self.workspaces_mut().current_mut().remove_floating(window) {
+                if self.pinned_floats.remove(&window) {
+                    let ws_idx = self.workspaces().current_index();
+                    self.set_window_desktop(window, ws_idx)?;
+                }
                 log::info!(
                     "Tiling floating window 0x{:x} (was at {}, {} {}x{})",
                     window, float_info.x, float_info.y, float_info.width, float_info.height
@@ -1984,6 +3185,9 @@ impl Wm {
             // Currently tiled -> make it floating
             // Get current geometry before removing from layout
             let geom = self.conn.get_geometry(window)?.reply()?;
+            let tiled_rect = self.user_config.general.float_at_tiled_position
+                .then(|| self.tiled_client_rect(window))
+                .flatten();
 
             // Remove from tiled layout
             if let Some(_frame_id) = self.workspaces_mut().current_mut().layout.remove_window(window) {
@@ -1992,14 +3196,14 @@ impl Wm {
                     window, geom.x, geom.y, geom.width, geom.height
                 );
 
-                // Add to floating windows with current geometry
-                self.workspaces_mut().current_mut().add_floating(
-                    window,
-                    geom.x as i32,
-                    geom.y as i32,
-                    geom.width as u32,
-                    geom.height as u32,
-                );
+                // Add to floating windows with current geometry, or exactly
+                // over the tiled slot (border included) kept on the monitor
+                let bounds = self.usable_screen();
+                let ws = self.workspaces_mut().current_mut();
+                match tiled_rect {
+                    Some(slot) => ws.add_floating_at(window, slot, bounds),
+                    None => ws.add_floating(window, geom.x as i32, geom.y as i32, geom.width as u32, geom.height as u32),
+                }
 
                 // Apply layout and focus
                 self.apply_layout()?;
@@ -2010,6 +3214,33 @@ impl Wm {
         Ok(())
     }
 
+    /// Toggle whether a floating window is pinned to its monitor.
+    /// A pinned float stays mapped at the same position when its monitor
+    /// switches workspace; tiling it or closing it drops the pin.
+    /// If window is None, uses the focused window. Returns the window and its new state.
+    fn toggle_float_pin(&mut self, window: Option<Window>) -> Result<(Window, bool)> {
+        let Some(window) = window.or(self.focused_window) else {
+            anyhow::bail!("No window to pin");
+        };
+        if self.pinned_floats.remove(&window) {
+            if let Some((_, ws_idx)) = self.find_window_global(window) {
+                self.set_window_desktop(window, ws_idx)?;
+            }
+            log::info!("Unpinned floating window 0x{:x}", window);
+            return Ok((window, false));
+        }
+
+        let visible_float = self.monitors.iter()
+            .any(|(_, m)| m.workspaces.current().is_floating(window));
+        if !visible_float {
+            anyhow::bail!("Window 0x{:x} is not a floating window on a visible workspace", window);
+        }
+        self.pinned_floats.insert(window);
+        self.set_window_desktop(window, ewmh::ALL_DESKTOPS)?;
+        log::info!("Pinned floating window 0x{:x} to all workspaces", window);
+        Ok((window, true))
+    }
+
     /// Toggle fullscreen mode for a window
     /// If window is None, uses the focused window
     fn toggle_fullscreen(&mut self, window: Option<Window>) -> Result<()> {
@@ -2044,6 +3275,58 @@ impl Wm {
         Ok(())
     }
 
+    /// Toggle zoom for a window: stretch it across the combined geometry of all monitors.
+    ///
+    /// The window keeps its place in the layout (or floating list), so toggling off
+    /// restores its previous tiled/floating geometry.
+    fn toggle_zoom(&mut self, window: Option<Window>) -> Result<()> {
+        let window = match window.or(self.focused_window) {
+            Some(w) => w,
+            None => {
+                log::info!("No window to toggle zoom");
+                return Ok(());
+            }
+        };
+
+        if self.workspaces().current().zoomed_window == Some(window) {
+            log::info!("Exiting zoom for window 0x{:x}", window);
+            self.workspaces_mut().current_mut().zoomed_window = None;
+        } else {
+            log::info!("Zooming window 0x{:x} across all monitors", window);
+            self.workspaces_mut().current_mut().zoomed_window = Some(window);
+        }
+
+        self.apply_layout()?;
+        self.focus_window(window)?;
+        Ok(())
+    }
+
+    /// Toggle maximize for a window: fill the usable area of the monitor,
+    /// leaving docks and the bar visible.
+    ///
+    /// Like zoom, the window keeps its place in the layout (or floating list).
+    fn toggle_maximize(&mut self, window: Option<Window>) -> Result<()> {
+        let window = match window.or(self.focused_window) {
+            Some(w) => w,
+            None => {
+                log::info!("No window to toggle maximize");
+                return Ok(());
+            }
+        };
+
+        if self.workspaces().current().maximized_window == Some(window) {
+            log::info!("Exiting maximize for window 0x{:x}", window);
+            self.workspaces_mut().current_mut().maximized_window = None;
+        } else {
+            log::info!("Maximizing window 0x{:x}", window);
+            self.workspaces_mut().current_mut().maximized_window = Some(window);
+        }
+
+        self.apply_layout()?;
+        self.focus_window(window)?;
+        Ok(())
+    }
+
     /// Update _NET_WM_STATE property for fullscreen
     fn update_wm_state(&self, window: Window, fullscreen: bool) -> Result<()> {
         ewmh::update_wm_state_fullscreen(&self.conn, &self.atoms, window, fullscreen)
@@ -2057,10 +3340,80 @@ impl Wm {
         Ok(())
     }
 
+    /// Toggle the focused frame between tabbed and stacked titles.
+    /// Returns true if the frame is now stacked.
+    fn toggle_stacked(&mut self) -> Result<bool> {
+        let stacked = self.workspaces_mut().current_mut().layout.toggle_stacked();
+        log::info!("Set frame style to {}", if stacked { "stacked" } else { "tabbed" });
+        self.apply_layout()?;
+        Ok(stacked)
+    }
+
+    /// Set horizontal or vertical tabs on the focused frame.
+    /// Returns the focused frame's resulting orientation (true = vertical).
+    fn set_vertical_tabs(&mut self, enabled: bool) -> Result<bool> {
+        if !self.workspaces_mut().current_mut().layout.set_vertical_tabs(enabled) {
+            anyhow::bail!("No focused frame");
+        }
+        log::info!("Set tabs to {}", if enabled { "vertical" } else { "horizontal" });
+        // apply_layout() resizes the tab bar window for the new orientation
+        self.apply_layout()?;
+        Ok(enabled)
+    }
+
+    /// Swap the focused frame with its sibling in the parent split
+    fn swap_with_sibling(&mut self) -> Result<()> {
+        self.workspaces_mut().current_mut().push_layout_history();
+        if self.workspaces_mut().current_mut().layout.swap_with_sibling() {
+            log::info!("Swapped focused frame with its sibling");
+            self.apply_layout()?;
+        } else {
+            self.workspaces_mut().current_mut().discard_layout_history();
+            log::info!("Focused frame has no sibling to swap with");
+        }
+        Ok(())
+    }
+
+    /// Restore the layout from before the last layout operation
+    fn undo_layout(&mut self) -> Result<()> {
+        if !self.workspaces_mut().current_mut().undo_layout() {
+            log::info!("Nothing to undo");
+            return Ok(());
+        }
+
+        self.apply_layout()?;
+        let window = self.workspaces().current().layout.focused_frame()
+            .and_then(|f| f.focused_window());
+        if let Some(window) = window {
+            self.suppress_enter_focus = true;
+            self.focus_window(window)?;
+        }
+        log::info!("Undid last layout operation");
+        Ok(())
+    }
+
+    /// Toggle the locked flag on the focused frame
+    fn toggle_frame_lock(&mut self) -> Result<()> {
+        let locked = self.workspaces_mut().current_mut().layout.toggle_frame_lock();
+        log::info!("Frame {}", if locked { "locked" } else { "unlocked" });
+        self.apply_layout()?;
+        Ok(())
+    }
+
     /// Cycle focus to the next/previous window (across all frames and floating windows)
     fn cycle_focus(&mut self, forward: bool) -> Result<()> {
-        // Build a list of all windows: tiled first, then floating
-        let mut windows = self.workspaces().current().layout.all_windows();
+        if self.user_config.general.cycle_monitor_scope == CycleMonitorScope::All
+            && self.monitors.count() > 1
+        {
+            return self.cycle_focus_all_monitors(forward);
+        }
+
+        // Build a list of windows: tiled first, then floating
+        let layout = &self.workspaces().current().layout;
+        let mut windows = match self.user_config.general.cycle_scope {
+            CycleScope::All => layout.all_windows(),
+            CycleScope::Visible => layout.visible_windows(),
+        };
         windows.extend(self.workspaces().current().floating_window_ids());
 
         if windows.is_empty() {
@@ -2071,14 +3424,9 @@ impl Wm {
             .and_then(|w| windows.iter().position(|&x| x == w))
             .unwrap_or(0);
 
-        let next_idx = if forward {
-            (current_idx + 1) % windows.len()
-        } else {
-            if current_idx == 0 {
-                windows.len() - 1
-            } else {
-                current_idx - 1
-            }
+        let wrap = self.user_config.general.focus_wrap;
+        let Some(next_idx) = layout::step_index(current_idx, windows.len(), forward, wrap) else {
+            return Ok(());
         };
 
         let window = windows[next_idx];
@@ -2087,12 +3435,45 @@ impl Wm {
         Ok(())
     }
 
+    /// Cycle focus through the current workspace of every monitor
+    /// (general.cycle_monitor_scope = "all"), moving monitor focus along
+    fn cycle_focus_all_monitors(&mut self, forward: bool) -> Result<()> {
+        let visible_only = self.user_config.general.cycle_scope == CycleScope::Visible;
+        let windows = self.monitors.cycle_windows(visible_only);
+        if windows.is_empty() {
+            return Ok(());
+        }
+
+        let current_idx = self.focused_window
+            .and_then(|w| windows.iter().position(|&(_, x)| x == w))
+            .unwrap_or(0);
+
+        let wrap = self.user_config.general.focus_wrap;
+        let Some(next_idx) = layout::step_index(current_idx, windows.len(), forward, wrap) else {
+            return Ok(());
+        };
+
+        let (monitor_id, window) = windows[next_idx];
+        if monitor_id != self.monitors.focused_id() {
+            // Leave the target as the monitor's last focused window, so
+            // switching monitors focuses it directly
+            if let Some(monitor) = self.monitors.get_mut(monitor_id) {
+                monitor.workspaces.current_mut().last_focused_window = Some(window);
+            }
+            self.focus_monitor(monitor_id)?;
+        }
+        self.focus_window(window)?;
+
+        Ok(())
+    }
+
     /// Cycle tabs within the focused frame
     fn cycle_tab(&mut self, forward: bool) -> Result<()> {
         // Capture old tab index for tracing
         let old_tab = self.workspaces().current().layout.focused_frame().map(|f| f.focused);
 
-        if let Some(window) = self.workspaces_mut().current_mut().layout.cycle_tab(forward) {
+        let wrap = self.user_config.general.focus_wrap;
+        if let Some(window) = self.workspaces_mut().current_mut().layout.cycle_tab(forward, wrap) {
             // Trace the tab switch
             if let (Some(old), Some(frame)) = (old_tab, self.workspaces().current().layout.focused_frame()) {
                 self.tracer.trace_transition(&StateTransition::TabSwitched {
@@ -2103,7 +3484,7 @@ impl Wm {
             }
 
             self.apply_layout()?;
-            self.focus_window(window)?;
+            self.focus_tab_window(window)?;
             log::info!("Cycled to {} tab", if forward { "next" } else { "previous" });
         }
         Ok(())
@@ -2127,16 +3508,69 @@ impl Wm {
             }
 
             self.apply_layout()?;
-            self.focus_window(window)?;
+            self.focus_tab_window(window)?;
             log::info!("Focused tab {}", num);
         }
         Ok(())
     }
 
+    /// Focus a window selected via keyboard tab navigation.
+    ///
+    /// Suppresses the EnterNotify generated by mapping the new tab so the pointer
+    /// position doesn't steal focus back, optionally warping the pointer to the window.
+    fn focus_tab_window(&mut self, window: Window) -> Result<()> {
+        self.suppress_enter_focus = true;
+        self.focus_window(window)?;
+
+        if self.user_config.general.tab_cycle_warps {
+            let geom = self.conn.get_geometry(window)?.reply()?;
+            self.conn.warp_pointer(
+                x11rb::NONE,
+                window,
+                0, 0, 0, 0,
+                (geom.width / 2) as i16,
+                (geom.height / 2) as i16,
+            )?;
+            self.conn.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Move the focused tab to the first position in its frame
+    fn promote_focused_tab(&mut self) -> Result<()> {
+        self.workspaces_mut().current_mut().push_layout_history();
+        if let Some(window) = self.workspaces_mut().current_mut().layout.promote_focused_tab() {
+            self.apply_layout()?;
+            self.focus_window(window)?;
+            log::info!("Promoted window 0x{:x} to first tab", window);
+        } else {
+            self.workspaces_mut().current_mut().discard_layout_history();
+        }
+        Ok(())
+    }
+
+    /// Check whether the focused frame can be split without going below min_frame_size
+    fn can_split_focused(&self, direction: SplitDirection) -> bool {
+        self.workspaces().current().layout.can_split_focused(
+            direction,
+            self.usable_screen(),
+            self.gap(),
+            self.config.min_frame_size,
+        )
+    }
+
     /// Split the focused frame
     fn split_focused(&mut self, direction: SplitDirection) -> Result<()> {
+        self.workspaces_mut().current_mut().layout.ensure_focused_frame();
+        if !self.can_split_focused(direction) {
+            log::info!("Not splitting {:?}: frames would be smaller than {}px", direction, self.config.min_frame_size);
+            return Ok(());
+        }
+
         let old_frame = self.workspaces().current().layout.focused;
+        self.workspaces_mut().current_mut().push_layout_history();
         self.workspaces_mut().current_mut().layout.split_focused(direction);
+        self.auto_balance(self.workspaces().current_index());
         let new_frame = self.workspaces().current().layout.focused;
 
         // Trace the split
@@ -2151,11 +3585,40 @@ impl Wm {
         Ok(())
     }
 
+    /// Add an empty frame on a side of the focused frame, leaving the focused
+    /// frame's windows where they are (see `LayoutTree::new_frame`)
+    fn new_frame(&mut self, side: Direction) -> Result<()> {
+        self.workspaces_mut().current_mut().layout.ensure_focused_frame();
+        let direction = match side {
+            Direction::Left | Direction::Right => SplitDirection::Horizontal,
+            Direction::Up | Direction::Down => SplitDirection::Vertical,
+        };
+        if !self.can_split_focused(direction) {
+            log::info!("Not adding a frame {:?}: frames would be smaller than {}px", side, self.config.min_frame_size);
+            return Ok(());
+        }
+
+        let old_frame = self.workspaces().current().layout.focused;
+        self.workspaces_mut().current_mut().push_layout_history();
+        let new_frame = self.workspaces_mut().current_mut().layout.new_frame(side);
+        self.auto_balance(self.workspaces().current_index());
+
+        self.tracer.trace_transition(&StateTransition::FrameSplit {
+            original_frame: format!("{:?}", old_frame),
+            new_frame: format!("{:?}", new_frame),
+            direction: format!("{:?}", direction),
+        });
+
+        self.apply_layout()?;
+        log::info!("Added empty frame {:?} of the focused frame", side);
+        Ok(())
+    }
+
     /// Focus frame in the given spatial direction
     fn focus_frame(&mut self, direction: Direction) -> Result<()> {
         let old_focused_frame = self.workspaces().current().layout.focused;
         let screen_rect = self.usable_screen();
-        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.config.gap);
+        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
 
         if self.workspaces_mut().current_mut().layout.focus_spatial(direction, &geometries) {
             let new_focused_frame = self.workspaces().current().layout.focused;
@@ -2204,7 +3667,7 @@ impl Wm {
                     self.conn.change_window_attributes(
                         empty_window,
                         &ChangeWindowAttributesAux::new()
-                            .border_pixel(self.config.border_focused),
+                            .border_pixel(self.border_focused()),
                     )?;
                 }
 
@@ -2214,6 +3677,101 @@ impl Wm {
         Ok(())
     }
 
+    /// Focus the frame in the given direction, continuing onto the adjacent
+    /// monitor's nearest frame when there is no frame that way on this one
+    fn focus_direction(&mut self, direction: Direction) -> Result<()> {
+        let screen_rect = self.usable_screen();
+        let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
+
+        if self.workspaces().current().layout.find_frame_in_direction(direction, &geometries).is_some() {
+            self.focus_frame(direction)?;
+        } else {
+            let focused_frame = self.workspaces().current().layout.focused;
+            let from = geometries.iter()
+                .find(|(id, _)| *id == focused_frame)
+                .map(|(_, rect)| *rect)
+                .unwrap_or(screen_rect);
+            let across = self.monitors.frame_across(direction, from, |id, monitor| {
+                (self.usable_area(id), self.config.gap_for(monitor.workspaces.current_index()))
+            });
+            let Some((target_monitor, target)) = across else {
+                return Ok(());
+            };
+
+            self.focus_monitor(target_monitor)?;
+            self.workspaces_mut().current_mut().layout.focused = target;
+            let window = self.workspaces().current().layout.focused_frame()
+                .and_then(|f| f.focused_window());
+            match window {
+                Some(window) => self.focus_window(window)?,
+                None => self.focused_window = None,
+            }
+            self.apply_layout()?;
+            log::info!("Focus {:?} crossed to monitor {:?}", direction, target_monitor);
+        }
+
+        if self.user_config.general.focus_direction_warps {
+            let screen_rect = self.usable_screen();
+            let layout = &self.workspaces().current().layout;
+            if let Some((_, rect)) = layout.calculate_geometries(screen_rect, self.gap())
+                .into_iter()
+                .find(|(id, _)| *id == layout.focused)
+            {
+                self.suppress_enter_focus = true;
+                self.conn.warp_pointer(
+                    x11rb::NONE,
+                    self.root,
+                    0, 0, 0, 0,
+                    rect.center_x() as i16,
+                    rect.center_y() as i16,
+                )?;
+                self.conn.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Focused border color for the current workspace
+    fn border_focused(&self) -> u32 {
+        self.config.border_focused_for(self.workspaces().current_index())
+    }
+
+    /// Gap between windows on the current workspace
+    fn gap(&self) -> u32 {
+        self.config.gap_for(self.workspaces().current_index())
+    }
+
+    /// Keep dock windows (status bars) and the built-in bar above tiled
+    /// windows and tab bars, and a fullscreen, zoomed or maximized window
+    /// above them.
+    fn raise_docks(&self) -> Result<()> {
+        self.raise_in_order(&self.dock_raise_order())
+    }
+
+    /// Docks and the built-in bar, then any fullscreen, zoomed or maximized
+    /// window on the current workspace, bottom to top
+    fn dock_raise_order(&self) -> Vec<Window> {
+        let span = self.monitors.bounding_box(self.user_config.general.bezel_gap);
+        let docks = self.dock_windows.keys().copied().chain(self.status_bar.windows());
+        self.workspaces().current().raise_order(
+            docks,
+            self.monitors.focused().geometry,
+            span,
+            self.usable_screen(),
+        )
+    }
+
+    /// Raise `windows` one after another, so the last ends up on top
+    fn raise_in_order(&self, windows: &[Window]) -> Result<()> {
+        for &window in windows {
+            self.conn.configure_window(
+                window,
+                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+            )?;
+        }
+        Ok(())
+    }
+
     /// Focus a specific monitor by ID
     fn focus_monitor(&mut self, monitor_id: MonitorId) -> Result<()> {
         let old_monitor_id = self.monitors.focused_id();
@@ -2233,6 +3791,7 @@ impl Wm {
         }
 
         log::info!("Focused monitor {:?}", monitor_id);
+        self.update_current_desktop()?;
 
         // Restore focus to new monitor's last focused window
         let last_focused = self.monitors.focused().workspaces.current().last_focused_window;
@@ -2250,6 +3809,25 @@ impl Wm {
         Ok(())
     }
 
+    /// Focus the monitor under the pointer (general.monitor_focus_follows_mouse).
+    ///
+    /// The pointer has to be a few pixels inside the new monitor before it
+    /// takes focus, so jitter along a shared edge doesn't flip focus back and forth.
+    fn focus_monitor_at_pointer(&mut self, root_x: i32, root_y: i32) -> Result<()> {
+        const EDGE_MARGIN: i32 = 8;
+
+        if !self.user_config.general.monitor_focus_follows_mouse || self.drag_state.is_some() {
+            return Ok(());
+        }
+        match self.monitors.monitor_at_inset(root_x, root_y, EDGE_MARGIN) {
+            Some(monitor_id) if monitor_id != self.monitors.focused_id() => {
+                log::debug!("Pointer moved onto monitor {:?}", monitor_id);
+                self.focus_monitor(monitor_id)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Focus monitor in the given direction
     fn focus_monitor_direction(&mut self, direction: Direction) -> Result<()> {
         if let Some(target_monitor) = self.monitors.monitor_in_direction(direction) {
@@ -2279,23 +3857,31 @@ impl Wm {
             }
         }
 
-        // Focus the new window
-        self.conn.set_input_focus(InputFocus::POINTER_ROOT, window, x11rb::CURRENT_TIME)?;
-
-        // Raise the window
-        self.conn.configure_window(
-            window,
-            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-        )?;
+        // Focus the new window. Following ICCCM's input models, windows with
+        // input=False in WM_HINTS never get set_input_focus, and clients
+        // listing WM_TAKE_FOCUS are also told to take focus themselves.
+        // Focus moves to the check window for input=False windows so keys
+        // don't keep going to the previously focused window
+        if window_query::accepts_input_focus(&self.conn, window) {
+            self.conn.set_input_focus(InputFocus::POINTER_ROOT, window, x11rb::CURRENT_TIME)?;
+        } else {
+            self.conn.set_input_focus(InputFocus::POINTER_ROOT, self.check_window, x11rb::CURRENT_TIME)?;
+        }
+        if window_query::read_protocols(&self.conn, &self.atoms, window).contains(&self.atoms.wm_take_focus) {
+            window_query::send_take_focus(&self.conn, &self.atoms, window, self.last_event_time)?;
+        }
 
         // Set focused border color
         self.conn.change_window_attributes(
             window,
             &ChangeWindowAttributesAux::new()
-                .border_pixel(self.config.border_focused),
+                .border_pixel(self.border_focused()),
         )?;
 
         self.focused_window = Some(window);
+        self.focus_history.retain(|&w| w != window);
+        self.focus_history.push(window);
+        self.workspaces_mut().record_focus(window);
 
         // Clear urgent state if the window was urgent
         if self.urgent.contains(window) {
@@ -2313,8 +3899,11 @@ impl Wm {
             });
         }
 
-        // For floating windows, just update EWMH and return
+        // For floating windows, raise them under the docks, update EWMH and return
         if self.workspaces().current().is_floating(window) {
+            let mut order = vec![window];
+            order.extend(self.dock_raise_order());
+            self.raise_in_order(&order)?;
             log::info!("Focused floating window 0x{:x}", window);
             self.update_active_window()?;
             self.conn.flush()?;
@@ -2328,17 +3917,19 @@ impl Wm {
             let mon_id = self.monitors.focused_id();
             let ws_idx = self.workspaces().current_index();
 
-            // Re-raise the tab bar if this frame has one (so it stays above the window)
-            if let Some(&tab_window) = self.tab_bars.windows.get(&(mon_id, ws_idx, frame_id)) {
-                self.conn.configure_window(
-                    tab_window,
-                    &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-                )?;
-            }
+            // Raise the window, its tab bar(s) above it, and docks above everything
+            let order = tab_bar::raise_order(
+                &self.tab_bars.windows,
+                window,
+                (mon_id, ws_idx, frame_id),
+                self.user_config.general.raise_tabbar_always,
+                self.dock_raise_order(),
+            );
+            self.raise_in_order(&order)?;
 
             // Redraw tab bars (always redraw current frame, also old frame if different)
             let screen_rect = self.usable_screen();
-            let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.config.gap);
+            let geometries = self.workspaces().current().layout.calculate_geometries(screen_rect, self.gap());
             let geometry_map: std::collections::HashMap<_, _> = geometries.into_iter().collect();
 
             // Redraw old focused frame's tab bar if it changed
@@ -2366,6 +3957,8 @@ impl Wm {
                     }
                 }
             }
+        } else {
+            self.raise_in_order(&[window])?;
         }
 
         // Update EWMH active window
@@ -2377,49 +3970,72 @@ impl Wm {
     }
 
     /// Close the focused window gracefully
-    fn close_focused_window(&self) -> Result<()> {
+    fn close_focused_window(&mut self) -> Result<()> {
         if let Some(window) = self.focused_window {
             log::info!("Closing window 0x{:x}", window);
-
-            if window_query::supports_delete_protocol(&self.conn, &self.atoms, window) {
-                log::debug!("Using WM_DELETE_WINDOW protocol");
-                window_query::send_delete_window(&self.conn, &self.atoms, window)?;
-            } else {
-                log::debug!("Window doesn't support WM_DELETE_WINDOW, killing client");
-                self.conn.kill_client(window)?;
-                self.conn.flush()?;
-            }
+            self.note_close_requested(&[window]);
+            self.request_close(window)?;
         }
         Ok(())
     }
 
-    /// Move a window to a different workspace
-    fn move_window_to_workspace(&mut self, window: Window, target: usize) -> Result<()> {
-        if target >= 9 {
-            return Ok(());
+    /// Ask every window in the focused frame to close.
+    ///
+    /// Windows supporting WM_DELETE_WINDOW get the graceful request and may
+    /// refuse (e.g. to ask about unsaved changes), in which case they stay in
+    /// the frame; others are killed. The frame itself is kept for reuse, and
+    /// the undo entry is only recorded once the first window actually goes.
+    /// Returns the number of windows asked to close.
+    fn close_focused_frame(&mut self) -> Result<usize> {
+        let windows = self.workspaces().current().layout.focused_frame()
+            .map(|f| f.windows.clone())
+            .unwrap_or_default();
+        if windows.is_empty() {
+            return Ok(0);
         }
 
-        let current_ws = self.workspaces().current_index();
-
-        // Find which workspace has this window
-        let source_ws = self.monitors.focused().workspaces.workspaces.iter()
-            .enumerate()
-            .find(|(_, ws)| ws.layout.find_window(window).is_some())
-            .map(|(idx, _)| idx);
+        self.note_close_requested(&windows);
+        for &window in &windows {
+            if let Err(e) = self.request_close(window) {
+                log::warn!("Failed to close window 0x{:x}: {}", window, e);
+            }
+        }
+        self.conn.flush()?;
+        log::info!("Asked {} window(s) in the focused frame to close", windows.len());
+        Ok(windows.len())
+    }
 
-        let Some(source_ws) = source_ws else {
-            return Ok(()); // Window not found
-        };
+    /// Remember windows asked to close together, so they share one undo entry
+    fn note_close_requested(&mut self, windows: &[Window]) {
+        let request = self.next_close_request;
+        self.next_close_request = request.wrapping_add(1);
+        for &window in windows {
+            self.close_requested.insert(window, request);
+        }
+    }
 
-        if source_ws == target {
-            return Ok(()); // Already on target workspace
+    /// Close a window via WM_DELETE_WINDOW if supported, otherwise kill its client
+    fn request_close(&self, window: Window) -> Result<()> {
+        if window_query::supports_delete_protocol(&self.conn, &self.atoms, window) {
+            log::debug!("Using WM_DELETE_WINDOW protocol for 0x{:x}", window);
+            window_query::send_delete_window(&self.conn, &self.atoms, window)?;
+        } else {
+            log::debug!("Window 0x{:x} doesn't support WM_DELETE_WINDOW, killing client", window);
+            self.conn.kill_client(window)?;
+            self.conn.flush()?;
         }
+        Ok(())
+    }
 
-        // Remove from source workspace
-        self.monitors.focused_mut().workspaces.workspaces[source_ws].layout.remove_window(window);
+    /// Move a window to a different workspace
+    fn move_window_to_workspace(&mut self, window: Window, target: usize, origin: MoveOrigin) -> Result<()> {
+        let current_ws = self.workspaces().current_index();
 
-        // Add to target workspace
-        self.monitors.focused_mut().workspaces.workspaces[target].layout.add_window(window);
+        // Nothing to do if the window isn't tiled here or is already there
+        let Some(source_ws) = self.workspaces_mut().move_window(window, target) else {
+            return Ok(());
+        };
+        self.auto_normalize(source_ws);
 
         // Update window's _NET_WM_DESKTOP property
         self.set_window_desktop(window, target)?;
@@ -2449,6 +4065,13 @@ impl Wm {
         self.update_client_list()?;
 
         log::info!("Moved window 0x{:x} from workspace {} to {}", window, source_ws + 1, target + 1);
+
+        // general.move_follows: go along with the window and focus it there
+        if origin.follows(self.user_config.general.move_follows) {
+            if let Some(old_idx) = self.workspaces_mut().follow_window(window, target) {
+                self.perform_workspace_switch(old_idx)?;
+            }
+        }
         Ok(())
     }
 
@@ -2468,12 +4091,138 @@ impl Wm {
         Ok(())
     }
 
+    /// Size the first child of the focused split to an exact pixel count.
+    /// Returns the resulting size, or None if the focused frame is not in a split.
+    fn set_split_pixels(&mut self, pixels: u32) -> Result<Option<u32>> {
+        let screen = self.usable_screen();
+        let gap = self.gap();
+        let result = self.workspaces_mut().current_mut().layout.set_focused_split_pixels(screen, gap, pixels);
+        if let Some(size) = result {
+            self.apply_layout()?;
+            log::info!("Set split first child to {}px (requested {}px)", size, pixels);
+        }
+        Ok(result)
+    }
+
+    /// Collapse empty frames left behind on a workspace of the focused monitor,
+    /// if `general.auto_normalize` is enabled.
+    fn auto_normalize(&mut self, ws_idx: usize) {
+        if !self.user_config.general.auto_normalize {
+            return;
+        }
+        let removed = self.monitors.focused_mut().workspaces.workspaces[ws_idx].layout.normalize();
+        if removed > 0 {
+            log::info!("Collapsed {} empty frame(s) on workspace {}", removed, ws_idx + 1);
+            self.auto_balance(ws_idx);
+        }
+    }
+
+    /// Equalize split ratios on a workspace of the focused monitor after frames
+    /// were added or removed, if `general.auto_balance` is enabled.
+    fn auto_balance(&mut self, ws_idx: usize) {
+        if self.user_config.general.auto_balance {
+            self.monitors.focused_mut().workspaces.workspaces[ws_idx].layout.balance();
+        }
+    }
+
+    /// Equalize all split ratios on the current workspace
+    fn balance_splits(&mut self) -> Result<()> {
+        self.workspaces_mut().current_mut().push_layout_history();
+        if !self.workspaces_mut().current_mut().layout.balance() {
+            self.workspaces_mut().current_mut().discard_layout_history();
+            return Ok(());
+        }
+        self.apply_layout()?;
+        log::info!("Balanced splits");
+        Ok(())
+    }
+
+    /// Collapse empty frames on the current workspace (see `LayoutTree::normalize`)
+    fn normalize_tree(&mut self) -> Result<usize> {
+        self.workspaces_mut().current_mut().push_layout_history();
+        let removed = self.workspaces_mut().current_mut().layout.normalize();
+        if removed == 0 {
+            self.workspaces_mut().current_mut().discard_layout_history();
+            return Ok(0);
+        }
+        self.auto_balance(self.workspaces().current_index());
+        self.apply_layout()?;
+        log::info!("Normalized layout tree, removed {} empty frame(s)", removed);
+        Ok(removed)
+    }
+
+    /// Re-apply border widths and colors to every managed window (and empty
+    /// frame placeholder) from the current focus and config, for when they
+    /// got out of step. Fullscreen, zoomed and maximized windows keep no border.
+    /// Returns the number of windows fixed up.
+    fn repair_borders(&mut self) -> Result<usize> {
+        let mut borders = Vec::new();
+        for (_, monitor) in self.monitors.iter() {
+            for (idx, ws) in monitor.workspaces.workspaces.iter().enumerate() {
+                let takeover = [ws.fullscreen_window, ws.zoomed_window, ws.maximized_window];
+                for window in ws.layout.all_windows().into_iter().chain(ws.floating_window_ids()) {
+                    let width = if takeover.contains(&Some(window)) { 0 } else { self.config.border_width };
+                    let color = if self.focused_window == Some(window) {
+                        self.config.border_focused_for(idx)
+                    } else {
+                        self.config.border_unfocused
+                    };
+                    borders.push((window, width, color));
+                }
+            }
+        }
+        for &(window, width, color) in &borders {
+            self.conn.configure_window(window, &ConfigureWindowAux::new().border_width(width))?;
+            self.conn.change_window_attributes(window, &ChangeWindowAttributesAux::new().border_pixel(color))?;
+        }
+
+        // Only the focused frame of the focused monitor's workspace is highlighted
+        let focused_key = (
+            self.monitors.focused_id(),
+            self.workspaces().current_index(),
+            self.workspaces().current().layout.focused,
+        );
+        for (&key, &empty_window) in &self.tab_bars.empty_frame_windows {
+            let color = if key == focused_key {
+                self.config.border_focused_for(key.1)
+            } else {
+                self.config.border_unfocused
+            };
+            self.conn.change_window_attributes(empty_window, &ChangeWindowAttributesAux::new().border_pixel(color))?;
+        }
+        self.conn.flush()?;
+
+        log::info!("Repaired borders of {} windows", borders.len());
+        Ok(borders.len())
+    }
+
+    /// Move every window on the current workspace into one frame
+    /// (see `LayoutTree::flatten`). Returns the number of frames removed.
+    fn flatten_workspace(&mut self) -> Result<usize> {
+        self.workspaces_mut().current_mut().push_layout_history();
+        let removed = self.workspaces_mut().current_mut().layout.flatten();
+        if removed == 0 {
+            self.workspaces_mut().current_mut().discard_layout_history();
+            return Ok(0);
+        }
+        self.apply_layout()?;
+        if let Some(window) = self.workspaces().current().layout.focused_frame().and_then(|f| f.focused_window()) {
+            self.suppress_enter_focus = true;
+            self.focus_window(window)?;
+        }
+        log::info!("Flattened workspace, removed {} frame(s)", removed);
+        Ok(removed)
+    }
+
     /// Move the focused window to an adjacent frame
     fn move_window(&mut self, forward: bool) -> Result<()> {
         // Capture source frame before move
         let from_frame = self.workspaces().current().layout.focused;
 
-        if let Some(window) = self.workspaces_mut().current_mut().layout.move_window_to_adjacent(forward) {
+        self.workspaces_mut().current_mut().push_layout_history();
+        let wrap = self.user_config.general.focus_wrap;
+        if let Some(window) = self.workspaces_mut().current_mut().layout.move_window_to_adjacent(forward, wrap) {
+            self.auto_normalize(self.workspaces().current_index());
             // Trace the move
             let to_frame = self.workspaces().current().layout.focused;
             self.tracer.trace_transition(&StateTransition::WindowMoved {
@@ -2486,24 +4235,46 @@ impl Wm {
             self.suppress_enter_focus = true;
             self.focus_window(window)?;
             log::info!("Moved window 0x{:x} to {} frame", window, if forward { "next" } else { "previous" });
+        } else {
+            self.workspaces_mut().current_mut().discard_layout_history();
         }
         Ok(())
     }
 
+    /// Pull a tiled window on the current workspace into the focused frame as
+    /// a new tab and focus it (the inverse of moving a window away). Does
+    /// nothing if the window is already in the focused frame.
+    fn pull_window(&mut self, window: Window) -> Result<()> {
+        let layout = &self.workspaces().current().layout;
+        let Some(from_frame) = layout.find_window(window) else {
+            anyhow::bail!("Window 0x{:x} is not tiled on the current workspace", window);
+        };
+        let to_frame = layout.focused;
+        if from_frame == to_frame {
+            log::debug!("Window 0x{:x} is already in the focused frame", window);
+            return Ok(());
+        }
+
+        self.workspaces_mut().current_mut().push_layout_history();
+        self.workspaces_mut().current_mut().layout.pull_window_to_focused(window);
+        self.auto_normalize(self.workspaces().current_index());
+        self.tracer.trace_transition(&StateTransition::WindowMoved {
+            window,
+            from_frame: format!("{:?}", from_frame),
+            to_frame: format!("{:?}", to_frame),
+        });
+
+        self.apply_layout()?;
+        self.suppress_enter_focus = true;
+        self.focus_window(window)?;
+        log::info!("Pulled window 0x{:x} into the focused frame", window);
+        Ok(())
+    }
+
     /// Execute a window manager action
     fn execute_action(&mut self, action: WmAction) -> Result<()> {
         match action {
-            WmAction::Spawn(ref command) => {
-                log::info!("Spawning: {}", command);
-                let parts: Vec<&str> = command.split_whitespace().collect();
-                if let Some((program, args)) = parts.split_first() {
-                    let mut cmd = Command::new(program);
-                    cmd.args(args);
-                    if let Err(e) = cmd.spawn() {
-                        log::error!("Failed to spawn {}: {}", command, e);
-                    }
-                }
-            }
+            WmAction::Spawn(ref command) => self.spawn(command),
             WmAction::CycleTabForward => self.cycle_tab(true)?,
             WmAction::CycleTabBackward => self.cycle_tab(false)?,
             WmAction::FocusNext => self.cycle_focus(true)?,
@@ -2531,10 +4302,32 @@ impl Wm {
             WmAction::UntagAll => self.untag_all_windows()?,
             WmAction::ToggleFloat => self.toggle_float(None)?,
             WmAction::ToggleFullscreen => self.toggle_fullscreen(None)?,
+            WmAction::ToggleZoomAllMonitors => self.toggle_zoom(None)?,
             WmAction::ToggleVerticalTabs => self.toggle_vertical_tabs()?,
+            WmAction::ToggleStacked => {
+                self.toggle_stacked()?;
+            }
+            WmAction::ToggleFrameLock => self.toggle_frame_lock()?,
+            WmAction::SwapWithSibling => self.swap_with_sibling()?,
+            WmAction::UndoLayout => self.undo_layout()?,
+            WmAction::TogglePresentationMode => self.toggle_presentation_mode()?,
+            WmAction::SetMark => self.start_mark_prompt(MarkPrompt::Set)?,
+            WmAction::GotoMark => self.start_mark_prompt(MarkPrompt::Goto)?,
+            WmAction::PullWindow => self.start_mark_prompt(MarkPrompt::Pull)?,
+            WmAction::Launcher => self.open_launcher()?,
+            WmAction::ShowHelp => self.show_help()?,
+            WmAction::CloseFrame => {
+                self.close_focused_frame()?;
+            }
+            WmAction::FlattenWorkspace => {
+                self.flatten_workspace()?;
+            }
             WmAction::FocusUrgent => self.focus_urgent()?,
             WmAction::FocusMonitorLeft => self.focus_monitor_direction(Direction::Left)?,
             WmAction::FocusMonitorRight => self.focus_monitor_direction(Direction::Right)?,
+            WmAction::FocusDirection(direction) => self.focus_direction(direction)?,
+            WmAction::NewFrame(side) => self.new_frame(side)?,
+            WmAction::PromoteToFirst => self.promote_focused_tab()?,
         }
         Ok(())
     }
@@ -2580,12 +4373,171 @@ impl Wm {
                     log::error!("Error polling for X11 event: {}", e);
                 }
             }
+
+            self.process_pending_unmanage();
+            self.step_animations();
+            if let Err(e) = self.workspace_indicator.step(&self.conn, self.atoms.net_wm_window_opacity, std::time::Instant::now()) {
+                log::warn!("Failed to update workspace indicator: {}", e);
+            }
+            self.check_idle();
+            self.children.reap();
         }
 
         log::info!("Exiting window manager");
         Ok(())
     }
 
+    /// Hand the display back cleanly (`general.exit_behavior = "remap"`):
+    /// map every managed window on every monitor and workspace without a
+    /// border, remove ttwm's own windows and stop redirecting the root.
+    /// Overlays not destroyed here go away when the connection closes.
+    fn shutdown(&mut self) -> Result<()> {
+        if let Some(barriers) = &mut self.barriers {
+            barriers.destroy(&self.conn)?;
+        }
+        if self.user_config.general.exit_behavior == ExitBehavior::Leave {
+            return Ok(());
+        }
+
+        let windows: Vec<Window> = self.monitors.iter()
+            .flat_map(|(_, monitor)| monitor.workspaces.workspaces.iter())
+            .flat_map(|ws| ws.layout.all_windows().into_iter().chain(ws.floating_window_ids()))
+            .collect();
+        for &window in &windows {
+            self.conn.configure_window(window, &ConfigureWindowAux::new().border_width(0))?;
+            self.conn.map_window(window)?;
+        }
+        log::info!("Remapped {} windows for exit", windows.len());
+
+        self.tab_bars.destroy_all(&self.conn);
+        self.conn.change_window_attributes(self.root, &ChangeWindowAttributesAux::new().event_mask(EventMask::NO_EVENT))?;
+        self.conn.delete_property(self.root, self.atoms.net_supporting_wm_check)?;
+        self.conn.delete_property(self.root, self.atoms.net_active_window)?;
+        self.conn.destroy_window(self.check_window)?;
+        self.conn.set_input_focus(InputFocus::POINTER_ROOT, x11rb::NONE, x11rb::CURRENT_TIME)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Geometry to give a tiled window whose final client rect is `target`.
+    ///
+    /// A window placed for the first time starts its spawn animation here; a
+    /// window that is still animating keeps its current geometry and heads
+    /// for the new target instead. Everything else goes straight to `target`.
+    fn spawn_animation_rect(&mut self, window: Window, target: types::Rect) -> types::Rect {
+        if self.spawn_pending.remove(&window) {
+            let from = animation::start_rect(self.config.spawn_animation, target);
+            let duration = std::time::Duration::from_millis(self.config.spawn_animation_ms);
+            self.animator.start(window, from, target, duration);
+            return from;
+        }
+        if self.animator.is_animating(window) {
+            self.animator.retarget(window, target);
+            if let Some(rect) = self.animator.current_rect(window, std::time::Instant::now()) {
+                return rect;
+            }
+        }
+        target
+    }
+
+    /// Advance running spawn animations (no-op when none are running)
+    fn step_animations(&mut self) {
+        let steps = self.animator.step(std::time::Instant::now());
+        if steps.is_empty() {
+            return;
+        }
+        for (window, rect) in steps {
+            let aux = ConfigureWindowAux::new()
+                .x(rect.x)
+                .y(rect.y)
+                .width(rect.width)
+                .height(rect.height);
+            if let Err(e) = self.conn.configure_window(window, &aux) {
+                log::warn!("Failed to animate window 0x{:x}: {}", window, e);
+            }
+        }
+        let _ = self.conn.flush();
+    }
+
+    /// Record user input, re-arming the idle action
+    fn note_input(&mut self) {
+        self.last_input = std::time::Instant::now();
+        self.idle_fired = false;
+    }
+
+    /// Run the idle action once the idle timeout has passed (once per idle period)
+    fn check_idle(&mut self) {
+        let Some(action) = self.idle_action.clone() else {
+            return;
+        };
+        let timeout = std::time::Duration::from_secs(self.user_config.general.idle_timeout_s);
+        if self.last_input.elapsed() < timeout
+            || self.idle_polled.elapsed() < std::time::Duration::from_secs(1)
+        {
+            return;
+        }
+
+        // Input to client windows never reaches ttwm, but the server's idle
+        // time covers it
+        self.idle_polled = std::time::Instant::now();
+        let server_idle = self.conn.screensaver_query_info(self.root).ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|info| std::time::Duration::from_millis(info.ms_since_user_input.into()));
+        if let Some(idle) = server_idle.filter(|&idle| idle < timeout) {
+            self.last_input = std::time::Instant::now().checked_sub(idle).unwrap_or(self.last_input);
+            self.idle_fired = false;
+            return;
+        }
+        if self.idle_fired {
+            return;
+        }
+
+        self.idle_fired = true;
+        log::info!("Idle for {}s, running {:?}", timeout.as_secs(), action);
+        let result = match action {
+            IdleAction::Workspace(index) => match self.workspaces_mut().switch_to(index) {
+                Some(old_idx) => self.perform_workspace_switch(old_idx),
+                None => Ok(()),
+            },
+            IdleAction::Exec(command) => self.execute_action(WmAction::Spawn(command)),
+        };
+        if let Err(e) = result {
+            log::error!("Idle action failed: {}", e);
+        }
+    }
+
+    /// Unmanage windows whose unmap grace period has expired without a remap
+    fn process_pending_unmanage(&mut self) {
+        if self.pending_unmanage.is_empty() {
+            return;
+        }
+
+        let now = std::time::Instant::now();
+        let expired: Vec<Window> = self.pending_unmanage.iter()
+            .filter(|(_, &deadline)| deadline <= now)
+            .map(|(&window, _)| window)
+            .collect();
+
+        for window in expired {
+            self.pending_unmanage.remove(&window);
+            log::debug!("Unmap grace period expired for window 0x{:x}", window);
+            if let Err(e) = self.unmanage_window_with_reason(window, UnmanageReason::ClientUnmapped) {
+                log::error!("Failed to unmanage window: {}", e);
+            }
+        }
+    }
+
+    /// Cancel a pending unmanage because the window mapped again.
+    /// Returns true if one was pending.
+    fn cancel_pending_unmanage(&mut self, window: Window) -> bool {
+        if self.pending_unmanage.remove(&window).is_some() {
+            log::debug!("Window 0x{:x} remapped within grace period, keeping it managed", window);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Capture a screenshot and save it to the specified path
     fn capture_screenshot(&self, path: &str) -> Result<()> {
         use image::{ImageBuffer, Rgba};
@@ -2676,7 +4628,7 @@ impl Wm {
         }
 
         // Spawn all apps at once
-        self.startup_manager.spawn_all();
+        self.startup_manager.spawn_all(&mut self.children);
 
         // Apply layout to show the configured frames
         self.apply_layout()?;
@@ -2685,7 +4637,22 @@ impl Wm {
     }
 }
 
+/// ttwm - Tabbed Tiling Window Manager
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Config file path (overrides $TTWM_CONFIG and ~/.config/ttwm/config.toml)
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Time tab bar redraws, background samples and layout passes
+    /// (query with `ttwmctl render-stats`)
+    #[arg(long)]
+    profile: bool,
+}
+
 fn main() -> Result<()> {
+    let args = Args::parse();
+
     // Initialize logging
     env_logger::Builder::from_env(
         env_logger::Env::default().default_filter_or("info")
@@ -2694,13 +4661,15 @@ fn main() -> Result<()> {
     log::info!("Starting ttwm - Tabbed Tiling Window Manager");
 
     // Create window manager
-    let mut wm = Wm::new()?;
+    let mut wm = Wm::new(args.config, args.profile)?;
 
     // Become the window manager
     wm.become_wm()?;
 
     // Set up EWMH properties
     wm.setup_ewmh()?;
+    wm.setup_bar()?;
+    wm.update_pointer_barriers()?;
 
     // Grab our keybindings
     wm.grab_keys()?;
@@ -2708,11 +4677,29 @@ fn main() -> Result<()> {
     // Apply startup layout configuration
     wm.apply_startup_config()?;
 
+    // Show the workspaces each monitor had before the last exit
+    let remember_session = wm.user_config.general.restore_session;
+    if remember_session {
+        if let Err(e) = wm.restore_session() {
+            log::warn!("Failed to restore session: {}", e);
+        }
+    }
+
     // Manage any existing windows
     wm.scan_existing_windows()?;
 
     // Run the event loop
     wm.run()?;
 
+    if remember_session {
+        if let Err(e) = wm.save_session() {
+            log::warn!("Failed to save session: {}", e);
+        }
+    }
+
+    if let Err(e) = wm.shutdown() {
+        log::warn!("Failed to clean up on exit: {}", e);
+    }
+
     Ok(())
 }
@@ -0,0 +1,119 @@
+//! Window marks (named bookmarks).
+//!
+//! A mark is a single character bound to a window, like marks in vim. Marks
+//! are set and jumped to either via IPC or with a prefix keybinding followed
+//! by the mark key.
+
+use std::collections::HashMap;
+
+use x11rb::protocol::xproto::Window;
+
+/// What the next key press does after a mark prefix keybinding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkPrompt {
+    /// Mark the focused window with the typed key
+    Set,
+    /// Jump to the window marked with the typed key
+    Goto,
+    /// Pull the window marked with the typed key into the focused frame
+    Pull,
+}
+
+/// Mapping from mark character to window.
+#[derive(Debug, Default)]
+pub struct Marks {
+    marks: HashMap<char, Window>,
+}
+
+impl Marks {
+    /// Create an empty mark table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind `mark` to `window`, replacing any window it pointed to before.
+    pub fn set(&mut self, mark: char, window: Window) {
+        self.marks.insert(mark, window);
+    }
+
+    /// Get the window a mark points to.
+    pub fn get(&self, mark: char) -> Option<Window> {
+        self.marks.get(&mark).copied()
+    }
+
+    /// Drop every mark pointing at a window (call when it is unmanaged).
+    pub fn remove_window(&mut self, window: Window) {
+        self.marks.retain(|_, &mut w| w != window);
+    }
+}
+
+/// Parse a mark name: exactly one printable, non-space ASCII character.
+pub fn parse_mark(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_graphic() => Some(c),
+        _ => None,
+    }
+}
+
+/// Map a keysym typed after a mark prefix to a mark character.
+///
+/// Printable ASCII keysyms share their character codes, so those map
+/// directly; anything else (function keys, Escape, ...) is not a mark.
+pub fn mark_from_keysym(keysym: u32) -> Option<char> {
+    char::from_u32(keysym).filter(|c| c.is_ascii_graphic())
+}
+
+/// Check whether a keysym is a modifier key (Shift, Control, Alt, Super, ...).
+pub fn is_modifier_keysym(keysym: u32) -> bool {
+    (0xffe1..=0xffee).contains(&keysym)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get_mark() {
+        let mut marks = Marks::new();
+        marks.set('a', 100);
+        marks.set('b', 200);
+        marks.set('a', 300);
+
+        assert_eq!(marks.get('a'), Some(300));
+        assert_eq!(marks.get('b'), Some(200));
+        assert_eq!(marks.get('c'), None);
+    }
+
+    #[test]
+    fn test_remove_window_clears_its_marks() {
+        let mut marks = Marks::new();
+        marks.set('a', 100);
+        marks.set('b', 100);
+        marks.set('c', 200);
+
+        marks.remove_window(100);
+        assert_eq!(marks.get('a'), None);
+        assert_eq!(marks.get('b'), None);
+        assert_eq!(marks.get('c'), Some(200));
+    }
+
+    #[test]
+    fn test_parse_mark() {
+        assert_eq!(parse_mark("a"), Some('a'));
+        assert_eq!(parse_mark("7"), Some('7'));
+        assert_eq!(parse_mark(""), None);
+        assert_eq!(parse_mark("ab"), None);
+        assert_eq!(parse_mark(" "), None);
+    }
+
+    #[test]
+    fn test_mark_from_keysym() {
+        assert_eq!(mark_from_keysym(0x61), Some('a'));
+        assert_eq!(mark_from_keysym(0x31), Some('1'));
+        assert_eq!(mark_from_keysym(0xff1b), None); // Escape
+        assert_eq!(mark_from_keysym(0x20), None); // Space
+        assert!(is_modifier_keysym(0xffe1)); // Shift_L
+        assert!(!is_modifier_keysym(0x61));
+    }
+}
@@ -0,0 +1,191 @@
+//! Window mirroring via the Composite and Damage extensions.
+//!
+//! A mirror shows a live, view-only copy of a managed window in another frame.
+//! The source window is redirected with automatic updates (so it keeps painting
+//! on screen as usual) and its offscreen pixmap is copied into a plain view
+//! window whenever Damage reports a change. Contents are cropped to the view,
+//! not scaled. Only compiled with the `mirror` feature.
+
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::composite::{self, ConnectionExt as _, Redirect};
+use x11rb::protocol::damage::{self, ConnectionExt as _, Damage, ReportLevel};
+use x11rb::protocol::xproto::*;
+
+use crate::layout::NodeId;
+use crate::monitor::MonitorId;
+
+/// A single mirror of a source window into a frame.
+pub struct Mirror {
+    /// Window whose contents are mirrored
+    pub source: Window,
+    /// View window the contents are copied into (receives no input)
+    pub view: Window,
+    /// Damage object tracking changes to the source
+    pub damage: Damage,
+    /// Monitor owning the target frame
+    pub monitor: MonitorId,
+    /// Workspace index owning the target frame
+    pub workspace: usize,
+    /// Frame the mirror is shown in
+    pub frame: NodeId,
+}
+
+/// Tracks active mirrors and whether the required extensions are present.
+pub struct MirrorManager {
+    mirrors: Vec<Mirror>,
+    available: bool,
+}
+
+impl MirrorManager {
+    /// Create the manager, negotiating Composite and Damage versions.
+    ///
+    /// Mirroring is disabled (and `add` fails) if either extension is missing.
+    pub fn new(conn: &impl Connection) -> Self {
+        let available = Self::init_extensions(conn).unwrap_or(false);
+        if !available {
+            log::warn!("Composite/Damage extensions unavailable, window mirroring disabled");
+        }
+        Self {
+            mirrors: Vec::new(),
+            available,
+        }
+    }
+
+    fn init_extensions(conn: &impl Connection) -> Result<bool> {
+        if conn.extension_information(composite::X11_EXTENSION_NAME)?.is_none()
+            || conn.extension_information(damage::X11_EXTENSION_NAME)?.is_none()
+        {
+            return Ok(false);
+        }
+        // Both extensions require a version handshake before use
+        conn.composite_query_version(0, 4)?.reply()?;
+        conn.damage_query_version(1, 1)?.reply()?;
+        Ok(true)
+    }
+
+    /// Start mirroring `source` into `frame`. Returns the view window (unmapped).
+    pub fn add(
+        &mut self,
+        conn: &impl Connection,
+        root: Window,
+        source: Window,
+        (monitor, workspace, frame): (MonitorId, usize, NodeId),
+        background: u32,
+    ) -> Result<Window> {
+        if !self.available {
+            anyhow::bail!("Composite/Damage extensions are not available");
+        }
+
+        // Only redirect once per source; further mirrors share the redirection
+        if !self.mirrors.iter().any(|m| m.source == source) {
+            conn.composite_redirect_window(source, Redirect::AUTOMATIC)?;
+        }
+
+        let view = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            view,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+                .background_pixel(background)
+                .override_redirect(1) // Don't manage this window
+                .event_mask(EventMask::EXPOSURE),
+        )?;
+
+        let damage = conn.generate_id()?;
+        conn.damage_create(damage, source, ReportLevel::NON_EMPTY)?;
+
+        self.mirrors.push(Mirror {
+            source,
+            view,
+            damage,
+            monitor,
+            workspace,
+            frame,
+        });
+        conn.flush()?;
+        Ok(view)
+    }
+
+    /// Iterate over all mirrors.
+    pub fn iter(&self) -> impl Iterator<Item = &Mirror> {
+        self.mirrors.iter()
+    }
+
+    /// Find the mirror owning a damage object.
+    pub fn find_by_damage(&self, damage: Damage) -> Option<&Mirror> {
+        self.mirrors.iter().find(|m| m.damage == damage)
+    }
+
+    /// Find the mirror with a given view window.
+    pub fn find_by_view(&self, view: Window) -> Option<&Mirror> {
+        self.mirrors.iter().find(|m| m.view == view)
+    }
+
+    /// Remove every mirror matching `predicate`, releasing its X resources.
+    ///
+    /// The source stays redirected while other mirrors of it remain.
+    pub fn remove_where(&mut self, conn: &impl Connection, predicate: impl Fn(&Mirror) -> bool) -> Result<()> {
+        let (removed, kept): (Vec<_>, Vec<_>) = self.mirrors.drain(..).partition(|m| predicate(m));
+        self.mirrors = kept;
+
+        for mirror in removed {
+            conn.damage_destroy(mirror.damage)?;
+            conn.destroy_window(mirror.view)?;
+            if !self.mirrors.iter().any(|m| m.source == mirror.source) {
+                // The source may already be gone; ignore errors from unredirecting it
+                let _ = conn.composite_unredirect_window(mirror.source, Redirect::AUTOMATIC);
+            }
+            log::info!("Removed mirror of window 0x{:x}", mirror.source);
+        }
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+/// Copy the source window's current contents into a mirror's view window.
+///
+/// Unmapped sources (e.g. background tabs) have no backing pixmap; the view
+/// keeps its last contents in that case.
+pub fn redraw(conn: &impl Connection, gc: Gcontext, mirror: &Mirror) -> Result<()> {
+    // Acknowledge the damage so further changes are reported
+    conn.damage_subtract(mirror.damage, x11rb::NONE, x11rb::NONE)?;
+
+    let Ok(source_geom) = conn.get_geometry(mirror.source)?.reply() else {
+        return Ok(());
+    };
+    let Ok(view_geom) = conn.get_geometry(mirror.view)?.reply() else {
+        return Ok(());
+    };
+    if source_geom.depth != view_geom.depth {
+        // copy_area requires matching depths (e.g. ARGB sources can't be mirrored)
+        return Ok(());
+    }
+
+    let pixmap = conn.generate_id()?;
+    if conn.composite_name_window_pixmap(mirror.source, pixmap)?.check().is_err() {
+        return Ok(());
+    }
+    conn.copy_area(
+        pixmap,
+        mirror.view,
+        gc,
+        0,
+        0,
+        0,
+        0,
+        source_geom.width.min(view_geom.width),
+        source_geom.height.min(view_geom.height),
+    )?;
+    conn.free_pixmap(pixmap)?;
+    conn.flush()?;
+    Ok(())
+}
@@ -11,9 +11,9 @@ use x11rb::protocol::randr::{self, Output};
 use x11rb::protocol::xproto::{ConnectionExt as XprotoExt, Window};
 use x11rb::rust_connection::RustConnection;
 
-use crate::layout::Direction;
+use crate::layout::{Direction, LayoutTree, NodeId};
 use crate::types::Rect;
-use crate::workspaces::WorkspaceManager;
+use crate::workspaces::{Workspace, WorkspaceManager};
 
 new_key_type! {
     /// Unique identifier for a monitor
@@ -48,6 +48,38 @@ impl Monitor {
     }
 }
 
+/// A line segment where two monitors meet, in root coordinates
+/// (x1 == x2 for side-by-side monitors, y1 == y2 for stacked ones)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Edge {
+    /// Whether the edge separates monitors side by side
+    pub fn is_vertical(&self) -> bool {
+        self.x1 == self.x2
+    }
+}
+
+/// Name of the monitor covering the whole screen when RandR reports none
+const FALLBACK_MONITOR: &str = "default";
+
+/// The split-monitor arrangement set aside while all monitors are merged
+#[derive(Debug)]
+struct SplitArrangement {
+    /// Monitor that grew to cover every output
+    merged: MonitorId,
+    /// Its own geometry and primary flag before merging
+    geometry: Rect,
+    primary: bool,
+    /// The other monitors, with the layouts they had when merged
+    others: Vec<Monitor>,
+}
+
 /// Manages all monitors and their workspaces
 #[derive(Debug)]
 pub struct MonitorManager {
@@ -57,6 +89,11 @@ pub struct MonitorManager {
     focused: MonitorId,
     /// Maps RandR output ID to MonitorId for quick lookup
     output_to_monitor: HashMap<Output, MonitorId>,
+    /// Set while every output is merged into one logical monitor
+    split: Option<SplitArrangement>,
+    /// Workspaces of monitors that disappeared while only the fallback
+    /// monitor was left, waiting for a real monitor to come back
+    held: Vec<WorkspaceManager>,
 }
 
 impl MonitorManager {
@@ -66,16 +103,14 @@ impl MonitorManager {
             monitors: SlotMap::with_key(),
             focused: MonitorId::default(),
             output_to_monitor: HashMap::new(),
+            split: None,
+            held: Vec::new(),
         }
     }
 
-    /// Query monitors via RandR and populate the manager
-    /// Returns the primary monitor ID
+    /// Query monitors via RandR and update the manager (see `update_monitors`)
+    /// Returns the focused monitor ID
     pub fn refresh(&mut self, conn: &RustConnection, root: Window) -> Result<MonitorId> {
-        // Clear existing monitors
-        self.monitors.clear();
-        self.output_to_monitor.clear();
-
         // Get monitors using RandR 1.5 GetMonitors (preferred)
         let monitors_reply = randr::get_monitors(conn, root, true)?
             .reply()
@@ -86,8 +121,7 @@ impl MonitorManager {
             monitors_reply.monitors.len()
         );
 
-        let mut primary_id: Option<MonitorId> = None;
-
+        let mut detected = Vec::new();
         for mon_info in monitors_reply.monitors {
             let name = get_atom_name(conn, mon_info.name)?;
             let geometry = Rect::new(
@@ -108,40 +142,177 @@ impl MonitorManager {
                 if is_primary { "(primary)" } else { "" }
             );
 
-            let outputs: Vec<Output> = mon_info.outputs.clone();
-            let monitor = Monitor::new(name.clone(), is_primary, geometry, outputs.clone());
-            let monitor_id = self.monitors.insert(monitor);
+            detected.push(Monitor::new(name, is_primary, geometry, mon_info.outputs.clone()));
+        }
 
-            // Map outputs to this monitor
-            for output in outputs {
-                self.output_to_monitor.insert(output, monitor_id);
+        let screen = &conn.setup().roots[0];
+        let fallback = Rect::new(
+            0,
+            0,
+            screen.width_in_pixels as u32,
+            screen.height_in_pixels as u32,
+        );
+        Ok(self.update_monitors(detected, fallback))
+    }
+
+    /// Replace the monitor set with `detected`, without losing windows.
+    ///
+    /// A monitor whose name was already known keeps its ID and workspaces
+    /// and only takes the new geometry. With nothing detected (e.g. in the
+    /// middle of a display reconfiguration) a fallback monitor covers
+    /// `fallback`, and the workspaces of the monitors that went away are
+    /// held until a real monitor is back; then they, and anything opened on
+    /// the fallback meanwhile, move onto the primary (or first) monitor.
+    /// While merged (see `merge`), a report of the same outputs at the same
+    /// places changes nothing; anything else unmerges first.
+    /// Returns the focused monitor ID, kept if that monitor still exists.
+    pub fn update_monitors(&mut self, detected: Vec<Monitor>, fallback: Rect) -> MonitorId {
+        if self.merged_from(&detected) {
+            return self.focused;
+        }
+        self.unmerge();
+
+        let detected = if detected.is_empty() {
+            log::warn!("No monitors detected, creating fallback from screen dimensions");
+            vec![Monitor::new(FALLBACK_MONITOR.to_string(), true, fallback, vec![])]
+        } else {
+            detected
+        };
+        let only_fallback = detected.len() == 1 && detected[0].name == FALLBACK_MONITOR && detected[0].outputs.is_empty();
+
+        let mut kept = Vec::new();
+        for monitor in detected {
+            let existing = self.monitors.iter()
+                .find(|(id, m)| m.name == monitor.name && !kept.contains(id))
+                .map(|(id, _)| id);
+            let id = match existing {
+                Some(id) => {
+                    let known = &mut self.monitors[id];
+                    known.primary = monitor.primary;
+                    known.geometry = monitor.geometry;
+                    known.outputs = monitor.outputs;
+                    id
+                }
+                None => self.monitors.insert(monitor),
+            };
+            kept.push(id);
+        }
+
+        // Hold on to the windows of monitors that went away
+        let gone: Vec<MonitorId> = self.monitors.keys().filter(|id| !kept.contains(id)).collect();
+        for id in gone {
+            if let Some(monitor) = self.monitors.remove(id) {
+                log::info!("Monitor '{}' disconnected, holding its workspaces", monitor.name);
+                self.held.push(monitor.workspaces);
             }
+        }
 
-            if is_primary {
-                primary_id = Some(monitor_id);
+        self.output_to_monitor = self.monitors.iter()
+            .flat_map(|(id, m)| m.outputs.iter().map(move |&output| (output, id)))
+            .collect();
+
+        let primary = self.monitors.iter()
+            .find(|(_, m)| m.primary)
+            .map(|(id, _)| id)
+            .unwrap_or(kept[0]);
+        if !only_fallback && !self.held.is_empty() {
+            let target = &mut self.monitors[primary].workspaces;
+            for source in self.held.drain(..) {
+                rehome_workspaces(target, source);
             }
+            log::info!("Moved held workspaces onto monitor '{}'", self.monitors[primary].name);
         }
 
-        // If no monitors found, create a fallback using screen dimensions
-        if self.monitors.is_empty() {
-            log::warn!("No monitors detected, creating fallback from screen dimensions");
-            let screen = &conn.setup().roots[0];
-            let geometry = Rect::new(
-                0,
-                0,
-                screen.width_in_pixels as u32,
-                screen.height_in_pixels as u32,
-            );
-            let monitor = Monitor::new("default".to_string(), true, geometry, vec![]);
-            primary_id = Some(self.monitors.insert(monitor));
+        if !self.monitors.contains_key(self.focused) {
+            self.focused = primary;
         }
+        self.focused
+    }
 
-        // Set focused to primary, or first monitor if no primary
-        self.focused = primary_id.unwrap_or_else(|| {
-            self.monitors.keys().next().expect("At least one monitor must exist")
-        });
+    /// Windows on the held workspaces of disconnected monitors (see
+    /// `update_monitors`), which are on no monitor until they're re-homed
+    pub fn held_windows(&self) -> Vec<Window> {
+        self.held.iter()
+            .flat_map(|manager| &manager.workspaces)
+            .flat_map(|ws| ws.layout.all_windows().into_iter().chain(ws.floating_window_ids()))
+            .collect()
+    }
 
-        Ok(self.focused)
+    /// Drop a window that went away from the held workspaces.
+    /// Returns true if it was held.
+    pub fn remove_held_window(&mut self, window: Window) -> bool {
+        let mut found = false;
+        for ws in self.held.iter_mut().flat_map(|manager| &mut manager.workspaces) {
+            found |= ws.layout.remove_window(window).is_some() || ws.remove_floating(window).is_some();
+            ws.forget_departed_windows();
+        }
+        found
+    }
+
+    /// Clear the zoom of a window that went away, on whichever monitor's
+    /// workspace zoomed it. Returns that monitor.
+    pub fn clear_zoom(&mut self, window: Window) -> Option<MonitorId> {
+        for (id, monitor) in &mut self.monitors {
+            for ws in &mut monitor.workspaces.workspaces {
+                if ws.zoomed_window == Some(window) {
+                    ws.zoomed_window = None;
+                    return Some(id);
+                }
+            }
+        }
+        None
+    }
+
+    /// Clear the maximize of a window that went away, on whichever monitor's
+    /// workspace maximized it. Returns that monitor.
+    pub fn clear_maximize(&mut self, window: Window) -> Option<MonitorId> {
+        for (id, monitor) in &mut self.monitors {
+            for ws in &mut monitor.workspaces.workspaces {
+                if ws.maximized_window == Some(window) {
+                    ws.maximized_window = None;
+                    return Some(id);
+                }
+            }
+        }
+        None
+    }
+
+    /// Find `window` as the fullscreen, zoomed or maximized window on any
+    /// monitor's current workspace, with the monitor and the geometry it is
+    /// held to (see `Workspace::takeover`). `usable` gives a monitor's
+    /// usable area.
+    pub fn takeover_of(
+        &self,
+        window: Window,
+        bezel_gap: u32,
+        usable: impl Fn(MonitorId) -> Rect,
+    ) -> Option<(MonitorId, Rect)> {
+        let span = self.bounding_box(bezel_gap);
+        self.monitors.iter().find_map(|(id, monitor)| {
+            monitor.workspaces.current()
+                .takeover(monitor.geometry, span, usable(id))
+                .filter(|&(held, _)| held == window)
+                .map(|(_, geom)| (id, geom))
+        })
+    }
+
+    /// Whether the monitors are merged and `detected` is exactly what they
+    /// were merged from (same names and geometries)
+    fn merged_from(&self, detected: &[Monitor]) -> bool {
+        let Some(split) = &self.split else {
+            return false;
+        };
+        let Some(merged) = self.monitors.get(split.merged) else {
+            return false;
+        };
+        let mut before: Vec<(&str, Rect)> = split.others.iter()
+            .map(|m| (m.name.as_str(), m.geometry))
+            .chain([(merged.name.as_str(), split.geometry)])
+            .collect();
+        let mut now: Vec<(&str, Rect)> = detected.iter().map(|m| (m.name.as_str(), m.geometry)).collect();
+        before.sort_by_key(|&(name, _)| name);
+        now.sort_by_key(|&(name, _)| name);
+        before == now
     }
 
     /// Get monitor by ID
@@ -180,6 +351,7 @@ impl MonitorManager {
     }
 
     /// Find the monitor containing a point (for focus-follows-mouse)
+    #[cfg(test)]
     pub fn monitor_at(&self, x: i32, y: i32) -> Option<MonitorId> {
         for (id, monitor) in &self.monitors {
             let g = &monitor.geometry;
@@ -194,6 +366,24 @@ impl MonitorManager {
         None
     }
 
+    /// Find the monitor containing a point at least `margin` pixels inside its edges.
+    ///
+    /// Points near a shared edge match no monitor, which gives pointer-driven
+    /// monitor focus some hysteresis against jitter on the boundary.
+    pub fn monitor_at_inset(&self, x: i32, y: i32, margin: i32) -> Option<MonitorId> {
+        for (id, monitor) in &self.monitors {
+            let g = &monitor.geometry;
+            if x >= g.x + margin
+                && x < g.x + g.width as i32 - margin
+                && y >= g.y + margin
+                && y < g.y + g.height as i32 - margin
+            {
+                return Some(id);
+            }
+        }
+        None
+    }
+
     /// Find monitor in a direction relative to the focused monitor
     pub fn monitor_in_direction(&self, direction: Direction) -> Option<MonitorId> {
         let focused = self.focused();
@@ -242,17 +432,134 @@ impl MonitorManager {
         best.map(|(id, _)| id)
     }
 
+    /// Where directional focus lands when it leaves the focused monitor from
+    /// the frame at `from`: the adjacent monitor in `direction` and the frame
+    /// of its current workspace nearest to `from`. `layout_area` gives a
+    /// monitor's usable area and the gap between its frames.
+    pub fn frame_across(
+        &self,
+        direction: Direction,
+        from: Rect,
+        layout_area: impl Fn(MonitorId, &Monitor) -> (Rect, u32),
+    ) -> Option<(MonitorId, NodeId)> {
+        let target = self.monitor_in_direction(direction)?;
+        let monitor = self.monitors.get(target)?;
+        let (area, gap) = layout_area(target, monitor);
+        let geometries = monitor.workspaces.current().layout.calculate_geometries(area, gap);
+        let frame = LayoutTree::nearest_frame_from(from, direction, &geometries)?;
+        Some((target, frame))
+    }
+
     /// Get all monitor IDs
     pub fn all_monitors(&self) -> Vec<MonitorId> {
         self.monitors.keys().collect()
     }
 
+    /// Edges shared by two monitors: the overlap of one's right (bottom)
+    /// edge with another's left (top) edge
+    pub fn shared_edges(&self) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        for (_, a) in self.monitors.iter() {
+            for (_, b) in self.monitors.iter() {
+                let (a, b) = (a.geometry, b.geometry);
+                if a.x + a.width as i32 == b.x {
+                    let (start, end) = (a.y.max(b.y), (a.y + a.height as i32).min(b.y + b.height as i32));
+                    if start < end {
+                        edges.push(Edge { x1: b.x, y1: start, x2: b.x, y2: end });
+                    }
+                }
+                if a.y + a.height as i32 == b.y {
+                    let (start, end) = (a.x.max(b.x), (a.x + a.width as i32).min(b.x + b.width as i32));
+                    if start < end {
+                        edges.push(Edge { x1: start, y1: b.y, x2: end, y2: b.y });
+                    }
+                }
+            }
+        }
+        edges
+    }
+
+    /// Windows on every monitor's current workspace, for focus cycling
+    /// across monitors. Monitors go left to right (top to bottom for equal
+    /// x); each contributes its tiled windows (only the focused tabs if
+    /// `visible_only`) followed by its floating windows.
+    pub fn cycle_windows(&self, visible_only: bool) -> Vec<(MonitorId, Window)> {
+        let mut ids = self.all_monitors();
+        ids.sort_by_key(|&id| (self.monitors[id].geometry.x, self.monitors[id].geometry.y));
+
+        let mut windows = Vec::new();
+        for id in ids {
+            let workspace = self.monitors[id].workspaces.current();
+            let tiled = if visible_only {
+                workspace.layout.visible_windows()
+            } else {
+                workspace.layout.all_windows()
+            };
+            windows.extend(tiled.into_iter().chain(workspace.floating_window_ids()).map(|w| (id, w)));
+        }
+        windows
+    }
+
+    /// Geometry of a monitor minus `bezel_gap / 2` along every edge it
+    /// shares with another monitor, so content keeps clear of the bezels.
+    pub fn bezel_adjusted(&self, id: MonitorId, bezel_gap: u32) -> Option<Rect> {
+        let g = self.monitors.get(id)?.geometry;
+        if bezel_gap == 0 {
+            return Some(g);
+        }
+        let half = (bezel_gap / 2) as i32;
+        let (right, bottom) = (g.x + g.width as i32, g.y + g.height as i32);
+        let (mut x1, mut y1, mut x2, mut y2) = (g.x, g.y, right, bottom);
+        for (other_id, other) in &self.monitors {
+            if other_id == id {
+                continue;
+            }
+            let o = other.geometry;
+            let (o_right, o_bottom) = (o.x + o.width as i32, o.y + o.height as i32);
+            let overlaps_vertically = o.y < bottom && o_bottom > g.y;
+            let overlaps_horizontally = o.x < right && o_right > g.x;
+            if overlaps_vertically && o_right == g.x {
+                x1 = g.x + half;
+            }
+            if overlaps_vertically && o.x == right {
+                x2 = right - half;
+            }
+            if overlaps_horizontally && o_bottom == g.y {
+                y1 = g.y + half;
+            }
+            if overlaps_horizontally && o.y == bottom {
+                y2 = bottom - half;
+            }
+        }
+        Some(Rect::new(x1, y1, (x2 - x1).max(1) as u32, (y2 - y1).max(1) as u32))
+    }
+
+    /// Get the bounding box of all monitor geometries, each first trimmed
+    /// by `bezel_gap` (see `bezel_adjusted`)
+    pub fn bounding_box(&self, bezel_gap: u32) -> Rect {
+        let mut iter = self.monitors.keys().filter_map(|id| self.bezel_adjusted(id, bezel_gap));
+        let Some(first) = iter.next() else {
+            return Rect::new(0, 0, 0, 0);
+        };
+        let (mut x1, mut y1) = (first.x, first.y);
+        let (mut x2, mut y2) = (first.x + first.width as i32, first.y + first.height as i32);
+        for g in iter {
+            x1 = x1.min(g.x);
+            y1 = y1.min(g.y);
+            x2 = x2.max(g.x + g.width as i32);
+            y2 = y2.max(g.y + g.height as i32);
+        }
+        Rect::new(x1, y1, (x2 - x1) as u32, (y2 - y1) as u32)
+    }
+
     /// Get the number of monitors
+    #[cfg(test)]
     pub fn count(&self) -> usize {
         self.monitors.len()
     }
 
     /// Find the primary monitor
+    #[cfg(test)]
     pub fn primary(&self) -> Option<MonitorId> {
         self.monitors
             .iter()
@@ -261,6 +568,7 @@ impl MonitorManager {
     }
 
     /// Find a monitor by name
+    #[cfg(test)]
     pub fn find_by_name(&self, name: &str) -> Option<MonitorId> {
         self.monitors
             .iter()
@@ -278,8 +586,109 @@ impl MonitorManager {
         self.monitors.iter_mut()
     }
 
+    /// Check whether all outputs are merged into one logical monitor
+    pub fn is_merged(&self) -> bool {
+        self.split.is_some()
+    }
+
+    /// Merge every monitor into the focused one, which grows to the bounding
+    /// box of all of them (for treating the outputs as one big screen).
+    ///
+    /// Windows of the other monitors join the same workspace on the merged
+    /// monitor: tiled ones as background tabs of its focused frame, floating
+    /// ones where they are. The other monitors are set aside with their
+    /// layouts so `unmerge` can restore them. Returns the IDs that went away
+    /// (empty if there was nothing to merge).
+    pub fn merge(&mut self) -> Vec<MonitorId> {
+        if self.split.is_some() || self.monitors.len() < 2 {
+            return Vec::new();
+        }
+        let union = self.bounding_box(0);
+        let merged_id = self.focused;
+        let removed: Vec<MonitorId> = self.monitors.keys().filter(|&id| id != merged_id).collect();
+        let others: Vec<Monitor> = removed.iter().filter_map(|&id| self.monitors.remove(id)).collect();
+
+        let merged = self.monitors.get_mut(merged_id).expect("Focused monitor must exist");
+        for other in &others {
+            for (target, source) in merged.workspaces.workspaces.iter_mut().zip(&other.workspaces.workspaces) {
+                absorb_workspace(target, source);
+            }
+        }
+
+        self.split = Some(SplitArrangement {
+            merged: merged_id,
+            geometry: merged.geometry,
+            primary: merged.primary,
+            others,
+        });
+        merged.geometry = union;
+        merged.primary = true;
+        merged.outputs = self.output_to_monitor.keys().copied().collect();
+        for monitor_id in self.output_to_monitor.values_mut() {
+            *monitor_id = merged_id;
+        }
+        removed
+    }
+
+    /// Undo `merge`: the merged monitor shrinks back to its own output and
+    /// the other monitors return with the layouts they had. Their windows go
+    /// home unless they were closed, floated or moved to another workspace
+    /// meanwhile; windows opened while merged stay on the merged monitor.
+    /// Returns the IDs of the restored monitors (new IDs, not the old ones).
+    pub fn unmerge(&mut self) -> Vec<MonitorId> {
+        let Some(split) = self.split.take() else {
+            return Vec::new();
+        };
+        let Some(merged) = self.monitors.get_mut(split.merged) else {
+            return Vec::new();
+        };
+        merged.geometry = split.geometry;
+        merged.primary = split.primary;
+        merged.outputs.clear();
+
+        let mut restored = Vec::new();
+        for mut other in split.others {
+            let merged = self.monitors.get_mut(split.merged).expect("Merged monitor must exist");
+            for (source, home) in merged.workspaces.workspaces.iter_mut().zip(other.workspaces.workspaces.iter_mut()) {
+                for window in home.layout.all_windows() {
+                    if source.layout.remove_window(window).is_none() {
+                        home.layout.remove_window(window);
+                    }
+                }
+                home.floating_windows.retain_mut(|floating| match source.remove_floating(floating.window) {
+                    Some(current) => {
+                        *floating = current;
+                        true
+                    }
+                    None => false,
+                });
+                source.forget_departed_windows();
+                home.forget_departed_windows();
+            }
+            let outputs = other.outputs.clone();
+            let id = self.monitors.insert(other);
+            for output in outputs {
+                self.output_to_monitor.insert(output, id);
+            }
+            restored.push(id);
+        }
+        for (&output, monitor_id) in &mut self.output_to_monitor {
+            if !restored.iter().any(|&id| self.monitors[id].outputs.contains(&output)) {
+                *monitor_id = split.merged;
+            }
+        }
+        if let Some(merged) = self.monitors.get_mut(split.merged) {
+            merged.outputs = self.output_to_monitor.iter()
+                .filter(|(_, &id)| id == split.merged)
+                .map(|(&output, _)| output)
+                .collect();
+        }
+        restored
+    }
+
     /// Add a mock monitor for testing (bypasses RandR)
     /// Returns the MonitorId of the newly added monitor
+    #[cfg(test)]
     pub fn add_mock_monitor(&mut self, name: &str, geometry: Rect, primary: bool) -> MonitorId {
         let monitor = Monitor::new(name.to_string(), primary, geometry, vec![]);
         let id = self.monitors.insert(monitor);
@@ -294,12 +703,13 @@ impl MonitorManager {
 
     /// Create a MonitorManager with mock monitors (for testing)
     /// Each config tuple is (name, geometry, is_primary)
+    #[cfg(test)]
     pub fn with_mock_monitors(configs: &[(&str, Rect, bool)]) -> Self {
         let mut manager = Self::new();
         let mut primary_id: Option<MonitorId> = None;
 
         for (name, geometry, is_primary) in configs {
-            let monitor = Monitor::new(name.to_string(), *is_primary, geometry.clone(), vec![]);
+            let monitor = Monitor::new(name.to_string(), *is_primary, *geometry, vec![]);
             let id = manager.monitors.insert(monitor);
             if *is_primary {
                 primary_id = Some(id);
@@ -321,6 +731,36 @@ impl Default for MonitorManager {
     }
 }
 
+/// Add the windows of `source` to `target`: tiled ones as background tabs
+/// of its focused frame, floating ones where they are.
+fn absorb_workspace(target: &mut Workspace, source: &Workspace) {
+    let frame_id = target.layout.focused;
+    let selected = target.layout.get(frame_id)
+        .and_then(|n| n.as_frame())
+        .filter(|f| !f.windows.is_empty())
+        .map(|f| f.focused);
+    for window in source.layout.all_windows() {
+        target.layout.add_window_to_frame(window, frame_id);
+    }
+    if let Some(index) = selected {
+        target.layout.focus_tab(index);
+    }
+    target.floating_windows.extend(source.floating_windows.iter().copied());
+}
+
+/// Move a disconnected monitor's workspaces onto `target`, workspace by
+/// workspace. An empty workspace takes the whole layout over; one with
+/// windows absorbs them.
+fn rehome_workspaces(target: &mut WorkspaceManager, source: WorkspaceManager) {
+    for (target, source) in target.workspaces.iter_mut().zip(source.workspaces) {
+        if target.layout.all_windows().is_empty() && target.floating_windows.is_empty() {
+            *target = source;
+        } else {
+            absorb_workspace(target, &source);
+        }
+    }
+}
+
 /// Get the string name of an X11 atom
 fn get_atom_name(conn: &RustConnection, atom: x11rb::protocol::xproto::Atom) -> Result<String> {
     let reply = conn.get_atom_name(atom)?.reply()?;
@@ -467,6 +907,37 @@ mod tests {
         assert_eq!(up, Some(top));
     }
 
+    #[test]
+    fn test_bounding_box_mixed_sizes() {
+        let manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 200, 1280, 1024), false),
+        ]);
+
+        assert_eq!(manager.bounding_box(0), Rect::new(0, 0, 3200, 1224));
+    }
+
+    #[test]
+    fn test_bezel_gap_trims_shared_edges() {
+        let manager = MonitorManager::with_mock_monitors(&[
+            ("LEFT", Rect::new(0, 0, 1920, 1080), true),
+            ("RIGHT", Rect::new(1920, 0, 1920, 1080), false),
+            ("BELOW", Rect::new(0, 1080, 1920, 1080), false),
+        ]);
+        let left = manager.find_by_name("LEFT").unwrap();
+        let right = manager.find_by_name("RIGHT").unwrap();
+        let below = manager.find_by_name("BELOW").unwrap();
+
+        assert_eq!(manager.bezel_adjusted(left, 0), Some(Rect::new(0, 0, 1920, 1080)));
+        // LEFT borders RIGHT and BELOW; outer edges are untouched
+        assert_eq!(manager.bezel_adjusted(left, 20), Some(Rect::new(0, 0, 1910, 1070)));
+        assert_eq!(manager.bezel_adjusted(right, 20), Some(Rect::new(1930, 0, 1910, 1080)));
+        assert_eq!(manager.bezel_adjusted(below, 20), Some(Rect::new(0, 1090, 1920, 1070)));
+
+        // The spanned box keeps its outer edges
+        assert_eq!(manager.bounding_box(20), Rect::new(0, 0, 3840, 2160));
+    }
+
     #[test]
     fn test_monitor_at_point() {
         let manager = MonitorManager::with_mock_monitors(&[
@@ -490,6 +961,98 @@ mod tests {
         assert_eq!(manager.monitor_at(5000, 100), None);
     }
 
+    #[test]
+    fn test_monitor_at_inset() {
+        let manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 1920, 1080), false),
+        ]);
+
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+
+        assert_eq!(manager.monitor_at_inset(1910, 500, 8), Some(dp1));
+        assert_eq!(manager.monitor_at_inset(1930, 500, 8), Some(hdmi1));
+
+        // Within the margin of the shared edge: no monitor on either side
+        assert_eq!(manager.monitor_at_inset(1915, 500, 8), None);
+        assert_eq!(manager.monitor_at_inset(1920, 500, 8), None);
+        assert_eq!(manager.monitor_at_inset(1927, 500, 8), None);
+    }
+
+    /// Frames of `monitor`'s current workspace laid out on its full geometry
+    fn frames_of(manager: &MonitorManager, monitor: MonitorId) -> Vec<(NodeId, Rect)> {
+        let monitor = manager.get(monitor).unwrap();
+        monitor.workspaces.current().layout.calculate_geometries(monitor.geometry, 0)
+    }
+
+    #[test]
+    fn test_frame_across_side_by_side() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 1920, 1080), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        let full = |_: MonitorId, m: &Monitor| (m.geometry, 0);
+
+        // HDMI-1 is split into a top and a bottom frame
+        manager.get_mut(hdmi1).unwrap().workspaces.current_mut().layout
+            .split_focused(crate::layout::SplitDirection::Vertical);
+        let frames = frames_of(&manager, hdmi1);
+        let top = frames.iter().find(|(_, r)| r.y == 0).unwrap().0;
+        let bottom = frames.iter().find(|(_, r)| r.y > 0).unwrap().0;
+
+        // Going right from DP-1 lands on the frame level with where focus was
+        manager.set_focused(dp1);
+        let lower_right = Rect::new(960, 540, 960, 540);
+        let upper_right = Rect::new(960, 0, 960, 540);
+        assert_eq!(manager.frame_across(Direction::Right, lower_right, full), Some((hdmi1, bottom)));
+        assert_eq!(manager.frame_across(Direction::Right, upper_right, full), Some((hdmi1, top)));
+
+        // Nothing beyond the outer edges
+        assert_eq!(manager.frame_across(Direction::Left, lower_right, full), None);
+        assert_eq!(manager.frame_across(Direction::Up, lower_right, full), None);
+
+        // And back from HDMI-1's bottom frame to DP-1's only frame
+        manager.set_focused(hdmi1);
+        let dp1_root = frames_of(&manager, dp1)[0].0;
+        let from = Rect::new(1920, 540, 1920, 540);
+        assert_eq!(manager.frame_across(Direction::Left, from, full), Some((dp1, dp1_root)));
+    }
+
+    #[test]
+    fn test_frame_across_stacked_monitors() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(0, 1080, 1920, 1080), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+
+        // HDMI-1 (below) is split into a left and a right frame
+        manager.get_mut(hdmi1).unwrap().workspaces.current_mut().layout
+            .split_focused(crate::layout::SplitDirection::Horizontal);
+        let frames = frames_of(&manager, hdmi1);
+        let left = frames.iter().find(|(_, r)| r.x == 0).unwrap().0;
+        let right = frames.iter().find(|(_, r)| r.x > 0).unwrap().0;
+
+        manager.set_focused(dp1);
+        let full = |_: MonitorId, m: &Monitor| (m.geometry, 0);
+        assert_eq!(manager.frame_across(Direction::Down, Rect::new(0, 0, 600, 1080), full), Some((hdmi1, left)));
+        assert_eq!(manager.frame_across(Direction::Down, Rect::new(1300, 0, 620, 1080), full), Some((hdmi1, right)));
+
+        // The target's layout area is used, e.g. a bar shrinking HDMI-1
+        let calls = std::cell::Cell::new(0);
+        let with_bar = |id: MonitorId, m: &Monitor| {
+            calls.set(calls.get() + 1);
+            assert_eq!(id, hdmi1);
+            (Rect::new(m.geometry.x, m.geometry.y + 30, m.geometry.width, m.geometry.height - 30), 8)
+        };
+        assert_eq!(manager.frame_across(Direction::Down, Rect::new(0, 0, 600, 1080), with_bar), Some((hdmi1, left)));
+        assert_eq!(calls.get(), 1);
+    }
+
     #[test]
     fn test_per_monitor_workspaces() {
         let manager = MonitorManager::with_mock_monitors(&[
@@ -567,6 +1130,337 @@ mod tests {
         assert_eq!(nav_right, Some(right));
     }
 
+    #[test]
+    fn test_merge_and_unmerge_preserve_windows() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 200, 1280, 1024), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        {
+            let ws = &mut manager.get_mut(dp1).unwrap().workspaces.workspaces;
+            ws[0].layout.add_window(1);
+            ws[2].layout.add_window(2);
+        }
+        {
+            let ws = &mut manager.get_mut(hdmi1).unwrap().workspaces.workspaces;
+            ws[0].layout.add_window(10);
+            ws[0].layout.split_focused(crate::layout::SplitDirection::Vertical);
+            ws[0].layout.add_window(11);
+            ws[0].add_floating(12, 2000, 300, 400, 300);
+            ws[2].layout.add_window(13);
+        }
+
+        assert_eq!(manager.merge(), vec![hdmi1]);
+        assert!(manager.is_merged());
+        assert_eq!(manager.count(), 1);
+        assert_eq!(manager.focused_id(), dp1);
+        let merged = manager.focused();
+        assert_eq!(merged.geometry, Rect::new(0, 0, 3200, 1224));
+        let ws = &merged.workspaces.workspaces;
+        assert_eq!(ws[0].layout.all_windows(), vec![1, 10, 11]);
+        assert!(ws[0].is_floating(12));
+        assert_eq!(ws[2].layout.all_windows(), vec![2, 13]);
+        // The merged frame keeps showing the tab it showed before
+        assert_eq!(ws[0].layout.focused_frame().and_then(|f| f.focused_window()), Some(1));
+
+        // Merging twice does nothing
+        assert!(manager.merge().is_empty());
+
+        // While merged, window 13 closes and window 20 opens
+        {
+            let ws = &mut manager.focused_mut().workspaces.workspaces;
+            ws[2].layout.remove_window(13);
+            ws[0].layout.add_window(20);
+        }
+
+        let restored = manager.unmerge();
+        assert_eq!(restored.len(), 1);
+        assert!(!manager.is_merged());
+        assert_eq!(manager.count(), 2);
+        let dp1_ws = &manager.get(dp1).unwrap().workspaces.workspaces;
+        assert_eq!(manager.get(dp1).unwrap().geometry, Rect::new(0, 0, 1920, 1080));
+        assert_eq!(dp1_ws[0].layout.all_windows(), vec![1, 20]);
+        assert!(dp1_ws[0].floating_windows.is_empty());
+        assert_eq!(dp1_ws[2].layout.all_windows(), vec![2]);
+
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        assert_eq!(hdmi1, restored[0]);
+        let hdmi1_ws = &manager.get(hdmi1).unwrap().workspaces.workspaces;
+        // The split arrangement comes back intact
+        assert_eq!(hdmi1_ws[0].layout.all_frames().len(), 2);
+        assert_eq!(hdmi1_ws[0].layout.all_windows(), vec![10, 11]);
+        assert!(hdmi1_ws[0].is_floating(12));
+        assert!(hdmi1_ws[2].layout.all_windows().is_empty());
+
+        // Nothing left to unmerge
+        assert!(manager.unmerge().is_empty());
+    }
+
+    #[test]
+    fn test_merge_needs_two_monitors() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+        ]);
+        assert!(manager.merge().is_empty());
+        assert!(!manager.is_merged());
+    }
+
+    #[test]
+    fn test_clear_zoom_on_unfocused_monitor() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 1920, 1080), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        manager.set_focused(dp1);
+        {
+            let ws = &mut manager.get_mut(hdmi1).unwrap().workspaces.workspaces[0];
+            ws.layout.add_window(10);
+            ws.zoomed_window = Some(10);
+        }
+
+        // The zoom lives on HDMI-1 even though DP-1 has focus
+        assert_eq!(manager.clear_zoom(11), None);
+        assert_eq!(manager.clear_zoom(10), Some(hdmi1));
+        assert_eq!(manager.get(hdmi1).unwrap().workspaces.workspaces[0].zoomed_window, None);
+        assert_eq!(manager.clear_zoom(10), None);
+    }
+
+    #[test]
+    fn test_clear_maximize_on_unfocused_monitor() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 1920, 1080), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        manager.set_focused(dp1);
+        {
+            let ws = &mut manager.get_mut(hdmi1).unwrap().workspaces.workspaces[2];
+            ws.layout.add_window(10);
+            ws.maximized_window = Some(10);
+        }
+
+        // The maximize lives on a workspace of HDMI-1 even though DP-1 has focus
+        assert_eq!(manager.clear_maximize(11), None);
+        assert_eq!(manager.clear_maximize(10), Some(hdmi1));
+        assert_eq!(manager.get(hdmi1).unwrap().workspaces.workspaces[2].maximized_window, None);
+        assert_eq!(manager.clear_maximize(10), None);
+    }
+
+    #[test]
+    fn test_takeover_on_unfocused_monitor() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 2560, 1440), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        manager.set_focused(dp1);
+        let usable = |id: MonitorId| {
+            if id == hdmi1 { Rect::new(1920, 30, 2560, 1410) } else { Rect::new(0, 0, 1920, 1080) }
+        };
+        {
+            let ws = manager.get_mut(hdmi1).unwrap().workspaces.current_mut();
+            ws.add_floating(10, 2000, 100, 800, 600);
+            ws.fullscreen_window = Some(10);
+        }
+
+        // Held over HDMI-1 although DP-1 has focus
+        assert_eq!(manager.takeover_of(10, 0, usable), Some((hdmi1, Rect::new(1920, 0, 2560, 1440))));
+        assert_eq!(manager.takeover_of(11, 0, usable), None);
+
+        {
+            let ws = manager.get_mut(hdmi1).unwrap().workspaces.current_mut();
+            ws.fullscreen_window = None;
+            ws.maximized_window = Some(10);
+        }
+        assert_eq!(manager.takeover_of(10, 0, usable), Some((hdmi1, Rect::new(1920, 30, 2560, 1410))));
+
+        // Only shown workspaces hold their windows
+        manager.get_mut(hdmi1).unwrap().workspaces.switch_to(1);
+        assert_eq!(manager.takeover_of(10, 0, usable), None);
+    }
+
+    #[test]
+    fn test_cycle_windows_across_monitors() {
+        // Inserted right monitor first; the cycle still goes left to right
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("RIGHT", Rect::new(1920, 0, 1920, 1080), false),
+            ("LEFT", Rect::new(0, 0, 1920, 1080), true),
+        ]);
+        let left = manager.find_by_name("LEFT").unwrap();
+        let right = manager.find_by_name("RIGHT").unwrap();
+        {
+            let ws = &mut manager.get_mut(left).unwrap().workspaces.workspaces;
+            ws[0].layout.add_window(1);
+            ws[0].layout.add_window(2);
+            ws[0].add_floating(3, 100, 100, 400, 300);
+            // Not the current workspace, never part of the cycle
+            ws[4].layout.add_window(4);
+        }
+        {
+            let monitor = manager.get_mut(right).unwrap();
+            monitor.workspaces.switch_to(4);
+            monitor.workspaces.workspaces[4].layout.add_window(10);
+            monitor.workspaces.workspaces[0].layout.add_window(11);
+        }
+
+        assert_eq!(
+            manager.cycle_windows(false),
+            vec![(left, 1), (left, 2), (left, 3), (right, 10)]
+        );
+        // Only window 2 is the visible tab of its frame
+        assert_eq!(
+            manager.cycle_windows(true),
+            vec![(left, 2), (left, 3), (right, 10)]
+        );
+    }
+
+    #[test]
+    fn test_windows_survive_losing_every_monitor() {
+        let screen = Rect::new(0, 0, 3840, 1080);
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 1920, 1080), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        {
+            let ws = &mut manager.get_mut(dp1).unwrap().workspaces.workspaces;
+            ws[0].layout.add_window(1);
+            ws[0].layout.split_focused(crate::layout::SplitDirection::Horizontal);
+            ws[0].layout.add_window(2);
+            ws[3].add_floating(3, 10, 10, 400, 300);
+        }
+        manager.get_mut(hdmi1).unwrap().workspaces.workspaces[0].layout.add_window(10);
+        assert!(manager.set_focused(hdmi1));
+
+        // RandR briefly reports nothing: one fallback monitor, windows held
+        let focused = manager.update_monitors(vec![], screen);
+        assert_eq!(manager.count(), 1);
+        assert_eq!(manager.focused().name, "default");
+        assert_eq!(manager.focused_id(), focused);
+        assert_eq!(manager.focused().geometry, screen);
+        assert_eq!(manager.held.len(), 2);
+
+        let mut held = manager.held_windows();
+        held.sort();
+        assert_eq!(held, vec![1, 2, 3, 10]);
+
+        // Still nothing; a window opens on the fallback meanwhile and a held
+        // one is closed
+        manager.update_monitors(vec![], screen);
+        assert_eq!(manager.count(), 1);
+        assert_eq!(manager.held.len(), 2);
+        manager.focused_mut().workspaces.workspaces[0].layout.add_window(20);
+        assert!(manager.remove_held_window(10));
+        assert!(!manager.remove_held_window(20));
+
+        // One monitor comes back and takes everything
+        let geometry = Rect::new(0, 0, 2560, 1440);
+        manager.update_monitors(vec![Monitor::new("DP-2".to_string(), false, geometry, vec![7])], screen);
+        assert_eq!(manager.count(), 1);
+        assert!(manager.held.is_empty());
+        let dp2 = manager.focused();
+        assert_eq!(dp2.name, "DP-2");
+        assert_eq!(dp2.geometry, geometry);
+        let ws = &dp2.workspaces.workspaces;
+        // DP-1's split layout survives on the workspace it had to itself
+        assert_eq!(ws[0].layout.all_frames().len(), 2);
+        let mut windows = ws[0].layout.all_windows();
+        windows.sort();
+        assert_eq!(windows, vec![1, 2, 20]);
+        assert!(ws[3].is_floating(3));
+    }
+
+    #[test]
+    fn test_update_monitors_keeps_known_monitors() {
+        let mut manager = MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 1920, 1080), false),
+        ]);
+        let dp1 = manager.find_by_name("DP-1").unwrap();
+        let hdmi1 = manager.find_by_name("HDMI-1").unwrap();
+        manager.get_mut(hdmi1).unwrap().workspaces.workspaces[0].layout.add_window(10);
+        assert!(manager.set_focused(hdmi1));
+
+        // HDMI-1 moves and DP-1 is unplugged: HDMI-1 keeps its ID, focus and
+        // windows, and takes in DP-1's right away
+        manager.get_mut(dp1).unwrap().workspaces.workspaces[0].layout.add_window(1);
+        let moved = Rect::new(0, 0, 1920, 1080);
+        let focused = manager.update_monitors(
+            vec![Monitor::new("HDMI-1".to_string(), true, moved, vec![5])],
+            Rect::new(0, 0, 1920, 1080),
+        );
+        assert_eq!(focused, hdmi1);
+        assert_eq!(manager.count(), 1);
+        assert!(manager.held.is_empty());
+        let hdmi = manager.get(hdmi1).unwrap();
+        assert_eq!(hdmi.geometry, moved);
+        assert_eq!(hdmi.workspaces.workspaces[0].layout.all_windows(), vec![10, 1]);
+    }
+
+    #[test]
+    fn test_update_monitors_keeps_merge() {
+        let left = Rect::new(0, 0, 1920, 1080);
+        let right = Rect::new(1920, 0, 1920, 1080);
+        let screen = Rect::new(0, 0, 3840, 1080);
+        let mut manager = MonitorManager::with_mock_monitors(&[("DP-1", left, true), ("HDMI-1", right, false)]);
+        let merged = manager.focused_id();
+        assert_eq!(manager.merge().len(), 1);
+
+        // RandR repeats the arrangement that was merged: still merged
+        let same = vec![
+            Monitor::new("HDMI-1".to_string(), false, right, vec![]),
+            Monitor::new("DP-1".to_string(), true, left, vec![]),
+        ];
+        assert_eq!(manager.update_monitors(same, screen), merged);
+        assert!(manager.is_merged());
+        assert_eq!(manager.count(), 1);
+        assert_eq!(manager.focused().geometry, screen);
+
+        // An output moved: back to separate monitors at the new places
+        let moved = Rect::new(0, 1080, 1920, 1080);
+        manager.update_monitors(vec![
+            Monitor::new("DP-1".to_string(), true, left, vec![]),
+            Monitor::new("HDMI-1".to_string(), false, moved, vec![]),
+        ], screen);
+        assert!(!manager.is_merged());
+        assert_eq!(manager.count(), 2);
+        assert_eq!(manager.get(manager.find_by_name("HDMI-1").unwrap()).unwrap().geometry, moved);
+    }
+
+    #[test]
+    fn test_shared_edges() {
+        // Two monitors side by side (offset vertically), a third below the
+        // first that also touches the bottom of the right one
+        let manager = MonitorManager::with_mock_monitors(&[
+            ("LEFT", Rect::new(0, 0, 1920, 1080), true),
+            ("RIGHT", Rect::new(1920, 200, 1280, 1024), false),
+            ("BELOW", Rect::new(0, 1080, 1920, 1080), false),
+        ]);
+        let mut edges = manager.shared_edges();
+        edges.sort_by_key(|e| (e.x1, e.y1));
+        assert_eq!(edges, vec![
+            Edge { x1: 0, y1: 1080, x2: 1920, y2: 1080 },
+            Edge { x1: 1920, y1: 200, x2: 1920, y2: 1080 },
+            Edge { x1: 1920, y1: 1080, x2: 1920, y2: 1224 },
+        ]);
+        assert!(!edges[0].is_vertical());
+        assert!(edges[1].is_vertical());
+
+        // Monitors that only touch at a corner, or not at all, share nothing
+        let manager = MonitorManager::with_mock_monitors(&[
+            ("A", Rect::new(0, 0, 1920, 1080), true),
+            ("B", Rect::new(1920, 1080, 1920, 1080), false),
+        ]);
+        assert!(manager.shared_edges().is_empty());
+    }
+
     #[test]
     fn test_workspace_independence() {
         let mut manager = MonitorManager::with_mock_monitors(&[
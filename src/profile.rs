@@ -0,0 +1,96 @@
+//! Render timing counters, enabled with `ttwm --profile`.
+//!
+//! Tab bar redraws, root background samples (`get_image`) and layout
+//! applications are the expensive paths when many frames are visible. With
+//! profiling on, each call is timed and its duration added to a running total
+//! that `ttwmctl render-stats` reports. Without the flag no clock is read.
+
+use std::time::Instant;
+
+use crate::ipc::{RenderStatsSnapshot, TimingStat};
+
+/// Which counter a measurement belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stat {
+    TabBarRedraw,
+    BackgroundSample,
+    LayoutApplication,
+}
+
+/// Cumulative timings since startup
+#[derive(Debug, Default)]
+pub struct RenderStats {
+    enabled: bool,
+    tab_bar_redraws: TimingStat,
+    background_samples: TimingStat,
+    layout_applications: TimingStat,
+}
+
+impl RenderStats {
+    /// Create the counters; `enabled` is false unless `--profile` was given.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, ..Self::default() }
+    }
+
+    /// Whether timings are being collected
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start timing a call. Returns None (and reads no clock) when disabled.
+    pub fn start(&self) -> Option<Instant> {
+        self.enabled.then(Instant::now)
+    }
+
+    /// Add the time elapsed since `started` to `stat`.
+    pub fn record(&mut self, stat: Stat, started: Option<Instant>) {
+        let Some(started) = started else { return };
+        let micros = started.elapsed().as_micros() as u64;
+        let entry = match stat {
+            Stat::TabBarRedraw => &mut self.tab_bar_redraws,
+            Stat::BackgroundSample => &mut self.background_samples,
+            Stat::LayoutApplication => &mut self.layout_applications,
+        };
+        entry.count += 1;
+        entry.total_us = entry.total_us.saturating_add(micros);
+    }
+
+    /// Current totals for IPC
+    pub fn snapshot(&self) -> RenderStatsSnapshot {
+        RenderStatsSnapshot {
+            tab_bar_redraws: self.tab_bar_redraws,
+            background_samples: self.background_samples,
+            layout_applications: self.layout_applications,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_stats_record_nothing() {
+        let mut stats = RenderStats::new(false);
+        let started = stats.start();
+        assert!(started.is_none());
+        stats.record(Stat::TabBarRedraw, started);
+        assert_eq!(stats.snapshot().tab_bar_redraws.count, 0);
+    }
+
+    #[test]
+    fn test_enabled_stats_count_calls() {
+        let mut stats = RenderStats::new(true);
+        for _ in 0..3 {
+            let started = stats.start();
+            stats.record(Stat::LayoutApplication, started);
+        }
+        let started = stats.start();
+        stats.record(Stat::BackgroundSample, started);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.layout_applications.count, 3);
+        assert_eq!(snapshot.background_samples.count, 1);
+        assert_eq!(snapshot.tab_bar_redraws.count, 0);
+    }
+}
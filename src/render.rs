@@ -6,10 +6,6 @@
 use std::path::PathBuf;
 use anyhow::{Context, Result};
 use freetype::Library as FtLibrary;
-use once_cell::sync::Lazy;
-
-/// Static default icon for windows without _NET_WM_ICON
-pub static DEFAULT_ICON: Lazy<CachedIcon> = Lazy::new(CachedIcon::default_icon);
 
 /// Tab bar rendering constants
 #[allow(dead_code)]
@@ -28,24 +24,27 @@ pub mod constants {
     pub const BEVEL_RADIUS: i16 = 6;
 }
 
-/// Cached window icon (20x20 BGRA pixels)
+/// Cached window icon (square, BGRA pixels)
 pub struct CachedIcon {
-    /// BGRA pixel data (20 * 20 * 4 = 1600 bytes)
+    /// BGRA pixel data (size * size * 4 bytes)
     pub pixels: Vec<u8>,
 }
 
 impl CachedIcon {
     /// Create a default icon for windows without _NET_WM_ICON
-    pub fn default_icon() -> Self {
-        CachedIcon { pixels: generate_default_icon() }
+    pub fn default_icon(size: u32) -> Self {
+        CachedIcon { pixels: generate_default_icon(size) }
     }
 }
 
-/// Generate a default 20x20 window icon (BGRA format)
-/// Design: Simple window outline with title bar
-pub fn generate_default_icon() -> Vec<u8> {
-    const SIZE: usize = 20;
-    let mut pixels = vec![0u8; SIZE * SIZE * 4];
+/// Generate a default window icon of the given size (BGRA format)
+/// Design: Simple window outline with title bar, proportions as drawn at 20x20
+pub fn generate_default_icon(size: u32) -> Vec<u8> {
+    let size = size as usize;
+    let margin = size / 10;
+    let far_edge = size.saturating_sub(margin + 1);
+    let title_rows = (size * 3 / 20).max(1);
+    let mut pixels = vec![0u8; size * size * 4];
 
     // Colors (BGRA format)
     let border = [0x88, 0x88, 0x88, 0xFF];      // Gray border
@@ -53,17 +52,17 @@ pub fn generate_default_icon() -> Vec<u8> {
     let background = [0x3A, 0x3A, 0x3A, 0xFF];  // Dark background
     let transparent = [0x00, 0x00, 0x00, 0x00]; // Transparent
 
-    for y in 0..SIZE {
-        for x in 0..SIZE {
-            let idx = (y * SIZE + x) * 4;
-            let pixel = if x < 2 || x >= 18 || y < 2 || y >= 18 {
+    for y in 0..size {
+        for x in 0..size {
+            let idx = (y * size + x) * 4;
+            let pixel = if !(margin..=far_edge).contains(&x) || !(margin..=far_edge).contains(&y) {
                 // Outside main area - transparent with padding
                 transparent
-            } else if x == 2 || x == 17 || y == 2 || y == 17 {
+            } else if x == margin || x == far_edge || y == margin || y == far_edge {
                 // Border
                 border
-            } else if y >= 3 && y <= 5 {
-                // Title bar area (3 pixels tall)
+            } else if y <= margin + title_rows {
+                // Title bar area (3 pixels tall at 20x20)
                 title_bar
             } else {
                 // Window content area
@@ -237,7 +236,7 @@ impl FontRenderer {
 
                 if path.is_dir() {
                     // Check ttf subdirectory first (common on Linux)
-                    if path.file_name().map_or(false, |n| n == "truetype" || n == "TTF") {
+                    if path.file_name().is_some_and(|n| n == "truetype" || n == "TTF") {
                         if let Some(found) = Self::find_any_font_in_dir(&path) {
                             return Some(found);
                         }
@@ -256,6 +255,11 @@ impl FontRenderer {
         None
     }
 
+    /// Line height of the loaded font in pixels
+    pub fn char_height(&self) -> u32 {
+        self.char_height
+    }
+
     /// Render text and return BGRA pixel data (for X11 ZPixmap format)
     pub fn render_text(&self, text: &str, fg_color: u32, bg_color: u32) -> (Vec<u8>, u32, u32) {
         if text.is_empty() {
@@ -469,4 +473,24 @@ mod tests {
         assert_eq!(result[1], 0x00); // G (from bg)
         assert_eq!(result[2], 0xFF); // R (from bg)
     }
+
+    #[test]
+    fn test_default_icon_scales_with_size() {
+        let pixel = |icon: &[u8], size: usize, x: usize, y: usize| {
+            let idx = (y * size + x) * 4;
+            [icon[idx], icon[idx + 1], icon[idx + 2], icon[idx + 3]]
+        };
+
+        let small = generate_default_icon(20);
+        assert_eq!(small.len(), 20 * 20 * 4);
+        assert_eq!(pixel(&small, 20, 0, 0)[3], 0x00); // Transparent margin
+        assert_eq!(pixel(&small, 20, 2, 10), [0x88, 0x88, 0x88, 0xFF]); // Border
+        assert_eq!(pixel(&small, 20, 10, 5), [0xAA, 0xAA, 0xAA, 0xFF]); // Title bar
+        assert_eq!(pixel(&small, 20, 10, 6), [0x3A, 0x3A, 0x3A, 0xFF]); // Content
+
+        let large = generate_default_icon(40);
+        assert_eq!(large.len(), 40 * 40 * 4);
+        assert_eq!(pixel(&large, 40, 4, 20), [0x88, 0x88, 0x88, 0xFF]);
+        assert_eq!(pixel(&large, 40, 35, 20), [0x88, 0x88, 0x88, 0xFF]);
+    }
 }
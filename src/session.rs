@@ -0,0 +1,137 @@
+//! Session state saved across restarts.
+//!
+//! Currently this records which workspace each monitor was showing, keyed by
+//! output name so assignments survive monitors being enumerated in a
+//! different order. It is written on exit (or via IPC) and restored at
+//! startup. Each X display has its own file, so a nested server doesn't
+//! overwrite the session of the one it runs in.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::monitor::{MonitorId, MonitorManager};
+use crate::workspaces::NUM_WORKSPACES;
+
+/// Saved session state
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Monitor output name -> current workspace index (0-based)
+    pub monitor_workspaces: BTreeMap<String, usize>,
+}
+
+impl Session {
+    /// Default session file path for the current display (from $DISPLAY)
+    pub fn default_path() -> PathBuf {
+        let display = std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
+        let sanitized = display.replace([':', '.'], "_");
+        dirs::state_dir()
+            .or_else(dirs::cache_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("ttwm")
+            .join(format!("session{}.toml", sanitized))
+    }
+
+    /// Record the current workspace of every connected monitor.
+    ///
+    /// Entries for monitors that are not connected right now are kept, so
+    /// unplugging a monitor doesn't forget its workspace.
+    pub fn capture(&mut self, monitors: &MonitorManager) {
+        for (_, monitor) in monitors.iter() {
+            self.monitor_workspaces.insert(monitor.name.clone(), monitor.workspaces.current_index());
+        }
+    }
+
+    /// Workspace to show on each connected monitor.
+    ///
+    /// Saved monitors that are no longer connected are ignored, and monitors
+    /// without a saved entry are left out (they keep their default workspace).
+    pub fn assignments(&self, monitors: &MonitorManager) -> Vec<(MonitorId, usize)> {
+        monitors.iter()
+            .filter_map(|(id, monitor)| {
+                let index = *self.monitor_workspaces.get(&monitor.name)?;
+                (index < NUM_WORKSPACES).then_some((id, index))
+            })
+            .collect()
+    }
+
+    /// Load a session file. Returns None if it doesn't exist or can't be parsed.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match toml::from_str(&contents) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                log::warn!("Ignoring invalid session file {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Write the session file, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {:?}", dir))?;
+        }
+        let contents = toml::to_string(self)?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write session file {:?}", path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Rect;
+
+    #[test]
+    fn test_monitor_workspaces_round_trip() {
+        let mut before = MonitorManager::new();
+        let left = before.add_mock_monitor("DP-1", Rect::new(0, 0, 1920, 1080), true);
+        let right = before.add_mock_monitor("HDMI-1", Rect::new(1920, 0, 1920, 1080), false);
+        before.get_mut(left).unwrap().workspaces.switch_to(2);
+        before.get_mut(right).unwrap().workspaces.switch_to(8);
+
+        let mut captured = Session::default();
+        captured.capture(&before);
+        let saved = toml::to_string(&captured).unwrap();
+        let session: Session = toml::from_str(&saved).unwrap();
+
+        // After restart: HDMI-1 is gone, a new monitor appeared, order changed
+        let mut after = MonitorManager::new();
+        let new = after.add_mock_monitor("eDP-1", Rect::new(0, 0, 1366, 768), true);
+        let dp = after.add_mock_monitor("DP-1", Rect::new(1366, 0, 1920, 1080), false);
+
+        let assignments = session.assignments(&after);
+        assert_eq!(assignments, vec![(dp, 2)]);
+        assert!(!assignments.iter().any(|&(id, _)| id == new));
+    }
+
+    #[test]
+    fn test_out_of_range_workspace_is_ignored() {
+        let mut monitors = MonitorManager::new();
+        monitors.add_mock_monitor("DP-1", Rect::new(0, 0, 1920, 1080), true);
+
+        let session: Session = toml::from_str("[monitor_workspaces]\n\"DP-1\" = 42").unwrap();
+        assert!(session.assignments(&monitors).is_empty());
+        assert_eq!(toml::from_str::<Session>("").unwrap(), Session::default());
+    }
+
+    #[test]
+    fn test_capture_keeps_disconnected_monitors() {
+        let mut session: Session = toml::from_str("[monitor_workspaces]\n\"DP-1\" = 3\n\"HDMI-1\" = 5").unwrap();
+
+        // Only DP-1 is connected now, showing a different workspace
+        let mut monitors = MonitorManager::new();
+        let dp = monitors.add_mock_monitor("DP-1", Rect::new(0, 0, 1920, 1080), true);
+        monitors.get_mut(dp).unwrap().workspaces.switch_to(1);
+        session.capture(&monitors);
+
+        assert_eq!(session.monitor_workspaces.get("DP-1"), Some(&1));
+        assert_eq!(session.monitor_workspaces.get("HDMI-1"), Some(&5));
+    }
+}
@@ -5,8 +5,8 @@
 
 use std::process::Command;
 
+use crate::children::ChildTracker;
 use crate::config::StartupConfig;
-use crate::layout::NodeId;
 use crate::workspaces::{Workspace, NUM_WORKSPACES};
 
 /// Information about a pending app spawn
@@ -16,8 +16,6 @@ pub struct PendingSpawn {
     pub command: String,
     /// Target workspace index (0-based)
     pub workspace_idx: usize,
-    /// Target frame NodeId within that workspace
-    pub frame_id: NodeId,
     /// Target frame name (for logging)
     pub frame_name: Option<String>,
 }
@@ -90,7 +88,6 @@ impl StartupManager {
                     all_spawns.push(PendingSpawn {
                         command,
                         workspace_idx: ws_idx,
-                        frame_id,
                         frame_name: frame_name.clone(),
                     });
                 }
@@ -101,16 +98,16 @@ impl StartupManager {
         all_spawns
     }
 
-    /// Spawn all pending apps at once
-    pub fn spawn_all(&mut self) {
+    /// Spawn all pending apps at once, recording them in `children`
+    pub fn spawn_all(&mut self, children: &mut ChildTracker) {
         for spawn in self.pending_spawns.drain(..) {
-            Self::spawn_command(&spawn.command, spawn.frame_name.as_deref());
+            Self::spawn_command(&spawn.command, spawn.frame_name.as_deref(), children);
         }
         self.startup_complete = true;
     }
 
     /// Spawn a single command
-    fn spawn_command(command: &str, frame_name: Option<&str>) {
+    fn spawn_command(command: &str, frame_name: Option<&str>, children: &mut ChildTracker) {
         let frame_info = frame_name
             .map(|n| format!(" in frame '{}'", n))
             .unwrap_or_default();
@@ -137,8 +134,9 @@ impl StartupManager {
                 }
             }
 
-            if let Err(e) = cmd.spawn() {
-                log::error!("Failed to spawn startup app '{}': {}", command, e);
+            match cmd.spawn() {
+                Ok(child) => children.track(command, child),
+                Err(e) => log::error!("Failed to spawn startup app '{}': {}", command, e),
             }
         }
     }
@@ -168,7 +166,7 @@ mod tests {
     use crate::config::{FrameConfig, LayoutNodeConfig, SplitConfig, SplitDirectionConfig, WorkspaceStartup};
 
     fn create_test_workspaces() -> [Workspace; NUM_WORKSPACES] {
-        std::array::from_fn(|i| Workspace::new(i + 1))
+        std::array::from_fn(|_| Workspace::new())
     }
 
     #[test]
@@ -320,7 +318,7 @@ mod tests {
         let mut manager = StartupManager::new();
         assert!(!manager.is_complete());
 
-        manager.spawn_all();
+        manager.spawn_all(&mut ChildTracker::new());
 
         assert!(manager.is_complete());
     }
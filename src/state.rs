@@ -1,11 +1,10 @@
-//! State machine types and validation for ttwm.
+//! State machine types for ttwm.
 //!
 //! This module formalizes the state machines in the window manager:
 //! - Window lifecycle states
 //! - Focus states
-//! - Frame states
 //!
-//! The state validator checks invariants that should always hold.
+//! It also defines the state transitions recorded by the event tracer.
 
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +37,7 @@ use serde::{Deserialize, Serialize};
 ///              │ Destroyed │
 ///              └───────────┘
 /// ```
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WindowState {
@@ -65,6 +65,7 @@ pub enum WindowState {
 ///     │                                      ▼
 ///     └──────────────────────────────────────┘
 /// ```
+#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FocusState {
@@ -74,69 +75,6 @@ pub enum FocusState {
     Focused { window: u32 },
 }
 
-/// Frame state machine
-///
-/// ```text
-///     ┌─────────────────────────────────────────────┐
-///     │                                             │
-///     ▼                                             │
-/// ┌─────────┐   add_window()   ┌──────────────┐     │
-/// │  Empty  │ ────────────────►│ SingleWindow │     │
-/// └────┬────┘                  └───────┬──────┘     │
-///      │                               │            │
-///      │ remove_empty_frames()         │ add_window()
-///      ▼                               ▼            │
-///   (removed)                   ┌─────────────┐     │
-///                               │   Tabbed    │ ────┘
-///                               │ (2+ windows)│ remove_window()
-///                               └─────────────┘     to 0 windows
-/// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum FrameState {
-    /// Frame has no windows (pending cleanup unless root)
-    Empty,
-    /// Frame has exactly one window (no tab bar shown)
-    SingleWindow { window: u32 },
-    /// Frame has multiple windows (tab bar shown)
-    Tabbed {
-        windows: Vec<u32>,
-        active_tab: usize,
-    },
-}
-
-/// State violations that can be detected
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub struct StateViolation {
-    pub kind: ViolationKind,
-    pub description: String,
-}
-
-/// Types of state violations
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ViolationKind {
-    /// Window exists in X but not tracked in layout
-    OrphanedWindow,
-    /// Window tracked in layout but doesn't exist in X
-    GhostWindow,
-    /// Focus is on a window that doesn't exist
-    InvalidFocus,
-    /// Empty frame that should have been cleaned up
-    EmptyFrameLeaked,
-    /// Split ratio outside valid bounds
-    SplitRatioOutOfBounds,
-    /// Tab index points to non-existent tab
-    TabIndexOutOfBounds,
-    /// Focused frame doesn't exist
-    FocusedFrameMissing,
-    /// Hidden window not tracked in layout
-    HiddenWindowOrphaned,
-    /// Tab bar exists for non-existent frame
-    OrphanedTabBar,
-}
-
 /// State transition events that can be traced
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "transition", rename_all = "snake_case")]
@@ -196,6 +134,8 @@ pub enum UnmanageReason {
     ClientUnmapped,
     /// WM closed the window (user action)
     WmClosed,
+    /// Window turned override-redirect and takes care of itself
+    OverrideRedirect,
 }
 
 #[cfg(test)]
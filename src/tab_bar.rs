@@ -6,17 +6,17 @@
 //! - Rounded rectangle shapes for tabs
 //! - Background fills and separators
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use anyhow::Result;
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 
-use crate::config::LayoutConfig;
+use crate::config::{LayoutConfig, TabLabel, TabSizing};
 use crate::ewmh::Atoms;
 use crate::icon;
 use crate::layout::{NodeId, Rect};
 use crate::monitor::MonitorId;
-use crate::render::{CachedIcon, FontRenderer, DEFAULT_ICON};
+use crate::render::{CachedIcon, FontRenderer};
 use crate::window_query;
 
 // =============================================================================
@@ -26,6 +26,61 @@ use crate::window_query;
 /// Key for identifying tab bar and empty frame windows
 pub type TabBarKey = (MonitorId, usize, NodeId);
 
+/// Window icons cached per (window, icon size), evicting the least
+/// recently used entry once more than `capacity` are held (0 = unlimited)
+pub struct IconCache {
+    entries: HashMap<(Window, u32), CachedIcon>,
+    /// Keys from least to most recently used
+    order: VecDeque<(Window, u32)>,
+    capacity: usize,
+}
+
+impl IconCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Look up an icon, marking it most recently used
+    pub fn get(&mut self, key: (Window, u32)) -> Option<&CachedIcon> {
+        if !self.entries.contains_key(&key) {
+            return None;
+        }
+        self.touch(key);
+        self.entries.get(&key)
+    }
+
+    /// Cache an icon, evicting the least recently used ones over capacity
+    pub fn insert(&mut self, key: (Window, u32), icon: CachedIcon) {
+        self.entries.insert(key, icon);
+        self.touch(key);
+        while self.capacity > 0 && self.entries.len() > self.capacity {
+            let Some(oldest) = self.order.pop_front() else { break };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    /// Drop every cached size of a window's icon
+    pub fn remove_window(&mut self, window: Window) {
+        self.entries.retain(|&(w, _), _| w != window);
+        self.order.retain(|&(w, _)| w != window);
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &(Window, u32)> {
+        self.entries.keys()
+    }
+
+    fn touch(&mut self, key: (Window, u32)) {
+        if let Some(pos) = self.order.iter().position(|&k| k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(key);
+    }
+}
+
 /// Tab bar state and rendering manager.
 ///
 /// Owns all tab bar-related state including window handles, pixmap buffers,
@@ -38,10 +93,16 @@ pub struct TabBarManager {
     pub pixmaps: HashMap<Window, u32>,
     /// Map from (monitor, workspace, frame) to empty frame placeholder window
     pub empty_frame_windows: HashMap<TabBarKey, Window>,
-    /// Cached window icons
-    pub icon_cache: HashMap<Window, CachedIcon>,
+    /// Cached window icons, keyed by window and icon size
+    pub icon_cache: IconCache,
+    /// Default icons for windows without _NET_WM_ICON, keyed by icon size
+    default_icons: HashMap<u32, CachedIcon>,
     /// Font renderer for tab text
     pub font_renderer: FontRenderer,
+    /// Renderers for per-monitor tab font sizes, one per distinct size
+    sized_fonts: HashMap<u32, FontRenderer>,
+    /// Tab font size by monitor name (`[monitor."NAME"] tab_font_size`)
+    monitor_font_sizes: HashMap<String, u32>,
     /// Graphics context for drawing
     pub gc: Gcontext,
     /// Screen color depth
@@ -50,23 +111,58 @@ pub struct TabBarManager {
 
 impl TabBarManager {
     /// Create a new tab bar manager.
-    pub fn new(font_renderer: FontRenderer, gc: Gcontext, screen_depth: u8) -> Self {
+    pub fn new(font_renderer: FontRenderer, gc: Gcontext, screen_depth: u8, icon_cache_size: usize) -> Self {
         Self {
             windows: HashMap::new(),
             pixmaps: HashMap::new(),
             empty_frame_windows: HashMap::new(),
-            icon_cache: HashMap::new(),
+            icon_cache: IconCache::new(icon_cache_size),
+            default_icons: HashMap::new(),
             font_renderer,
+            sized_fonts: HashMap::new(),
+            monitor_font_sizes: HashMap::new(),
             gc,
             screen_depth,
         }
     }
 
+    /// Load a renderer for each distinct per-monitor tab font size. Sizes
+    /// whose font fails to load fall back to the global renderer.
+    pub fn load_monitor_fonts(&mut self, font_name: &str, sizes: HashMap<String, u32>) {
+        for &size in sizes.values() {
+            if self.sized_fonts.contains_key(&size) {
+                continue;
+            }
+            match FontRenderer::new(font_name, size) {
+                Ok(renderer) => {
+                    self.sized_fonts.insert(size, renderer);
+                }
+                Err(e) => log::warn!("Failed to load tab font at size {}: {}", size, e),
+            }
+        }
+        self.monitor_font_sizes = sizes;
+    }
+
+    /// Line height of the tallest loaded tab font (global or per-monitor)
+    pub fn max_char_height(&self) -> u32 {
+        self.sized_fonts.values()
+            .map(FontRenderer::char_height)
+            .fold(self.font_renderer.char_height(), u32::max)
+    }
+
+    /// Renderer for tab text on the named monitor
+    pub fn font_for_monitor(&self, monitor: &str) -> &FontRenderer {
+        self.monitor_font_sizes.get(monitor)
+            .and_then(|size| self.sized_fonts.get(size))
+            .unwrap_or(&self.font_renderer)
+    }
+
     // =========================================================================
     // Tab bar window lifecycle
     // =========================================================================
 
     /// Get or create a tab bar window for a frame.
+    /// The bar sits at the frame's top-left corner with the given size.
     pub fn get_or_create_window(
         &mut self,
         conn: &impl Connection,
@@ -74,16 +170,9 @@ impl TabBarManager {
         config: &LayoutConfig,
         key: TabBarKey,
         rect: &Rect,
-        vertical: bool,
+        (width, height): (u32, u32),
     ) -> Result<Window> {
-        // Calculate dimensions based on orientation
-        let (x, y, width, height) = if vertical {
-            // Vertical: left side of frame, full height
-            (rect.x, rect.y, config.vertical_tab_width, rect.height)
-        } else {
-            // Horizontal: top of frame, full width
-            (rect.x, rect.y, rect.width, config.tab_bar_height)
-        };
+        let (x, y) = (rect.x, rect.y);
 
         if let Some(&window) = self.windows.get(&key) {
             // Update position and size
@@ -175,11 +264,29 @@ impl TabBarManager {
         }
     }
 
+    /// Destroy every tab bar and empty frame placeholder (on exit).
+    pub fn destroy_all(&mut self, conn: &impl Connection) {
+        for (_, window) in self.windows.drain() {
+            if let Some(pixmap) = self.pixmaps.remove(&window) {
+                let _ = conn.free_pixmap(pixmap);
+            }
+            if let Err(e) = conn.destroy_window(window) {
+                log::error!("Failed to destroy tab bar window: {}", e);
+            }
+        }
+        for (_, window) in self.empty_frame_windows.drain() {
+            if let Err(e) = conn.destroy_window(window) {
+                log::error!("Failed to destroy empty frame window: {}", e);
+            }
+        }
+    }
+
     // =========================================================================
     // Empty frame window lifecycle
     // =========================================================================
 
     /// Get or create a placeholder window for an empty frame (shows border).
+    #[allow(clippy::too_many_arguments)]
     pub fn get_or_create_empty_frame(
         &mut self,
         conn: &impl Connection,
@@ -188,12 +295,13 @@ impl TabBarManager {
         key: TabBarKey,
         rect: &Rect,
         is_focused: bool,
+        background: u32,
     ) -> Result<Window> {
         let border = config.border_width;
         let client_y = rect.y;
         let client_height = rect.height;
         let border_color = if is_focused {
-            config.border_focused
+            config.border_focused_for(key.1)
         } else {
             config.border_unfocused
         };
@@ -211,8 +319,10 @@ impl TabBarManager {
             )?;
             conn.change_window_attributes(
                 window,
-                &ChangeWindowAttributesAux::new().border_pixel(border_color),
+                &ChangeWindowAttributesAux::new().border_pixel(border_color).background_pixel(background),
             )?;
+            // Repaint in case the background changed
+            conn.clear_area(false, window, 0, 0, 0, 0)?;
             // Re-map in case it was hidden (e.g., workspace switch)
             conn.map_window(window)?;
             return Ok(window);
@@ -232,7 +342,7 @@ impl TabBarManager {
             WindowClass::INPUT_OUTPUT,
             x11rb::COPY_FROM_PARENT,
             &CreateWindowAux::new()
-                .background_pixel(config.tab_bar_bg)
+                .background_pixel(background)
                 .border_pixel(border_color)
                 .event_mask(EventMask::BUTTON_PRESS),
         )?;
@@ -281,29 +391,27 @@ impl TabBarManager {
     // Icon management
     // =========================================================================
 
-    /// Get window icon, fetching from X11 if not cached.
+    /// Get window icon scaled to `size`, fetching from X11 if not cached.
     /// Returns a reference to the default icon if the window has no icon.
-    pub fn get_icon(&mut self, conn: &impl Connection, atoms: &Atoms, window: Window) -> &CachedIcon {
-        const ICON_SIZE: u32 = 20;
-
+    pub fn get_icon(&mut self, conn: &impl Connection, atoms: &Atoms, window: Window, size: u32) -> &CachedIcon {
         // Check cache first
-        if self.icon_cache.contains_key(&window) {
-            return self.icon_cache.get(&window).unwrap();
+        if self.icon_cache.get((window, size)).is_some() {
+            return self.icon_cache.get((window, size)).unwrap();
         }
 
         // Try to fetch _NET_WM_ICON - only cache if we get an actual icon
-        if let Some(icon) = icon::fetch_icon(conn, atoms, window, ICON_SIZE) {
-            self.icon_cache.insert(window, icon);
-            return self.icon_cache.get(&window).unwrap();
+        if let Some(icon) = icon::fetch_icon(conn, atoms, window, size) {
+            self.icon_cache.insert((window, size), icon);
+            return self.icon_cache.get((window, size)).unwrap();
         }
 
         // Return default icon for windows without _NET_WM_ICON
-        &DEFAULT_ICON
+        self.default_icons.entry(size).or_insert_with(|| CachedIcon::default_icon(size))
     }
 
-    /// Invalidate cached icon for a window (call when PropertyNotify for _NET_WM_ICON).
+    /// Invalidate cached icons for a window (call when PropertyNotify for _NET_WM_ICON).
     pub fn invalidate_icon(&mut self, window: Window) {
-        self.icon_cache.remove(&window);
+        self.icon_cache.remove_window(window);
     }
 
     // =========================================================================
@@ -312,40 +420,28 @@ impl TabBarManager {
 
     /// Calculate tab widths based on window titles (Chrome-style content-based sizing).
     /// Returns a vector of (x_position, width) for each tab.
+    /// Titles are measured in the tab font of `monitor`.
+    #[allow(clippy::too_many_arguments)]
     pub fn calculate_tab_layout(
         &self,
         conn: &impl Connection,
         atoms: &Atoms,
         config: &LayoutConfig,
+        monitor: &str,
         windows: &[Window],
+        sizing: TabSizing,
+        bar_width: u32,
     ) -> Vec<(i16, u32)> {
-        const MIN_TAB_WIDTH: u32 = 80;
-        const MAX_TAB_WIDTH: u32 = 200;
-        const H_PADDING: u32 = 24; // Total horizontal padding (12px each side)
-        const ICON_SIZE: u32 = 20;
-        const ICON_PADDING: u32 = 4; // Padding after icon
-
-        // Extra width for icon when enabled
-        let icon_width = if config.show_tab_icons {
-            ICON_SIZE + ICON_PADDING
+        // Only content sizing depends on the titles, and compact tabs have none
+        let font = self.font_for_monitor(monitor);
+        let title_widths: Vec<u32> = if sizing == TabSizing::Content && !config.tab_compact {
+            windows.iter()
+                .map(|&w| font.measure_text(&window_tab_label(conn, atoms, config, w)))
+                .collect()
         } else {
-            0
+            vec![0; windows.len()]
         };
-
-        let mut result = Vec::new();
-        let mut x_offset: i16 = 0;
-
-        for &client_window in windows {
-            let title = window_query::get_window_title(conn, atoms, client_window);
-            let title_width = self.font_renderer.measure_text(&title);
-            let tab_width = (title_width + H_PADDING + icon_width)
-                .clamp(MIN_TAB_WIDTH + icon_width, MAX_TAB_WIDTH + icon_width);
-
-            result.push((x_offset, tab_width));
-            x_offset += tab_width as i16;
-        }
-
-        result
+        tab_layout(config, sizing, &title_widths, bar_width)
     }
 
     /// Sample the root window background at the given position.
@@ -371,14 +467,195 @@ impl TabBarManager {
     }
 }
 
+/// Text shown in a window's tab, per `appearance.tab_label` and its per-class overrides.
+pub fn window_tab_label(conn: &impl Connection, atoms: &Atoms, config: &LayoutConfig, window: Window) -> String {
+    let title = window_query::get_window_title(conn, atoms, window);
+    if config.tab_label == TabLabel::Title && config.tab_label_by_class.is_empty() {
+        return title;
+    }
+
+    let class = window_query::get_window_class(conn, window).map(|(_, class)| class);
+    let mode = class.as_ref()
+        .and_then(|class| config.tab_label_by_class.get(class))
+        .copied()
+        .unwrap_or(config.tab_label);
+    format_tab_label(mode, class.as_deref(), &title)
+}
+
+/// Build a tab label from a window's class and title.
+/// Windows without a WM_CLASS always show their title.
+pub fn format_tab_label(mode: TabLabel, class: Option<&str>, title: &str) -> String {
+    match (mode, class) {
+        (TabLabel::Class, Some(class)) => class.to_string(),
+        (TabLabel::ClassTitle, Some(class)) => format!("{}: {}", class, title),
+        _ => title.to_string(),
+    }
+}
+
+/// Padding above/below the tab contents (and beside vertical tab icons)
+/// when tab bar sizes are set to `auto`.
+pub const AUTO_SIZE_PADDING: u32 = 4;
+
+/// Tab bar height that fits the font's line height and the tab icon.
+pub fn auto_tab_bar_height(char_height: u32, icon_size: u32, show_icons: bool) -> u32 {
+    let content = if show_icons { char_height.max(icon_size) } else { char_height };
+    content + 2 * AUTO_SIZE_PADDING
+}
+
+/// Vertical tab bar width that fits the tab icon.
+pub fn auto_vertical_tab_width(icon_size: u32) -> u32 {
+    icon_size + 2 * AUTO_SIZE_PADDING
+}
+
+/// Height of a stacked frame's title rows (one per window). Rows that don't
+/// fit are cut off so the focused window keeps at least one row's height.
+pub fn stacked_bar_height(row_height: u32, tabs: usize, frame_height: u32) -> u32 {
+    (row_height * tabs as u32).min(frame_height.saturating_sub(row_height.max(1)))
+}
+
+/// Area left for a frame's windows (borders included) once its tab bar is
+/// taken out. Empty frames have no tab bar.
+pub fn client_area(
+    frame: Rect,
+    tabs: usize,
+    stacked: bool,
+    vertical_tabs: bool,
+    tab_bar_height: u32,
+    vertical_tab_width: u32,
+) -> Rect {
+    if tabs == 0 {
+        frame
+    } else if stacked {
+        // Stacked: client area is below one title row per window
+        let bar_height = stacked_bar_height(tab_bar_height, tabs, frame.height);
+        Rect::new(frame.x, frame.y + bar_height as i32, frame.width, frame.height - bar_height)
+    } else if vertical_tabs {
+        // Vertical tabs: client area is to the right of the tab bar
+        Rect::new(
+            frame.x + vertical_tab_width as i32,
+            frame.y,
+            frame.width.saturating_sub(vertical_tab_width),
+            frame.height,
+        )
+    } else {
+        // Horizontal tabs: client area is below the tab bar
+        Rect::new(
+            frame.x,
+            frame.y + tab_bar_height as i32,
+            frame.width,
+            frame.height.saturating_sub(tab_bar_height),
+        )
+    }
+}
+
+/// Index of the tab at `offset` pixels along a bar of equal-sized tabs
+/// (vertical tabs or stacked rows).
+pub fn tab_at_offset(offset: i32, tab_size: u32, tabs: usize) -> Option<usize> {
+    if offset < 0 || tab_size == 0 {
+        return None;
+    }
+    let index = offset as usize / tab_size as usize;
+    (index < tabs).then_some(index)
+}
+
+/// Width of a horizontal tab whose title measures `title_width` pixels.
+///
+/// The title is clamped to a minimum/maximum width, with room for the icon
+/// (and its padding) added on top when icons are shown.
+pub fn tab_width(config: &LayoutConfig, title_width: u32) -> u32 {
+    const MIN_TAB_WIDTH: u32 = 80;
+    const MAX_TAB_WIDTH: u32 = 200;
+    const H_PADDING: u32 = 24; // Total horizontal padding (12px each side)
+
+    // Extra width for icon when enabled
+    let icon_width = if config.show_tab_icons {
+        config.tab_icon_size + config.tab_icon_padding
+    } else {
+        0
+    };
+
+    (title_width + H_PADDING + icon_width)
+        .clamp(MIN_TAB_WIDTH + icon_width, MAX_TAB_WIDTH + icon_width)
+}
+
+/// Width of a compact (icon-only) tab: the icon with padding on each side.
+pub fn compact_tab_width(config: &LayoutConfig) -> u32 {
+    const COMPACT_PADDING: u32 = 8;
+    config.tab_icon_size + 2 * COMPACT_PADDING
+}
+
+/// Lay out horizontal tabs as (x, width) pairs from their title widths.
+///
+/// `Equal` splits `bar_width` between the tabs (the last one takes the
+/// rounding remainder); titles that don't fit are truncated when drawn.
+/// Compact tabs are all `compact_tab_width`, whatever the sizing mode.
+pub fn tab_layout(config: &LayoutConfig, sizing: TabSizing, title_widths: &[u32], bar_width: u32) -> Vec<(i16, u32)> {
+    let count = title_widths.len() as u32;
+    let mut x_offset: i16 = 0;
+    title_widths.iter().enumerate().map(|(i, &title_width)| {
+        let width = match sizing {
+            _ if config.tab_compact => compact_tab_width(config),
+            TabSizing::Content => tab_width(config, title_width),
+            TabSizing::Fixed => config.fixed_tab_width,
+            TabSizing::Equal if i as u32 == count - 1 => {
+                (bar_width / count + bar_width % count).max(1)
+            }
+            TabSizing::Equal => (bar_width / count).max(1),
+        };
+        let tab = (x_offset, width);
+        x_offset += width as i16;
+        tab
+    }).collect()
+}
+
+/// Windows to raise, bottom to top, when the tiled window `client` in the
+/// frame of `key` gets focus: the client, then its frame's tab bar (or every
+/// tab bar on that monitor's workspace when `all_tab_bars` is set), then
+/// `above` - the docks and any fullscreen window (see `Workspace::raise_order`).
+pub fn raise_order(
+    tab_bars: &HashMap<TabBarKey, Window>,
+    client: Window,
+    key: TabBarKey,
+    all_tab_bars: bool,
+    above: impl IntoIterator<Item = Window>,
+) -> Vec<Window> {
+    let (monitor, workspace, _) = key;
+    let mut order = vec![client];
+    if all_tab_bars {
+        let mut bars: Vec<Window> = tab_bars.iter()
+            .filter(|(&(m, w, _), _)| m == monitor && w == workspace)
+            .map(|(_, &tab_window)| tab_window)
+            .collect();
+        bars.sort_unstable();
+        order.extend(bars);
+    } else {
+        order.extend(tab_bars.get(&key).copied());
+    }
+    order.extend(above);
+    order
+}
+
 // =============================================================================
 // Low-level drawing primitives
 // =============================================================================
 
+/// Corner radius actually drawn on a `width` x `height` tab: the requested
+/// radius, clamped to half of each dimension so opposite corners never overlap.
+pub fn clamp_corner_radius(radius: u32, width: u32, height: u32) -> u32 {
+    radius.min(width / 2).min(height / 2)
+}
+
+/// Corner radius for vertical tabs, which are small squares and look best
+/// with two thirds of `appearance.tab_corner_radius` (4px for the default 6px).
+pub fn vertical_corner_radius(radius: u32) -> u32 {
+    radius * 2 / 3
+}
+
 /// Draw a filled rectangle with rounded top corners.
 ///
 /// Uses X11 arcs to create smooth quarter-circle corners at the top-left
 /// and top-right, with the bottom edge remaining square.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_rounded_top_rect(
     conn: &impl Connection,
     gc: Gcontext,
@@ -389,7 +666,7 @@ pub fn draw_rounded_top_rect(
     height: u32,
     radius: u32,
 ) -> Result<()> {
-    let r = radius.min(width / 2).min(height / 2) as i16;
+    let r = clamp_corner_radius(radius, width, height) as i16;
     let w = width as i16;
     let h = height as i16;
 
@@ -419,6 +696,10 @@ pub fn draw_rounded_top_rect(
         )?;
     }
 
+    if r == 0 {
+        return Ok(());
+    }
+
     // Draw top-left corner arc (quarter circle)
     // Arc angles are in 1/64th of a degree, starting from 3 o'clock going counterclockwise
     // Top-left: start at 90°, sweep 90° counterclockwise
@@ -457,6 +738,7 @@ pub fn draw_rounded_top_rect(
 ///
 /// Used for vertical tabs (left edge of frame). Creates rounded corners
 /// at top-left and bottom-left, with the right edge remaining square.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_rounded_left_rect(
     conn: &impl Connection,
     gc: Gcontext,
@@ -467,7 +749,7 @@ pub fn draw_rounded_left_rect(
     height: u32,
     radius: u32,
 ) -> Result<()> {
-    let r = radius.min(width / 2).min(height / 2) as i16;
+    let r = clamp_corner_radius(radius, width, height) as i16;
     let w = width as i16;
     let h = height as i16;
 
@@ -497,6 +779,10 @@ pub fn draw_rounded_left_rect(
         )?;
     }
 
+    if r == 0 {
+        return Ok(());
+    }
+
     // Draw top-left corner arc (quarter circle)
     // Arc angles are in 1/64th of a degree, starting from 3 o'clock going counterclockwise
     // Top-left: start at 90°, sweep 90° counterclockwise to 180°
@@ -631,3 +917,228 @@ pub fn clear_area(
     )?;
     Ok(())
 }
+
+/// Size of the lock glyph drawn on locked frames' tab bars.
+pub const LOCK_GLYPH_SIZE: u16 = 10;
+
+/// Draw a small padlock glyph with its top-left corner at (x, y).
+///
+/// The glyph is a shackle arc over a filled body, LOCK_GLYPH_SIZE pixels square.
+/// Note: The GC foreground color must be set before calling this function.
+pub fn draw_lock_glyph(
+    conn: &impl Connection,
+    gc: Gcontext,
+    drawable: Drawable,
+    x: i16,
+    y: i16,
+) -> Result<()> {
+    let size = LOCK_GLYPH_SIZE;
+    let body_height = size / 2;
+    let shackle_width = size - 4;
+
+    conn.poly_arc(
+        drawable,
+        gc,
+        &[Arc {
+            x: x + 2,
+            y,
+            width: shackle_width - 1,
+            height: size - 2,
+            angle1: 0,
+            angle2: 180 * 64, // Upper half (angles in 1/64 degree units)
+        }],
+    )?;
+    conn.poly_fill_rectangle(
+        drawable,
+        gc,
+        &[Rectangle {
+            x,
+            y: y + (size - body_height) as i16,
+            width: size,
+            height: body_height,
+        }],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_icon_cache_evicts_least_recently_used() {
+        let mut cache = IconCache::new(3);
+        for window in 1..=3 {
+            cache.insert((window, 20), CachedIcon::default_icon(2));
+        }
+        // Using window 1 makes window 2 the oldest
+        assert!(cache.get((1, 20)).is_some());
+
+        cache.insert((4, 20), CachedIcon::default_icon(2));
+        cache.insert((5, 20), CachedIcon::default_icon(2));
+        let mut keys: Vec<_> = cache.keys().map(|&(w, _)| w).collect();
+        keys.sort();
+        assert_eq!(keys, vec![1, 4, 5]);
+        assert!(cache.get((2, 20)).is_none());
+
+        // Invalidation still drops a window's icons by key
+        cache.remove_window(4);
+        assert!(cache.get((4, 20)).is_none());
+        cache.insert((6, 20), CachedIcon::default_icon(2));
+        assert_eq!(cache.keys().count(), 3);
+    }
+
+    #[test]
+    fn test_tab_width_grows_with_icon_size() {
+        let mut config = LayoutConfig::default();
+        let small = tab_width(&config, 100);
+        config.tab_icon_size = 32;
+        let large = tab_width(&config, 100);
+        assert_eq!(large, small + 12);
+
+        // Width is clamped around the title, so the icon is always added on top
+        assert_eq!(tab_width(&config, 0), 80 + 32 + 4);
+        assert_eq!(tab_width(&config, 1000), 200 + 32 + 4);
+    }
+
+    #[test]
+    fn test_format_tab_label() {
+        let title = "/home/user/src/ttwm/src/main.rs - Emacs";
+        assert_eq!(format_tab_label(TabLabel::Title, Some("Emacs"), title), title);
+        assert_eq!(format_tab_label(TabLabel::Class, Some("Emacs"), title), "Emacs");
+        assert_eq!(
+            format_tab_label(TabLabel::ClassTitle, Some("Emacs"), "notes"),
+            "Emacs: notes"
+        );
+        assert_eq!(format_tab_label(TabLabel::Class, None, title), title);
+    }
+
+    #[test]
+    fn test_corner_radius() {
+        assert_eq!(clamp_corner_radius(6, 120, 28), 6);
+        assert_eq!(clamp_corner_radius(0, 120, 28), 0);
+        // Never more than half the tab, so the corners don't overlap
+        assert_eq!(clamp_corner_radius(40, 120, 28), 14);
+        assert_eq!(clamp_corner_radius(40, 20, 28), 10);
+
+        // Vertical tabs keep their smaller default
+        assert_eq!(vertical_corner_radius(6), 4);
+        assert_eq!(vertical_corner_radius(0), 0);
+    }
+
+    #[test]
+    fn test_auto_tab_bar_sizes() {
+        // Defaults (20px icons, ~17px line height) keep the classic 28px bars
+        assert_eq!(auto_tab_bar_height(17, 20, true), 28);
+        assert_eq!(auto_vertical_tab_width(20), 28);
+
+        // Large fonts grow the bar; icons only count when shown
+        assert_eq!(auto_tab_bar_height(30, 20, true), 38);
+        assert_eq!(auto_tab_bar_height(12, 20, false), 20);
+    }
+
+    #[test]
+    fn test_stacked_rows() {
+        assert_eq!(stacked_bar_height(28, 3, 600), 84);
+        assert_eq!(stacked_bar_height(28, 30, 600), 572);
+        assert_eq!(stacked_bar_height(28, 3, 20), 0);
+
+        // Too many rows for the frame still leave the window some room
+        let frame = Rect::new(0, 0, 800, 600);
+        let client = client_area(frame, 30, true, false, 28, 0);
+        assert_eq!(client, Rect::new(0, 572, 800, 28));
+
+        assert_eq!(tab_at_offset(0, 28, 3), Some(0));
+        assert_eq!(tab_at_offset(56, 28, 3), Some(2));
+        assert_eq!(tab_at_offset(84, 28, 3), None);
+        assert_eq!(tab_at_offset(-1, 28, 3), None);
+    }
+
+    #[test]
+    fn test_client_area() {
+        let frame = Rect::new(10, 20, 800, 600);
+        assert_eq!(client_area(frame, 0, false, false, 28, 150), frame);
+        assert_eq!(client_area(frame, 2, false, false, 28, 150), Rect::new(10, 48, 800, 572));
+        assert_eq!(client_area(frame, 2, false, true, 28, 150), Rect::new(160, 20, 650, 600));
+        assert_eq!(client_area(frame, 3, true, false, 28, 150), Rect::new(10, 104, 800, 516));
+    }
+
+    #[test]
+    fn test_tab_width_ignores_icon_when_hidden() {
+        let config = LayoutConfig {
+            show_tab_icons: false,
+            tab_icon_size: 64,
+            ..Default::default()
+        };
+        assert_eq!(tab_width(&config, 100), 124);
+    }
+
+    #[test]
+    fn test_tab_layout_per_sizing_mode() {
+        let config = LayoutConfig {
+            show_tab_icons: false,
+            fixed_tab_width: 150,
+            ..Default::default()
+        };
+        let titles = [10, 100, 500];
+
+        let content = tab_layout(&config, TabSizing::Content, &titles, 1000);
+        assert_eq!(content, vec![(0, 80), (80, 124), (204, 200)]);
+
+        let equal = tab_layout(&config, TabSizing::Equal, &titles, 1000);
+        assert_eq!(equal, vec![(0, 333), (333, 333), (666, 334)]);
+
+        let fixed = tab_layout(&config, TabSizing::Fixed, &titles, 1000);
+        assert_eq!(fixed, vec![(0, 150), (150, 150), (300, 150)]);
+    }
+
+    #[test]
+    fn test_compact_tab_layout() {
+        let normal = LayoutConfig::default();
+        let compact = LayoutConfig { tab_compact: true, ..Default::default() };
+        let titles = [10, 100, 500];
+
+        let content = tab_layout(&normal, TabSizing::Content, &titles, 1000);
+        assert_eq!(content, vec![(0, 104), (104, 148), (252, 224)]);
+
+        // Icon plus padding, no matter the titles or sizing mode
+        let expected = vec![(0, 36), (36, 36), (72, 36)];
+        assert_eq!(tab_layout(&compact, TabSizing::Content, &titles, 1000), expected);
+        assert_eq!(tab_layout(&compact, TabSizing::Equal, &titles, 1000), expected);
+        assert_eq!(tab_layout(&compact, TabSizing::Fixed, &titles, 1000), expected);
+
+        let big_icons = LayoutConfig { tab_compact: true, tab_icon_size: 32, ..Default::default() };
+        assert_eq!(compact_tab_width(&big_icons), 48);
+    }
+
+    #[test]
+    fn test_raise_order() {
+        let monitors = crate::monitor::MonitorManager::with_mock_monitors(&[
+            ("DP-1", Rect::new(0, 0, 1920, 1080), true),
+            ("HDMI-1", Rect::new(1920, 0, 1920, 1080), false),
+        ]);
+        let dp = monitors.find_by_name("DP-1").unwrap();
+        let hdmi = monitors.find_by_name("HDMI-1").unwrap();
+        let mut tree = crate::layout::LayoutTree::new();
+        let left = tree.focused;
+        let right = tree.split_focused(crate::layout::SplitDirection::Horizontal);
+
+        let tab_bars: HashMap<TabBarKey, Window> = [
+            ((dp, 0, left), 10),
+            ((dp, 0, right), 11),
+            ((dp, 1, left), 12),
+            ((hdmi, 0, left), 13),
+        ].into_iter().collect();
+        let docks = [7, 8];
+
+        // The client goes under its frame's tab bar, which goes under the docks
+        assert_eq!(raise_order(&tab_bars, 1, (dp, 0, left), false, docks), vec![1, 10, 7, 8]);
+        // Frames without a tab bar only raise the client under the docks
+        assert_eq!(raise_order(&tab_bars, 1, (dp, 2, left), false, docks), vec![1, 7, 8]);
+
+        // raise_tabbar_always raises every tab bar of that monitor's
+        // workspace, still under the docks
+        assert_eq!(raise_order(&tab_bars, 1, (dp, 0, right), true, docks), vec![1, 10, 11, 7, 8]);
+        assert_eq!(raise_order(&tab_bars, 1, (hdmi, 0, left), true, docks), vec![1, 13, 7, 8]);
+    }
+}
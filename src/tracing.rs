@@ -7,7 +7,7 @@ use std::collections::VecDeque;
 use std::time::Instant;
 
 
-use crate::ipc::EventLogEntry;
+use crate::ipc::{EventKind, EventLogEntry};
 use crate::state::StateTransition;
 
 /// Maximum number of events to keep in the trace buffer
@@ -46,7 +46,7 @@ impl EventTracer {
 
     /// Trace an X11 event
     pub fn trace_x11_event(&mut self, event_type: &str, window: Option<u32>, details: &str) {
-        self.add_entry(event_type.to_string(), window, details.to_string());
+        self.add_entry(EventKind::X11, event_type.to_string(), window, details.to_string());
     }
 
     /// Trace a state transition
@@ -78,16 +78,16 @@ impl EventTracer {
                 ("frame_removed".to_string(), None, format!("frame={}", frame))
             }
         };
-        self.add_entry(event_type, window, details);
+        self.add_entry(EventKind::Transition, event_type, window, details);
     }
 
     /// Trace an IPC command
     pub fn trace_ipc(&mut self, command: &str, result: &str) {
-        self.add_entry("ipc_command".to_string(), None, format!("cmd={} result={}", command, result));
+        self.add_entry(EventKind::Ipc, "ipc_command".to_string(), None, format!("cmd={} result={}", command, result));
     }
 
     /// Add an entry to the trace buffer
-    fn add_entry(&mut self, event_type: String, window: Option<u32>, details: String) {
+    fn add_entry(&mut self, kind: EventKind, event_type: String, window: Option<u32>, details: String) {
         // Remove oldest entry if at capacity
         if self.entries.len() >= self.max_entries {
             self.entries.pop_front();
@@ -97,6 +97,7 @@ impl EventTracer {
         self.entries.push_back(EventLogEntry {
             sequence: self.sequence,
             timestamp_ms: self.timestamp(),
+            kind,
             event_type,
             window,
             details,
@@ -118,6 +119,25 @@ impl EventTracer {
         self.entries.iter().cloned().collect()
     }
 
+    /// Get entries with a sequence number above `since_seq` (for incremental
+    /// polling) whose type matches `event_type`, keeping the newest `count`.
+    ///
+    /// `event_type` is either a kind ("x11", "transition", "ipc") or an exact
+    /// event type such as "MapRequest" or "window_managed".
+    pub fn query(&self, count: Option<usize>, since_seq: Option<u64>, event_type: Option<&str>) -> Vec<EventLogEntry> {
+        let kind = event_type.and_then(|t| serde_json::from_value::<EventKind>(t.into()).ok());
+        let matches: Vec<&EventLogEntry> = self.entries.iter()
+            .filter(|e| since_seq.is_none_or(|seq| e.sequence > seq))
+            .filter(|e| match (kind, event_type) {
+                (Some(kind), _) => e.kind == kind,
+                (None, Some(t)) => e.event_type == t,
+                (None, None) => true,
+            })
+            .collect();
+        let start = count.map_or(0, |n| matches.len().saturating_sub(n));
+        matches[start..].iter().map(|&e| e.clone()).collect()
+    }
+
     /// Clear the trace buffer
     pub fn clear(&mut self) {
         self.entries.clear();
@@ -185,6 +205,35 @@ mod tests {
         assert_eq!(last_3[2].event_type, "event9");
     }
 
+    #[test]
+    fn test_query_filters() {
+        let mut tracer = EventTracer::new();
+        tracer.trace_x11_event("MapRequest", Some(1), "");
+        tracer.trace_ipc("get_state", "ok");
+        tracer.trace_transition(&StateTransition::FrameRemoved { frame: "f".to_string() });
+        tracer.trace_x11_event("UnmapNotify", Some(1), "");
+        tracer.trace_ipc("split", "ok");
+
+        // No filters is the same as get_all
+        assert_eq!(tracer.query(None, None, None).len(), 5);
+
+        let ipc = tracer.query(None, None, Some("ipc"));
+        assert_eq!(ipc.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![2, 5]);
+        assert!(ipc.iter().all(|e| e.kind == EventKind::Ipc));
+
+        let x11 = tracer.query(None, None, Some("x11"));
+        assert_eq!(x11.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![1, 4]);
+
+        // Exact event types and incremental polling
+        assert_eq!(tracer.query(None, None, Some("frame_removed"))[0].sequence, 3);
+        let after = tracer.query(None, Some(3), None);
+        assert_eq!(after.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![4, 5]);
+
+        // count keeps the newest matches
+        let last = tracer.query(Some(1), Some(1), Some("ipc"));
+        assert_eq!(last.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![5]);
+    }
+
     #[test]
     fn test_sequence_numbers() {
         let mut tracer = EventTracer::new();
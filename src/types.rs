@@ -57,15 +57,6 @@ pub struct StrutPartial {
     pub right: u32,
     pub top: u32,
     pub bottom: u32,
-    // Extended fields for multi-monitor (start/end coords)
-    pub left_start_y: u32,
-    pub left_end_y: u32,
-    pub right_start_y: u32,
-    pub right_end_y: u32,
-    pub top_start_x: u32,
-    pub top_end_x: u32,
-    pub bottom_start_x: u32,
-    pub bottom_end_x: u32,
 }
 
 /// Snapshot of the layout tree for IPC serialization
@@ -84,6 +75,8 @@ pub enum NodeSnapshot {
         name: Option<String>,
         windows: Vec<u32>,
         focused_tab: usize,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        locked: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         geometry: Option<RectSnapshot>,
     },
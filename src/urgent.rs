@@ -7,10 +7,13 @@ use anyhow::Result;
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 
+use crate::config::UrgentOrder;
+
 /// Manager for tracking urgent windows and the visual indicator.
 ///
 /// Windows are stored in FIFO order (oldest first) so that FocusUrgent
-/// always focuses the window that has been waiting longest.
+/// focuses the window that has been waiting longest, or with
+/// `general.urgent_order = "lifo"` the one that became urgent last.
 pub struct UrgentManager {
     /// Urgent windows in FIFO order (oldest first)
     windows: Vec<Window>,
@@ -44,14 +47,18 @@ impl UrgentManager {
         self.windows.contains(&window)
     }
 
-    /// Get the oldest urgent window (first in FIFO order).
-    pub fn first(&self) -> Option<Window> {
-        self.windows.first().copied()
+    /// Get the urgent window FocusUrgent goes to next: the oldest for
+    /// FIFO, the newest for LIFO.
+    pub fn next(&self, order: UrgentOrder) -> Option<Window> {
+        self.ordered(order).next()
     }
 
-    /// Check if there are no urgent windows.
-    pub fn is_empty(&self) -> bool {
-        self.windows.is_empty()
+    /// Urgent windows in the order FocusUrgent visits them.
+    pub fn ordered(&self, order: UrgentOrder) -> Box<dyn Iterator<Item = Window> + '_> {
+        match order {
+            UrgentOrder::Fifo => Box::new(self.windows.iter().copied()),
+            UrgentOrder::Lifo => Box::new(self.windows.iter().rev().copied()),
+        }
     }
 
     /// Iterate over urgent windows.
@@ -161,3 +168,41 @@ pub fn hide_indicator(conn: &impl Connection, window: Window) -> Result<()> {
     conn.flush()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Windows in the order repeated FocusUrgent calls reach them; focusing
+    /// a window clears its urgent state
+    fn focus_order(urgent: &mut UrgentManager, order: UrgentOrder) -> Vec<Window> {
+        let mut focused = Vec::new();
+        while let Some(window) = urgent.next(order) {
+            urgent.remove(window);
+            focused.push(window);
+        }
+        focused
+    }
+
+    #[test]
+    fn test_focus_order() {
+        let mut urgent = UrgentManager::new();
+        for window in [3, 1, 2] {
+            urgent.add(window);
+        }
+        // Becoming urgent again doesn't move a window to the back
+        urgent.add(3);
+        assert_eq!(urgent.ordered(UrgentOrder::Lifo).collect::<Vec<_>>(), vec![2, 1, 3]);
+        assert_eq!(focus_order(&mut urgent, UrgentOrder::Fifo), vec![3, 1, 2]);
+        assert_eq!(urgent.next(UrgentOrder::Fifo), None);
+
+        for window in [3, 1, 2] {
+            urgent.add(window);
+        }
+        assert_eq!(urgent.next(UrgentOrder::Lifo), Some(2));
+        // A window that got focused some other way drops out of the order
+        urgent.remove(1);
+        assert_eq!(focus_order(&mut urgent, UrgentOrder::Lifo), vec![2, 3]);
+        assert_eq!(urgent.next(UrgentOrder::Fifo), None);
+    }
+}
@@ -0,0 +1,235 @@
+//! Root window wallpaper.
+//!
+//! Loads an image, renders it onto a root-sized pixmap (once per monitor, so
+//! each output gets its own centered/scaled copy) and installs that pixmap as
+//! the root background. `_XROOTPMAP_ID`/`ESETROOT_PMAP_ID` are set so that
+//! pseudo-transparent clients (including our tab bars) pick it up.
+//!
+//! Only the image formats enabled for the `image` crate (PNG) can be loaded.
+
+use anyhow::{Context, Result};
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::wrapper::ConnectionExt as _;
+
+use crate::types::Rect;
+
+/// How the image is fitted to each monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WallpaperMode {
+    /// Unscaled, centered (cropped if larger than the monitor)
+    Center,
+    /// Unscaled, repeated from the monitor's top-left corner
+    Tile,
+    /// Scaled to exactly the monitor size, ignoring aspect ratio
+    Stretch,
+    /// Scaled to cover the monitor, keeping aspect ratio (excess is cropped)
+    #[default]
+    Fill,
+}
+
+impl WallpaperMode {
+    /// Parse a mode name ("center", "tile", "stretch" or "fill").
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "center" | "centre" => Some(Self::Center),
+            "tile" => Some(Self::Tile),
+            "stretch" => Some(Self::Stretch),
+            "fill" => Some(Self::Fill),
+            _ => None,
+        }
+    }
+}
+
+/// Load an image file, with a readable error for unsupported formats.
+pub fn load(path: &str) -> Result<RgbaImage> {
+    let img = image::open(path).with_context(|| format!("Failed to load wallpaper '{}'", path))?;
+    Ok(img.to_rgba8())
+}
+
+/// Render `img` into a `width` x `height` area as 0x00RRGGBB pixels (row-major).
+/// Uncovered areas are filled with `background`.
+pub fn render(img: &RgbaImage, width: u32, height: u32, mode: WallpaperMode, background: u32) -> Vec<u32> {
+    let mut out = vec![background; (width as usize) * (height as usize)];
+    if width == 0 || height == 0 || img.width() == 0 || img.height() == 0 {
+        return out;
+    }
+
+    // Source image and its offset within the area (may be negative = cropped)
+    let (source, offset_x, offset_y) = match mode {
+        WallpaperMode::Center => (
+            img.clone(),
+            (width as i64 - img.width() as i64) / 2,
+            (height as i64 - img.height() as i64) / 2,
+        ),
+        WallpaperMode::Tile => (img.clone(), 0, 0),
+        WallpaperMode::Stretch => (imageops::resize(img, width, height, FilterType::Triangle), 0, 0),
+        WallpaperMode::Fill => {
+            let scale = (width as f64 / img.width() as f64).max(height as f64 / img.height() as f64);
+            let scaled_w = ((img.width() as f64 * scale).ceil() as u32).max(width);
+            let scaled_h = ((img.height() as f64 * scale).ceil() as u32).max(height);
+            let scaled = imageops::resize(img, scaled_w, scaled_h, FilterType::Triangle);
+            (scaled, (width as i64 - scaled_w as i64) / 2, (height as i64 - scaled_h as i64) / 2)
+        }
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let (sx, sy) = if mode == WallpaperMode::Tile {
+                (x % source.width(), y % source.height())
+            } else {
+                let sx = x as i64 - offset_x;
+                let sy = y as i64 - offset_y;
+                if sx < 0 || sy < 0 || sx >= source.width() as i64 || sy >= source.height() as i64 {
+                    continue;
+                }
+                (sx as u32, sy as u32)
+            };
+            let [r, g, b, a] = source.get_pixel(sx, sy).0;
+            out[(y * width + x) as usize] = blend(background, r, g, b, a);
+        }
+    }
+    out
+}
+
+/// Alpha-blend an RGBA pixel over a 0xRRGGBB background.
+fn blend(background: u32, r: u8, g: u8, b: u8, a: u8) -> u32 {
+    let mix = |fg: u8, bg: u32| -> u32 {
+        (fg as u32 * a as u32 + bg * (255 - a as u32)) / 255
+    };
+    let bg_r = (background >> 16) & 0xFF;
+    let bg_g = (background >> 8) & 0xFF;
+    let bg_b = background & 0xFF;
+    (mix(r, bg_r) << 16) | (mix(g, bg_g) << 8) | mix(b, bg_b)
+}
+
+/// Draw `img` onto a new root-sized pixmap and make it the root background.
+///
+/// `monitors` are the areas the image is fitted into. Returns the new pixmap,
+/// which must stay alive while it is the root background (free the previous
+/// one, if any, after this succeeds).
+pub fn apply(
+    conn: &impl Connection,
+    root: Window,
+    depth: u8,
+    monitors: &[Rect],
+    img: &RgbaImage,
+    mode: WallpaperMode,
+    atoms: (Atom, Atom),
+) -> Result<Pixmap> {
+    if depth != 24 && depth != 32 {
+        anyhow::bail!("Unsupported color depth: {}", depth);
+    }
+
+    let geometry = conn.get_geometry(root)?.reply()?;
+    let (root_w, root_h) = (geometry.width as u32, geometry.height as u32);
+
+    // Compose all monitors into one root-sized buffer
+    let mut pixels = vec![0u32; (root_w as usize) * (root_h as usize)];
+    for rect in monitors {
+        let rendered = render(img, rect.width, rect.height, mode, 0x000000);
+        for y in 0..rect.height {
+            let ry = rect.y + y as i32;
+            if ry < 0 || ry >= root_h as i32 {
+                continue;
+            }
+            for x in 0..rect.width {
+                let rx = rect.x + x as i32;
+                if rx < 0 || rx >= root_w as i32 {
+                    continue;
+                }
+                pixels[ry as usize * root_w as usize + rx as usize] = rendered[(y * rect.width + x) as usize];
+            }
+        }
+    }
+
+    let pixmap = conn.generate_id()?;
+    conn.create_pixmap(depth, pixmap, root, root_w as u16, root_h as u16)?;
+    let gc = conn.generate_id()?;
+    conn.create_gc(gc, pixmap, &CreateGCAux::new())?;
+
+    // Upload in row strips that fit in a single request
+    let row_bytes = root_w as usize * 4;
+    let max_rows = ((conn.maximum_request_bytes().saturating_sub(64)) / row_bytes.max(1)).max(1);
+    for (strip_idx, rows) in pixels.chunks(root_w as usize * max_rows).enumerate() {
+        let data: Vec<u8> = rows.iter()
+            .flat_map(|&p| [(p & 0xFF) as u8, ((p >> 8) & 0xFF) as u8, ((p >> 16) & 0xFF) as u8, 0])
+            .collect();
+        let strip_height = rows.len() / root_w as usize;
+        conn.put_image(
+            ImageFormat::Z_PIXMAP,
+            pixmap,
+            gc,
+            root_w as u16,
+            strip_height as u16,
+            0,
+            (strip_idx * max_rows) as i16,
+            0,
+            depth,
+            &data,
+        )?;
+    }
+    conn.free_gc(gc)?;
+
+    // Install as the root background and advertise it to pseudo-transparent clients
+    conn.change_window_attributes(root, &ChangeWindowAttributesAux::new().background_pixmap(pixmap))?;
+    conn.clear_area(false, root, 0, 0, 0, 0)?;
+    let (xrootpmap_id, esetroot_pmap_id) = atoms;
+    conn.change_property32(PropMode::REPLACE, root, xrootpmap_id, AtomEnum::PIXMAP, &[pixmap])?;
+    conn.change_property32(PropMode::REPLACE, root, esetroot_pmap_id, AtomEnum::PIXMAP, &[pixmap])?;
+    conn.flush()?;
+
+    Ok(pixmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn checker() -> RgbaImage {
+        // 2x2: red, green / blue, white
+        let mut img = RgbaImage::new(2, 2);
+        img.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        img.put_pixel(1, 0, Rgba([0, 255, 0, 255]));
+        img.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+        img.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        img
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(WallpaperMode::parse("Fill"), Some(WallpaperMode::Fill));
+        assert_eq!(WallpaperMode::parse("tile"), Some(WallpaperMode::Tile));
+        assert_eq!(WallpaperMode::parse("zoom"), None);
+    }
+
+    #[test]
+    fn test_render_center_and_tile() {
+        let img = checker();
+
+        let centered = render(&img, 4, 4, WallpaperMode::Center, 0x123456);
+        assert_eq!(centered[0], 0x123456);
+        assert_eq!(centered[4 + 1], 0xFF0000);
+        assert_eq!(centered[2 * 4 + 2], 0xFFFFFF);
+
+        let tiled = render(&img, 4, 2, WallpaperMode::Tile, 0);
+        assert_eq!(&tiled[..4], &[0xFF0000, 0x00FF00, 0xFF0000, 0x00FF00]);
+    }
+
+    #[test]
+    fn test_render_fill_covers_area() {
+        let img = RgbaImage::from_pixel(4, 2, Rgba([10, 20, 30, 255]));
+        let filled = render(&img, 3, 3, WallpaperMode::Fill, 0xFFFFFF);
+        assert!(filled.iter().all(|&p| p == 0x0A141E));
+    }
+
+    #[test]
+    fn test_render_blends_transparency() {
+        let img = RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 0]));
+        let out = render(&img, 1, 1, WallpaperMode::Stretch, 0x336699);
+        assert_eq!(out, vec![0x336699]);
+    }
+}
@@ -51,6 +51,27 @@ pub fn get_window_title(conn: &impl Connection, atoms: &Atoms, window: Window) -
     format!("0x{:x}", window)
 }
 
+/// Get a window's WM_CLASS as (instance, class), if set.
+pub fn get_window_class(conn: &impl Connection, window: Window) -> Option<(String, String)> {
+    let reply = conn
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)
+        .ok()?
+        .reply()
+        .ok()?;
+    parse_wm_class(&reply.value)
+}
+
+/// Parse a WM_CLASS value: two NUL-terminated strings, instance then class.
+pub fn parse_wm_class(value: &[u8]) -> Option<(String, String)> {
+    let mut parts = value.split(|&b| b == 0);
+    let instance = String::from_utf8_lossy(parts.next()?).into_owned();
+    let class = String::from_utf8_lossy(parts.next()?).into_owned();
+    if class.is_empty() {
+        return None;
+    }
+    Some((instance, class))
+}
+
 /// Check if a window should float based on _NET_WM_WINDOW_TYPE.
 /// Returns true for dialogs, splash screens, toolbars, utilities, menus, tooltips, notifications.
 pub fn should_float(conn: &impl Connection, atoms: &Atoms, window: Window) -> bool {
@@ -92,6 +113,15 @@ pub fn should_float(conn: &impl Connection, atoms: &Atoms, window: Window) -> bo
     false
 }
 
+/// Check if a window was created by ttwm (has `_TTWM_INTERNAL`), e.g. a tab
+/// bar left behind by a previous instance.
+pub fn is_internal_window(conn: &impl Connection, atoms: &Atoms, window: Window) -> bool {
+    conn.get_property(false, window, atoms.ttwm_internal, AtomEnum::CARDINAL, 0, 1)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .is_some_and(|reply| reply.type_ != x11rb::NONE)
+}
+
 /// Check if a window is a dock (status bar like polybar).
 pub fn is_dock_window(conn: &impl Connection, atoms: &Atoms, window: Window) -> bool {
     let reply = match conn.get_property(
@@ -121,7 +151,8 @@ pub fn is_dock_window(conn: &impl Connection, atoms: &Atoms, window: Window) ->
 
 /// Read strut partial from a window (returns Default if none set).
 pub fn read_struts(conn: &impl Connection, atoms: &Atoms, window: Window) -> StrutPartial {
-    // Try _NET_WM_STRUT_PARTIAL first (12 values)
+    // Try _NET_WM_STRUT_PARTIAL first (12 values, of which only the edge
+    // widths are used; the start/end coordinates are ignored)
     if let Ok(cookie) = conn.get_property(
         false,
         window,
@@ -138,14 +169,6 @@ pub fn read_struts(conn: &impl Connection, atoms: &Atoms, window: Window) -> Str
                     right: values[1],
                     top: values[2],
                     bottom: values[3],
-                    left_start_y: values[4],
-                    left_end_y: values[5],
-                    right_start_y: values[6],
-                    right_end_y: values[7],
-                    top_start_x: values[8],
-                    top_end_x: values[9],
-                    bottom_start_x: values[10],
-                    bottom_end_x: values[11],
                 };
             }
         }
@@ -168,7 +191,6 @@ pub fn read_struts(conn: &impl Connection, atoms: &Atoms, window: Window) -> Str
                     right: values[1],
                     top: values[2],
                     bottom: values[3],
-                    ..Default::default()
                 };
             }
         }
@@ -188,10 +210,7 @@ pub fn is_window_urgent(conn: &impl Connection, atoms: &Atoms, window: Window) -
         0,
         1024,
     ) {
-        Ok(cookie) => match cookie.reply() {
-            Ok(reply) => Some(reply),
-            Err(_) => None,
-        },
+        Ok(cookie) => cookie.reply().ok(),
         Err(_) => None,
     };
 
@@ -215,10 +234,7 @@ pub fn is_window_urgent(conn: &impl Connection, atoms: &Atoms, window: Window) -
         0,
         9, // WM_HINTS has 9 CARD32 values
     ) {
-        Ok(cookie) => match cookie.reply() {
-            Ok(reply) => Some(reply),
-            Err(_) => None,
-        },
+        Ok(cookie) => cookie.reply().ok(),
         Err(_) => None,
     };
 
@@ -237,9 +253,9 @@ pub fn is_window_urgent(conn: &impl Connection, atoms: &Atoms, window: Window) -
     false
 }
 
-/// Check if a window supports the WM_DELETE_WINDOW protocol.
-pub fn supports_delete_protocol(conn: &impl Connection, atoms: &Atoms, window: Window) -> bool {
-    // Get WM_PROTOCOLS property
+/// Read the atoms listed in a window's WM_PROTOCOLS property
+/// (empty if the property is missing).
+pub fn read_protocols(conn: &impl Connection, atoms: &Atoms, window: Window) -> Vec<Atom> {
     if let Ok(cookie) = conn.get_property(
         false,
         window,
@@ -250,11 +266,53 @@ pub fn supports_delete_protocol(conn: &impl Connection, atoms: &Atoms, window: W
     ) {
         if let Ok(reply) = cookie.reply() {
             if let Some(protocol_atoms) = reply.value32() {
-                return protocol_atoms.into_iter().any(|a| a == atoms.wm_delete_window);
+                return protocol_atoms.collect();
             }
         }
     }
-    false
+    Vec::new()
+}
+
+/// Check if a window supports the WM_DELETE_WINDOW protocol.
+pub fn supports_delete_protocol(conn: &impl Connection, atoms: &Atoms, window: Window) -> bool {
+    read_protocols(conn, atoms, window).contains(&atoms.wm_delete_window)
+}
+
+/// Whether a window accepts keyboard focus from the WM: the `input` field of
+/// WM_HINTS when its InputHint flag is set, true otherwise (ICCCM 4.1.7).
+pub fn accepts_input_focus(conn: &impl Connection, window: Window) -> bool {
+    let values: Vec<u32> = conn
+        .get_property(false, window, AtomEnum::WM_HINTS, AtomEnum::WM_HINTS, 0, 9)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .and_then(|reply| reply.value32().map(|v| v.collect()))
+        .unwrap_or_default();
+    input_hint(&values)
+}
+
+/// Interpret the WM_HINTS values for `accepts_input_focus`
+fn input_hint(values: &[u32]) -> bool {
+    const INPUT_HINT: u32 = 1;
+    match values {
+        [flags, input, ..] if flags & INPUT_HINT != 0 => *input != 0,
+        _ => true,
+    }
+}
+
+/// Send a WM_TAKE_FOCUS client message. `time` must be the timestamp of the
+/// event that caused the focus change, not CurrentTime.
+pub fn send_take_focus(conn: &impl Connection, atoms: &Atoms, window: Window, time: Timestamp) -> Result<()> {
+    let data = ClientMessageData::from([atoms.wm_take_focus, time, 0u32, 0u32, 0u32]);
+    let event = ClientMessageEvent {
+        response_type: CLIENT_MESSAGE_EVENT,
+        format: 32,
+        sequence: 0,
+        window,
+        type_: atoms.wm_protocols,
+        data,
+    };
+    conn.send_event(false, window, EventMask::NO_EVENT, event)?;
+    Ok(())
 }
 
 /// Send WM_DELETE_WINDOW client message to request graceful close.
@@ -277,3 +335,97 @@ pub fn send_delete_window(conn: &impl Connection, atoms: &Atoms, window: Window)
     conn.flush()?;
     Ok(())
 }
+
+/// Tell a client its actual geometry with a synthetic ConfigureNotify (ICCCM
+/// 4.1.5), for requests the WM denied or moves that didn't resize the window.
+pub fn send_configure_notify(
+    conn: &impl Connection,
+    window: Window,
+    x: i16,
+    y: i16,
+    width: u16,
+    height: u16,
+    border_width: u16,
+) -> Result<()> {
+    let event = ConfigureNotifyEvent {
+        response_type: CONFIGURE_NOTIFY_EVENT,
+        sequence: 0,
+        event: window,
+        window,
+        above_sibling: x11rb::NONE,
+        x,
+        y,
+        width,
+        height,
+        border_width,
+        override_redirect: false,
+    };
+    conn.send_event(false, window, EventMask::STRUCTURE_NOTIFY, event)?;
+    Ok(())
+}
+
+/// Extract `Xft.dpi` from the contents of the RESOURCE_MANAGER property.
+pub fn parse_xft_dpi(resources: &str) -> Option<f64> {
+    resources.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == "Xft.dpi").then(|| value.trim().parse().ok()).flatten()
+    })
+}
+
+/// Screen DPI: `Xft.dpi` from the X resources if set, otherwise computed
+/// from the screen's reported physical size.
+pub fn read_dpi(conn: &impl Connection, screen: &Screen) -> Option<f64> {
+    let resources = conn.get_property(
+        false,
+        screen.root,
+        AtomEnum::RESOURCE_MANAGER,
+        AtomEnum::STRING,
+        0,
+        u32::MAX / 4,
+    ).ok()?.reply().ok();
+    if let Some(dpi) = resources.and_then(|r| parse_xft_dpi(&String::from_utf8_lossy(&r.value))) {
+        return Some(dpi);
+    }
+    (screen.width_in_millimeters > 0).then(|| {
+        screen.width_in_pixels as f64 * 25.4 / screen.width_in_millimeters as f64
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_hint() {
+        // No WM_HINTS, or InputHint flag unset: assume the window wants focus
+        assert!(input_hint(&[]));
+        assert!(input_hint(&[0, 0, 0, 0, 0, 0, 0, 0, 0]));
+        // InputHint set: follow the input field
+        assert!(input_hint(&[1, 1, 0, 0, 0, 0, 0, 0, 0]));
+        assert!(!input_hint(&[1, 0, 0, 0, 0, 0, 0, 0, 0]));
+        // Urgency flag alone doesn't matter
+        assert!(!input_hint(&[257, 0]));
+    }
+
+    #[test]
+    fn test_parse_xft_dpi() {
+        assert_eq!(parse_xft_dpi("Xft.antialias:\t1\nXft.dpi:\t192\n"), Some(192.0));
+        assert_eq!(parse_xft_dpi("Xft.dpi: 144.5"), Some(144.5));
+        assert_eq!(parse_xft_dpi("Xcursor.size:\t24\n"), None);
+        assert_eq!(parse_xft_dpi("Xft.dpi:\tlarge"), None);
+    }
+
+    #[test]
+    fn test_parse_wm_class() {
+        assert_eq!(
+            parse_wm_class(b"navigator\0Firefox\0"),
+            Some(("navigator".to_string(), "Firefox".to_string()))
+        );
+        assert_eq!(
+            parse_wm_class(b"xterm\0XTerm"),
+            Some(("xterm".to_string(), "XTerm".to_string()))
+        );
+        assert_eq!(parse_wm_class(b""), None);
+        assert_eq!(parse_wm_class(b"only-instance\0"), None);
+    }
+}
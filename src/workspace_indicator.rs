@@ -0,0 +1,196 @@
+//! Brief workspace indicator shown after switching workspaces.
+//!
+//! With `general.workspace_indicator_ms` above 0, every workspace switch pops
+//! up a small centered override-redirect window naming the new workspace. The
+//! main loop hides it once the time is up. Under a compositor that honors
+//! `_NET_WM_WINDOW_OPACITY` it fades out over the last third of that time;
+//! without one it simply disappears.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::wrapper::ConnectionExt as _;
+
+use crate::config::LayoutConfig;
+use crate::render::FontRenderer;
+use crate::types::Rect;
+
+/// Padding around the label
+const PADDING: u32 = 24;
+
+/// Opacity for `_NET_WM_WINDOW_OPACITY` at `elapsed` into a `duration`-long
+/// display, or None once it should be hidden. Fully opaque for the first two
+/// thirds, then linear down to 0.
+pub fn opacity_at(elapsed: Duration, duration: Duration) -> Option<u32> {
+    if elapsed >= duration {
+        return None;
+    }
+    let fade = duration / 3;
+    let fade_start = duration - fade;
+    if elapsed <= fade_start || fade.is_zero() {
+        return Some(u32::MAX);
+    }
+    let remaining = (duration - elapsed).as_secs_f64() / fade.as_secs_f64();
+    Some((u32::MAX as f64 * remaining) as u32)
+}
+
+/// Indicator window and its display timer
+pub struct WorkspaceIndicator {
+    /// Indicator window, created on first use
+    window: Option<Window>,
+    /// When the indicator was last shown (None while hidden)
+    shown_at: Option<Instant>,
+    duration: Duration,
+    label: String,
+    /// Opacity last written to the window
+    opacity: u32,
+}
+
+impl WorkspaceIndicator {
+    /// Create a hidden indicator (no window yet).
+    pub fn new() -> Self {
+        Self {
+            window: None,
+            shown_at: None,
+            duration: Duration::ZERO,
+            label: String::new(),
+            opacity: u32::MAX,
+        }
+    }
+
+    /// Check whether a window is the indicator
+    pub fn owns_window(&self, window: Window) -> bool {
+        self.window == Some(window)
+    }
+
+    /// Show `label` centered on `monitor` for `duration`, restarting the
+    /// timer if the indicator is already up.
+    #[allow(clippy::too_many_arguments)]
+    pub fn show(
+        &mut self,
+        conn: &impl Connection,
+        root: Window,
+        opacity_atom: Atom,
+        monitor: Rect,
+        label: String,
+        duration: Duration,
+        font: &FontRenderer,
+        gc: Gcontext,
+        config: &LayoutConfig,
+    ) -> Result<()> {
+        let width = (font.measure_text(&label) + PADDING * 2).min(monitor.width).max(1);
+        let height = (font.char_height() + PADDING * 2).min(monitor.height).max(1);
+
+        let window = match self.window {
+            Some(window) => window,
+            None => {
+                let window = conn.generate_id()?;
+                conn.create_window(
+                    x11rb::COPY_DEPTH_FROM_PARENT,
+                    window,
+                    root,
+                    0,
+                    0,
+                    1,
+                    1,
+                    0,
+                    WindowClass::INPUT_OUTPUT,
+                    x11rb::COPY_FROM_PARENT,
+                    &CreateWindowAux::new()
+                        .background_pixel(config.tab_focused_bg)
+                        .border_pixel(config.border_focused)
+                        .override_redirect(1) // Don't manage this window
+                        .event_mask(EventMask::EXPOSURE),
+                )?;
+                self.window = Some(window);
+                window
+            }
+        };
+
+        self.label = label;
+        self.duration = duration;
+        self.shown_at = Some(Instant::now());
+        self.opacity = u32::MAX;
+        conn.change_property32(PropMode::REPLACE, window, opacity_atom, AtomEnum::CARDINAL, &[self.opacity])?;
+        conn.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(monitor.x + (monitor.width as i32 - width as i32) / 2)
+                .y(monitor.y + (monitor.height as i32 - height as i32) / 2)
+                .width(width)
+                .height(height)
+                .border_width(config.border_width)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        conn.map_window(window)?;
+        self.draw(conn, font, gc, config)?;
+        Ok(())
+    }
+
+    /// Advance the fade and hide the indicator once its time is up
+    /// (called from the main loop).
+    pub fn step(&mut self, conn: &impl Connection, opacity_atom: Atom, now: Instant) -> Result<()> {
+        let (Some(window), Some(shown_at)) = (self.window, self.shown_at) else {
+            return Ok(());
+        };
+        match opacity_at(now.saturating_duration_since(shown_at), self.duration) {
+            None => {
+                self.shown_at = None;
+                conn.unmap_window(window)?;
+                conn.flush()?;
+            }
+            // Only talk to the server when the opacity moved noticeably
+            Some(opacity) if self.opacity - opacity > u32::MAX / 20 => {
+                self.opacity = opacity;
+                conn.change_property32(PropMode::REPLACE, window, opacity_atom, AtomEnum::CARDINAL, &[opacity])?;
+                conn.flush()?;
+            }
+            Some(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Draw the label centered in the window.
+    pub fn draw(&self, conn: &impl Connection, font: &FontRenderer, gc: Gcontext, config: &LayoutConfig) -> Result<()> {
+        let Some(window) = self.window.filter(|_| self.shown_at.is_some()) else {
+            return Ok(());
+        };
+        let (pixels, w, h) = font.render_text(&self.label, config.tab_text_color, config.tab_focused_bg);
+        if !pixels.is_empty() && w > 0 && h > 0 {
+            conn.put_image(
+                ImageFormat::Z_PIXMAP,
+                window,
+                gc,
+                w as u16,
+                h as u16,
+                PADDING as i16,
+                PADDING as i16,
+                0,
+                24, // depth (24-bit color, will be padded to 32)
+                &pixels,
+            )?;
+        }
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opacity_fades_over_last_third() {
+        let duration = Duration::from_millis(900);
+        assert_eq!(opacity_at(Duration::ZERO, duration), Some(u32::MAX));
+        assert_eq!(opacity_at(Duration::from_millis(600), duration), Some(u32::MAX));
+
+        let halfway = opacity_at(Duration::from_millis(750), duration).unwrap();
+        assert!(halfway > u32::MAX / 2 - u32::MAX / 100 && halfway < u32::MAX / 2 + u32::MAX / 100);
+
+        assert_eq!(opacity_at(Duration::from_millis(900), duration), None);
+        assert_eq!(opacity_at(Duration::from_millis(1000), duration), None);
+    }
+}
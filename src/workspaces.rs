@@ -3,13 +3,39 @@
 //! This module provides workspace management for ttwm, allowing users
 //! to organize windows across multiple virtual desktops.
 
+use std::collections::VecDeque;
+
 use x11rb::protocol::xproto::Window;
 
-use crate::layout::LayoutTree;
+use crate::layout::{FrameStyle, LayoutTree};
+use crate::types::Rect;
 
 /// Number of workspaces (virtual desktops)
 pub const NUM_WORKSPACES: usize = 9;
 
+/// Number of layout snapshots kept per workspace for undo
+pub const LAYOUT_HISTORY_LIMIT: usize = 20;
+
+/// Smallest size a client can shrink a floating window to via ConfigureRequest
+pub const MIN_FLOAT_SIZE: u32 = 50;
+
+/// Who asked for a window to move to another workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveOrigin {
+    /// The user, through IPC or a key binding
+    User,
+    /// A client's `_NET_WM_DESKTOP` request (a pager or the application)
+    Client,
+}
+
+impl MoveOrigin {
+    /// Whether the user goes along to the destination workspace. Only the
+    /// user's own moves follow, so a pager can't switch workspaces under them.
+    pub fn follows(self, move_follows: bool) -> bool {
+        move_follows && self == MoveOrigin::User
+    }
+}
+
 /// A floating window with its geometry
 #[derive(Debug, Clone, Copy)]
 pub struct FloatingWindow {
@@ -20,11 +46,36 @@ pub struct FloatingWindow {
     pub height: u32,
 }
 
+impl FloatingWindow {
+    /// Apply a client's ConfigureRequest to the stored geometry. Fields the
+    /// client didn't ask to change are `None`; sizes include the border.
+    /// The size is clamped to `bounds` (and `MIN_FLOAT_SIZE`) and the window
+    /// is moved back inside `bounds` if the request would push it off.
+    pub fn apply_request(
+        &mut self,
+        x: Option<i32>,
+        y: Option<i32>,
+        width: Option<u32>,
+        height: Option<u32>,
+        bounds: Rect,
+    ) {
+        let max_w = bounds.width.max(MIN_FLOAT_SIZE);
+        let max_h = bounds.height.max(MIN_FLOAT_SIZE);
+        self.width = width.unwrap_or(self.width).clamp(MIN_FLOAT_SIZE, max_w);
+        self.height = height.unwrap_or(self.height).clamp(MIN_FLOAT_SIZE, max_h);
+
+        let x = x.unwrap_or(self.x);
+        let y = y.unwrap_or(self.y);
+        let max_x = bounds.x + bounds.width as i32 - self.width as i32;
+        let max_y = bounds.y + bounds.height as i32 - self.height as i32;
+        self.x = x.min(max_x).max(bounds.x);
+        self.y = y.min(max_y).max(bounds.y);
+    }
+}
+
 /// A workspace (virtual desktop) containing an independent layout tree
 #[derive(Debug)]
 pub struct Workspace {
-    /// Unique identifier (1-9)
-    pub id: usize,
     /// The layout tree for this workspace
     pub layout: LayoutTree,
     /// The last focused window in this workspace (for focus restoration)
@@ -33,18 +84,86 @@ pub struct Workspace {
     pub floating_windows: Vec<FloatingWindow>,
     /// Fullscreen window in this workspace (only one at a time)
     pub fullscreen_window: Option<Window>,
+    /// Window zoomed across all monitors (tracked separately from fullscreen)
+    pub zoomed_window: Option<Window>,
+    /// Window maximized to the monitor's usable area (below docks and the bar)
+    pub maximized_window: Option<Window>,
+    /// Layout snapshots taken before layout operations (oldest first)
+    layout_history: VecDeque<LayoutTree>,
 }
 
 impl Workspace {
-    /// Create a new workspace with the given id
-    pub fn new(id: usize) -> Self {
+    /// Create a new empty workspace
+    pub fn new() -> Self {
         let layout = LayoutTree::new();
         Self {
-            id,
             layout,
             last_focused_window: None,
             floating_windows: Vec::new(),
             fullscreen_window: None,
+            zoomed_window: None,
+            maximized_window: None,
+            layout_history: VecDeque::new(),
+        }
+    }
+
+    /// Snapshot the current layout so the next layout operation can be undone
+    pub fn push_layout_history(&mut self) {
+        if self.layout_history.len() >= LAYOUT_HISTORY_LIMIT {
+            self.layout_history.pop_front();
+        }
+        self.layout_history.push_back(self.layout.clone());
+    }
+
+    /// Check whether there is a layout operation to undo
+    pub fn has_layout_history(&self) -> bool {
+        !self.layout_history.is_empty()
+    }
+
+    /// Drop the most recent snapshot (for operations that turned out to be no-ops)
+    pub fn discard_layout_history(&mut self) {
+        self.layout_history.pop_back();
+    }
+
+    /// Restore the most recent layout snapshot. Returns false if there is none.
+    ///
+    /// Windows can't be brought back, so the snapshot is reconciled with the
+    /// current layout: windows closed (or floated, or moved away) since are
+    /// dropped, and windows added since go into the restored focused frame.
+    pub fn undo_layout(&mut self) -> bool {
+        let Some(mut restored) = self.layout_history.pop_back() else {
+            return false;
+        };
+
+        let current = self.layout.all_windows();
+        let snapshot = restored.all_windows();
+        for &window in snapshot.iter().filter(|w| !current.contains(w)) {
+            restored.remove_window(window);
+        }
+        for &window in current.iter().filter(|w| !snapshot.contains(w)) {
+            restored.add_window(window);
+        }
+
+        self.layout = restored;
+        true
+    }
+
+    /// Clear the fullscreen, zoomed, maximized and last focused windows if
+    /// they are no longer on this workspace
+    pub fn forget_departed_windows(&mut self) {
+        let slots = [
+            &mut self.fullscreen_window,
+            &mut self.zoomed_window,
+            &mut self.maximized_window,
+            &mut self.last_focused_window,
+        ];
+        for slot in slots {
+            let present = |w: Window| {
+                self.layout.find_window(w).is_some() || self.floating_windows.iter().any(|f| f.window == w)
+            };
+            if slot.is_some_and(|w| !present(w)) {
+                *slot = None;
+            }
         }
     }
 
@@ -83,10 +202,94 @@ impl Workspace {
         self.floating_windows.iter().any(|f| f.window == window)
     }
 
+    /// Where a tiled window sits on screen, border included: its frame's area
+    /// within `screen` minus the tab bar, as `Wm::apply_layout` places it.
+    pub fn tiled_client_rect(
+        &self,
+        window: Window,
+        screen: Rect,
+        gap: u32,
+        tab_bar_height: u32,
+        vertical_tab_width: u32,
+    ) -> Option<Rect> {
+        let frame_id = self.layout.find_window(window)?;
+        let frame = self.layout.get(frame_id)?.as_frame()?;
+        let (_, rect) = self.layout
+            .calculate_geometries(screen, gap)
+            .into_iter()
+            .find(|(id, _)| *id == frame_id)?;
+        Some(crate::tab_bar::client_area(
+            rect,
+            frame.windows.len(),
+            frame.style == FrameStyle::Stacked,
+            frame.vertical_tabs,
+            tab_bar_height,
+            vertical_tab_width,
+        ))
+    }
+
+    /// Float a window exactly over `slot` (its old tiled rect), moved and
+    /// shrunk as needed to stay inside `bounds`
+    pub fn add_floating_at(&mut self, window: Window, slot: Rect, bounds: Rect) {
+        let mut float = FloatingWindow { window, x: slot.x, y: slot.y, width: slot.width, height: slot.height };
+        float.apply_request(None, None, None, None, bounds);
+        self.floating_windows.push(float);
+    }
+
     /// Get all floating window IDs
     pub fn floating_window_ids(&self) -> Vec<Window> {
         self.floating_windows.iter().map(|f| f.window).collect()
     }
+
+    /// The window covering the screen and the exact rect it must have: a
+    /// zoomed window spans `span`, a fullscreen one fills `monitor` and a
+    /// maximized one fills `usable`.
+    pub fn takeover(&self, monitor: Rect, span: Rect, usable: Rect) -> Option<(Window, Rect)> {
+        self.zoomed_window
+            .map(|window| (window, span))
+            .or_else(|| self.fullscreen_window.map(|window| (window, monitor)))
+            .or_else(|| self.maximized_window.map(|window| (window, usable)))
+    }
+
+    /// Windows to raise, bottom to top, once tiled windows and tab bars are
+    /// stacked: the docks, then any fullscreen, zoomed or maximized window,
+    /// which stays above them.
+    pub fn raise_order(
+        &self,
+        docks: impl IntoIterator<Item = Window>,
+        monitor: Rect,
+        span: Rect,
+        usable: Rect,
+    ) -> Vec<Window> {
+        let mut order: Vec<Window> = docks.into_iter().collect();
+        order.extend(self.takeover(monitor, span, usable).map(|(window, _)| window));
+        order
+    }
+
+    /// Apply a client's ConfigureRequest to a floating window (see
+    /// `FloatingWindow::apply_request`). Returns false, changing nothing,
+    /// for windows that aren't floating or are fullscreen/zoomed/maximized:
+    /// those keep the geometry ttwm gives them.
+    pub fn apply_configure_request(
+        &mut self,
+        window: Window,
+        x: Option<i32>,
+        y: Option<i32>,
+        width: Option<u32>,
+        height: Option<u32>,
+        bounds: Rect,
+    ) -> bool {
+        if [self.fullscreen_window, self.zoomed_window, self.maximized_window].contains(&Some(window)) {
+            return false;
+        }
+        match self.find_floating_mut(window) {
+            Some(float) => {
+                float.apply_request(x, y, width, height, bounds);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 /// Manages multiple workspaces (virtual desktops)
@@ -102,7 +305,7 @@ impl WorkspaceManager {
     /// Create a new workspace manager with 9 workspaces
     pub fn new() -> Self {
         Self {
-            workspaces: std::array::from_fn(|i| Workspace::new(i + 1)),
+            workspaces: std::array::from_fn(|_| Workspace::new()),
             current: 0,
         }
     }
@@ -133,24 +336,94 @@ impl WorkspaceManager {
         Some(old)
     }
 
-    /// Cycle to the next workspace (wrapping around)
-    /// Returns the old workspace index
-    pub fn next(&mut self) -> usize {
-        let old = self.current;
-        self.current = (self.current + 1) % NUM_WORKSPACES;
-        old
+    /// Move a tiled window to workspace `target`. Returns the workspace it
+    /// came from, or None if it isn't tiled here or is already on `target`.
+    pub fn move_window(&mut self, window: Window, target: usize) -> Option<usize> {
+        if target >= NUM_WORKSPACES {
+            return None;
+        }
+        let source = self.workspaces.iter().position(|ws| ws.layout.find_window(window).is_some())?;
+        if source == target {
+            return None;
+        }
+        self.workspaces[source].layout.remove_window(window);
+        self.workspaces[target].layout.add_window(window);
+        Some(source)
     }
 
-    /// Cycle to the previous workspace (wrapping around)
-    /// Returns the old workspace index
-    pub fn prev(&mut self) -> usize {
-        let old = self.current;
-        self.current = if self.current == 0 {
-            NUM_WORKSPACES - 1
+    /// Switch to workspace `target` with `window` (already moved there) as
+    /// the window `restore_focus` brings back. Returns the old index like
+    /// `switch_to`
+    pub fn follow_window(&mut self, window: Window, target: usize) -> Option<usize> {
+        self.workspaces.get_mut(target)?.last_focused_window = Some(window);
+        self.switch_to(target)
+    }
+
+    /// Remember `window` as the last focused window of the current workspace,
+    /// if it lives there (tiled or floating)
+    pub fn record_focus(&mut self, window: Window) {
+        let ws = self.current_mut();
+        if ws.layout.find_window(window).is_some() || ws.is_floating(window) {
+            ws.last_focused_window = Some(window);
+        }
+    }
+
+    /// Select the current workspace's last focused window again, making its
+    /// frame and tab the focused ones in the layout. Returns None if nothing
+    /// was recorded or the window has since left the workspace.
+    pub fn restore_focus(&mut self) -> Option<Window> {
+        let ws = self.current_mut();
+        let window = ws.last_focused_window?;
+        if ws.is_floating(window) {
+            return Some(window);
+        }
+        let Some(frame_id) = ws.layout.find_window(window) else {
+            ws.last_focused_window = None;
+            return None;
+        };
+        let tab_idx = ws.layout.get(frame_id)
+            .and_then(|n| n.as_frame())
+            .and_then(|frame| frame.windows.iter().position(|&w| w == window))?;
+        ws.layout.focused = frame_id;
+        ws.layout.focus_tab(tab_idx);
+        Some(window)
+    }
+
+    /// Cycle to the next of the first `count` workspaces, wrapping to the
+    /// first one if `wrap` is set. Returns the old workspace index, or None
+    /// if already at the end without wrapping
+    pub fn next(&mut self, count: usize, wrap: bool) -> Option<usize> {
+        let last = count.clamp(1, NUM_WORKSPACES) - 1;
+        let target = if self.current < last {
+            self.current + 1
+        } else if wrap {
+            0
         } else {
-            self.current - 1
+            return None;
         };
-        old
+        self.switch_to(target)
+    }
+
+    /// Cycle to the previous of the first `count` workspaces, wrapping to
+    /// the last one if `wrap` is set. Returns the old workspace index, or
+    /// None if already at the start without wrapping
+    pub fn prev(&mut self, count: usize, wrap: bool) -> Option<usize> {
+        let last = count.clamp(1, NUM_WORKSPACES) - 1;
+        let target = if self.current == 0 {
+            if !wrap {
+                return None;
+            }
+            last
+        } else {
+            (self.current - 1).min(last)
+        };
+        self.switch_to(target)
+    }
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -164,16 +437,257 @@ impl Default for WorkspaceManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_restore_focus_after_switch() {
+        let mut wm = WorkspaceManager::new();
+        wm.current_mut().layout.add_window(1);
+        wm.current_mut().layout.add_window(2);
+        wm.current_mut().layout.add_window(3);
+        wm.current_mut().layout.focus_tab(1);
+        wm.record_focus(2);
+
+        // Windows elsewhere aren't recorded against this workspace
+        wm.record_focus(99);
+        assert_eq!(wm.current().last_focused_window, Some(2));
+
+        wm.switch_to(4);
+        wm.current_mut().layout.add_window(10);
+        wm.record_focus(10);
+
+        // Meanwhile the frame's selected tab drifted
+        wm.workspaces[0].layout.focus_tab(2);
+        wm.switch_to(0);
+        assert_eq!(wm.restore_focus(), Some(2));
+        assert_eq!(wm.current().layout.focused_frame().and_then(|f| f.focused_window()), Some(2));
+
+        // A window that has since gone isn't restored
+        wm.current_mut().layout.remove_window(2);
+        assert_eq!(wm.restore_focus(), None);
+        assert_eq!(wm.current().last_focused_window, None);
+    }
+
+    #[test]
+    fn test_move_with_and_without_follow() {
+        let cases = [
+            (MoveOrigin::User, true, true),
+            (MoveOrigin::User, false, false),
+            // A pager moving a window never drags the user along
+            (MoveOrigin::Client, true, false),
+            (MoveOrigin::Client, false, false),
+        ];
+        for (origin, move_follows, expect_follow) in cases {
+            let mut wm = WorkspaceManager::new();
+            wm.workspaces[3].layout.add_window(5);
+            wm.workspaces[3].layout.add_window(6);
+            wm.workspaces[2].layout.add_window(8);
+            wm.current_mut().layout.add_window(7);
+
+            // Move window 5 from workspace 4 to workspace 3, following it
+            // the way move_window_to_workspace does
+            assert_eq!(wm.move_window(5, 2), Some(3));
+            assert!(wm.workspaces[2].layout.find_window(5).is_some());
+            assert!(wm.workspaces[3].layout.find_window(5).is_none());
+            let follow = origin.follows(move_follows);
+            assert_eq!(follow, expect_follow, "{:?} with move_follows={}", origin, move_follows);
+            if follow {
+                assert_eq!(wm.follow_window(5, 2), Some(0));
+                assert_eq!(wm.restore_focus(), Some(5));
+                assert_eq!(wm.current_index(), 2);
+                assert_eq!(wm.current().layout.focused_frame().and_then(|f| f.focused_window()), Some(5));
+            } else {
+                assert_eq!(wm.current_index(), 0);
+                assert_eq!(wm.workspaces[2].last_focused_window, None);
+            }
+        }
+
+        // Moving to where the window already is, or to no workspace, does nothing
+        let mut wm = WorkspaceManager::new();
+        wm.workspaces[2].layout.add_window(5);
+        assert_eq!(wm.move_window(5, 2), None);
+        assert_eq!(wm.move_window(5, NUM_WORKSPACES), None);
+        assert_eq!(wm.move_window(6, 1), None);
+    }
+
+    #[test]
+    fn test_next_prev_wrap() {
+        let mut wm = WorkspaceManager::new();
+        wm.switch_to(8);
+
+        // At workspace 9 without wrapping, next stays put
+        assert_eq!(wm.next(NUM_WORKSPACES, false), None);
+        assert_eq!(wm.current_index(), 8);
+
+        // With wrapping it goes to 1, and prev comes back
+        assert_eq!(wm.next(NUM_WORKSPACES, true), Some(8));
+        assert_eq!(wm.current_index(), 0);
+        assert_eq!(wm.prev(NUM_WORKSPACES, false), None);
+        assert_eq!(wm.prev(NUM_WORKSPACES, true), Some(0));
+        assert_eq!(wm.current_index(), 8);
+    }
+
+    #[test]
+    fn test_next_prev_count() {
+        let mut wm = WorkspaceManager::new();
+        wm.switch_to(2);
+
+        // Only the first 3 workspaces are cycled through
+        assert_eq!(wm.next(3, true), Some(2));
+        assert_eq!(wm.current_index(), 0);
+        assert_eq!(wm.prev(3, true), Some(0));
+        assert_eq!(wm.current_index(), 2);
+        assert_eq!(wm.next(3, false), None);
+
+        // From a workspace past the range, prev re-enters it at the top
+        wm.switch_to(6);
+        assert_eq!(wm.prev(3, false), Some(6));
+        assert_eq!(wm.current_index(), 2);
+    }
+
     #[test]
     fn test_workspace_new_has_empty_floating() {
-        let ws = Workspace::new(1);
-        assert_eq!(ws.id, 1);
+        let ws = Workspace::new();
         assert!(ws.floating_windows.is_empty());
     }
 
+    #[test]
+    fn test_floating_configure_request_is_clamped() {
+        let bounds = Rect::new(0, 0, 1920, 1080);
+        let mut float = FloatingWindow { window: 100, x: 100, y: 100, width: 640, height: 480 };
+
+        // Size-only request keeps the position
+        float.apply_request(None, None, Some(1280), Some(720), bounds);
+        assert_eq!((float.x, float.y, float.width, float.height), (100, 100, 1280, 720));
+
+        // Too large: capped to the monitor and pulled back on screen
+        float.apply_request(None, None, Some(4000), Some(20), bounds);
+        assert_eq!((float.x, float.y, float.width, float.height), (0, 100, 1920, MIN_FLOAT_SIZE));
+
+        // Moves past the edge are kept inside the monitor
+        float.apply_request(Some(-500), Some(1050), Some(800), Some(600), bounds);
+        assert_eq!((float.x, float.y, float.width, float.height), (0, 480, 800, 600));
+    }
+
+    #[test]
+    fn test_fullscreen_ignores_configure_requests() {
+        let monitor = Rect::new(1920, 0, 2560, 1440);
+        let span = Rect::new(0, 0, 4480, 1440);
+        let usable = Rect::new(1928, 36, 2544, 1396);
+        let mut ws = Workspace::new();
+        ws.add_floating(100, 2000, 100, 800, 600);
+        assert_eq!(ws.takeover(monitor, span, usable), None);
+        assert_eq!(ws.raise_order([7, 8], monitor, span, usable), vec![7, 8]);
+
+        ws.fullscreen_window = Some(100);
+        assert_eq!(ws.takeover(monitor, span, usable), Some((100, monitor)));
+        // Raising docks on focus changes keeps them under the fullscreen window
+        assert_eq!(ws.raise_order([7, 8], monitor, span, usable), vec![7, 8, 100]);
+
+        // The client tries to shrink itself: nothing changes
+        assert!(!ws.apply_configure_request(100, Some(2100), Some(200), Some(640), Some(480), monitor));
+        assert_eq!(ws.takeover(monitor, span, usable), Some((100, monitor)));
+        let float = ws.floating_windows[0];
+        assert_eq!((float.x, float.y, float.width, float.height), (2000, 100, 800, 600));
+
+        // Zoom wins over fullscreen
+        ws.zoomed_window = Some(100);
+        assert_eq!(ws.takeover(monitor, span, usable), Some((100, span)));
+
+        // A maximized window fills the usable area, under fullscreen and zoom
+        ws.maximized_window = Some(100);
+        assert_eq!(ws.takeover(monitor, span, usable), Some((100, span)));
+        ws.fullscreen_window = None;
+        ws.zoomed_window = None;
+        assert_eq!(ws.takeover(monitor, span, usable), Some((100, usable)));
+        assert!(!ws.apply_configure_request(100, None, None, Some(640), Some(480), monitor));
+
+        // Once back to normal, requests apply again
+        ws.maximized_window = None;
+        assert!(ws.apply_configure_request(100, None, None, Some(640), Some(480), monitor));
+        assert_eq!(ws.floating_windows[0].width, 640);
+    }
+
+    #[test]
+    fn test_float_at_tiled_position_matches_tiled_rect() {
+        let mut ws = Workspace::new();
+        ws.layout.add_window(100);
+        let right = ws.layout.split_focused(crate::layout::SplitDirection::Horizontal);
+        ws.layout.focused = right;
+        ws.layout.add_window(200);
+        ws.layout.add_window(300);
+        let screen = Rect::new(0, 0, 1920, 1080);
+
+        // Each frame is 956 wide around the 8px gap, clients sit below a 28px tab bar
+        assert_eq!(ws.tiled_client_rect(100, screen, 8, 28, 150), Some(Rect::new(0, 28, 956, 1052)));
+        let slot = ws.tiled_client_rect(200, screen, 8, 28, 150).unwrap();
+        assert_eq!(slot, Rect::new(964, 28, 956, 1052));
+        assert_eq!(ws.tiled_client_rect(999, screen, 8, 28, 150), None);
+
+        // Vertical tabs move the client right of the tab column instead
+        ws.layout.toggle_vertical_tabs();
+        assert_eq!(ws.tiled_client_rect(300, screen, 8, 28, 150), Some(Rect::new(1114, 0, 806, 1080)));
+        ws.layout.toggle_vertical_tabs();
+
+        // Floating lands exactly on the slot the window left
+        ws.layout.remove_window(200);
+        ws.add_floating_at(200, slot, screen);
+        let float = ws.find_floating(200).unwrap();
+        assert_eq!(Rect::new(float.x, float.y, float.width, float.height), slot);
+
+        // A slot hanging off a smaller monitor is pulled back on screen
+        ws.layout.remove_window(300);
+        ws.add_floating_at(300, slot, Rect::new(0, 0, 1280, 720));
+        let float = ws.find_floating(300).unwrap();
+        assert_eq!((float.x, float.y, float.width, float.height), (324, 0, 956, 720));
+    }
+
+    #[test]
+    fn test_undo_layout_restores_split() {
+        let mut ws = Workspace::new();
+        ws.layout.add_window(100);
+        ws.layout.add_window(200);
+
+        ws.push_layout_history();
+        ws.layout.split_focused(crate::layout::SplitDirection::Horizontal);
+        assert_eq!(ws.layout.all_frames().len(), 2);
+
+        assert!(ws.undo_layout());
+        assert_eq!(ws.layout.all_frames().len(), 1);
+        assert_eq!(ws.layout.all_windows().len(), 2);
+        assert!(!ws.undo_layout());
+    }
+
+    #[test]
+    fn test_undo_layout_reconciles_windows() {
+        let mut ws = Workspace::new();
+        ws.layout.add_window(100);
+        ws.layout.add_window(200);
+
+        ws.push_layout_history();
+        ws.layout.remove_window(200);
+        ws.layout.add_window(300);
+
+        assert!(ws.undo_layout());
+        let mut windows = ws.layout.all_windows();
+        windows.sort();
+        assert_eq!(windows, vec![100, 300]);
+    }
+
+    #[test]
+    fn test_layout_history_is_bounded() {
+        let mut ws = Workspace::new();
+        for _ in 0..LAYOUT_HISTORY_LIMIT + 5 {
+            ws.push_layout_history();
+        }
+        let mut undone = 0;
+        while ws.undo_layout() {
+            undone += 1;
+        }
+        assert_eq!(undone, LAYOUT_HISTORY_LIMIT);
+    }
+
     #[test]
     fn test_add_floating_window() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 10, 20, 300, 200);
 
         assert_eq!(ws.floating_windows.len(), 1);
@@ -186,7 +700,7 @@ mod tests {
 
     #[test]
     fn test_add_multiple_floating_windows() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 0, 0, 100, 100);
         ws.add_floating(200, 50, 50, 150, 150);
         ws.add_floating(300, 100, 100, 200, 200);
@@ -197,7 +711,7 @@ mod tests {
 
     #[test]
     fn test_remove_floating_window() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 10, 20, 300, 200);
         ws.add_floating(200, 50, 60, 400, 300);
 
@@ -214,7 +728,7 @@ mod tests {
 
     #[test]
     fn test_remove_nonexistent_floating_window() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 0, 0, 100, 100);
 
         let removed = ws.remove_floating(999);
@@ -224,7 +738,7 @@ mod tests {
 
     #[test]
     fn test_find_floating_window() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 10, 20, 300, 200);
         ws.add_floating(200, 50, 60, 400, 300);
 
@@ -238,13 +752,13 @@ mod tests {
 
     #[test]
     fn test_find_floating_window_not_found() {
-        let ws = Workspace::new(1);
+        let ws = Workspace::new();
         assert!(ws.find_floating(999).is_none());
     }
 
     #[test]
     fn test_find_floating_mut() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 10, 20, 300, 200);
 
         if let Some(fw) = ws.find_floating_mut(100) {
@@ -263,7 +777,7 @@ mod tests {
 
     #[test]
     fn test_is_floating() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 0, 0, 100, 100);
 
         assert!(ws.is_floating(100));
@@ -272,7 +786,7 @@ mod tests {
 
     #[test]
     fn test_floating_window_ids() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         assert!(ws.floating_window_ids().is_empty());
 
         ws.add_floating(100, 0, 0, 100, 100);
@@ -310,7 +824,7 @@ mod tests {
 
     #[test]
     fn test_remove_middle_floating_window() {
-        let mut ws = Workspace::new(1);
+        let mut ws = Workspace::new();
         ws.add_floating(100, 0, 0, 100, 100);
         ws.add_floating(200, 0, 0, 100, 100);
         ws.add_floating(300, 0, 0, 100, 100);
@@ -12,10 +12,19 @@ use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use image::{DynamicImage, GenericImageView, ImageBuffer, Rgba};
 use serde_json::Value;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum, ButtonPressEvent, ChangeWindowAttributesAux, ConfigureWindowAux, ConnectionExt as _,
+    CreateWindowAux, EventMask, ImageFormat, MapState, PropMode, WindowClass, BUTTON_PRESS_EVENT,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::COPY_DEPTH_FROM_PARENT;
 
 /// Check if Xvfb is available
 fn xvfb_available() -> bool {
@@ -28,17 +37,64 @@ fn xvfb_available() -> bool {
         .unwrap_or(false)
 }
 
+/// A client window created over its own X connection.
+///
+/// Dropping it closes the connection, which destroys the window.
+struct TestWindow {
+    conn: RustConnection,
+    id: u32,
+}
+
+impl TestWindow {
+    /// Root coordinates and size of the window's inside (border excluded)
+    fn root_rect(&self) -> (i16, i16, u16, u16) {
+        let root = self.conn.setup().roots[0].root;
+        let geometry = self.conn.get_geometry(self.id).unwrap().reply().unwrap();
+        let origin = self.conn.translate_coordinates(self.id, root, 0, 0).unwrap().reply().unwrap();
+        (origin.dst_x, origin.dst_y, geometry.width, geometry.height)
+    }
+}
+
+/// A temp path unique to this test process and call, since the tests run
+/// as parallel threads of one process
+fn unique_temp_path(prefix: &str) -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "{}_{}_{}",
+        prefix,
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
 /// Test fixture that manages Xvfb and ttwm lifecycle
 struct TestHarness {
     xvfb: Child,
     wm: Child,
     display: String,
     socket_path: PathBuf,
+    state_dir: PathBuf,
+    config_path: Option<PathBuf>,
 }
 
 impl TestHarness {
     /// Create a new test harness with Xvfb and ttwm
     fn new() -> Option<Self> {
+        Self::start(None)
+    }
+
+    /// Create a test harness whose ttwm loads `config` (TOML) instead of the defaults
+    fn with_config(config: &str) -> Option<Self> {
+        let path = unique_temp_path("ttwm_test_config").with_extension("toml");
+        std::fs::write(&path, config).ok()?;
+        let harness = Self::start(Some(&path));
+        if harness.is_none() {
+            let _ = std::fs::remove_file(&path);
+        }
+        harness
+    }
+
+    fn start(config: Option<&Path>) -> Option<Self> {
         if !xvfb_available() {
             eprintln!("Xvfb not available, skipping integration tests");
             return None;
@@ -71,10 +127,20 @@ impl TestHarness {
         // Remove old socket if present
         let _ = std::fs::remove_file(&socket_path);
 
+        // Give each test its own state directory so a session saved by one
+        // run is never picked up by the next
+        let state_dir = unique_temp_path("ttwm_test_state");
+        let _ = std::fs::remove_dir_all(&state_dir);
+
         // Start ttwm
-        let wm = match Command::new("./target/debug/ttwm")
+        let mut command = Command::new("./target/debug/ttwm");
+        if let Some(config) = config {
+            command.arg("--config").arg(config);
+        }
+        let wm = match command
             .env("DISPLAY", display)
             .env("RUST_LOG", "info")
+            .env("XDG_STATE_HOME", &state_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
@@ -104,6 +170,8 @@ impl TestHarness {
             wm,
             display: display.to_string(),
             socket_path,
+            state_dir,
+            config_path: config.map(Path::to_path_buf),
         })
     }
 
@@ -153,6 +221,59 @@ impl TestHarness {
         Ok(())
     }
 
+    /// Create and map a plain client window (no xterm needed)
+    fn create_window(&self) -> Result<TestWindow, String> {
+        self.create_window_with_class(None)
+    }
+
+    /// Like `create_window`, setting WM_CLASS to (instance, class) before mapping
+    fn create_window_with_class(&self, wm_class: Option<(&str, &str)>) -> Result<TestWindow, String> {
+        let (conn, screen_num) = x11rb::connect(Some(&self.display))
+            .map_err(|e| format!("Failed to connect to X: {}", e))?;
+        let screen = &conn.setup().roots[screen_num];
+        let id = conn.generate_id().map_err(|e| format!("Failed to allocate id: {}", e))?;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            id,
+            screen.root,
+            0,
+            0,
+            100,
+            100,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new().background_pixel(screen.white_pixel),
+        )
+        .map_err(|e| format!("Failed to create window: {}", e))?;
+        if let Some((instance, class)) = wm_class {
+            let value = format!("{}\0{}\0", instance, class);
+            conn.change_property8(PropMode::REPLACE, id, AtomEnum::WM_CLASS, AtomEnum::STRING, value.as_bytes())
+                .map_err(|e| format!("Failed to set WM_CLASS: {}", e))?;
+        }
+        conn.map_window(id).map_err(|e| format!("Failed to map window: {}", e))?;
+        conn.flush().map_err(|e| format!("Failed to flush: {}", e))?;
+
+        // Wait for window to be managed
+        std::thread::sleep(Duration::from_millis(200));
+        Ok(TestWindow { conn, id })
+    }
+
+    /// Read the color of one screen pixel as 0xRRGGBB
+    fn pixel_at(&self, x: i16, y: i16) -> Result<u32, String> {
+        let (conn, screen_num) = x11rb::connect(Some(&self.display))
+            .map_err(|e| format!("Failed to connect to X: {}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+        let image = conn.get_image(ImageFormat::Z_PIXMAP, root, x, y, 1, 1, !0)
+            .map_err(|e| format!("Failed to request image: {}", e))?
+            .reply()
+            .map_err(|e| format!("Failed to get image: {}", e))?;
+        let bytes: [u8; 4] = image.data.get(..4)
+            .and_then(|b| b.try_into().ok())
+            .ok_or("Short image reply")?;
+        Ok(u32::from_le_bytes(bytes) & 0xffffff)
+    }
+
     /// Split the focused frame
     fn split(&self, direction: &str) -> Result<Value, String> {
         self.send_command(&serde_json::json!({
@@ -371,6 +492,10 @@ impl Drop for TestHarness {
         let _ = self.wm.wait();
         let _ = self.xvfb.kill();
         let _ = self.xvfb.wait();
+        let _ = std::fs::remove_dir_all(&self.state_dir);
+        if let Some(path) = &self.config_path {
+            let _ = std::fs::remove_file(path);
+        }
     }
 }
 
@@ -407,6 +532,196 @@ fn test_state_validation() {
     assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
 }
 
+#[test]
+fn test_repair_borders() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let window = harness.create_window().expect("Failed to create window");
+    let left_border = || {
+        std::thread::sleep(Duration::from_millis(100));
+        let (x, y, _, _) = window.root_rect();
+        harness.pixel_at(x - 1, y + 10).expect("Failed to read pixel")
+    };
+    assert_eq!(left_border(), 0x5294e2);
+
+    // Repaint the border behind the WM's back. Attribute changes aren't
+    // redirected to the WM, unlike a border_width ConfigureRequest
+    window.conn.change_window_attributes(window.id, &ChangeWindowAttributesAux::new().border_pixel(0xff0000))
+        .expect("Failed to change border");
+    window.conn.flush().expect("Failed to flush");
+    assert_eq!(left_border(), 0xff0000);
+
+    let result = harness.send_command(&serde_json::json!({"command": "repair_borders"}))
+        .expect("Failed to repair borders");
+    assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("borders_repaired"));
+    assert_eq!(result.get("windows").and_then(|v| v.as_u64()), Some(1));
+    assert_eq!(left_border(), 0x5294e2);
+    let geometry = window.conn.get_geometry(window.id).unwrap().reply().unwrap();
+    assert_eq!(geometry.border_width, 2);
+
+    // Idempotent, and leaves the state valid
+    harness.send_command(&serde_json::json!({"command": "repair_borders"}))
+        .expect("Failed to repair borders");
+    assert_eq!(left_border(), 0x5294e2);
+    let result = harness.validate().expect("Failed to validate");
+    assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
+}
+
+/// Colors of the focused tab's top-left pixel and of the pixel 8px to its
+/// right, drawn with `tab_corner_radius = radius`
+fn tab_corner_pixels(radius: u32) -> Option<(u32, u32)> {
+    let config = format!(
+        "[appearance]\nborder_width = 2\ntab_bar_height = 28\ntab_corner_radius = {}\n\
+         [colors]\ntab_focused_bg = \"#00ff00\"\n",
+        radius
+    );
+    let harness = TestHarness::with_config(&config)?;
+    let window = harness.create_window().expect("Failed to create window");
+    std::thread::sleep(Duration::from_millis(100));
+
+    // The tab bar sits right above the window's border
+    let (x, y, _, _) = window.root_rect();
+    let (tab_x, tab_y) = (x - 2, y - 2 - 28);
+    let corner = harness.pixel_at(tab_x, tab_y).expect("Failed to read pixel");
+    let inset = harness.pixel_at(tab_x + 8, tab_y).expect("Failed to read pixel");
+    Some((corner, inset))
+}
+
+#[test]
+fn test_tab_corner_radius_rounds_tab_corners() {
+    let Some((corner, inset)) = tab_corner_pixels(6) else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+    // The (pseudo-transparent) bar background shows outside the rounded corner
+    assert_ne!(corner, 0x00ff00);
+    assert_eq!(inset, 0x00ff00);
+
+    let Some((corner, inset)) = tab_corner_pixels(0) else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+    // Square tabs fill their corner
+    assert_eq!(corner, 0x00ff00);
+    assert_eq!(inset, 0x00ff00);
+}
+
+#[test]
+fn test_workspace_border_color_at_frame_seam() {
+    let Some(harness) = TestHarness::with_config("[workspace.2]\nborder_focused = \"#e06c75\"\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    // The focused window's right border sits on the seam between two frames
+    let seam_color = |window: &TestWindow| {
+        std::thread::sleep(Duration::from_millis(100));
+        let (x, y, width, _) = window.root_rect();
+        harness.pixel_at(x + width as i16, y + 10).expect("Failed to read pixel")
+    };
+
+    harness.split("horizontal").expect("Failed to split");
+    harness.focus_frame(false).expect("Failed to focus left frame");
+    let first = harness.create_window().expect("Failed to create window");
+    assert_eq!(seam_color(&first), 0x5294e2);
+
+    // Workspace 2 has its own focused border color
+    harness.switch_workspace(1).expect("Failed to switch workspace");
+    harness.split("horizontal").expect("Failed to split");
+    harness.focus_frame(false).expect("Failed to focus left frame");
+    let second = harness.create_window().expect("Failed to create window");
+    assert_eq!(seam_color(&second), 0xe06c75);
+
+    // Back on workspace 1 the global color applies again
+    harness.switch_workspace(0).expect("Failed to switch workspace");
+    harness.focus_window(first.id).expect("Failed to focus window");
+    assert_eq!(seam_color(&first), 0x5294e2);
+}
+
+#[test]
+fn test_set_gaps_replaces_workspace_overrides() {
+    let Some(harness) = TestHarness::with_config("[appearance]\ngap = 8\n[workspace.1]\ngap = 0\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+    let set_gaps = |gap: Option<u32>| {
+        let result = harness.send_command(&serde_json::json!({"command": "set_gaps", "gap": gap}))
+            .expect("Failed to set gaps");
+        assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("gaps"));
+        result.get("gap").and_then(|v| v.as_u64())
+    };
+
+    // A query reports the override in effect on workspace 1
+    assert_eq!(set_gaps(None), Some(0));
+
+    // Setting a gap applies it on every workspace
+    assert_eq!(set_gaps(Some(6)), Some(6));
+    harness.switch_workspace(1).expect("Failed to switch workspace");
+    assert_eq!(set_gaps(None), Some(6));
+    harness.switch_workspace(0).expect("Failed to switch workspace");
+    assert_eq!(set_gaps(None), Some(6));
+}
+
+#[test]
+fn test_unmap_grace_keeps_remapped_window() {
+    let Some(harness) = TestHarness::with_config("[general]\nunmap_grace_ms = 1000\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+    let window_count = || {
+        let state = harness.get_state().expect("Failed to get state");
+        state.get("data").and_then(|d| d.get("window_count")).and_then(|v| v.as_u64())
+    };
+
+    let window = harness.create_window().expect("Failed to create window");
+    assert_eq!(window_count(), Some(1));
+
+    // Unmapping and mapping again within the grace period keeps the window
+    window.conn.unmap_window(window.id).expect("Failed to unmap window");
+    window.conn.flush().expect("Failed to flush");
+    std::thread::sleep(Duration::from_millis(200));
+    window.conn.map_window(window.id).expect("Failed to map window");
+    window.conn.flush().expect("Failed to flush");
+    std::thread::sleep(Duration::from_millis(1200));
+    assert_eq!(window_count(), Some(1));
+    let focused = harness.get_focused().expect("Failed to get focused");
+    assert_eq!(focused.get("window").and_then(|v| v.as_u64()), Some(window.id as u64));
+
+    // Staying unmapped past the grace period unmanages it
+    window.conn.unmap_window(window.id).expect("Failed to unmap window");
+    window.conn.flush().expect("Failed to flush");
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(window_count(), Some(1));
+    std::thread::sleep(Duration::from_millis(1200));
+    assert_eq!(window_count(), Some(0));
+}
+
+#[test]
+fn test_schema_version() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let schema = harness.send_command(&serde_json::json!({"command": "get_schema"}))
+        .expect("Failed to get schema");
+    assert_eq!(schema.get("status").and_then(|v| v.as_str()), Some("schema"));
+    let version = schema.get("schema_version").and_then(|v| v.as_u64()).expect("No schema_version");
+    assert_eq!(schema["data"]["version"].as_u64(), Some(version));
+
+    // The state snapshot matches the schema's description of it
+    let state = harness.get_state().expect("Failed to get state");
+    assert_eq!(state.get("schema_version").and_then(|v| v.as_u64()), Some(version));
+    let data = state["data"].as_object().expect("No state data");
+    let described = schema["data"]["types"]["WmStateSnapshot"].as_object().expect("No WmStateSnapshot");
+    for field in described.keys() {
+        assert!(data.contains_key(field), "state is missing {}", field);
+    }
+}
+
 #[test]
 fn test_split_creates_two_frames() {
     let Some(harness) = TestHarness::new() else {
@@ -433,6 +748,248 @@ fn test_split_creates_two_frames() {
     assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
 }
 
+#[test]
+fn test_promote_middle_tab() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    // Three windows in the same frame
+    let _first = harness.create_window().expect("Failed to create window");
+    let second = harness.create_window().expect("Failed to create window");
+    let _third = harness.create_window().expect("Failed to create window");
+
+    // Focus the middle tab and promote it
+    harness.focus_window(second.id).expect("Failed to focus window");
+    let middle = second.id as u64;
+    let result = harness.send_command(&serde_json::json!({"command": "promote"}))
+        .expect("Failed to promote");
+    assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("ok"));
+
+    // Promoted window should be first and still focused
+    let windows = harness.get_windows().expect("Failed to get windows");
+    let data = windows.get("data").and_then(|v| v.as_array()).expect("Missing data");
+    let first = data.iter()
+        .find(|w| w.get("tab_index").and_then(|v| v.as_u64()) == Some(0))
+        .and_then(|w| w.get("id").and_then(|v| v.as_u64()));
+    assert_eq!(first, Some(middle));
+    let focused = harness.get_focused().expect("Failed to get focused");
+    assert_eq!(focused.get("window").and_then(|v| v.as_u64()), Some(middle));
+}
+
+#[test]
+fn test_close_frame_closes_all_tabs() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    // Three tabs in one frame
+    let _first = harness.create_window().expect("Failed to create window");
+    let _second = harness.create_window().expect("Failed to create window");
+    let _third = harness.create_window().expect("Failed to create window");
+
+    let result = harness.send_command(&serde_json::json!({"command": "close_frame"}))
+        .expect("Failed to close frame");
+    assert_eq!(result.get("requested").and_then(|v| v.as_u64()), Some(3));
+
+    // The test windows don't speak WM_DELETE_WINDOW, so their clients are killed
+    std::thread::sleep(Duration::from_millis(500));
+    let state = harness.get_state().expect("Failed to get state");
+    let data = state.get("data").expect("Missing data");
+    assert_eq!(data.get("window_count").and_then(|v| v.as_u64()), Some(0));
+    assert_eq!(data.get("frame_count").and_then(|v| v.as_u64()), Some(1));
+
+    // The whole frame close is a single undo entry
+    let undo = serde_json::json!({"command": "undo"});
+    let result = harness.send_command(&undo).expect("Failed to undo");
+    assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("ok"));
+    let result = harness.send_command(&undo).expect("Failed to undo");
+    assert_eq!(result.get("code").and_then(|v| v.as_str()), Some("nothing_to_undo"));
+}
+
+#[test]
+fn test_focus_no_input_window_takes_keyboard_focus_away() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let typing = harness.create_window().expect("Failed to create window");
+    let no_input = harness.create_window().expect("Failed to create window");
+
+    // WM_HINTS with the InputHint flag set and input = False
+    no_input.conn.change_property32(
+        PropMode::REPLACE,
+        no_input.id,
+        AtomEnum::WM_HINTS,
+        AtomEnum::WM_HINTS,
+        &[1, 0, 0, 0, 0, 0, 0, 0, 0],
+    ).expect("Failed to set WM_HINTS");
+    no_input.conn.flush().expect("Failed to flush");
+
+    let input_focus = || typing.conn.get_input_focus().unwrap().reply().unwrap().focus;
+
+    harness.focus_window(typing.id).expect("Failed to focus window");
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(input_focus(), typing.id);
+
+    // The no-input window is focused as far as ttwm is concerned, but X
+    // input focus must not stay on (or go to) a client window
+    harness.focus_window(no_input.id).expect("Failed to focus window");
+    std::thread::sleep(Duration::from_millis(100));
+    let focused = harness.get_focused().expect("Failed to get focused");
+    assert_eq!(focused.get("window").and_then(|v| v.as_u64()), Some(no_input.id as u64));
+    let focus = input_focus();
+    assert_ne!(focus, typing.id, "Keys would still go to the previously focused window");
+    assert_ne!(focus, no_input.id);
+}
+
+#[test]
+fn test_unmanaged_class_is_left_alone() {
+    let Some(harness) = TestHarness::with_config("[general]\nunmanaged_classes = [\"Conky\"]\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let ignored = harness.create_window_with_class(Some(("conky", "Conky")))
+        .expect("Failed to create window");
+    let managed = harness.create_window_with_class(Some(("xterm", "XTerm")))
+        .expect("Failed to create window");
+
+    // Only the other window made it into the layout
+    let windows = harness.get_windows().expect("Failed to get windows");
+    let ids: Vec<u64> = windows.get("data").and_then(|v| v.as_array()).expect("Missing data")
+        .iter()
+        .filter_map(|w| w.get("id").and_then(|v| v.as_u64()))
+        .collect();
+    assert_eq!(ids, vec![managed.id as u64]);
+
+    // Mapped where it asked to be: still a child of the root, with no border
+    let conn = &ignored.conn;
+    let root = conn.setup().roots[0].root;
+    let attrs = conn.get_window_attributes(ignored.id).unwrap().reply().unwrap();
+    assert_eq!(attrs.map_state, MapState::VIEWABLE);
+    assert_eq!(conn.query_tree(ignored.id).unwrap().reply().unwrap().parent, root);
+    let geometry = conn.get_geometry(ignored.id).unwrap().reply().unwrap();
+    assert_eq!(geometry.border_width, 0);
+    assert_eq!((geometry.x, geometry.y, geometry.width, geometry.height), (0, 0, 100, 100));
+}
+
+/// Make a managed window override-redirect and move it, as clients that
+/// place their own windows do. Returns the ids ttwm manages afterwards.
+fn flip_override_redirect(harness: &TestHarness, window: &TestWindow) -> Vec<u64> {
+    window.conn.change_window_attributes(window.id, &ChangeWindowAttributesAux::new().override_redirect(1))
+        .expect("Failed to set override-redirect");
+    window.conn.configure_window(window.id, &ConfigureWindowAux::new().x(40).y(40))
+        .expect("Failed to move window");
+    window.conn.flush().expect("Failed to flush");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let windows = harness.get_windows().expect("Failed to get windows");
+    windows.get("data").and_then(|v| v.as_array()).expect("Missing data")
+        .iter()
+        .filter_map(|w| w.get("id").and_then(|v| v.as_u64()))
+        .collect()
+}
+
+#[test]
+fn test_override_redirect_flip_releases_window() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let window = harness.create_window().expect("Failed to create window");
+    assert_eq!(flip_override_redirect(&harness, &window), Vec::<u64>::new());
+
+    // Released, not hidden: still mapped, without ttwm's border
+    let attrs = window.conn.get_window_attributes(window.id).unwrap().reply().unwrap();
+    assert_eq!(attrs.map_state, MapState::VIEWABLE);
+    let geometry = window.conn.get_geometry(window.id).unwrap().reply().unwrap();
+    assert_eq!(geometry.border_width, 0);
+    let result = harness.validate().expect("Failed to validate");
+    assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
+}
+
+#[test]
+fn test_override_redirect_flip_ignore_keeps_managing() {
+    let Some(harness) = TestHarness::with_config("[general]\noverride_redirect_flip = \"ignore\"\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let window = harness.create_window().expect("Failed to create window");
+    assert_eq!(flip_override_redirect(&harness, &window), vec![window.id as u64]);
+}
+
+/// With `tab_bar_click_through` set to `enabled`, click the background of
+/// the unfocused left frame's tab bar (past its only tab) and return the
+/// window ttwm focuses, along with the left and right windows
+fn click_tab_bar_background(enabled: bool) -> Option<(u64, u64, u64)> {
+    let config = format!(
+        "[appearance]\nborder_width = 2\ntab_bar_height = 28\ntab_bar_click_through = {}\n",
+        enabled
+    );
+    let harness = TestHarness::with_config(&config)?;
+    harness.split("horizontal").expect("Failed to split");
+    harness.focus_frame(false).expect("Failed to focus left frame");
+    let left = harness.create_window().expect("Failed to create window");
+    harness.focus_frame(true).expect("Failed to focus right frame");
+    let right = harness.create_window().expect("Failed to create window");
+
+    // The tab bar is the top-level window just above the left window's border
+    let (x, y, width, _) = left.root_rect();
+    let conn = &left.conn;
+    let root = conn.setup().roots[0].root;
+    let (root_x, root_y) = (x + width as i16 - 5, y - 2 - 14);
+    let tab_bar = conn.translate_coordinates(root, root, root_x, root_y).unwrap().reply().unwrap().child;
+    let origin = conn.translate_coordinates(tab_bar, root, 0, 0).unwrap().reply().unwrap();
+
+    // No XTEST here, so deliver the press the way the server would
+    let press = ButtonPressEvent {
+        response_type: BUTTON_PRESS_EVENT,
+        detail: 1,
+        sequence: 0,
+        time: x11rb::CURRENT_TIME,
+        root,
+        event: tab_bar,
+        child: x11rb::NONE,
+        root_x,
+        root_y,
+        event_x: root_x - origin.dst_x,
+        event_y: root_y - origin.dst_y,
+        state: 0u16.into(),
+        same_screen: true,
+    };
+    conn.send_event(false, tab_bar, EventMask::BUTTON_PRESS, press).expect("Failed to send click");
+    conn.flush().expect("Failed to flush");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let focused = harness.get_focused().expect("Failed to get focused");
+    let focused = focused.get("window").and_then(|v| v.as_u64()).expect("Nothing focused");
+    Some((focused, left.id as u64, right.id as u64))
+}
+
+#[test]
+fn test_tab_bar_click_through_focuses_frame() {
+    let Some((focused, left, _)) = click_tab_bar_background(true) else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+    assert_eq!(focused, left);
+}
+
+#[test]
+fn test_tab_bar_background_click_ignored_by_default() {
+    let Some((focused, _, right)) = click_tab_bar_background(false) else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+    assert_eq!(focused, right);
+}
+
 // Note: Tests that spawn windows require xterm and may be flaky
 // They are left as examples but commented out by default
 
@@ -787,6 +1344,20 @@ fn test_get_windows_empty_initially() {
     assert!(windows.is_empty(), "Should have no windows initially");
 }
 
+#[test]
+fn test_close_frame_empty() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    // Nothing to close in an empty frame
+    let result = harness.send_command(&serde_json::json!({"command": "close_frame"}))
+        .expect("Failed to send close_frame");
+    assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("frame_closed"));
+    assert_eq!(result.get("requested").and_then(|v| v.as_u64()), Some(0));
+}
+
 #[test]
 fn test_get_focused_none_initially() {
     let Some(harness) = TestHarness::new() else {
@@ -909,6 +1480,82 @@ fn test_validation_after_multiple_operations() {
     }
 }
 
+#[test]
+fn test_split_rejected_below_min_frame_size() {
+    let Some(harness) = TestHarness::with_config("[appearance]\nmin_frame_size = 100\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    // Keep splitting until the WM refuses
+    let mut rejected = None;
+    for _ in 0..20 {
+        let result = harness.split("horizontal").expect("Failed to split");
+        if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+            rejected = Some(result);
+            break;
+        }
+    }
+    let rejected = rejected.expect("Split should eventually be rejected");
+    assert_eq!(rejected.get("code").and_then(|v| v.as_str()), Some("frame_too_small"));
+
+    // Further splits stay rejected and the frame count no longer changes
+    let state = harness.get_state().expect("Failed to get state");
+    let frame_count = state.get("data").and_then(|d| d.get("frame_count")).cloned();
+    let result = harness.split("horizontal").expect("Failed to split");
+    assert_eq!(result.get("code").and_then(|v| v.as_str()), Some("frame_too_small"));
+    let state = harness.get_state().expect("Failed to get state");
+    assert_eq!(state.get("data").and_then(|d| d.get("frame_count")).cloned(), frame_count);
+
+    let result = harness.validate().expect("Failed to validate");
+    assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
+}
+
+#[test]
+fn test_refresh_is_idempotent() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    harness.split("horizontal").expect("Failed to split");
+    let before = harness.get_layout().expect("Failed to get layout");
+
+    // Refreshing twice should succeed and leave the layout untouched
+    for _ in 0..2 {
+        let result = harness.send_command(&serde_json::json!({"command": "refresh"}))
+            .expect("Failed to refresh");
+        assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("ok"));
+    }
+
+    let after = harness.get_layout().expect("Failed to get layout");
+    assert_eq!(before, after, "Refresh should not change the layout");
+
+    let result = harness.validate().expect("Failed to validate");
+    assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
+}
+
+#[test]
+fn test_focus_follows_mouse_toggle() {
+    let Some(harness) = TestHarness::new() else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let query = serde_json::json!({"command": "get_focus_follows_mouse"});
+    let result = harness.send_command(&query).expect("Failed to query");
+    assert_eq!(result.get("enabled").and_then(|v| v.as_bool()), Some(true));
+
+    let result = harness.send_command(&serde_json::json!({
+        "command": "set_focus_follows_mouse",
+        "enabled": false
+    })).expect("Failed to disable");
+    assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("ok"));
+
+    let result = harness.send_command(&query).expect("Failed to query");
+    assert_eq!(result.get("enabled").and_then(|v| v.as_bool()), Some(false));
+}
+
 // =============================================================================
 // Resize Split Tests
 // =============================================================================
@@ -972,13 +1619,48 @@ fn test_resize_split_bounds() {
     let root = data.get("root").expect("Missing root");
     let ratio = root.get("ratio").and_then(|v| v.as_f64()).unwrap_or(0.5);
 
-    assert!(ratio >= 0.1 && ratio <= 0.9, "Ratio should be within bounds [0.1, 0.9], got {}", ratio);
+    assert!((0.1..=0.9).contains(&ratio), "Ratio should be within bounds [0.1, 0.9], got {}", ratio);
 
     // State should still be valid
     let result = harness.validate().expect("Failed to validate");
     assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
 }
 
+/// Ratio of the root split after splitting the focused frame twice in the
+/// same direction, giving [A | [B | C]]
+fn root_ratio_after_two_splits(harness: &TestHarness) -> f64 {
+    harness.split("horizontal").expect("Failed to split");
+    harness.split("horizontal").expect("Failed to split");
+
+    let layout = harness.get_layout().expect("Failed to get layout");
+    let root = layout.get("data").and_then(|d| d.get("root")).expect("Missing root");
+    root.get("ratio").and_then(|v| v.as_f64()).expect("Root should be a split")
+}
+
+#[test]
+fn test_auto_balance_after_split() {
+    let Some(harness) = TestHarness::with_config("[general]\nauto_balance = true\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    // Three columns each get a third
+    let ratio = root_ratio_after_two_splits(&harness);
+    assert!((ratio - 1.0 / 3.0).abs() < 0.01, "Root ratio should be balanced to 1/3, got {}", ratio);
+}
+
+#[test]
+fn test_no_auto_balance_after_split() {
+    let Some(harness) = TestHarness::with_config("[general]\nauto_balance = false\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    // The first split keeps its half; only the second frame was split
+    let ratio = root_ratio_after_two_splits(&harness);
+    assert!((ratio - 0.5).abs() < 0.01, "Root ratio should stay at 0.5, got {}", ratio);
+}
+
 // =============================================================================
 // Floating Window Tests
 // =============================================================================
@@ -1045,6 +1727,37 @@ fn test_toggle_float_nonexistent_window() {
     assert_eq!(result.get("valid").and_then(|v| v.as_bool()), Some(true));
 }
 
+#[test]
+fn test_configure_request_on_hidden_workspace_floating_window() {
+    let Some(harness) = TestHarness::with_config("[appearance]\nborder_width = 2\n") else {
+        eprintln!("Skipping test: could not create test harness");
+        return;
+    };
+
+    let window = harness.create_window().expect("Failed to create window");
+    harness.toggle_float(Some(window.id)).expect("Failed to toggle float");
+    let result = harness.send_command(&serde_json::json!({
+        "command": "move_to_workspace",
+        "window": window.id,
+        "workspace": 1
+    })).expect("Failed to move window");
+    assert_eq!(result.get("status").and_then(|v| v.as_str()), Some("ok"));
+
+    // The client moves and resizes itself while its workspace is hidden
+    window.conn.configure_window(
+        window.id,
+        &ConfigureWindowAux::new().x(300).y(200).width(400).height(300),
+    ).expect("Failed to configure window");
+    window.conn.flush().expect("Failed to flush");
+    std::thread::sleep(Duration::from_millis(100));
+
+    // Showing the workspace keeps the requested geometry instead of
+    // snapping back to the stale one
+    harness.switch_workspace(1).expect("Failed to switch workspace");
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(window.root_rect(), (302, 202, 400, 300));
+}
+
 #[test]
 fn test_floating_windows_in_state() {
     let Some(harness) = TestHarness::new() else {